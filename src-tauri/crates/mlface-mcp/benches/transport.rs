@@ -0,0 +1,101 @@
+//! Transport throughput and latency benchmarks.
+//!
+//! Run with `cargo bench -p mlface-mcp`. The stdio benchmarks use `cat` as an
+//! echo server, so they only run on Unix.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mlface_mcp::transport::{MemoryTransport, Transport};
+use mlface_mcp::types::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest};
+use serde_json::json;
+use tokio::runtime::Runtime;
+
+const BATCH: usize = 1_000;
+const PAYLOAD_SIZES: [usize; 3] = [1 << 10, 1 << 16, 1 << 20];
+
+fn notification(i: usize) -> JsonRpcMessage {
+    JsonRpcMessage::Notification(JsonRpcNotification {
+        jsonrpc: "2.0".to_string(),
+        method: "notifications/progress".to_string(),
+        params: Some(json!({ "progressToken": "bench", "progress": i, "total": BATCH })),
+    })
+}
+
+fn large_request(size: usize) -> JsonRpcMessage {
+    JsonRpcMessage::Request(JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: json!("1"),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": "echo", "arguments": { "text": "x".repeat(size) } })),
+    })
+}
+
+/// Send a batch of notifications through `tx` and read them all back from `rx`
+async fn round_trip_batch(tx: &dyn Transport, rx: &dyn Transport) {
+    for i in 0..BATCH {
+        tx.send(notification(i)).await.unwrap();
+    }
+    for _ in 0..BATCH {
+        rx.receive().await.unwrap();
+    }
+}
+
+fn bench_memory(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let (a, b) = MemoryTransport::pair();
+
+    let mut group = c.benchmark_group("memory");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("notifications", |bench| {
+        bench.to_async(&runtime).iter(|| round_trip_batch(&a, &b));
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("memory_large_payload");
+    for size in PAYLOAD_SIZES {
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |bench, &size| {
+            bench.to_async(&runtime).iter(|| async {
+                a.send(large_request(size)).await.unwrap();
+                b.receive().await.unwrap();
+            });
+        });
+    }
+    group.finish();
+}
+
+#[cfg(unix)]
+fn bench_stdio(c: &mut Criterion) {
+    use mlface_mcp::transport::StdioTransport;
+
+    let runtime = Runtime::new().unwrap();
+    // `cat` echoes every line back, so each message makes a full round trip
+    // through the writer task, the pipe and the reader's JSON parsing
+    let transport = runtime.block_on(StdioTransport::new("cat", vec![])).unwrap();
+
+    let mut group = c.benchmark_group("stdio");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("notifications", |bench| {
+        bench.to_async(&runtime).iter(|| round_trip_batch(&transport, &transport));
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("stdio_large_payload");
+    for size in PAYLOAD_SIZES {
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |bench, &size| {
+            bench.to_async(&runtime).iter(|| async {
+                transport.send(large_request(size)).await.unwrap();
+                transport.receive().await.unwrap();
+            });
+        });
+    }
+    group.finish();
+
+    runtime.block_on(transport.close()).unwrap();
+}
+
+#[cfg(not(unix))]
+fn bench_stdio(_c: &mut Criterion) {}
+
+criterion_group!(benches, bench_memory, bench_stdio);
+criterion_main!(benches);
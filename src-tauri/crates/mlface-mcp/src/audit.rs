@@ -0,0 +1,405 @@
+//! Audit trail of tool calls and resource reads.
+//!
+//! Every call is appended as one JSON line to a file per UTC day,
+//! `<dir>/YYYY-MM-DD.jsonl`. Entries are never rewritten: the retention
+//! policy only deletes whole days once they are older than the configured
+//! number of days. Arguments are recorded as a SHA-256 hash so the log can
+//! show that two calls were the same without keeping what was passed.
+
+use crate::types::{CallToolResult, McpError, ReadResourceResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Directory of the audit log, relative to the app data directory
+pub const AUDIT_DIR: &str = "audit";
+
+/// Days of entries kept unless configured otherwise
+pub const DEFAULT_RETENTION_DAYS: u32 = 90;
+
+const SETTINGS_FILE: &str = "settings.json";
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    ToolCall,
+    ResourceRead,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    /// The tool ran and reported an error in its result
+    ToolError,
+    Failed,
+    Denied,
+    Cancelled,
+}
+
+/// One recorded call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Milliseconds since the Unix epoch, when the call was requested
+    pub timestamp: u64,
+    pub action: AuditAction,
+    pub server: String,
+    /// Tool name or resource URI
+    pub target: String,
+    /// SHA-256 of the arguments as JSON, for tool calls with arguments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args_hash: Option<String>,
+    /// Size of the result as JSON, when there is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_bytes: Option<u64>,
+    /// From the request to its outcome, including any wait for approval
+    pub duration_ms: u64,
+    pub outcome: AuditOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Conversation that made the call, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation: Option<String>,
+}
+
+impl AuditEntry {
+    /// Entry for a tool call requested at `started`
+    pub fn tool_call(
+        server: &str,
+        tool: &str,
+        args: Option<&Value>,
+        conversation: Option<&str>,
+        started: Instant,
+        result: &Result<CallToolResult, McpError>,
+    ) -> Self {
+        let mut entry = Self::finished(AuditAction::ToolCall, server, tool, conversation, started, result);
+        entry.args_hash = args.map(hash_args);
+        if let Ok(CallToolResult { is_error: Some(true), .. }) = result {
+            entry.outcome = AuditOutcome::ToolError;
+        }
+        entry
+    }
+    
+    /// Entry for a resource read requested at `started`
+    pub fn resource_read(
+        server: &str,
+        uri: &str,
+        conversation: Option<&str>,
+        started: Instant,
+        result: &Result<ReadResourceResult, McpError>,
+    ) -> Self {
+        Self::finished(AuditAction::ResourceRead, server, uri, conversation, started, result)
+    }
+    
+    fn finished<T: Serialize>(
+        action: AuditAction,
+        server: &str,
+        target: &str,
+        conversation: Option<&str>,
+        started: Instant,
+        result: &Result<T, McpError>,
+    ) -> Self {
+        let elapsed = started.elapsed();
+        let requested = SystemTime::now()
+            .checked_sub(elapsed)
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        
+        let (outcome, error, result_bytes) = match result {
+            Ok(value) => (
+                AuditOutcome::Success,
+                None,
+                json_len(value),
+            ),
+            Err(McpError::Denied(_)) => (AuditOutcome::Denied, None, None),
+            Err(McpError::Cancelled) => (AuditOutcome::Cancelled, None, None),
+            Err(e) => (AuditOutcome::Failed, Some(e.to_string()), None),
+        };
+        
+        Self {
+            timestamp: requested,
+            action,
+            server: server.to_string(),
+            target: target.to_string(),
+            args_hash: None,
+            result_bytes,
+            duration_ms: elapsed.as_millis() as u64,
+            outcome,
+            error,
+            conversation: conversation.map(str::to_string),
+        }
+    }
+}
+
+/// Length of `value` serialized as JSON, counted without building the JSON,
+/// as results may be large resources
+fn json_len<T: Serialize>(value: &T) -> Option<u64> {
+    struct Counter(u64);
+    
+    impl io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len() as u64;
+            Ok(buf.len())
+        }
+        
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    
+    let mut counter = Counter(0);
+    serde_json::to_writer(&mut counter, value).ok()?;
+    Some(counter.0)
+}
+
+/// Hex SHA-256 of `args` serialized as JSON
+pub fn hash_args(args: &Value) -> String {
+    let json = serde_json::to_vec(args).unwrap_or_default();
+    format!("{:x}", Sha256::digest(json))
+}
+
+/// Filter for `AuditLog::query`; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditQuery {
+    pub server: Option<String>,
+    pub action: Option<AuditAction>,
+    pub outcome: Option<AuditOutcome>,
+    pub conversation: Option<String>,
+    /// Milliseconds since the Unix epoch, inclusive
+    pub since: Option<u64>,
+    /// Milliseconds since the Unix epoch, exclusive
+    pub until: Option<u64>,
+    /// Most recent entries to return
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.server.as_ref().is_none_or(|server| *server == entry.server)
+            && self.action.is_none_or(|action| action == entry.action)
+            && self.outcome.is_none_or(|outcome| outcome == entry.outcome)
+            && self.conversation.as_ref().is_none_or(|c| entry.conversation.as_ref() == Some(c))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct AuditSettings {
+    /// Whole days kept; 0 keeps everything
+    retention_days: u32,
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        Self { retention_days: DEFAULT_RETENTION_DAYS }
+    }
+}
+
+/// The audit log in a directory, or a log that records nothing when it has none
+#[derive(Default)]
+pub struct AuditLog {
+    dir: Option<PathBuf>,
+    state: Mutex<AuditState>,
+}
+
+#[derive(Default)]
+struct AuditState {
+    settings: AuditSettings,
+    /// Day the retention policy was last applied, so it runs once a day
+    pruned_day: Option<u64>,
+}
+
+impl AuditLog {
+    /// Open the log in `dir`, creating it. The retention policy is applied
+    /// by `prune`, or by the first `record` of each day.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let settings = fs::read_to_string(dir.join(SETTINGS_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        
+        Ok(Self {
+            dir: Some(dir),
+            state: Mutex::new(AuditState { settings, pruned_day: None }),
+        })
+    }
+    
+    pub fn retention_days(&self) -> u32 {
+        self.state.lock().map(|state| state.settings.retention_days).unwrap_or(DEFAULT_RETENTION_DAYS)
+    }
+    
+    /// Keep `days` whole days of entries, or everything with 0. Older days
+    /// are deleted right away.
+    pub fn set_retention_days(&self, days: u32) -> io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let mut state = self.state.lock().map_err(poisoned)?;
+        state.settings.retention_days = days;
+        let json = serde_json::to_string_pretty(&state.settings)?;
+        fs::write(dir.join(SETTINGS_FILE), json)?;
+        
+        state.pruned_day = None;
+        prune_locked(dir, &mut state, today())?;
+        Ok(())
+    }
+    
+    /// Delete the days that fall outside the retention period. Returns the
+    /// number of day files removed.
+    pub fn prune(&self) -> io::Result<usize> {
+        let Some(dir) = &self.dir else {
+            return Ok(0);
+        };
+        let mut state = self.state.lock().map_err(poisoned)?;
+        prune_locked(dir, &mut state, today())
+    }
+    
+    /// Append `entry` to the file of its day
+    pub fn record(&self, entry: &AuditEntry) -> io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        
+        // Held while writing so concurrent calls never interleave lines
+        let mut state = self.state.lock().map_err(poisoned)?;
+        let day = today();
+        if state.pruned_day != Some(day) {
+            prune_locked(dir, &mut state, day)?;
+        }
+        
+        let path = dir.join(day_file(entry.timestamp / MS_PER_DAY));
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(&line)
+    }
+    
+    /// Entries matching `query`, most recent first
+    pub fn query(&self, query: &AuditQuery) -> io::Result<Vec<AuditEntry>> {
+        let Some(dir) = &self.dir else {
+            return Ok(Vec::new());
+        };
+        let _state = self.state.lock().map_err(poisoned)?;
+        
+        let first_day = query.since.map(|since| since / MS_PER_DAY);
+        let last_day = query.until.map(|until| until.saturating_sub(1) / MS_PER_DAY);
+        let mut days: Vec<(u64, PathBuf)> = day_files(dir)?
+            .into_iter()
+            .filter(|(day, _)| first_day.is_none_or(|first| *day >= first))
+            .filter(|(day, _)| last_day.is_none_or(|last| *day <= last))
+            .collect();
+        days.sort_by_key(|(day, _)| std::cmp::Reverse(*day));
+        
+        let limit = query.limit.unwrap_or(usize::MAX);
+        let mut entries = Vec::new();
+        for (_, path) in days {
+            let content = fs::read_to_string(&path)?;
+            let mut day_entries: Vec<AuditEntry> = content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .filter(|entry| query.matches(entry))
+                .collect();
+            day_entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+            entries.extend(day_entries);
+            
+            if entries.len() >= limit {
+                entries.truncate(limit);
+                break;
+            }
+        }
+        Ok(entries)
+    }
+}
+
+fn poisoned<T>(_: T) -> io::Error {
+    io::Error::other("audit log lock poisoned")
+}
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64 / MS_PER_DAY)
+        .unwrap_or(0)
+}
+
+fn prune_locked(dir: &Path, state: &mut AuditState, today: u64) -> io::Result<usize> {
+    state.pruned_day = Some(today);
+    let retention = u64::from(state.settings.retention_days);
+    if retention == 0 {
+        return Ok(0);
+    }
+    
+    let mut removed = 0;
+    for (day, path) in day_files(dir)? {
+        if day + retention <= today {
+            fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Day files in `dir`, by days since the Unix epoch
+fn day_files(dir: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let day = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".jsonl"))
+            .and_then(parse_date);
+        if let Some(day) = day {
+            files.push((day, path));
+        }
+    }
+    Ok(files)
+}
+
+/// `YYYY-MM-DD.jsonl` for a day since the Unix epoch
+fn day_file(day: u64) -> String {
+    // Civil date from a day number, after Howard Hinnant's `civil_from_days`
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}.jsonl", y, m, d)
+}
+
+/// Day since the Unix epoch of a `YYYY-MM-DD` date
+fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-');
+    let y: i64 = parts.next()?.parse().ok()?;
+    let m: i64 = parts.next()?.parse().ok()?;
+    let d: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+    
+    // Inverse of the above, Hinnant's `days_from_civil`
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    u64::try_from(era * 146_097 + doe - 719_468).ok()
+}
@@ -0,0 +1,101 @@
+//! Shareable bundles of server configurations.
+//!
+//! A bundle gives every workstation of a team the same set of servers.
+//! Environment values that look like credentials are replaced on export with
+//! `${env:NAME}` references, which are expanded at launch, so each machine
+//! supplies its own value and the bundle itself carries no secrets.
+
+use crate::secrets::has_secret_reference;
+use crate::server::McpServerConfig;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Marks a JSON document as a server bundle
+pub const BUNDLE_FORMAT: &str = "mlface-server-bundle";
+
+/// Bundle version written by this build
+pub const BUNDLE_VERSION: u64 = 1;
+
+/// Parts of an environment variable name that mark its value as a secret
+const SECRET_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL", "AUTH", "PRIVATE"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub format: String,
+    pub version: u64,
+    pub servers: Vec<McpServerConfig>,
+}
+
+/// An environment value left out of a bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaceheldSecret {
+    pub server: String,
+    pub variable: String,
+}
+
+/// Result of an export
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportReport {
+    /// Names of the exported servers
+    pub servers: Vec<String>,
+    /// Secrets the importing machine has to provide in its environment
+    pub placeheld: Vec<PlaceheldSecret>,
+}
+
+impl ConfigBundle {
+    /// Bundle `servers`, replacing secret environment values with references
+    pub fn export(servers: impl IntoIterator<Item = McpServerConfig>) -> (Self, ExportReport) {
+        let mut servers: Vec<McpServerConfig> = servers.into_iter().collect();
+        servers.sort_by(|a, b| a.name.cmp(&b.name));
+        
+        let mut placeheld = Vec::new();
+        for server in &mut servers {
+            for (variable, value) in server.env.iter_mut() {
+                // Values that refer to the environment or the keychain are safe to share
+                if is_secret(variable) && !value.contains("${") && !has_secret_reference(value) {
+                    *value = format!("${{env:{}}}", variable);
+                    placeheld.push(PlaceheldSecret {
+                        server: server.name.clone(),
+                        variable: variable.clone(),
+                    });
+                }
+            }
+        }
+        placeheld.sort_by(|a, b| (&a.server, &a.variable).cmp(&(&b.server, &b.variable)));
+        
+        let report = ExportReport {
+            servers: servers.iter().map(|server| server.name.clone()).collect(),
+            placeheld,
+        };
+        let bundle = Self {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            servers,
+        };
+        (bundle, report)
+    }
+    
+    /// Parse a bundle written by `export`
+    pub fn parse(content: &str) -> Result<Self> {
+        let bundle: Self = serde_json::from_str(content)
+            .map_err(|e| anyhow!("Not an mlFace server bundle: {}", e))?;
+        
+        if bundle.format != BUNDLE_FORMAT {
+            return Err(anyhow!("Not an mlFace server bundle (format {:?})", bundle.format));
+        }
+        if bundle.version > BUNDLE_VERSION {
+            return Err(anyhow!(
+                "The bundle was written by a newer version of mlFace (version {}, this build reads up to {})",
+                bundle.version,
+                BUNDLE_VERSION
+            ));
+        }
+        
+        Ok(bundle)
+    }
+}
+
+fn is_secret(variable: &str) -> bool {
+    let variable = variable.to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|marker| variable.contains(marker))
+}
@@ -0,0 +1,195 @@
+//! A curated list of servers that can be installed without writing a config.
+//!
+//! The catalog ships with the app in `catalog.json`, so it can be browsed
+//! offline. `fetch` downloads the latest one from the repository; the app
+//! caches it and prefers it over the bundled copy unless it is older.
+//!
+//! Entries name a package rather than a command. Environment variables an
+//! entry requires are asked for when it is installed. Those marked `secret`
+//! are put in the `SecretStore`, and the config refers to them as
+//! `{{secret:NAME}}`, so tokens never end up in the config file.
+
+use crate::package::PackageSpec;
+use crate::secrets::{validate_secret_name, SecretStore};
+use crate::server::McpServerConfig;
+use crate::types::McpError;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Where `fetch` gets the latest catalog
+pub const CATALOG_URL: &str = "https://raw.githubusercontent.com/boorich/mlFace/main/src-tauri/crates/mlface-mcp/catalog.json";
+
+/// File name the app caches a fetched catalog under
+pub const CATALOG_FILE: &str = "catalog.json";
+
+const BUNDLED: &str = include_str!("../catalog.json");
+
+/// The servers on offer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Catalog {
+    /// Raised with every change, so an older download never replaces a newer
+    /// bundled catalog
+    pub version: u32,
+    pub servers: Vec<CatalogEntry>,
+}
+
+/// A server on offer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub package: PackageSpec,
+    /// Args passed to the package before any the user adds
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// What args the user is expected to add, for the install form
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args_hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<CatalogEnvVar>,
+    /// Where to learn more, or to get the credentials `env` asks for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// An environment variable the user provides at install
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEnvVar {
+    pub name: String,
+    pub description: String,
+    /// Keep the value in the secret store rather than the config
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secret: bool,
+    #[serde(default = "required_by_default", skip_serializing_if = "is_required")]
+    pub required: bool,
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+fn is_required(required: &bool) -> bool {
+    *required
+}
+
+impl Catalog {
+    /// The catalog shipped with the app
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED).expect("the bundled catalog is valid")
+    }
+    
+    pub fn parse(content: &str) -> Result<Self> {
+        serde_json::from_str(content).map_err(|e| anyhow!("Invalid server catalog: {}", e))
+    }
+    
+    /// The catalog cached at `path` if it is at least as new as the bundled
+    /// one, otherwise the bundled one. A missing or broken cache is ignored.
+    pub fn load(path: Option<&Path>) -> Self {
+        let bundled = Self::bundled();
+        let cached = path
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| Self::parse(&content).ok());
+        match cached {
+            Some(cached) if cached.version >= bundled.version => cached,
+            _ => bundled,
+        }
+    }
+    
+    /// Download the latest catalog from `url`
+    pub async fn fetch(client: &reqwest::Client, url: &str) -> Result<Self, McpError> {
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| McpError::TransportError(format!("Failed to fetch the server catalog: {}", e)))?;
+        let content = response
+            .text()
+            .await
+            .map_err(|e| McpError::TransportError(format!("Failed to fetch the server catalog: {}", e)))?;
+        Self::parse(&content).map_err(|e| McpError::ParseError { message: e.to_string(), data: None })
+    }
+    
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+    
+    pub fn get(&self, id: &str) -> Option<&CatalogEntry> {
+        self.servers.iter().find(|entry| entry.id == id)
+    }
+    
+    /// Entries whose id, name, description or tags contain every word of
+    /// `query`, ignoring case; all of them for an empty query
+    pub fn search(&self, query: &str) -> Vec<&CatalogEntry> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        self.servers
+            .iter()
+            .filter(|entry| {
+                let text = format!("{} {} {} {}", entry.id, entry.name, entry.description, entry.tags.join(" ")).to_lowercase();
+                words.iter().all(|word| text.contains(word.as_str()))
+            })
+            .collect()
+    }
+}
+
+impl CatalogEntry {
+    /// Name of the secret holding the value of the variable `var`, shared by
+    /// the servers installed from this entry
+    pub fn secret_name(&self, var: &str) -> String {
+        format!("{}.{}", self.id, var)
+    }
+    
+    /// A config for this entry, named `name` or after the entry. `values`
+    /// holds the user's values for `env` by variable name; secret ones are
+    /// stored in `secrets`. `args` are added after the entry's own.
+    pub fn install(
+        &self,
+        name: Option<&str>,
+        args: &[String],
+        values: &HashMap<String, String>,
+        secrets: Option<&dyn SecretStore>,
+    ) -> Result<McpServerConfig> {
+        let missing: Vec<&str> = self
+            .env
+            .iter()
+            .filter(|var| var.required && values.get(&var.name).is_none_or(|value| value.trim().is_empty()))
+            .map(|var| var.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!("{} needs {}", self.name, missing.join(", ")));
+        }
+        
+        let mut env = HashMap::new();
+        for var in &self.env {
+            let Some(value) = values.get(&var.name).filter(|value| !value.trim().is_empty()) else {
+                continue;
+            };
+            if var.secret {
+                let secret = self.secret_name(&var.name);
+                validate_secret_name(&secret)?;
+                let store = secrets.ok_or_else(|| anyhow!("{} needs a secret store to keep {}", self.name, var.name))?;
+                store.set(&secret, value.trim())?;
+                env.insert(var.name.clone(), format!("{{{{secret:{}}}}}", secret));
+            } else {
+                env.insert(var.name.clone(), value.clone());
+            }
+        }
+        
+        Ok(McpServerConfig {
+            name: name.map(str::trim).filter(|name| !name.is_empty()).unwrap_or(&self.id).to_string(),
+            args: self.args.iter().chain(args).cloned().collect(),
+            env,
+            package: Some(self.package.clone()),
+            description: Some(self.description.clone()),
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,867 @@
+use crate::types::*;
+use crate::compat::{self, ProtocolShim};
+use crate::metrics::RequestMetrics;
+use crate::middleware::McpMiddleware;
+use crate::priority::{self, RequestLimiter};
+use crate::roots::{Roots, RootsEnforcement, RootsMiddleware};
+use crate::trace;
+use crate::transport::Transport;
+use dashmap::DashMap;
+use futures::channel::oneshot;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{broadcast, watch};
+use tokio::sync::Mutex as TokioMutex;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration, Instant};
+use tracing::Instrument;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const NOTIFICATION_CHANNEL_CAPACITY: usize = 256;
+
+/// Callback invoked for every server notification with a matching method
+pub type NotificationHandler = Arc<dyn Fn(&JsonRpcNotification) + Send + Sync>;
+
+/// Callback answering a request sent by the server (e.g. `roots/list`)
+pub type RequestHandler =
+    Arc<dyn Fn(Option<Value>) -> BoxFuture<'static, Result<Value, McpError>> + Send + Sync>;
+
+/// A request that is taking longer than the client's slow-call threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowCall {
+    pub server: String,
+    pub method: String,
+    /// Milliseconds since the request was sent
+    pub elapsed_ms: u64,
+    /// Whether the request has finished since; a slow request is reported
+    /// once when it crosses the threshold and once when it finishes
+    pub finished: bool,
+}
+
+/// Callback told about requests that are taking long
+pub type SlowCallHandler = Arc<dyn Fn(SlowCall) + Send + Sync>;
+
+/// A request waiting for its response
+struct PendingRequest {
+    trace_id: String,
+    sender: oneshot::Sender<Result<JsonRpcResponse, McpError>>,
+}
+
+/// In-flight requests keyed by request id. A concurrent map keeps the reader
+/// task and callers from contending on a single lock.
+type PendingRequests = Arc<DashMap<String, PendingRequest>>;
+
+/// Tells the server to stop working on a request whose caller stopped waiting,
+/// whether it was dropped or timed out. Disarmed once the response arrives.
+struct CancelOnDrop {
+    transport: Arc<dyn Transport>,
+    pending_requests: PendingRequests,
+    id: String,
+    armed: bool,
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        
+        self.pending_requests.remove(&self.id);
+        
+        // Nothing to notify if the runtime itself is going away
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        
+        let transport = self.transport.clone();
+        let notification = JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/cancelled".to_string(),
+            params: Some(serde_json::json!({
+                "requestId": self.id,
+                "reason": "The client stopped waiting for the result",
+            })),
+        };
+        runtime.spawn(async move {
+            let _ = transport.send(JsonRpcMessage::Notification(notification)).await;
+        });
+    }
+}
+
+type NotificationHandlers = Arc<RwLock<HashMap<String, Vec<NotificationHandler>>>>;
+type RequestHandlers = Arc<HashMap<String, RequestHandler>>;
+
+/// The MCP client that handles the protocol communication
+pub struct McpClient {
+    transport: Arc<dyn Transport>,
+    next_id: AtomicU64,
+    // Watched so callers can wait for the connection to end
+    connected: watch::Sender<bool>,
+    message_handler: Mutex<Option<JoinHandle<()>>>,
+    last_activity: Mutex<Instant>,
+    pending_requests: PendingRequests,
+    server_info: Arc<TokioMutex<Option<InitializeResult>>>,
+    // Selected from the server's protocol version during initialize
+    shim: RwLock<Option<Arc<dyn ProtocolShim>>>,
+    notification_handlers: NotificationHandlers,
+    notification_tx: broadcast::Sender<JsonRpcNotification>,
+    request_handlers: RequestHandlers,
+    middleware: Vec<Arc<dyn McpMiddleware>>,
+    request_limiter: Option<RequestLimiter>,
+    request_timeout: Duration,
+    capabilities: ClientCapabilities,
+    trace: bool,
+    metrics: Option<Arc<RequestMetrics>>,
+    slow_call: Option<(Duration, SlowCallHandler)>,
+    label: String,
+    client_name: String,
+    client_version: String,
+}
+
+/// Builder for configuring an `McpClient` before it starts processing messages
+pub struct McpClientBuilder {
+    transport: Arc<dyn Transport>,
+    client_name: String,
+    client_version: String,
+    request_timeout: Duration,
+    capabilities: ClientCapabilities,
+    notification_handlers: HashMap<String, Vec<NotificationHandler>>,
+    request_handlers: HashMap<String, RequestHandler>,
+    middleware: Vec<Arc<dyn McpMiddleware>>,
+    max_concurrent_requests: Option<usize>,
+    trace: bool,
+    metrics: Option<Arc<RequestMetrics>>,
+    slow_call: Option<(Duration, SlowCallHandler)>,
+    label: String,
+}
+
+impl McpClientBuilder {
+    pub fn new(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            client_name: "mlFace".to_string(),
+            client_version: env!("CARGO_PKG_VERSION").to_string(),
+            request_timeout: REQUEST_TIMEOUT,
+            capabilities: ClientCapabilities {
+                resources: Some(ResourcesClientCapabilities::default()),
+                tools: Some(ToolsClientCapabilities::default()),
+                prompts: Some(PromptsClientCapabilities::default()),
+                sampling: Some(SamplingClientCapabilities::default()),
+                roots: None,
+            },
+            notification_handlers: HashMap::new(),
+            request_handlers: HashMap::new(),
+            middleware: Vec::new(),
+            max_concurrent_requests: None,
+            trace: false,
+            metrics: None,
+            slow_call: None,
+            label: "mcp".to_string(),
+        }
+    }
+    
+    /// Set the name and version reported to the server during initialization
+    pub fn client_info(mut self, name: &str, version: &str) -> Self {
+        self.client_name = name.to_string();
+        self.client_version = version.to_string();
+        self
+    }
+    
+    /// Set how long to wait for a response before failing with `TimeoutError`
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+    
+    /// Set the capabilities advertised to the server
+    pub fn capabilities(mut self, capabilities: ClientCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+    
+    /// Register a handler for server notifications with the given method
+    pub fn on_notification<F>(mut self, method: &str, handler: F) -> Self
+    where
+        F: Fn(&JsonRpcNotification) + Send + Sync + 'static,
+    {
+        self.notification_handlers
+            .entry(method.to_string())
+            .or_default()
+            .push(Arc::new(handler));
+        self
+    }
+    
+    /// Register a handler answering server-to-client requests with the given method.
+    /// Requests without a handler are answered with a "method not found" error.
+    pub fn on_request<F, Fut>(mut self, method: &str, handler: F) -> Self
+    where
+        F: Fn(Option<Value>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<Value, McpError>> + Send + 'static,
+    {
+        let handler: RequestHandler = Arc::new(move |params| Box::pin(handler(params)));
+        self.request_handlers.insert(method.to_string(), handler);
+        self
+    }
+    
+    /// Append a middleware to the interceptor chain
+    pub fn middleware(mut self, middleware: Arc<dyn McpMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+    
+    /// Advertise `roots`, answer the server's `roots/list` requests with them
+    /// and check its traffic against them as `enforcement` says. Set the
+    /// label first; violations are logged under it.
+    pub fn roots(mut self, roots: Roots, enforcement: RootsEnforcement) -> Self {
+        self.capabilities.roots = Some(RootsClientCapabilities::default());
+        let list = roots.list();
+        let middleware = Arc::new(RootsMiddleware::new(roots, enforcement, &self.label));
+        self.on_request("roots/list", move |_| {
+            let list = list.clone();
+            async move { Ok(list) }
+        })
+        .middleware(middleware)
+    }
+    
+    /// Limit how many requests may be in flight at once. Requests beyond the
+    /// limit wait for a free slot instead of racing the transport, interactive
+    /// ones before background ones and otherwise in FIFO order.
+    pub fn max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = Some(limit.max(1));
+        self
+    }
+    
+    /// Name used to tag this client's log lines, usually the server name
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
+    }
+    
+    /// Log every outgoing and incoming message at debug level
+    pub fn trace(mut self, enabled: bool) -> Self {
+        self.trace = enabled;
+        self
+    }
+    
+    /// Record every request in `metrics`, under the label
+    pub fn metrics(mut self, metrics: Arc<RequestMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+    
+    /// Call `handler` when a request is still waiting for its response after
+    /// `threshold`, and again when it finishes
+    pub fn on_slow_call<F>(mut self, threshold: Duration, handler: F) -> Self
+    where
+        F: Fn(SlowCall) + Send + Sync + 'static,
+    {
+        self.slow_call = Some((threshold, Arc::new(handler)));
+        self
+    }
+    
+    /// Build the client and start processing incoming messages
+    pub fn build(self) -> McpClient {
+        let (notification_tx, _) = broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+        
+        let client = McpClient {
+            transport: self.transport,
+            next_id: AtomicU64::new(1),
+            connected: watch::Sender::new(true),
+            message_handler: Mutex::new(None),
+            last_activity: Mutex::new(Instant::now()),
+            pending_requests: Arc::new(DashMap::new()),
+            server_info: Arc::new(TokioMutex::new(None)),
+            shim: RwLock::new(None),
+            notification_handlers: Arc::new(RwLock::new(self.notification_handlers)),
+            notification_tx,
+            request_handlers: Arc::new(self.request_handlers),
+            middleware: self.middleware,
+            request_limiter: self.max_concurrent_requests.map(RequestLimiter::new),
+            request_timeout: self.request_timeout,
+            capabilities: self.capabilities,
+            trace: self.trace,
+            metrics: self.metrics,
+            slow_call: self.slow_call,
+            label: self.label,
+            client_name: self.client_name,
+            client_version: self.client_version,
+        };
+        
+        // Spawn a task to handle incoming messages
+        let handle = client.start_message_handler();
+        if let Ok(mut message_handler) = client.message_handler.lock() {
+            *message_handler = Some(handle);
+        }
+        
+        client
+    }
+}
+
+impl McpClient {
+    pub async fn new(
+        transport: Arc<dyn Transport>, 
+        client_name: &str, 
+        client_version: &str
+    ) -> Result<Self, McpError> {
+        Ok(Self::builder(transport)
+            .client_info(client_name, client_version)
+            .build())
+    }
+    
+    /// Start configuring a client for the given transport
+    pub fn builder(transport: Arc<dyn Transport>) -> McpClientBuilder {
+        McpClientBuilder::new(transport)
+    }
+    
+    /// Initialize the connection with the server
+    pub async fn initialize(&self) -> Result<InitializeResult, McpError> {
+        // If we already have server info, return it
+        {
+            let server_info = self.server_info.lock().await;
+            if let Some(ref info) = *server_info {
+                return Ok(info.clone());
+            }
+        }
+        
+        // Create initialize request
+        let params = InitializeParams {
+            protocol_version: MCP_PROTOCOL_VERSION.to_string(),
+            name: self.client_name.clone(),
+            version: self.client_version.clone(),
+            capabilities: self.capabilities.clone(),
+        };
+        
+        // Send initialize request
+        let mut params_value = serde_json::to_value(params).map_err(McpError::from)?;
+        compat::add_initialize_aliases(&mut params_value);
+        let mut result: Value = self.send_request("initialize", Some(params_value)).await?;
+        
+        // Talk to servers on another revision through a shim from here on
+        let shim = compat::reported_version(&result).and_then(compat::shim_for);
+        if let Some(shim) = &shim {
+            tracing::info!(server = %self.label, "Using the {} protocol shim", shim.name());
+            shim.rewrite_result("initialize", &mut result);
+        }
+        if let Ok(mut current) = self.shim.write() {
+            *current = shim;
+        }
+        
+        // Parse and store result
+        let server_info: InitializeResult = serde_json::from_value(result).map_err(McpError::from)?;
+        *self.server_info.lock().await = Some(server_info.clone());
+        
+        // Send initialized notification
+        self.send_notification("initialized", None).await?;
+        
+        Ok(server_info)
+    }
+    
+    /// What the server reported about itself during initialize; `None` before
+    pub async fn server_info(&self) -> Option<InitializeResult> {
+        self.server_info.lock().await.clone()
+    }
+    
+    /// Name of the compatibility shim selected for this server, if any
+    pub fn protocol_shim(&self) -> Option<&'static str> {
+        self.current_shim().map(|shim| shim.name())
+    }
+    
+    fn current_shim(&self) -> Option<Arc<dyn ProtocolShim>> {
+        self.shim.read().ok().and_then(|shim| shim.clone())
+    }
+    
+    /// Check that the server is still responsive
+    pub async fn ping(&self) -> Result<(), McpError> {
+        self.send_request::<Value>("ping", None).await.map(|_| ())
+    }
+    
+    /// Get available tools from the server
+    pub async fn list_tools(&self) -> Result<ListToolsResult, McpError> {
+        let result: Value = self.send_request("tools/list", None).await?;
+        let tools: ListToolsResult = serde_json::from_value(result).map_err(McpError::from)?;
+        Ok(tools)
+    }
+    
+    /// Call a tool on the server. Dropping the returned future before it
+    /// completes sends `notifications/cancelled` so the server can stop the work.
+    pub async fn call_tool(&self, name: &str, arguments: Option<Value>) -> Result<CallToolResult, McpError> {
+        self.call_tool_params(CallToolParams {
+            name: name.to_string(),
+            arguments,
+            meta: None,
+        })
+        .await
+    }
+    
+    /// Call a tool, asking the server to report progress as
+    /// `notifications/progress` carrying `progress_token`
+    pub async fn call_tool_with_progress(
+        &self,
+        name: &str,
+        arguments: Option<Value>,
+        progress_token: Value,
+    ) -> Result<CallToolResult, McpError> {
+        self.call_tool_params(CallToolParams {
+            name: name.to_string(),
+            arguments,
+            meta: Some(serde_json::json!({ "progressToken": progress_token })),
+        })
+        .await
+    }
+    
+    async fn call_tool_params(&self, params: CallToolParams) -> Result<CallToolResult, McpError> {
+        let params_value = serde_json::to_value(params).map_err(McpError::from)?;
+        let result: Value = self.send_request("tools/call", Some(params_value)).await?;
+        let call_result: CallToolResult = serde_json::from_value(result).map_err(McpError::from)?;
+        Ok(call_result)
+    }
+    
+    /// List available resources on the server
+    pub async fn list_resources(&self) -> Result<ListResourcesResult, McpError> {
+        let result: Value = self.send_request("resources/list", None).await?;
+        let resources: ListResourcesResult = serde_json::from_value(result).map_err(McpError::from)?;
+        Ok(resources)
+    }
+    
+    /// Read a resource from the server
+    pub async fn read_resource(&self, uri: &str) -> Result<ReadResourceResult, McpError> {
+        let params = ReadResourceParams {
+            uri: uri.to_string(),
+        };
+        
+        let params_value = serde_json::to_value(params).map_err(McpError::from)?;
+        let result: Value = self.send_request("resources/read", Some(params_value)).await?;
+        let read_result: ReadResourceResult = serde_json::from_value(result).map_err(McpError::from)?;
+        Ok(read_result)
+    }
+    
+    /// List available prompts on the server
+    pub async fn list_prompts(&self) -> Result<ListPromptsResult, McpError> {
+        let result: Value = self.send_request("prompts/list", None).await?;
+        let prompts: ListPromptsResult = serde_json::from_value(result).map_err(McpError::from)?;
+        Ok(prompts)
+    }
+    
+    /// Get a prompt from the server
+    pub async fn get_prompt(&self, id: &str, parameters: Option<Value>) -> Result<GetPromptResult, McpError> {
+        let params = GetPromptParams {
+            id: id.to_string(),
+            parameters,
+        };
+        
+        let params_value = serde_json::to_value(params).map_err(McpError::from)?;
+        let result: Value = self.send_request("prompts/get", Some(params_value)).await?;
+        let prompt_result: GetPromptResult = serde_json::from_value(result).map_err(McpError::from)?;
+        Ok(prompt_result)
+    }
+    
+    /// Register a handler for server notifications with the given method
+    /// (e.g. `notifications/tools/list_changed`)
+    pub fn on_notification<F>(&self, method: &str, handler: F)
+    where
+        F: Fn(&JsonRpcNotification) + Send + Sync + 'static,
+    {
+        match self.notification_handlers.write() {
+            Ok(mut handlers) => {
+                handlers
+                    .entry(method.to_string())
+                    .or_default()
+                    .push(Arc::new(handler));
+            }
+            Err(e) => tracing::error!("Failed to lock notification_handlers: {}", e),
+        }
+    }
+    
+    /// Subscribe to every notification sent by the server
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<JsonRpcNotification> {
+        self.notification_tx.subscribe()
+    }
+    
+    /// Whether the transport is still alive. Once the connection is closed the
+    /// client cannot recover and must be replaced with a new one.
+    pub fn is_connected(&self) -> bool {
+        *self.connected.borrow()
+    }
+    
+    /// Time since the last request was sent or answered
+    pub fn idle_time(&self) -> Duration {
+        self.last_activity
+            .lock()
+            .map(|last_activity| last_activity.elapsed())
+            .unwrap_or_default()
+    }
+    
+    /// Whether requests are waiting for a response
+    pub fn has_pending_requests(&self) -> bool {
+        !self.pending_requests.is_empty()
+    }
+    
+    fn touch(&self) {
+        if let Ok(mut last_activity) = self.last_activity.lock() {
+            *last_activity = Instant::now();
+        }
+    }
+    
+    /// Resolves once the connection has ended, whether it was closed, shut down or
+    /// lost. The returned future does not keep the client alive.
+    pub fn closed(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut connected = self.connected.subscribe();
+        async move {
+            let _ = connected.wait_for(|connected| !*connected).await;
+        }
+    }
+    
+    /// Close the connection gracefully
+    pub async fn close(&self) -> Result<(), McpError> {
+        // Send shutdown request and exit notification. Servers are not required
+        // to implement them, so failures must not keep the transport open.
+        match timeout(SHUTDOWN_TIMEOUT, self.send_request::<Value>("shutdown", None)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => tracing::debug!(server = %self.label, "Server did not acknowledge shutdown: {}", e),
+            Err(_) => tracing::debug!(server = %self.label, "Server did not answer shutdown in time"),
+        }
+        let _ = self.send_notification("exit", None).await;
+        
+        // Close transport, then stop background processing
+        let result = self.transport.close().await;
+        self.shutdown();
+        result
+    }
+    
+    /// Stop processing incoming messages and fail all pending requests with
+    /// `ConnectionClosed`. Runs automatically when the client is dropped; unlike
+    /// `close`, it does not notify the server.
+    pub fn shutdown(&self) {
+        if let Ok(mut message_handler) = self.message_handler.lock() {
+            if let Some(handle) = message_handler.take() {
+                handle.abort();
+            }
+        }
+        
+        self.connected.send_replace(false);
+        fail_pending_requests(&self.pending_requests);
+    }
+    
+    /// Generate a unique request ID
+    fn next_id(&self) -> String {
+        self.next_id.fetch_add(1, Ordering::SeqCst).to_string()
+    }
+    
+    /// Send a request and wait for response. The request gets a trace id that
+    /// tags every log line about it, including those written by the transport.
+    async fn send_request<T: for<'de> serde::Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<T, McpError> {
+        let trace_id = trace::next_trace_id();
+        self.touch();
+        let started = Instant::now();
+        let span = tracing::info_span!("request", server = %self.label, method, trace = %trace_id);
+        let request = trace::with_trace_id(trace_id, self.dispatch_request(method, params)).instrument(span.clone());
+        let result = match &self.slow_call {
+            Some((threshold, handler)) => self.watch_slow_call(method, started, *threshold, handler, request).await,
+            None => request.await,
+        };
+        self.touch();
+        
+        if let Some(metrics) = &self.metrics {
+            let error_kind = result.as_ref().err().map(McpError::kind);
+            metrics.record(&self.label, method, started.elapsed(), error_kind);
+        }
+        
+        if let Err(e) = &result {
+            span.in_scope(|| tracing::warn!("Request failed: {}", e));
+        }
+        
+        result
+    }
+    
+    /// Await `request`, reporting it to `handler` if it takes longer than `threshold`
+    async fn watch_slow_call<T>(
+        &self,
+        method: &str,
+        started: Instant,
+        threshold: Duration,
+        handler: &SlowCallHandler,
+        request: impl Future<Output = Result<T, McpError>>,
+    ) -> Result<T, McpError> {
+        tokio::pin!(request);
+        tokio::select! {
+            result = &mut request => return result,
+            _ = tokio::time::sleep(threshold) => {}
+        }
+        
+        let report = |finished| SlowCall {
+            server: self.label.clone(),
+            method: method.to_string(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            finished,
+        };
+        tracing::warn!(server = %self.label, "Request {} is taking longer than {} ms", method, threshold.as_millis());
+        handler(report(false));
+        let result = request.await;
+        handler(report(true));
+        result
+    }
+    
+    async fn dispatch_request<T: for<'de> serde::Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<T, McpError> {
+        let trace_id = trace::current_trace_id();
+        
+        // Wait for a free slot if the server has a concurrency limit; the permit
+        // is held until the response arrives or the request times out
+        let _permit = match &self.request_limiter {
+            Some(limiter) => Some(limiter.acquire(priority::current_priority()).await),
+            None => None,
+        };
+        
+        let id = self.next_id();
+        
+        let mut request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::Value::String(id.clone()),
+            method: method.to_string(),
+            params,
+        };
+        
+        // Let the middleware chain observe or rewrite the request
+        for middleware in &self.middleware {
+            middleware.on_request(&mut request).await?;
+        }
+        
+        let shim = self.current_shim();
+        if let Some(shim) = &shim {
+            shim.rewrite_request(&mut request);
+        }
+        
+        if self.trace {
+            tracing::info!(direction = "out", id = %id, "request {}", method);
+        }
+        
+        let (tx, rx) = oneshot::channel();
+        
+        // Register the request
+        self.pending_requests.insert(id.clone(), PendingRequest {
+            trace_id,
+            sender: tx,
+        });
+        
+        // Send the request
+        if let Err(e) = self.transport.send(JsonRpcMessage::Request(request)).await {
+            self.pending_requests.remove(&id);
+            return Err(e);
+        }
+        
+        // From here on, giving up on the request cancels it on the server
+        let mut cancel_guard = CancelOnDrop {
+            transport: self.transport.clone(),
+            pending_requests: self.pending_requests.clone(),
+            id: id.clone(),
+            armed: true,
+        };
+        
+        // Wait for response with timeout
+        let mut response = match timeout(self.request_timeout, rx).await {
+            Ok(result) => {
+                cancel_guard.armed = false;
+                match result {
+                    Ok(response) => response,
+                    Err(_) => return Err(McpError::internal("Response channel closed")),
+                }
+            }
+            Err(_) => return Err(McpError::TimeoutError),
+        }?;
+        
+        if let (Some(shim), Some(result)) = (&shim, response.result.as_mut()) {
+            shim.rewrite_result(method, result);
+        }
+        
+        // Run the middleware chain over the response in reverse order
+        for middleware in self.middleware.iter().rev() {
+            middleware.on_response(method, &mut response).await?;
+        }
+        
+        // Extract result
+        if let Some(error) = response.error {
+            return Err(McpError::from(error));
+        }
+        
+        if let Some(result) = response.result {
+            match serde_json::from_value(result) {
+                Ok(value) => Ok(value),
+                Err(e) => Err(McpError::ParseError {
+                    message: format!("Failed to parse result: {}", e),
+                    data: None,
+                }),
+            }
+        } else {
+            Err(McpError::ProtocolError("Response missing result".to_string()))
+        }
+    }
+    
+    /// Send a notification (one-way message)
+    async fn send_notification(
+        &self,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<(), McpError> {
+        if self.trace {
+            tracing::info!(server = %self.label, direction = "out", "notification {}", method);
+        }
+        
+        let method = match self.current_shim() {
+            Some(shim) => shim.notification_method(method).to_string(),
+            None => method.to_string(),
+        };
+        
+        let notification = JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method,
+            params,
+        };
+        
+        self.transport.send(JsonRpcMessage::Notification(notification)).await
+    }
+    
+    /// Start a background task to handle incoming messages
+    fn start_message_handler(&self) -> JoinHandle<()> {
+        let transport = self.transport.clone();
+        let connected = self.connected.clone();
+        let pending_requests = self.pending_requests.clone();
+        let notification_handlers = self.notification_handlers.clone();
+        let notification_tx = self.notification_tx.clone();
+        let request_handlers = self.request_handlers.clone();
+        let trace = self.trace;
+        let label = self.label.clone();
+        
+        tokio::spawn(async move {
+            loop {
+                match transport.receive().await {
+                    Ok(message) => {
+                        if trace {
+                            match &message {
+                                JsonRpcMessage::Request(r) => tracing::info!(server = %label, direction = "in", id = %r.id, "request {}", r.method),
+                                JsonRpcMessage::Notification(n) => tracing::info!(server = %label, direction = "in", "notification {}", n.method),
+                                // Responses are logged below, once their trace id is known
+                                JsonRpcMessage::Response(_) => {}
+                            }
+                        }
+                        
+                        match message {
+                            JsonRpcMessage::Response(response) => {
+                                // Get the request ID
+                                let id = match &response.id {
+                                    Value::String(s) => s.clone(),
+                                    Value::Number(n) => n.to_string(),
+                                    _ => {
+                                        tracing::warn!(server = %label, "Invalid response ID type");
+                                        continue;
+                                    }
+                                };
+                                
+                                // Find and complete the pending request
+                                if let Some((_, pending)) = pending_requests.remove(&id) {
+                                    if trace {
+                                        tracing::info!(server = %label, direction = "in", id = %id, trace = %pending.trace_id, "response");
+                                    }
+                                    let _ = pending.sender.send(Ok(response));
+                                } else {
+                                    tracing::warn!(server = %label, "Received response for unknown request ID: {}", id);
+                                }
+                            }
+                            JsonRpcMessage::Notification(notification) => {
+                                // Run method-specific handlers first
+                                let handlers = match notification_handlers.read() {
+                                    Ok(handlers) => handlers.get(&notification.method).cloned(),
+                                    Err(e) => {
+                                        tracing::error!("Failed to lock notification_handlers: {}", e);
+                                        None
+                                    }
+                                };
+                                
+                                for handler in handlers.into_iter().flatten() {
+                                    handler(&notification);
+                                }
+                                
+                                // Then fan out to subscribers; no receivers is not an error
+                                let _ = notification_tx.send(notification);
+                            }
+                            JsonRpcMessage::Request(request) => {
+                                // Answer server-initiated requests without blocking the reader
+                                let transport = transport.clone();
+                                let label = label.clone();
+                                let handler = request_handlers.get(&request.method).cloned();
+                                
+                                tokio::spawn(async move {
+                                    let result = match handler {
+                                        Some(handler) => handler(request.params).await,
+                                        None => Err(McpError::method_not_found(request.method)),
+                                    };
+                                    
+                                    let response = match result {
+                                        Ok(value) => JsonRpcResponse {
+                                            jsonrpc: "2.0".to_string(),
+                                            id: request.id,
+                                            result: Some(value),
+                                            error: None,
+                                        },
+                                        Err(e) => JsonRpcResponse {
+                                            jsonrpc: "2.0".to_string(),
+                                            id: request.id,
+                                            result: None,
+                                            error: Some(e.to_json_rpc_error()),
+                                        },
+                                    };
+                                    
+                                    if let Err(e) = transport.send(JsonRpcMessage::Response(response)).await {
+                                        tracing::warn!(server = %label, "Failed to answer server request: {}", e);
+                                    }
+                                });
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!(server = %label, "Error receiving message: {}", e);
+                        
+                        // If connection was closed, complete all pending requests with error
+                        if matches!(e, McpError::ConnectionClosed) {
+                            connected.send_replace(false);
+                            fail_pending_requests(&pending_requests);
+                            break;
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Complete every pending request with `ConnectionClosed`
+fn fail_pending_requests(pending_requests: &PendingRequests) {
+    let ids: Vec<String> = pending_requests
+        .iter()
+        .map(|entry| entry.key().clone())
+        .collect();
+    
+    for id in ids {
+        if let Some((_, pending)) = pending_requests.remove(&id) {
+            let _ = pending.sender.send(Err(McpError::ConnectionClosed));
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Compatibility shims for servers that speak another protocol revision.
+//!
+//! The client speaks its own revision (`MCP_PROTOCOL_VERSION`). Once the
+//! initialize handshake reports the server's revision, a shim may be selected
+//! that rewrites method names, params and results on the wire, so the rest of
+//! the client only ever sees its own shapes.
+
+use crate::types::{JsonRpcRequest, MCP_PROTOCOL_VERSION};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+/// Rewrites traffic between the client's revision and the server's
+pub trait ProtocolShim: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+    
+    /// Method name the server expects for an outgoing notification
+    fn notification_method<'a>(&self, method: &'a str) -> &'a str {
+        method
+    }
+    
+    /// Rewrite an outgoing request after the middleware chain has run
+    fn rewrite_request(&self, _request: &mut JsonRpcRequest) {}
+    
+    /// Rewrite the result of a request with the given method before it is parsed
+    fn rewrite_result(&self, _method: &str, _result: &mut Value) {}
+}
+
+/// The shim for a server reporting `protocol_version`, if it needs one
+pub fn shim_for(protocol_version: &str) -> Option<Arc<dyn ProtocolShim>> {
+    if protocol_version == MCP_PROTOCOL_VERSION {
+        return None;
+    }
+    
+    if is_dated_revision(protocol_version) {
+        return Some(Arc::new(DatedRevisionShim));
+    }
+    
+    tracing::warn!("Unknown MCP protocol version {}, talking to the server as-is", protocol_version);
+    None
+}
+
+/// The protocol version a server reported in its initialize result
+pub fn reported_version(result: &Value) -> Option<&str> {
+    result
+        .get("protocol_version")
+        .or_else(|| result.get("protocolVersion"))
+        .and_then(Value::as_str)
+}
+
+/// Add the spellings dated revisions expect to the initialize params, so
+/// servers of either kind accept the handshake and reply with their revision
+pub fn add_initialize_aliases(params: &mut Value) {
+    let Some(params) = params.as_object_mut() else {
+        return;
+    };
+    
+    if let Some(version) = params.get("protocol_version").cloned() {
+        params.insert("protocolVersion".to_string(), version);
+    }
+    let client_info = json!({
+        "name": params.get("name").cloned().unwrap_or_default(),
+        "version": params.get("version").cloned().unwrap_or_default(),
+    });
+    params.insert("clientInfo".to_string(), client_info);
+}
+
+/// Date-style revisions such as `2024-11-05`
+fn is_dated_revision(version: &str) -> bool {
+    let bytes = version.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+/// Servers on the dated revisions name the `initialized` notification
+/// `notifications/initialized`, address prompts by `name` with `arguments`,
+/// use camelCase fields and return prompts as messages.
+struct DatedRevisionShim;
+
+impl ProtocolShim for DatedRevisionShim {
+    fn name(&self) -> &'static str {
+        "dated-revision"
+    }
+    
+    fn notification_method<'a>(&self, method: &'a str) -> &'a str {
+        match method {
+            "initialized" => "notifications/initialized",
+            _ => method,
+        }
+    }
+    
+    fn rewrite_request(&self, request: &mut JsonRpcRequest) {
+        if request.method == "prompts/get" {
+            if let Some(Value::Object(params)) = request.params.as_mut() {
+                rename_key(params, "id", "name");
+                rename_key(params, "parameters", "arguments");
+            }
+        }
+    }
+    
+    fn rewrite_result(&self, method: &str, result: &mut Value) {
+        let Some(result) = result.as_object_mut() else {
+            return;
+        };
+        
+        match method {
+            "initialize" => {
+                rename_key(result, "protocolVersion", "protocol_version");
+                if let Some(Value::Object(info)) = result.remove("serverInfo") {
+                    result.extend(info);
+                }
+                result.entry("capabilities").or_insert_with(|| json!({}));
+            }
+            "tools/list" => {
+                for tool in objects_mut(result, "tools") {
+                    rename_key(tool, "inputSchema", "input_schema");
+                }
+            }
+            "tools/call" => {
+                rename_key(result, "isError", "is_error");
+                for item in objects_mut(result, "content") {
+                    normalize_content(item);
+                }
+            }
+            "resources/list" => {
+                for resource in objects_mut(result, "resources") {
+                    rename_key(resource, "mimeType", "mime_type");
+                }
+            }
+            "resources/read" => {
+                let content: Vec<Value> = result
+                    .remove("contents")
+                    .and_then(|contents| contents.as_array().cloned())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(resource_contents_to_content)
+                    .collect();
+                result.insert("content".to_string(), Value::Array(content));
+            }
+            "prompts/list" => {
+                for prompt in objects_mut(result, "prompts") {
+                    if let Some(name) = prompt.get("name").cloned() {
+                        prompt.entry("id").or_insert(name);
+                    }
+                    if let Some(arguments) = prompt.remove("arguments") {
+                        prompt.insert("parameter_schema".to_string(), arguments_to_schema(&arguments));
+                    }
+                }
+            }
+            "prompts/get" => {
+                let content: Vec<Value> = result
+                    .remove("messages")
+                    .and_then(|messages| messages.as_array().cloned())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|mut message| {
+                        let mut content = message.get_mut("content")?.take();
+                        if let Some(item) = content.as_object_mut() {
+                            normalize_content(item);
+                        }
+                        Some(content)
+                    })
+                    .collect();
+                result.insert("content".to_string(), Value::Array(content));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn rename_key(object: &mut Map<String, Value>, from: &str, to: &str) {
+    if let Some(value) = object.remove(from) {
+        object.entry(to).or_insert(value);
+    }
+}
+
+fn objects_mut<'a>(object: &'a mut Map<String, Value>, key: &str) -> impl Iterator<Item = &'a mut Map<String, Value>> {
+    object
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+fn normalize_content(item: &mut Map<String, Value>) {
+    rename_key(item, "mimeType", "mime_type");
+    
+    if item.get("type").and_then(Value::as_str) == Some("resource") {
+        let resource = item.remove("resource").unwrap_or_default();
+        *item = match resource_contents_to_content(resource) {
+            Value::Object(content) => content,
+            _ => return,
+        };
+    }
+}
+
+/// Convert an entry of `resources/read` contents into a content item. Every
+/// item keeps the uri it was read from, so roots can still be checked.
+fn resource_contents_to_content(contents: Value) -> Value {
+    let uri = contents.get("uri").cloned().unwrap_or_default();
+    let mime_type = contents.get("mimeType").and_then(Value::as_str).unwrap_or_default();
+    
+    if let Some(text) = contents.get("text") {
+        json!({ "type": "text", "text": text, "uri": uri })
+    } else if let (Some(blob), true) = (contents.get("blob"), mime_type.starts_with("image/")) {
+        json!({ "type": "image", "mime_type": mime_type, "data": blob, "uri": uri })
+    } else {
+        json!({ "type": "embedded_resource", "uri": uri })
+    }
+}
+
+/// Turn a prompt's `arguments` list into a JSON Schema for its parameters
+fn arguments_to_schema(arguments: &Value) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    
+    for argument in arguments.as_array().into_iter().flatten() {
+        let Some(name) = argument.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        
+        let mut property = json!({ "type": "string" });
+        if let Some(description) = argument.get("description") {
+            property["description"] = description.clone();
+        }
+        properties.insert(name.to_string(), property);
+        
+        if argument.get("required").and_then(Value::as_bool) == Some(true) {
+            required.push(Value::String(name.to_string()));
+        }
+    }
+    
+    json!({ "type": "object", "properties": properties, "required": required })
+}
@@ -0,0 +1,76 @@
+//! On-disk format of the server configuration file.
+//!
+//! The file is a JSON document `{ "version": N, "servers": { name: config } }`.
+//! Older layouts are upgraded on load by running every migration from the
+//! file's version up to `CONFIG_VERSION`, so the rest of the code only ever
+//! sees the current one. Version 1 is the original flat `{ name: config }` map.
+
+use crate::server::McpServerConfig;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Version written by this build
+pub const CONFIG_VERSION: u64 = 2;
+
+/// A migration upgrading a document from the version before it
+type Migration = fn(Value) -> Result<Value>;
+
+/// `MIGRATIONS[n]` upgrades version `n + 1` to version `n + 2`
+const MIGRATIONS: &[Migration] = &[wrap_flat_map];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigFile {
+    pub version: u64,
+    #[serde(default)]
+    pub servers: HashMap<String, McpServerConfig>,
+}
+
+impl ConfigFile {
+    pub fn new(servers: HashMap<String, McpServerConfig>) -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            servers,
+        }
+    }
+    
+    /// Parse a config file of any known version
+    pub fn parse(content: &str) -> Result<Self> {
+        let document = migrate(serde_json::from_str(content)?)?;
+        Ok(serde_json::from_value(document)?)
+    }
+}
+
+/// Version of a parsed document. A flat map may hold a server named
+/// `version`, so only a number counts.
+fn version_of(document: &Value) -> u64 {
+    document.get("version").and_then(Value::as_u64).unwrap_or(1)
+}
+
+/// Upgrade a document to `CONFIG_VERSION`
+pub fn migrate(mut document: Value) -> Result<Value> {
+    let version = version_of(&document);
+    if version > CONFIG_VERSION {
+        return Err(anyhow!(
+            "The server config was written by a newer version of mlFace (format {}, this build reads up to {})",
+            version,
+            CONFIG_VERSION
+        ));
+    }
+    
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version.saturating_sub(1) as usize) {
+        document = migration(document)
+            .map_err(|e| anyhow!("Cannot upgrade the server config to format {}: {}", index + 2, e))?;
+    }
+    
+    Ok(document)
+}
+
+/// 1 -> 2: move the flat map under `servers`
+fn wrap_flat_map(document: Value) -> Result<Value> {
+    if !document.is_object() {
+        return Err(anyhow!("expected an object of servers"));
+    }
+    Ok(json!({ "version": 2, "servers": document }))
+}
@@ -0,0 +1,86 @@
+//! Records of server processes that crashed.
+//!
+//! When a local server exits with a failure, the manager keeps how it ended
+//! and the last lines it wrote to stderr, which usually say why. The record
+//! is written next to the server's log so it survives a restart of the app.
+
+use crate::logs::LogLine;
+use crate::transport::{OutputStream, ProcessExit};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Lines of stderr kept in a crash record
+pub const CRASH_STDERR_LINES: usize = 20;
+
+/// Longest stderr line quoted in a summary
+const SUMMARY_LINE_CHARS: usize = 120;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashRecord {
+    pub server: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub exit: ProcessExit,
+    /// The last lines the process wrote to stderr, oldest first
+    pub stderr: Vec<String>,
+}
+
+impl CrashRecord {
+    /// A record of `server` ending as `exit`, with the stderr lines of `log`
+    pub fn new(server: &str, exit: ProcessExit, log: &[LogLine]) -> Self {
+        let mut stderr: Vec<String> = log
+            .iter()
+            .rev()
+            .filter(|line| line.stream == OutputStream::Stderr)
+            .take(CRASH_STDERR_LINES)
+            .map(|line| line.line.clone())
+            .collect();
+        stderr.reverse();
+        
+        Self {
+            server: server.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            exit,
+            stderr,
+        }
+    }
+    
+    /// How the process ended and the line of stderr most likely to explain
+    /// it, such as `exit 1 — Error: Cannot find module 'x'`
+    pub fn summary(&self) -> String {
+        let lines = || self.stderr.iter().rev().map(|line| line.trim()).filter(|line| !line.is_empty());
+        let explanation = lines()
+            .find(|line| line.to_lowercase().contains("error"))
+            .or_else(|| lines().next());
+        
+        match explanation {
+            Some(line) if line.chars().count() > SUMMARY_LINE_CHARS => {
+                let line: String = line.chars().take(SUMMARY_LINE_CHARS).collect();
+                format!("{} — {}…", self.exit, line)
+            }
+            Some(line) => format!("{} — {}", self.exit, line),
+            None => self.exit.to_string(),
+        }
+    }
+    
+    /// Read a record written by `save`; `None` if there is none
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map(Some).map_err(io::Error::from),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+}
@@ -0,0 +1,136 @@
+//! What an overview page shows about every server, in one payload.
+//!
+//! The manager keeps a `ServerHealth` up to date as servers change state:
+//! when each one connected, how often it was restarted after its connection
+//! died, the last error it failed with and, once asked for, how many tools it
+//! offers. `McpServerManager::dashboard` combines that with the request
+//! metrics into a `Dashboard`.
+
+use crate::server::ConnectionState;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// How the app talks to a server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionType {
+    /// A local process over stdin and stdout
+    Stdio,
+    /// A remote server over HTTP and SSE
+    Remote,
+}
+
+/// The last error a server failed with
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerError {
+    pub message: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerSummary {
+    pub name: String,
+    pub connection_type: ConnectionType,
+    pub state: ConnectionState,
+    pub enabled: bool,
+    /// Milliseconds since the current connection came up; `None` while not
+    /// connected
+    pub uptime_ms: Option<u64>,
+    /// Restarts after the connection died, since the server was registered
+    pub restarts: u32,
+    pub last_error: Option<ServerError>,
+    /// `None` while not connected, or if the server did not list its tools
+    pub tool_count: Option<usize>,
+    pub requests: u64,
+    pub errors: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Dashboard {
+    pub servers: Vec<ServerSummary>,
+}
+
+#[derive(Default)]
+struct HealthRecord {
+    connected_at: Option<Instant>,
+    restarts: u32,
+    last_error: Option<ServerError>,
+    tool_count: Option<usize>,
+}
+
+/// What the manager remembers about each server's connections
+#[derive(Default)]
+pub(crate) struct ServerHealth {
+    servers: Mutex<HashMap<String, HealthRecord>>,
+}
+
+/// A server's entry in `ServerHealth`, as of the moment it was read
+pub(crate) struct HealthSnapshot {
+    pub uptime_ms: Option<u64>,
+    pub restarts: u32,
+    pub last_error: Option<ServerError>,
+    pub tool_count: Option<usize>,
+}
+
+impl ServerHealth {
+    /// Note that `server` went to `state`, for `reason` if it was unexpected
+    pub(crate) fn state_changed(&self, server: &str, state: ConnectionState, reason: Option<&str>) {
+        let Ok(mut servers) = self.servers.lock() else {
+            return;
+        };
+        let record = servers.entry(server.to_string()).or_default();
+        
+        // A new connection may offer other tools
+        record.tool_count = None;
+        record.connected_at = (state == ConnectionState::Connected).then(Instant::now);
+        
+        // Reasons for other states are progress, such as the next restart attempt
+        let failed = matches!(state, ConnectionState::Crashed | ConnectionState::Disconnected);
+        if let Some(reason) = reason.filter(|_| failed) {
+            record.last_error = Some(ServerError {
+                message: reason.to_string(),
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0),
+            });
+        }
+    }
+    
+    pub(crate) fn restarted(&self, server: &str) {
+        if let Ok(mut servers) = self.servers.lock() {
+            servers.entry(server.to_string()).or_default().restarts += 1;
+        }
+    }
+    
+    /// Remember how many tools `server` offers; `None` forgets it, such as
+    /// when the server says its tools changed
+    pub(crate) fn set_tool_count(&self, server: &str, count: Option<usize>) {
+        if let Ok(mut servers) = self.servers.lock() {
+            servers.entry(server.to_string()).or_default().tool_count = count;
+        }
+    }
+    
+    pub(crate) fn forget(&self, server: &str) {
+        if let Ok(mut servers) = self.servers.lock() {
+            servers.remove(server);
+        }
+    }
+    
+    pub(crate) fn snapshot(&self, server: &str) -> HealthSnapshot {
+        let servers = self.servers.lock().ok();
+        let record = servers.as_ref().and_then(|servers| servers.get(server));
+        
+        HealthSnapshot {
+            uptime_ms: record
+                .and_then(|record| record.connected_at)
+                .map(|at| at.elapsed().as_millis() as u64),
+            restarts: record.map_or(0, |record| record.restarts),
+            last_error: record.and_then(|record| record.last_error.clone()),
+            tool_count: record.and_then(|record| record.tool_count),
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! Finding MCP servers installed on this machine.
+//!
+//! Discovery only looks at files; it never runs what it finds unless asked
+//! to probe. A server that ships a manifest, an `mcp.json` or `server.json`
+//! next to its executable or at the root of its project, is offered as the
+//! manifest describes it. Such a file may also hold an `mcpServers` or
+//! `servers` map, as clients keep their configs in, for several servers.
+//!
+//! Without a manifest, a directory the user points at is taken as a
+//! directory of servers, so every executable in it is offered, and so is
+//! every project in it that has a manifest. On `PATH` and in the directories
+//! package managers install to, an executable is offered when its name has
+//! `mcp` as a word, like `mcp-server-fetch` or `github-mcp-server`, or when a
+//! globally installed npm package that describes itself as an MCP server
+//! declares it as a bin.
+
+use crate::catalog::CatalogEnvVar;
+use crate::import::{self, ImportSource};
+use crate::package::{self, PackageSpec};
+use crate::server::McpServerConfig;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// File names of server manifests; the first one found in a directory is used
+pub const MANIFEST_FILES: [&str; 2] = ["mcp.json", "server.json"];
+
+/// Package keywords marking an npm package as an MCP server
+const SERVER_KEYWORDS: [&str; 3] = ["mcp", "mcp-server", "modelcontextprotocol"];
+
+/// How a server says it is run. `command` is relative to the manifest's
+/// directory, which the server is started in.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ServerManifest {
+    /// Defaults to the name of the executable, or of the directory
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub command: String,
+    pub args: Vec<String>,
+    /// Variables the server reads. Required ones are passed on from the
+    /// environment, so a missing one stops the launch with its name.
+    pub env: Vec<CatalogEnvVar>,
+    /// Package to run instead of a `command`
+    pub package: Option<PackageSpec>,
+}
+
+/// A server that may be registered
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub config: McpServerConfig,
+    /// The executable it runs, to probe and pin; none for a server run from a
+    /// package, a URL or a command looked up on `PATH`
+    pub path: Option<PathBuf>,
+}
+
+/// Candidates in `dir`: those its manifests or its projects' manifests
+/// describe, then all of its executables. Then those on `PATH` and in the
+/// known install directories: the ones manifests there describe, then the
+/// executables that look like servers. The first server of each name wins,
+/// as it would on `PATH`. Blocks on the file system.
+pub fn scan(dir: Option<&Path>) -> Vec<Candidate> {
+    let mut found = Found::default();
+    if let Some(dir) = dir {
+        for candidate in manifests_in(dir) {
+            found.push(candidate);
+        }
+        for project in subdirectories(dir) {
+            for candidate in manifests_in(&project) {
+                found.push(candidate);
+            }
+        }
+        for path in executables(dir) {
+            found.push_executable(path);
+        }
+    }
+    
+    let dirs = search_dirs();
+    let bins = manifest_bins(&dirs);
+    for dir in &dirs {
+        for candidate in manifests_in(dir) {
+            found.push(candidate);
+        }
+        for path in executables(dir) {
+            let stem = stem(&path);
+            if looks_like_server(&stem) || bins.contains(&stem) {
+                found.push_executable(path);
+            }
+        }
+    }
+    found.candidates
+}
+
+/// The servers the manifest at `path` describes, whose relative commands
+/// and working directories are resolved against its directory
+pub fn read_manifest(path: &Path) -> Result<Vec<Candidate>> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let content = std::fs::read_to_string(path)?;
+    let document: Value = serde_json::from_str(&content).map_err(|e| anyhow!("Invalid manifest {}: {}", path.display(), e))?;
+    
+    let configs = if document.get("mcpServers").is_some() {
+        import::parse_mcp_servers(&content)?.0
+    } else if document.get("servers").is_some() {
+        ImportSource::VsCode.parse(&content)?.0
+    } else {
+        let manifest: ServerManifest =
+            serde_json::from_value(document).map_err(|e| anyhow!("Invalid manifest {}: {}", path.display(), e))?;
+        vec![manifest.config(dir)?]
+    };
+    
+    Ok(configs
+        .into_iter()
+        .map(|mut config| {
+            let path = resolve_command(dir, &mut config.command);
+            let cwd = match &config.cwd {
+                Some(cwd) => dir.join(cwd),
+                None => dir.to_path_buf(),
+            };
+            config.cwd = Some(cwd.to_string_lossy().into_owned());
+            Candidate { config, path }
+        })
+        .collect())
+}
+
+impl ServerManifest {
+    /// The config of the server, named after its command or `dir` unless the
+    /// manifest names it
+    pub fn config(&self, dir: &Path) -> Result<McpServerConfig> {
+        if self.command.is_empty() && self.package.is_none() {
+            return Err(anyhow!("The manifest in {} has neither a command nor a package", dir.display()));
+        }
+        let name = self
+            .name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .or_else(|| Path::new(&self.command).file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .or_else(|| dir.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .ok_or_else(|| anyhow!("The manifest in {} has no name", dir.display()))?;
+        let env: HashMap<String, String> = self
+            .env
+            .iter()
+            .filter(|var| var.required)
+            .map(|var| (var.name.clone(), format!("${{{}}}", var.name)))
+            .collect();
+        
+        Ok(McpServerConfig {
+            name,
+            command: self.command.clone(),
+            args: self.args.clone(),
+            env,
+            package: self.package.clone(),
+            description: self.description.clone(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Candidates collected so far, with the names and executables taken
+#[derive(Default)]
+struct Found {
+    candidates: Vec<Candidate>,
+    names: HashSet<String>,
+    paths: HashSet<PathBuf>,
+}
+
+impl Found {
+    fn push(&mut self, candidate: Candidate) {
+        if candidate.path.as_ref().is_some_and(|path| self.paths.contains(path)) {
+            return;
+        }
+        if !self.names.insert(candidate.config.name.to_lowercase()) {
+            return;
+        }
+        self.paths.extend(candidate.path.clone());
+        self.candidates.push(candidate);
+    }
+    
+    fn push_executable(&mut self, path: PathBuf) {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return;
+        };
+        // Matched by the name it is run by, so `tool.exe` and `tool.cmd` are one
+        if self.paths.contains(&path) || !self.names.insert(stem(&path)) {
+            return;
+        }
+        self.paths.insert(path.clone());
+        self.candidates.push(Candidate {
+            config: McpServerConfig {
+                name: name.to_string(),
+                command: path.to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+            path: Some(path),
+        });
+    }
+}
+
+/// The servers described by the first manifest in `dir`; none when there is
+/// none or it can't be used
+fn manifests_in(dir: &Path) -> Vec<Candidate> {
+    let Some(manifest) = MANIFEST_FILES.iter().map(|file| dir.join(file)).find(|path| path.is_file()) else {
+        return Vec::new();
+    };
+    read_manifest(&manifest).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring {}: {}", manifest.display(), e);
+        Vec::new()
+    })
+}
+
+/// Make a command naming a file in `dir` absolute, returning the file. Bare
+/// names not found in `dir` are left to `PATH`, and URLs as they are.
+fn resolve_command(dir: &Path, command: &mut String) -> Option<PathBuf> {
+    if command.is_empty() || command.starts_with("http://") || command.starts_with("https://") {
+        return None;
+    }
+    let file = dir.join(&*command);
+    let is_path = Path::new(command.as_str()).components().count() > 1;
+    if file.is_file() {
+        *command = file.to_string_lossy().into_owned();
+        Some(file)
+    } else {
+        if is_path {
+            *command = file.to_string_lossy().into_owned();
+        }
+        None
+    }
+}
+
+/// Subdirectories of `dir`, sorted, leaving out hidden ones
+fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Whether an executable named `name` looks like an MCP server: `mcp` is one
+/// of the words of its name
+pub fn looks_like_server(name: &str) -> bool {
+    name.to_lowercase().split(['-', '_', '.']).any(|word| word == "mcp")
+}
+
+/// The directories on `PATH` followed by those package managers install
+/// executables to, without duplicates
+pub fn search_dirs() -> Vec<PathBuf> {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in std::env::split_paths(&path).chain(package::install_dirs()).chain(npm_bin_dirs()) {
+        if !dir.as_os_str().is_empty() && !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// Executables directly in `dir`, sorted by name; none when it can't be read
+fn executables(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_executable(path))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext == "exe" || ext == "bat" || ext == "cmd")
+}
+
+/// The name an executable is run by, without a Windows extension
+fn stem(path: &Path) -> String {
+    #[cfg(windows)]
+    let name = path.file_stem();
+    #[cfg(not(windows))]
+    let name = path.file_name();
+    name.map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
+
+/// Where npm links the bins of global packages when its prefix was changed
+/// from the default, as its docs suggest to avoid installing with sudo
+fn npm_bin_dirs() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        candidates.push(home.join(".npm-global").join("bin"));
+    }
+    #[cfg(windows)]
+    if let Some(app_data) = std::env::var_os("APPDATA") {
+        candidates.push(PathBuf::from(app_data).join("npm"));
+    }
+    candidates
+}
+
+/// The global `node_modules` belonging to a bin directory: `../lib` next to
+/// it on Unix, inside it on Windows
+pub(crate) fn node_modules_of(bin: &Path) -> Option<PathBuf> {
+    #[cfg(windows)]
+    let node_modules = bin.join("node_modules");
+    #[cfg(not(windows))]
+    let node_modules = bin.parent()?.join("lib").join("node_modules");
+    node_modules.is_dir().then_some(node_modules)
+}
+
+/// Bin names declared by the globally installed npm packages next to `dirs`
+/// that describe themselves as MCP servers
+fn manifest_bins(dirs: &[PathBuf]) -> HashSet<String> {
+    let mut roots: Vec<PathBuf> = dirs.iter().filter_map(|dir| node_modules_of(dir)).collect();
+    roots.dedup();
+    
+    let mut bins = HashSet::new();
+    for root in roots {
+        for manifest in package_manifests(&root) {
+            let Some(package) = std::fs::read_to_string(&manifest)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+            else {
+                continue;
+            };
+            if is_server_package(&package) {
+                bins.extend(bin_names(&package));
+            }
+        }
+    }
+    bins
+}
+
+/// The `package.json` of each package in `node_modules`, scoped ones included
+fn package_manifests(node_modules: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(node_modules) else {
+        return Vec::new();
+    };
+    let mut manifests = Vec::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('@') {
+            if let Ok(scoped) = std::fs::read_dir(&path) {
+                manifests.extend(scoped.filter_map(|entry| entry.ok()).map(|entry| entry.path().join("package.json")));
+            }
+        } else {
+            manifests.push(path.join("package.json"));
+        }
+    }
+    manifests
+}
+
+fn is_server_package(package: &Value) -> bool {
+    let name = package["name"].as_str().unwrap_or_default();
+    let unscoped = name.rsplit('/').next().unwrap_or(name);
+    let keywords = package["keywords"].as_array().into_iter().flatten().filter_map(Value::as_str);
+    name.starts_with("@modelcontextprotocol/server-")
+        || looks_like_server(unscoped)
+        || keywords.map(str::to_lowercase).any(|keyword| SERVER_KEYWORDS.contains(&keyword.as_str()))
+}
+
+/// The commands a package installs: the keys of its `bin`, or its unscoped
+/// name when `bin` is a single path
+fn bin_names(package: &Value) -> Vec<String> {
+    match &package["bin"] {
+        Value::Object(bins) => bins.keys().map(|name| name.to_lowercase()).collect(),
+        Value::String(_) => package["name"]
+            .as_str()
+            .and_then(|name| name.rsplit('/').next())
+            .map(|name| vec![name.to_lowercase()])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
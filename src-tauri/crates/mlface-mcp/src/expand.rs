@@ -0,0 +1,53 @@
+//! Variable expansion in server configurations.
+//!
+//! Configs copied from Claude Desktop or VS Code refer to `~`, `${VAR}`,
+//! `${env:VAR}` and `${workspaceFolder}`. They are stored verbatim and only
+//! expanded when the server is launched, so secrets can stay in the
+//! environment instead of the config file.
+
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+/// Expand `~` at the start of `value` and every `${...}` reference in it
+pub fn expand(value: &str, workspace_folder: Option<&Path>) -> Result<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    
+    if let Some(after) = rest.strip_prefix('~') {
+        if after.is_empty() || after.starts_with('/') || after.starts_with('\\') {
+            let home = dirs::home_dir().ok_or_else(|| anyhow!("Cannot expand ~: no home directory"))?;
+            result.push_str(&home.to_string_lossy());
+            rest = after;
+        }
+    }
+    
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        
+        let reference = &rest[start + 2..];
+        let end = reference
+            .find('}')
+            .ok_or_else(|| anyhow!("Unterminated ${{ in {:?}", value))?;
+        
+        result.push_str(&resolve(&reference[..end], workspace_folder)?);
+        rest = &reference[end + 1..];
+    }
+    
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn resolve(name: &str, workspace_folder: Option<&Path>) -> Result<String> {
+    match name {
+        "workspaceFolder" => workspace_folder
+            .map(|dir| dir.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow!("${{workspaceFolder}} is used but no workspace folder is open")),
+        "userHome" => dirs::home_dir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow!("Cannot expand ${{userHome}}: no home directory")),
+        _ => {
+            let var = name.strip_prefix("env:").unwrap_or(name);
+            std::env::var(var).map_err(|_| anyhow!("Environment variable {} is not set", var))
+        }
+    }
+}
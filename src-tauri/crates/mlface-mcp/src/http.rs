@@ -0,0 +1,89 @@
+//! One HTTP client for all remote servers.
+//!
+//! A reqwest client keeps a connection pool and DNS cache of its own, so a
+//! client per remote server holds as many pools as there are servers, even
+//! when they live on the same host. The manager's `HttpClients` hands out one
+//! shared client instead. Servers with TLS settings of their own get a client
+//! for those settings, shared with every server using the same ones. Headers
+//! such as credentials are added by each transport per request.
+
+use crate::types::McpError;
+use reqwest::{Certificate, Client as HttpClient};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long to wait for a connection to a remote server
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Connections kept open per host while idle
+const MAX_IDLE_PER_HOST: usize = 8;
+
+/// TLS options of a remote server that differ from the defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsSettings {
+    /// PEM file with further root certificates to trust, e.g. a company CA
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_file: Option<PathBuf>,
+    /// Accept any certificate; only meant for development servers
+    #[serde(skip_serializing_if = "is_false")]
+    pub accept_invalid_certs: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// HTTP clients shared by remote servers, one per set of TLS settings
+#[derive(Default)]
+pub struct HttpClients {
+    clients: Mutex<HashMap<TlsSettings, HttpClient>>,
+}
+
+impl HttpClients {
+    /// The client for servers with `tls`, built on first use. Clones share
+    /// their connection pool.
+    pub fn client(&self, tls: &TlsSettings) -> Result<HttpClient, McpError> {
+        let mut clients = self.clients.lock().map_err(|e| McpError::internal(format!("Failed to lock HTTP clients: {}", e)))?;
+        if let Some(client) = clients.get(tls) {
+            return Ok(client.clone());
+        }
+        
+        let client = build(tls)?;
+        clients.insert(tls.clone(), client.clone());
+        Ok(client)
+    }
+    
+    /// How many clients, and so connection pools, were built
+    pub fn len(&self) -> usize {
+        self.clients.lock().map(|clients| clients.len()).unwrap_or(0)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn build(tls: &TlsSettings) -> Result<HttpClient, McpError> {
+    let mut builder = HttpClient::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .pool_max_idle_per_host(MAX_IDLE_PER_HOST);
+    
+    if let Some(ca_file) = &tls.ca_file {
+        let pem = std::fs::read(ca_file)
+            .map_err(|e| McpError::TransportError(format!("Cannot read {}: {}", ca_file.display(), e)))?;
+        let certificate = Certificate::from_pem(&pem)
+            .map_err(|e| McpError::TransportError(format!("Invalid certificate in {}: {}", ca_file.display(), e)))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+    if tls.accept_invalid_certs {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    
+    builder
+        .build()
+        .map_err(|e| McpError::TransportError(format!("Failed to create HTTP client: {}", e)))
+}
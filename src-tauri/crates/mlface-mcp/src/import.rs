@@ -0,0 +1,220 @@
+//! Importing server configurations from other MCP clients.
+//!
+//! Claude Desktop, Cursor and Windsurf keep their servers in an `mcpServers`
+//! map of `{ command, args, env }` or `{ url }` entries, keyed by server name.
+//! VS Code uses a `servers` map of the same entries, either in `.vscode/mcp.json`
+//! or under `mcp` in its JSON-with-comments `settings.json`.
+
+use crate::server::McpServerConfig;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// What to do with an imported server whose name is already registered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Keep the existing server and skip the imported one
+    #[default]
+    Skip,
+    /// Replace the existing server
+    Overwrite,
+    /// Import under a new name with a numeric suffix
+    Rename,
+}
+
+/// A server that was not imported
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedServer {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Result of an import
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    /// Names the servers were registered under
+    pub imported: Vec<String>,
+    pub skipped: Vec<SkippedServer>,
+}
+
+/// An MCP client whose configuration can be imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    ClaudeDesktop,
+    Cursor,
+    VsCode,
+    Windsurf,
+}
+
+impl ImportSource {
+    /// The user-wide config file of this client on this platform
+    pub fn default_path(self) -> Option<PathBuf> {
+        match self {
+            // ~/Library/Application Support on macOS, %APPDATA% on Windows, ~/.config on Linux
+            Self::ClaudeDesktop => dirs::config_dir().map(|dir| dir.join("Claude").join("claude_desktop_config.json")),
+            Self::VsCode => dirs::config_dir().map(|dir| dir.join("Code").join("User").join("settings.json")),
+            Self::Cursor => dirs::home_dir().map(|dir| dir.join(".cursor").join("mcp.json")),
+            Self::Windsurf => dirs::home_dir().map(|dir| dir.join(".codeium").join("windsurf").join("mcp_config.json")),
+        }
+    }
+    
+    /// Parse a config file of this client. Entries that cannot be used are
+    /// reported in the returned skip list.
+    pub fn parse(self, content: &str) -> Result<(Vec<McpServerConfig>, Vec<SkippedServer>)> {
+        match self {
+            Self::VsCode => {
+                let document: Value = serde_json::from_str(&strip_jsonc(content))?;
+                // .vscode/mcp.json has the map at the top, settings.json under "mcp"
+                let servers = document
+                    .get("servers")
+                    .or_else(|| document.get("mcp").and_then(|mcp| mcp.get("servers")))
+                    .ok_or_else(|| anyhow!("The config has no MCP servers section"))?;
+                parse_server_map(servers)
+            }
+            _ => parse_mcp_servers(content),
+        }
+    }
+}
+
+/// What importing a config file would do, for review before importing
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub path: PathBuf,
+    pub servers: Vec<McpServerConfig>,
+    pub skipped: Vec<SkippedServer>,
+    /// Names of `servers` that are already registered
+    pub conflicts: Vec<String>,
+}
+
+/// Parse the `mcpServers` map of a Claude Desktop style config. Entries that
+/// cannot be used are reported in the returned skip list.
+pub fn parse_mcp_servers(content: &str) -> Result<(Vec<McpServerConfig>, Vec<SkippedServer>)> {
+    let document: Value = serde_json::from_str(content)?;
+    let servers = document
+        .get("mcpServers")
+        .ok_or_else(|| anyhow!("The config has no mcpServers section"))?;
+    parse_server_map(servers)
+}
+
+fn parse_server_map(servers: &Value) -> Result<(Vec<McpServerConfig>, Vec<SkippedServer>)> {
+    let servers = servers
+        .as_object()
+        .ok_or_else(|| anyhow!("The servers section is not an object"))?;
+    
+    let mut configs = Vec::new();
+    let mut skipped = Vec::new();
+    for (name, entry) in servers {
+        match parse_entry(name, entry) {
+            Ok(config) => configs.push(config),
+            Err(e) => skipped.push(SkippedServer {
+                name: name.clone(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+    
+    configs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok((configs, skipped))
+}
+
+fn parse_entry(name: &str, entry: &Value) -> Result<McpServerConfig> {
+    // Remote servers are configured by URL; the manager treats an http(s)
+    // command as the server's endpoint
+    let command = entry
+        .get("command")
+        .or_else(|| entry.get("url"))
+        .or_else(|| entry.get("serverUrl"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("No command or URL"))?;
+    
+    if entry.to_string().contains("${input:") {
+        return Err(anyhow!("Uses VS Code input variables, which mlFace cannot prompt for"));
+    }
+    
+    let args = match entry.get("args") {
+        Some(args) => serde_json::from_value(args.clone()).map_err(|_| anyhow!("args must be a list of strings"))?,
+        None => Vec::new(),
+    };
+    
+    let env: HashMap<String, String> = match entry.get("env") {
+        Some(env) => serde_json::from_value(env.clone()).map_err(|_| anyhow!("env must map names to strings"))?,
+        None => HashMap::new(),
+    };
+    
+    Ok(McpServerConfig {
+        name: name.to_string(),
+        command: command.to_string(),
+        args,
+        env,
+        cwd: entry.get("cwd").and_then(Value::as_str).map(str::to_string),
+        ..Default::default()
+    })
+}
+
+/// Turn JSON with comments and trailing commas, as VS Code writes it, into JSON
+fn strip_jsonc(content: &str) -> String {
+    // First drop the comments, then the commas before closing brackets
+    let mut uncommented = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    
+    while let Some(c) = chars.next() {
+        if in_string {
+            uncommented.push(c);
+            match c {
+                '\\' => uncommented.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => {
+                in_string = c == '"';
+                uncommented.push(c);
+            }
+        }
+    }
+    
+    let mut output = String::with_capacity(uncommented.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in uncommented.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = uncommented[i + 1..].trim_start();
+            if next.starts_with('}') || next.starts_with(']') {
+                continue;
+            }
+        }
+        output.push(c);
+    }
+    
+    output
+}
@@ -0,0 +1,101 @@
+//! Fingerprints of local server executables.
+//!
+//! A server found by `discover_servers` is pinned to the SHA-256 of its
+//! executable, and any server can be pinned later with
+//! `McpServerManager::pin_binary`. Before each launch the manager hashes the
+//! executable again: a changed binary is reported with the connection state,
+//! or refused when the pin is enforced. Bare commands such as `npx` are looked
+//! up on `PATH`, so only the launcher itself is covered, not what it runs.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The executable a local server is expected to launch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinaryPin {
+    /// Hex SHA-256 of the executable
+    pub sha256: String,
+    /// Refuse to launch a changed binary instead of warning about it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub enforce: bool,
+}
+
+/// How a server's executable compares to its pin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum BinaryCheck {
+    /// Nothing is pinned, or the server is remote
+    Unpinned,
+    Unchanged { path: PathBuf },
+    Changed { path: PathBuf, expected: String, actual: String },
+}
+
+impl BinaryCheck {
+    /// A warning for the user when the executable changed
+    pub fn warning(&self) -> Option<String> {
+        match self {
+            Self::Changed { path, .. } => Some(format!("{} changed since it was pinned", path.display())),
+            _ => None,
+        }
+    }
+}
+
+impl BinaryPin {
+    /// Pin the executable `command` launches as it is now
+    pub fn of(command: &str, enforce: bool) -> Result<Self> {
+        let path = resolve_executable(command)?;
+        Ok(Self {
+            sha256: hash_file(&path)?,
+            enforce,
+        })
+    }
+    
+    /// Hash the executable `command` launches and compare it to the pin
+    pub fn check(&self, command: &str) -> Result<BinaryCheck> {
+        let path = resolve_executable(command)?;
+        let actual = hash_file(&path)?;
+        if actual.eq_ignore_ascii_case(&self.sha256) {
+            Ok(BinaryCheck::Unchanged { path })
+        } else {
+            Ok(BinaryCheck::Changed {
+                path,
+                expected: self.sha256.clone(),
+                actual,
+            })
+        }
+    }
+}
+
+/// Hex SHA-256 of a file's contents
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The file `command` runs: the command itself when it is a path, otherwise
+/// the first match on `PATH`
+pub fn resolve_executable(command: &str) -> Result<PathBuf> {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        return match path.is_file() {
+            true => Ok(path.to_path_buf()),
+            false => Err(anyhow::anyhow!("{} not found", command)),
+        };
+    }
+    
+    #[cfg(windows)]
+    let extensions = ["", ".exe", ".cmd", ".bat"];
+    #[cfg(not(windows))]
+    let extensions = [""];
+    
+    let dirs = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&dirs)
+        .flat_map(|dir| extensions.iter().map(move |ext| dir.join(format!("{}{}", command, ext))))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| anyhow::anyhow!("{} not found on PATH", command))
+}
@@ -0,0 +1,47 @@
+//! Model Context Protocol support for mlFace.
+//!
+//! This crate has no Tauri dependency: it provides the protocol types, the
+//! stdio and HTTP/SSE transports, the `McpClient` and the `McpServerManager`.
+//! The Tauri app wraps it in thin `#[tauri::command]` functions.
+//!
+//! The `otlp` feature adds export of spans and metrics to an OpenTelemetry
+//! collector.
+
+pub mod audit;
+pub mod bundle;
+pub mod catalog;
+pub mod client;
+pub mod compat;
+pub mod config;
+pub mod crash;
+pub mod dashboard;
+pub mod discovery;
+pub mod expand;
+pub mod http;
+pub mod import;
+pub mod integrity;
+pub mod logs;
+pub mod mdns;
+pub mod metrics;
+pub mod middleware;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+pub mod package;
+pub mod priority;
+pub mod project;
+pub mod rate_limit;
+pub mod registry;
+pub mod resource;
+pub mod roots;
+pub mod secrets;
+pub mod types;
+pub mod transport;
+pub mod server;
+pub mod session;
+pub(crate) mod single_flight;
+pub mod spill;
+pub mod telemetry;
+pub mod tool_schema;
+pub mod trace;
+pub mod traffic;
+pub mod updates;
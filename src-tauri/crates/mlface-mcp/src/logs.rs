@@ -0,0 +1,155 @@
+//! Captured output of server processes.
+//!
+//! Everything a stdio server writes that is not a protocol message ends up in
+//! its `ServerLog`: a ring buffer of recent lines, optionally mirrored to a
+//! log file, that can be tailed and followed. It also knows how the process
+//! writing it ended.
+
+use crate::transport::{OutputHandler, OutputStream, ProcessExit};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, watch};
+
+/// Number of lines kept in memory per server
+pub const DEFAULT_LOG_CAPACITY: usize = 1000;
+
+/// Log files are rotated to `<name>.1` once they grow past this size
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+const FOLLOW_CHANNEL_CAPACITY: usize = 256;
+
+/// A line of server output
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// Recent output of one server
+pub struct ServerLog {
+    lines: Mutex<VecDeque<LogLine>>,
+    capacity: usize,
+    file: Mutex<Option<LineWriter<File>>>,
+    follow_tx: broadcast::Sender<LogLine>,
+    // Exit status of the current process, if it is a local one
+    exit: Mutex<Option<watch::Receiver<Option<ProcessExit>>>>,
+}
+
+impl Default for ServerLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+impl ServerLog {
+    pub fn new(capacity: usize) -> Self {
+        let (follow_tx, _) = broadcast::channel(FOLLOW_CHANNEL_CAPACITY);
+        
+        Self {
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            file: Mutex::new(None),
+            follow_tx,
+            exit: Mutex::new(None),
+        }
+    }
+    
+    /// Also append every line to the file at `path`, rotating an oversized
+    /// previous file out of the way first
+    pub fn persist_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        
+        if std::fs::metadata(path).is_ok_and(|metadata| metadata.len() > MAX_LOG_FILE_BYTES) {
+            let mut rotated = path.as_os_str().to_owned();
+            rotated.push(".1");
+            std::fs::rename(path, rotated)?;
+        }
+        
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        if let Ok(mut guard) = self.file.lock() {
+            *guard = Some(LineWriter::new(file));
+        }
+        
+        Ok(())
+    }
+    
+    /// Record a line of output
+    pub fn push(&self, stream: OutputStream, line: &str) {
+        let entry = LogLine {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            stream,
+            line: line.to_string(),
+        };
+        
+        if let Ok(mut guard) = self.file.lock() {
+            if let Some(file) = guard.as_mut() {
+                let stream = match stream {
+                    OutputStream::Stdout => "stdout",
+                    OutputStream::Stderr => "stderr",
+                };
+                if let Err(e) = writeln!(file, "{} [{}] {}", entry.timestamp, stream, entry.line) {
+                    tracing::warn!("Failed to write server log, no longer persisting it: {}", e);
+                    *guard = None;
+                }
+            }
+        }
+        
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(entry.clone());
+        }
+        
+        // Nobody following is not an error
+        let _ = self.follow_tx.send(entry);
+    }
+    
+    /// The last `count` lines, oldest first, or everything buffered
+    pub fn tail(&self, count: Option<usize>) -> Vec<LogLine> {
+        let Ok(lines) = self.lines.lock() else {
+            return Vec::new();
+        };
+        
+        let skip = count.map_or(0, |count| lines.len().saturating_sub(count));
+        lines.iter().skip(skip).cloned().collect()
+    }
+    
+    /// Receive lines as they are recorded
+    pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
+        self.follow_tx.subscribe()
+    }
+    
+    /// Track the exit of the process now writing to this log, or of none
+    pub fn watch_exit(&self, exit: Option<watch::Receiver<Option<ProcessExit>>>) {
+        if let Ok(mut current) = self.exit.lock() {
+            *current = exit;
+        }
+    }
+    
+    /// How the process ended, waiting up to `wait` for it to. `None` if it is
+    /// still running or was stopped on purpose.
+    pub async fn exit_status(&self, wait: Duration) -> Option<ProcessExit> {
+        let mut exit = self.exit.lock().ok()?.clone()?;
+        let exit = tokio::time::timeout(wait, exit.wait_for(Option::is_some)).await.ok()?.ok()?;
+        *exit
+    }
+    
+    /// An output handler for `StdioTransport` that records into this log
+    pub fn handler(self: &Arc<Self>) -> OutputHandler {
+        let log = self.clone();
+        Arc::new(move |stream, line| log.push(stream, line))
+    }
+}
@@ -0,0 +1,83 @@
+//! Finding MCP servers advertised on the local network.
+//!
+//! A server reachable over HTTP, such as one on a home server, can announce
+//! itself with DNS-SD as an `_mcp._tcp` service, the way printers and media
+//! servers do. Its TXT record says how to reach it: `path` is the endpoint,
+//! `/sse` unless given, and `scheme=https` asks for TLS. `description` is
+//! shown with the server. `browse` listens for
+//! announcements for a while and returns a config for each server found,
+//! ready to be registered.
+
+use crate::server::McpServerConfig;
+use crate::types::McpError;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// The DNS-SD service type MCP servers advertise
+pub const SERVICE_TYPE: &str = "_mcp._tcp.local.";
+
+/// How long `browse` listens unless the caller asks otherwise
+pub const DEFAULT_BROWSE_TIME: Duration = Duration::from_secs(2);
+
+const DEFAULT_PATH: &str = "/sse";
+
+/// Configs for the servers that announced themselves within `duration`, in
+/// the order they answered
+pub async fn browse(duration: Duration) -> Result<Vec<McpServerConfig>, McpError> {
+    let daemon = ServiceDaemon::new().map_err(|e| McpError::TransportError(format!("Failed to start mDNS: {}", e)))?;
+    let events = daemon
+        .browse(SERVICE_TYPE)
+        .map_err(|e| McpError::TransportError(format!("Failed to browse for {}: {}", SERVICE_TYPE, e)))?;
+    
+    let deadline = Instant::now() + duration;
+    let mut configs: Vec<McpServerConfig> = Vec::new();
+    while let Ok(Ok(event)) = tokio::time::timeout_at(deadline, events.recv_async()).await {
+        let ServiceEvent::ServiceResolved(info) = event else {
+            continue;
+        };
+        // A server is resolved again as more of its addresses come in
+        let config = config_for(&info);
+        if !configs.iter().any(|known| known.name == config.name) {
+            configs.push(config);
+        }
+    }
+    
+    if let Err(e) = daemon.shutdown() {
+        tracing::warn!("Failed to stop mDNS: {}", e);
+    }
+    Ok(configs)
+}
+
+/// The config reaching an advertised server, named after its instance
+pub fn config_for(info: &ServiceInfo) -> McpServerConfig {
+    let fullname = info.get_fullname();
+    let name = fullname
+        .strip_suffix(SERVICE_TYPE)
+        .map(|instance| instance.trim_end_matches('.'))
+        .filter(|instance| !instance.is_empty())
+        .unwrap_or(fullname);
+    
+    let scheme = match info.get_property_val_str("scheme") {
+        Some("https") => "https",
+        _ => "http",
+    };
+    let path = info
+        .get_property_val_str("path")
+        .filter(|path| path.starts_with('/'))
+        .unwrap_or(DEFAULT_PATH);
+    // An address still works where local host names don't resolve
+    let host = info
+        .get_addresses_v4()
+        .into_iter()
+        .min()
+        .map(|address| address.to_string())
+        .unwrap_or_else(|| info.get_hostname().trim_end_matches('.').to_string());
+    
+    McpServerConfig {
+        name: name.to_string(),
+        command: format!("{}://{}:{}{}", scheme, host, info.get_port(), path),
+        description: info.get_property_val_str("description").map(str::to_string),
+        ..Default::default()
+    }
+}
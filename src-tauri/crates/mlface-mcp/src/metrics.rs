@@ -0,0 +1,160 @@
+//! Request counts, errors and latencies per server and method.
+//!
+//! Every `McpClient` built by the manager records each request it sends in
+//! the shared `RequestMetrics`: how long it took and, if it failed, the
+//! `McpError::kind` it failed with. Percentiles are computed over the most
+//! recent `LATENCY_SAMPLES` requests of each method, so they follow the
+//! server's current behavior rather than its whole history.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Latencies kept per server and method for the percentiles
+pub const LATENCY_SAMPLES: usize = 1024;
+
+/// Counts and latencies of one method, or of all methods of a server
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MethodMetrics {
+    pub requests: u64,
+    pub errors: u64,
+    /// Failed requests by `McpError::kind`
+    pub errors_by_kind: BTreeMap<String, u64>,
+    /// Share of requests that failed, from 0 to 1
+    pub error_rate: f64,
+    /// Latency percentiles in milliseconds; `None` before the first request
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ServerMetrics {
+    /// All methods together
+    pub total: MethodMetrics,
+    pub methods: BTreeMap<String, MethodMetrics>,
+}
+
+/// Metrics of every server that has been sent a request
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetricsReport {
+    pub servers: BTreeMap<String, ServerMetrics>,
+}
+
+#[derive(Default)]
+struct MethodStats {
+    requests: u64,
+    errors_by_kind: BTreeMap<String, u64>,
+    // Most recent latencies in milliseconds, oldest first
+    latencies: VecDeque<f64>,
+}
+
+impl MethodStats {
+    fn summary(&self) -> MethodMetrics {
+        let mut latencies: Vec<f64> = self.latencies.iter().copied().collect();
+        summarize(self.requests, self.errors_by_kind.clone(), &mut latencies)
+    }
+}
+
+/// Shared store the clients record their requests in
+#[derive(Default)]
+pub struct RequestMetrics {
+    servers: Mutex<HashMap<String, HashMap<String, MethodStats>>>,
+}
+
+impl RequestMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Record a request to `server` that took `elapsed` and, if it failed,
+    /// failed with an error of `error_kind`
+    pub fn record(&self, server: &str, method: &str, elapsed: Duration, error_kind: Option<&str>) {
+        let Ok(mut servers) = self.servers.lock() else {
+            return;
+        };
+        
+        let stats = servers
+            .entry(server.to_string())
+            .or_default()
+            .entry(method.to_string())
+            .or_default();
+        stats.requests += 1;
+        if let Some(kind) = error_kind {
+            *stats.errors_by_kind.entry(kind.to_string()).or_default() += 1;
+        }
+        if stats.latencies.len() == LATENCY_SAMPLES {
+            stats.latencies.pop_front();
+        }
+        stats.latencies.push_back(elapsed.as_secs_f64() * 1000.0);
+    }
+    
+    /// Metrics of `server`, or of all servers
+    pub fn report(&self, server: Option<&str>) -> MetricsReport {
+        let Ok(servers) = self.servers.lock() else {
+            return MetricsReport::default();
+        };
+        
+        let servers = servers
+            .iter()
+            .filter(|(name, _)| server.is_none_or(|server| server == name.as_str()))
+            .map(|(name, methods)| {
+                let mut requests = 0;
+                let mut errors_by_kind = BTreeMap::new();
+                let mut latencies = Vec::new();
+                for stats in methods.values() {
+                    requests += stats.requests;
+                    for (kind, count) in &stats.errors_by_kind {
+                        *errors_by_kind.entry(kind.clone()).or_default() += count;
+                    }
+                    latencies.extend(stats.latencies.iter().copied());
+                }
+                
+                let metrics = ServerMetrics {
+                    total: summarize(requests, errors_by_kind, &mut latencies),
+                    methods: methods.iter().map(|(method, stats)| (method.clone(), stats.summary())).collect(),
+                };
+                (name.clone(), metrics)
+            })
+            .collect();
+        
+        MetricsReport { servers }
+    }
+    
+    /// Start counting `server`, or all servers, from zero again
+    pub fn reset(&self, server: Option<&str>) {
+        if let Ok(mut servers) = self.servers.lock() {
+            match server {
+                Some(server) => {
+                    servers.remove(server);
+                }
+                None => servers.clear(),
+            }
+        }
+    }
+}
+
+fn summarize(requests: u64, errors_by_kind: BTreeMap<String, u64>, latencies: &mut [f64]) -> MethodMetrics {
+    let errors = errors_by_kind.values().sum();
+    latencies.sort_by(f64::total_cmp);
+    
+    MethodMetrics {
+        requests,
+        errors,
+        errors_by_kind,
+        error_rate: if requests == 0 { 0.0 } else { errors as f64 / requests as f64 },
+        p50_ms: percentile(latencies, 50),
+        p95_ms: percentile(latencies, 95),
+        p99_ms: percentile(latencies, 99),
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[f64], percent: usize) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    Some(sorted[rank - 1])
+}
@@ -0,0 +1,22 @@
+use crate::types::{JsonRpcRequest, JsonRpcResponse, McpError};
+use async_trait::async_trait;
+
+/// Interceptor that can observe or rewrite traffic passing through an `McpClient`.
+///
+/// Middleware runs in registration order for outgoing requests and in reverse
+/// order for incoming responses, so the first middleware registered sees the
+/// request first and the response last. Returning an error aborts the request.
+#[async_trait]
+pub trait McpMiddleware: Send + Sync {
+    /// Called before a request is handed to the transport. The request id must
+    /// not be changed, since it is used to correlate the response.
+    async fn on_request(&self, _request: &mut JsonRpcRequest) -> Result<(), McpError> {
+        Ok(())
+    }
+    
+    /// Called when the response to a request with the given method arrives,
+    /// before the result is extracted
+    async fn on_response(&self, _method: &str, _response: &mut JsonRpcResponse) -> Result<(), McpError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+//! Export of MCP operations to an OpenTelemetry collector, with the `otlp`
+//! feature.
+//!
+//! `OtlpExporter` is a `telemetry::SpanSink`: passed to `telemetry::init`, it
+//! collects every closed span, including the `request` span of each request
+//! and the `connect` span of each server start. `run` adds a span for every
+//! connection state change of a server and, every `OtlpConfig::interval`,
+//! posts the collected spans and the manager's request metrics to the
+//! collector as OTLP/HTTP JSON.
+
+use crate::metrics::{MethodMetrics, MetricsReport};
+use crate::server::{McpServerManager, ServerEvent};
+use crate::telemetry::{FinishedSpan, SpanSink};
+use crate::types::McpError;
+use serde_json::{json, Map, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
+
+/// How often spans and metrics are sent unless configured otherwise
+pub const DEFAULT_EXPORT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Spans kept while the collector is unreachable; older ones are dropped
+const MAX_PENDING_SPANS: usize = 4096;
+
+const SCOPE_NAME: &str = "mlface-mcp";
+
+// OTLP span kinds and status codes
+const SPAN_KIND_INTERNAL: u8 = 1;
+const SPAN_KIND_CLIENT: u8 = 3;
+const STATUS_ERROR: u8 = 2;
+
+/// Cumulative aggregation temporality of OTLP sums
+const CUMULATIVE: u8 = 2;
+
+#[derive(Debug, Clone)]
+pub struct OtlpConfig {
+    /// Base URL of the collector's OTLP/HTTP receiver, such as
+    /// `http://localhost:4318`
+    pub endpoint: String,
+    /// Sent with every export, such as an API key
+    pub headers: Vec<(String, String)>,
+    pub service_name: String,
+    pub interval: Duration,
+}
+
+impl OtlpConfig {
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            headers: Vec::new(),
+            service_name: "mlface".to_string(),
+            interval: DEFAULT_EXPORT_INTERVAL,
+        }
+    }
+    
+    /// Read the standard `OTEL_EXPORTER_OTLP_ENDPOINT`,
+    /// `OTEL_EXPORTER_OTLP_HEADERS` and `OTEL_SERVICE_NAME` variables. `None`
+    /// unless an endpoint is set.
+    pub fn from_env() -> Option<Self> {
+        let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok().filter(|e| !e.is_empty())?;
+        let mut config = Self::new(&endpoint);
+        
+        // `key=value` pairs separated by commas
+        if let Ok(headers) = std::env::var("OTEL_EXPORTER_OTLP_HEADERS") {
+            config.headers = headers
+                .split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .collect();
+        }
+        if let Ok(name) = std::env::var("OTEL_SERVICE_NAME") {
+            config.service_name = name;
+        }
+        Some(config)
+    }
+}
+
+/// Collects spans and sends them, with the request metrics, to a collector
+pub struct OtlpExporter {
+    config: OtlpConfig,
+    http: reqwest::Client,
+    spans: Mutex<Vec<FinishedSpan>>,
+    // When the cumulative metrics started counting, in Unix nanoseconds
+    started: u128,
+    next_event: AtomicU64,
+}
+
+impl SpanSink for OtlpExporter {
+    fn span_closed(&self, span: FinishedSpan) {
+        if let Ok(mut spans) = self.spans.lock() {
+            if spans.len() == MAX_PENDING_SPANS {
+                spans.remove(0);
+            }
+            spans.push(span);
+        }
+    }
+}
+
+impl OtlpExporter {
+    pub fn new(config: OtlpConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            spans: Mutex::new(Vec::new()),
+            started: now_nanos(),
+            next_event: AtomicU64::new(1),
+        }
+    }
+    
+    /// Export until the manager is dropped: a span for each connection state
+    /// change, and the collected spans and metrics every interval
+    pub async fn run(self: Arc<Self>, manager: Arc<McpServerManager>) {
+        let mut events = manager.subscribe_events();
+        let mut interval = tokio::time::interval(self.config.interval);
+        
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = self.export(&manager.metrics(None)).await {
+                        tracing::warn!("Failed to export to {}: {}", self.config.endpoint, e);
+                    }
+                }
+                event = events.recv() => match event {
+                    Ok(event) => self.record_event(&event),
+                    Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    }
+    
+    /// Record a server's connection state change as a span of no length
+    pub fn record_event(&self, event: &ServerEvent) {
+        let mut fields = Map::new();
+        fields.insert("server".to_string(), Value::from(event.server.as_str()));
+        fields.insert("state".to_string(), serde_json::to_value(event.state).unwrap_or(Value::Null));
+        if let Some(reason) = &event.reason {
+            fields.insert("reason".to_string(), Value::from(reason.as_str()));
+        }
+        
+        let now = now_nanos();
+        let id = self.next_event.fetch_add(1, Ordering::Relaxed);
+        self.span_closed(FinishedSpan {
+            trace_id: now ^ u128::from(id),
+            span_id: (now as u64) ^ id,
+            parent_span_id: None,
+            name: "server_state",
+            target: SCOPE_NAME,
+            fields,
+            start: now,
+            end: now,
+            failed: event.reason.is_some(),
+        });
+    }
+    
+    /// Spans collected since the last export
+    pub fn pending_spans(&self) -> Vec<FinishedSpan> {
+        self.spans.lock().map(|spans| spans.clone()).unwrap_or_default()
+    }
+    
+    /// Send the collected spans and `metrics` to the collector. Spans are
+    /// kept for the next attempt if sending them fails.
+    pub async fn export(&self, metrics: &MetricsReport) -> Result<(), McpError> {
+        let spans = self.spans.lock().map(|mut spans| std::mem::take(&mut *spans)).unwrap_or_default();
+        if !spans.is_empty() {
+            if let Err(e) = self.post("/v1/traces", &self.traces_body(&spans)).await {
+                if let Ok(mut pending) = self.spans.lock() {
+                    let mut spans = spans;
+                    spans.append(&mut pending);
+                    let excess = spans.len().saturating_sub(MAX_PENDING_SPANS);
+                    spans.drain(..excess);
+                    *pending = spans;
+                }
+                return Err(e);
+            }
+        }
+        
+        if !metrics.servers.is_empty() {
+            self.post("/v1/metrics", &self.metrics_body(metrics, now_nanos())).await?;
+        }
+        Ok(())
+    }
+    
+    async fn post(&self, path: &str, body: &Value) -> Result<(), McpError> {
+        let mut request = self.http.post(format!("{}{}", self.config.endpoint, path)).json(body);
+        for (key, value) in &self.config.headers {
+            request = request.header(key, value);
+        }
+        
+        let response = request.send().await.map_err(|e| McpError::TransportError(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(McpError::TransportError(format!("Collector answered {}", response.status())));
+        }
+        Ok(())
+    }
+    
+    fn resource(&self) -> Value {
+        json!({ "attributes": [attribute("service.name", &Value::from(self.config.service_name.as_str()))] })
+    }
+    
+    /// An `ExportTraceServiceRequest` in the OTLP JSON encoding
+    pub fn traces_body(&self, spans: &[FinishedSpan]) -> Value {
+        let spans: Vec<Value> = spans
+            .iter()
+            .map(|span| {
+                let mut encoded = json!({
+                    "traceId": format!("{:032x}", span.trace_id),
+                    "spanId": format!("{:016x}", span.span_id),
+                    "name": span.name,
+                    "kind": if span.name == "request" { SPAN_KIND_CLIENT } else { SPAN_KIND_INTERNAL },
+                    "startTimeUnixNano": span.start.to_string(),
+                    "endTimeUnixNano": span.end.to_string(),
+                    "attributes": span
+                        .fields
+                        .iter()
+                        .map(|(key, value)| attribute(key, value))
+                        .collect::<Vec<_>>(),
+                });
+                if let Some(parent) = span.parent_span_id {
+                    encoded["parentSpanId"] = Value::from(format!("{:016x}", parent));
+                }
+                if span.failed {
+                    encoded["status"] = json!({ "code": STATUS_ERROR });
+                }
+                encoded
+            })
+            .collect();
+        
+        json!({
+            "resourceSpans": [{
+                "resource": self.resource(),
+                "scopeSpans": [{ "scope": { "name": SCOPE_NAME }, "spans": spans }],
+            }],
+        })
+    }
+    
+    /// An `ExportMetricsServiceRequest` in the OTLP JSON encoding: request and
+    /// error counts as cumulative sums, latency percentiles as gauges, per
+    /// server and method
+    pub fn metrics_body(&self, report: &MetricsReport, now: u128) -> Value {
+        let mut requests = Vec::new();
+        let mut errors = Vec::new();
+        let mut latency = Vec::new();
+        
+        for (server, metrics) in &report.servers {
+            for (method, method_metrics) in &metrics.methods {
+                let labels = [("server", server.as_str()), ("method", method.as_str())];
+                requests.push(int_point(&labels, method_metrics.requests, self.started, now));
+                for (kind, count) in &method_metrics.errors_by_kind {
+                    let labels = [labels[0], labels[1], ("error.kind", kind.as_str())];
+                    errors.push(int_point(&labels, *count, self.started, now));
+                }
+                latency.extend(latency_points(&labels, method_metrics, now));
+            }
+        }
+        
+        let sum = |points: Vec<Value>| {
+            json!({ "aggregationTemporality": CUMULATIVE, "isMonotonic": true, "dataPoints": points })
+        };
+        json!({
+            "resourceMetrics": [{
+                "resource": self.resource(),
+                "scopeMetrics": [{
+                    "scope": { "name": SCOPE_NAME },
+                    "metrics": [
+                        { "name": "mcp.client.requests", "unit": "{request}", "sum": sum(requests) },
+                        { "name": "mcp.client.errors", "unit": "{request}", "sum": sum(errors) },
+                        { "name": "mcp.client.latency", "unit": "ms", "gauge": { "dataPoints": latency } },
+                    ],
+                }],
+            }],
+        })
+    }
+}
+
+fn int_point(labels: &[(&str, &str)], value: u64, start: u128, now: u128) -> Value {
+    json!({
+        "attributes": labels.iter().map(|(key, value)| attribute(key, &Value::from(*value))).collect::<Vec<_>>(),
+        "startTimeUnixNano": start.to_string(),
+        "timeUnixNano": now.to_string(),
+        "asInt": value.to_string(),
+    })
+}
+
+fn latency_points(labels: &[(&str, &str)], metrics: &MethodMetrics, now: u128) -> Vec<Value> {
+    [("p50", metrics.p50_ms), ("p95", metrics.p95_ms), ("p99", metrics.p99_ms)]
+        .into_iter()
+        .filter_map(|(quantile, value)| {
+            let mut attributes: Vec<Value> =
+                labels.iter().map(|(key, value)| attribute(key, &Value::from(*value))).collect();
+            attributes.push(attribute("quantile", &Value::from(quantile)));
+            Some(json!({ "attributes": attributes, "timeUnixNano": now.to_string(), "asDouble": value? }))
+        })
+        .collect()
+}
+
+/// An OTLP `KeyValue`
+fn attribute(key: &str, value: &Value) -> Value {
+    let value = match value {
+        Value::Bool(value) => json!({ "boolValue": value }),
+        Value::Number(number) if number.is_f64() => json!({ "doubleValue": number }),
+        Value::Number(number) => json!({ "intValue": number.to_string() }),
+        Value::String(value) => json!({ "stringValue": value }),
+        other => json!({ "stringValue": other.to_string() }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+fn now_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
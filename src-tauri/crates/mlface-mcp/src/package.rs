@@ -0,0 +1,455 @@
+//! Servers distributed as packages.
+//!
+//! Most community servers are published to npm or PyPI and meant to be run
+//! with `npx` or `uvx` rather than installed as an executable. A server
+//! configured with a `package` instead of a `command` is launched that way:
+//! the runner is looked up when the server starts and runs the package over
+//! stdio, installing it into the runner's cache on first use. When the
+//! runtime is missing the launch fails saying what to install, rather than
+//! with a bare "not found".
+//!
+//! Python packages are run with `uvx`, or `pipx run` where uv is not
+//! installed. Both give each package an environment of its own; variables
+//! that would let the user's Python setup leak into it are cleared.
+//!
+//! Apps started from a desktop shell often get a shorter `PATH` than a
+//! terminal, so the directories the runtimes' installers commonly use are
+//! searched too.
+//!
+//! A package is downloaded the first time its server starts, which can take
+//! longer than the handshake waits. `install` fetches it ahead of time with
+//! `npm install -g`, `uv tool install` or `pipx install`, passing the
+//! installer's output on as it comes.
+
+use crate::discovery::node_modules_of;
+use crate::integrity::resolve_executable;
+use crate::transport::OutputStream;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// Where Node.js can be downloaded
+pub const NODE_DOWNLOAD_URL: &str = "https://nodejs.org";
+
+/// Where uv, which provides `uvx`, can be downloaded
+pub const UV_DOWNLOAD_URL: &str = "https://docs.astral.sh/uv/";
+
+/// Set for Python packages unless the server's env says otherwise: no user
+/// site-packages and no inherited module path
+const PYTHON_ISOLATION: [(&str, &str); 2] = [("PYTHONNOUSERSITE", "1"), ("PYTHONPATH", "")];
+
+/// Which ecosystem a package comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageRuntime {
+    /// An npm package, run with `npx`
+    #[default]
+    Node,
+    /// A PyPI package, run with `uvx` or `pipx run`
+    Python,
+}
+
+/// The package a server runs. Written as just the name for an npm package,
+/// or as `{ "name", "runtime", "version" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "RawPackage", into = "RawPackage")]
+pub struct PackageSpec {
+    pub name: String,
+    pub runtime: PackageRuntime,
+    /// Exact version to run instead of the latest one
+    pub version: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawPackage {
+    Name(String),
+    Full {
+        name: String,
+        #[serde(default)]
+        runtime: PackageRuntime,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<String>,
+    },
+}
+
+impl From<RawPackage> for PackageSpec {
+    fn from(raw: RawPackage) -> Self {
+        match raw {
+            RawPackage::Name(name) => Self::npm(name),
+            RawPackage::Full { name, runtime, version } => Self { name, runtime, version },
+        }
+    }
+}
+
+impl From<PackageSpec> for RawPackage {
+    fn from(spec: PackageSpec) -> Self {
+        match (spec.runtime, spec.version) {
+            (PackageRuntime::Node, None) => Self::Name(spec.name),
+            (runtime, version) => Self::Full { name: spec.name, runtime, version },
+        }
+    }
+}
+
+impl PackageSpec {
+    /// The npm package `name`, at its latest version
+    pub fn npm(name: impl Into<String>) -> Self {
+        Self { name: name.into(), runtime: PackageRuntime::Node, version: None }
+    }
+    
+    /// The PyPI package `name`, at its latest version
+    pub fn python(name: impl Into<String>) -> Self {
+        Self { name: name.into(), runtime: PackageRuntime::Python, version: None }
+    }
+    
+    /// Pin the package to `version`
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+}
+
+/// A Node.js installation able to run npm packages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeRuntime {
+    pub node: PathBuf,
+    pub npx: PathBuf,
+    /// As `node --version` prints it, e.g. `v20.11.0`
+    pub version: String,
+}
+
+/// The runner Python packages are launched with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PythonRuntime {
+    /// `uvx`, or `pipx` where uv is not installed
+    pub launcher: PathBuf,
+    /// As the launcher's `--version` prints it
+    pub version: String,
+}
+
+/// How to launch a package: the program, its args, and environment variables
+/// to set unless the server's own env has them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageLaunch {
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+}
+
+/// Find Node.js and `npx`, failing with install instructions when either is
+/// missing. Runs `node --version`, so it blocks.
+pub fn detect_node() -> Result<NodeRuntime> {
+    let node = find_node_program("node")?;
+    let npx = find_node_program("npx")?;
+    
+    let version = version_of(&node)?;
+    Ok(NodeRuntime { node, npx, version })
+}
+
+/// Find `uvx`, or `pipx` without it, failing with install instructions when
+/// neither is there. Runs the launcher's `--version`, so it blocks.
+pub fn detect_python() -> Result<PythonRuntime> {
+    let launcher = find_python_launcher()?;
+    let version = version_of(&launcher)?;
+    Ok(PythonRuntime { launcher, version })
+}
+
+/// Launch `package` with `args` through the runner of its runtime
+pub fn launch(package: &PackageSpec, args: &[String]) -> Result<PackageLaunch> {
+    match package.runtime {
+        PackageRuntime::Node => {
+            let package = match &package.version {
+                Some(version) => format!("{}@{}", package.name, version),
+                None => package.name.clone(),
+            };
+            npm_launch(&package, args)
+        }
+        PackageRuntime::Python => python_launch(&package.name, package.version.as_deref(), args),
+    }
+}
+
+/// Launch the npm `package` with `args` through `npx`. `-y` keeps npx from
+/// asking before it installs the package, which would stall the handshake.
+pub fn npm_launch(package: &str, args: &[String]) -> Result<PackageLaunch> {
+    // npx is a script run by `node`, which has to be found on its `PATH` too
+    let node = find_node_program("node")?;
+    let npx = find_node_program("npx")?;
+    
+    let mut npx_args = vec!["-y".to_string(), package.to_string()];
+    npx_args.extend(args.iter().cloned());
+    
+    Ok(PackageLaunch {
+        command: npx.to_string_lossy().into_owned(),
+        args: npx_args,
+        env: path_with(node.parent()).map(|path| ("PATH".to_string(), path)).into_iter().collect(),
+    })
+}
+
+/// Launch the PyPI `package` with `args` through `uvx` or `pipx run`, which
+/// run the command of the package's name, at `version` if given
+pub fn python_launch(package: &str, version: Option<&str>, args: &[String]) -> Result<PackageLaunch> {
+    let launcher = find_python_launcher()?;
+    let pinned = version.map(|version| format!("{}=={}", package, version));
+    
+    let mut launcher_args = Vec::new();
+    if is_pipx(&launcher) {
+        launcher_args.push("run".to_string());
+        if let Some(pinned) = pinned {
+            launcher_args.extend(["--spec".to_string(), pinned]);
+        }
+    } else if let Some(pinned) = pinned {
+        launcher_args.extend(["--from".to_string(), pinned]);
+    }
+    launcher_args.push(package.to_string());
+    launcher_args.extend(args.iter().cloned());
+    
+    Ok(PackageLaunch {
+        command: launcher.to_string_lossy().into_owned(),
+        args: launcher_args,
+        env: PYTHON_ISOLATION.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+    })
+}
+
+/// Whether `package` is installed the way `install` would: in npm's global
+/// packages, or as a uv or pipx tool, at the pinned version if any. A Python
+/// package whose version can't be told counts when a command of its name is
+/// installed. Only looks at files, so nothing is run.
+pub fn is_installed(package: &PackageSpec) -> bool {
+    match installed_version(package) {
+        Some(installed) => package.version.as_deref().is_none_or(|version| version == installed),
+        None => {
+            package.runtime == PackageRuntime::Python && package.version.is_none() && find_program(&package.name).is_some()
+        }
+    }
+}
+
+/// The version of `package` installed by `install`, if it is. Only looks at
+/// files, so nothing is run.
+pub fn installed_version(package: &PackageSpec) -> Option<String> {
+    match package.runtime {
+        PackageRuntime::Node => {
+            let manifest = find_program("npm")
+                .and_then(|npm| npm.parent().and_then(node_modules_of))?
+                .join(&package.name)
+                .join("package.json");
+            let installed: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(manifest).ok()?).ok()?;
+            installed["version"].as_str().map(str::to_string)
+        }
+        PackageRuntime::Python => python_tool_dirs()
+            .into_iter()
+            .find_map(|tools| dist_info_version(&tools.join(&package.name), &package.name)),
+    }
+}
+
+/// Where uv and pipx keep the environment of each tool they install
+fn python_tool_dirs() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(dir) = std::env::var_os("UV_TOOL_DIR") {
+        candidates.push(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("PIPX_HOME") {
+        candidates.push(PathBuf::from(dir).join("venvs"));
+    }
+    #[cfg(windows)]
+    if let Some(data) = dirs::data_dir() {
+        candidates.push(data.join("uv").join("data").join("tools"));
+        candidates.push(data.join("pipx").join("venvs"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        candidates.push(home.join(".local").join("share").join("uv").join("tools"));
+        candidates.push(home.join(".local").join("share").join("pipx").join("venvs"));
+        // Where pipx kept them before 1.3
+        candidates.push(home.join(".local").join("pipx").join("venvs"));
+    }
+    candidates
+}
+
+/// The version of the distribution `name` installed in the environment
+/// `venv`, from the name of its `.dist-info` directory
+fn dist_info_version(venv: &Path, name: &str) -> Option<String> {
+    // Distribution names are normalized to lowercase with underscores there
+    let prefix = format!("{}-", name.to_lowercase().replace(['-', '.'], "_"));
+    let site_packages: Vec<PathBuf> = if cfg!(windows) {
+        vec![venv.join("Lib").join("site-packages")]
+    } else {
+        std::fs::read_dir(venv.join("lib"))
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join("site-packages"))
+            .collect()
+    };
+    site_packages.iter().find_map(|dir| {
+        std::fs::read_dir(dir).ok()?.filter_map(|entry| entry.ok()).find_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let version = file_name.strip_suffix(".dist-info")?;
+            let (dist, version) = version.split_at_checked(prefix.len())?;
+            (dist.to_lowercase() == prefix).then(|| version.to_string())
+        })
+    })
+}
+
+/// The command installing `package`: `npm install -g` for npm packages,
+/// `uv tool install` or `pipx install` for Python ones. An installed version
+/// is replaced.
+pub fn install_command(package: &PackageSpec) -> Result<PackageLaunch> {
+    match package.runtime {
+        PackageRuntime::Node => {
+            let node = find_node_program("node")?;
+            let npm = find_node_program("npm")?;
+            let package = match &package.version {
+                Some(version) => format!("{}@{}", package.name, version),
+                None => package.name.clone(),
+            };
+            Ok(PackageLaunch {
+                command: npm.to_string_lossy().into_owned(),
+                args: vec!["install".to_string(), "-g".to_string(), package],
+                env: path_with(node.parent()).map(|path| ("PATH".to_string(), path)).into_iter().collect(),
+            })
+        }
+        PackageRuntime::Python => {
+            let launcher = find_python_launcher()?;
+            let package = match &package.version {
+                Some(version) => format!("{}=={}", package.name, version),
+                None => package.name.clone(),
+            };
+            let (installer, args) = if is_pipx(&launcher) {
+                (launcher, vec!["install".to_string(), "--force".to_string(), package])
+            } else {
+                // uv is installed next to uvx
+                let uv = match launcher.extension() {
+                    Some(ext) => launcher.with_file_name("uv").with_extension(ext),
+                    None => launcher.with_file_name("uv"),
+                };
+                (uv, vec!["tool".to_string(), "install".to_string(), "--force".to_string(), package])
+            };
+            Ok(PackageLaunch {
+                command: installer.to_string_lossy().into_owned(),
+                args,
+                env: PYTHON_ISOLATION.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+            })
+        }
+    }
+}
+
+/// Install `package`, passing each line the installer prints to `on_output`.
+/// Dropping the future stops the installer.
+pub async fn install(package: &PackageSpec, on_output: impl Fn(OutputStream, &str)) -> Result<()> {
+    let launch = install_command(package)?;
+    let mut child = tokio::process::Command::new(&launch.command)
+        .args(&launch.args)
+        .envs(launch.env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run {}: {}", launch.command, e))?;
+    
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err(anyhow!("Failed to read the output of {}", launch.command));
+    };
+    let mut stdout = BufReader::new(stdout).lines();
+    let mut stderr = BufReader::new(stderr).lines();
+    let (mut stdout_open, mut stderr_open) = (true, true);
+    loop {
+        tokio::select! {
+            line = stdout.next_line(), if stdout_open => match line {
+                Ok(Some(line)) => on_output(OutputStream::Stdout, &line),
+                _ => stdout_open = false,
+            },
+            line = stderr.next_line(), if stderr_open => match line {
+                Ok(Some(line)) => on_output(OutputStream::Stderr, &line),
+                _ => stderr_open = false,
+            },
+            else => break,
+        }
+    }
+    
+    let status = child.wait().await?;
+    if !status.success() {
+        return Err(anyhow!("Installing {} failed: {} exited with {}", package.name, launch.command, status));
+    }
+    Ok(())
+}
+
+fn version_of(program: &Path) -> Result<String> {
+    let output = Command::new(program).arg("--version").output()?;
+    if !output.status.success() {
+        return Err(anyhow!("{} --version failed with {}", program.display(), output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn find_python_launcher() -> Result<PathBuf> {
+    find_program("uvx").or_else(|| find_program("pipx")).ok_or_else(|| {
+        anyhow!(
+            "Neither uvx nor pipx was found. Servers distributed as Python packages need uv, from {}, or pipx; install one and restart mlFace",
+            UV_DOWNLOAD_URL
+        )
+    })
+}
+
+fn is_pipx(launcher: &Path) -> bool {
+    launcher.file_stem().is_some_and(|stem| stem == "pipx")
+}
+
+fn find_node_program(program: &str) -> Result<PathBuf> {
+    find_program(program).ok_or_else(|| {
+        anyhow!(
+            "{} was not found. Servers distributed as npm packages need Node.js; install it from {} and restart mlFace",
+            program,
+            NODE_DOWNLOAD_URL
+        )
+    })
+}
+
+/// `program` on `PATH`, or in one of the directories runtimes are commonly
+/// installed to
+pub(crate) fn find_program(program: &str) -> Option<PathBuf> {
+    if let Ok(path) = resolve_executable(program) {
+        return Some(path);
+    }
+    
+    #[cfg(windows)]
+    let extensions = [".exe", ".cmd"];
+    #[cfg(not(windows))]
+    let extensions = [""];
+    
+    install_dirs()
+        .into_iter()
+        .flat_map(|dir| extensions.iter().map(move |ext| dir.join(format!("{}{}", program, ext))))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Directories runtimes and their tools are commonly installed to
+pub(crate) fn install_dirs() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    #[cfg(unix)]
+    candidates.extend(["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin"].map(PathBuf::from));
+    #[cfg(windows)]
+    if let Some(program_files) = std::env::var_os("ProgramFiles") {
+        candidates.push(PathBuf::from(program_files).join("nodejs"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        candidates.push(home.join(".volta").join("bin"));
+        // Where uv and pipx install themselves and their tools
+        candidates.push(home.join(".local").join("bin"));
+        candidates.push(home.join(".cargo").join("bin"));
+    }
+    candidates
+}
+
+/// The inherited `PATH` with `dir` in front, or `None` when it is on it
+/// already
+pub(crate) fn path_with(dir: Option<&Path>) -> Option<String> {
+    let dir = dir?;
+    let current = std::env::var_os("PATH").unwrap_or_default();
+    if std::env::split_paths(&current).any(|entry| entry == dir) {
+        return None;
+    }
+    
+    let entries = std::iter::once(dir.to_path_buf()).chain(std::env::split_paths(&current));
+    std::env::join_paths(entries).ok().map(|path| path.to_string_lossy().into_owned())
+}
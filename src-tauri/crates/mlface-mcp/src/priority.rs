@@ -0,0 +1,121 @@
+//! Letting what the user waits for go ahead of background work.
+//!
+//! A server with a concurrency limit queues requests beyond it. Health checks
+//! and refreshes the app makes on its own then hold up the tool the user just
+//! clicked. Requests run inside `with_priority(Priority::Background, ..)` are
+//! marked as such, the same way trace ids are passed along in a task-local,
+//! and a `RequestLimiter` hands free slots to interactive requests first.
+//! Requests of the same priority keep their order.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Whether someone is waiting for a request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// Work the app does on its own, such as health checks
+    Background,
+    /// Requests a user is waiting for
+    #[default]
+    Interactive,
+}
+
+tokio::task_local! {
+    static PRIORITY: Priority;
+}
+
+/// Run a future with its requests sent at `priority`
+pub async fn with_priority<F: Future>(priority: Priority, future: F) -> F::Output {
+    PRIORITY.scope(priority, future).await
+}
+
+/// Priority of the requests sent by the current task; interactive unless set
+pub fn current_priority() -> Priority {
+    PRIORITY.try_with(|priority| *priority).unwrap_or_default()
+}
+
+#[derive(Default)]
+struct LimiterState {
+    free: usize,
+    interactive: VecDeque<oneshot::Sender<RequestPermit>>,
+    background: VecDeque<oneshot::Sender<RequestPermit>>,
+}
+
+/// Limits how many requests are in flight at once, like a semaphore whose
+/// waiters are served by priority
+pub(crate) struct RequestLimiter {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+/// A slot taken from a `RequestLimiter`, given back when dropped
+pub(crate) struct RequestPermit {
+    // `None` once the slot was passed on
+    state: Option<Arc<Mutex<LimiterState>>>,
+}
+
+impl RequestLimiter {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(LimiterState { free: limit, ..Default::default() })),
+        }
+    }
+    
+    /// Wait for a free slot. Interactive requests are given one before any
+    /// background request waiting.
+    pub(crate) async fn acquire(&self, priority: Priority) -> RequestPermit {
+        let rx = {
+            let Ok(mut state) = self.state.lock() else {
+                // A poisoned limiter limits nothing
+                return RequestPermit { state: Some(self.state.clone()) };
+            };
+            let queued = match priority {
+                Priority::Interactive => state.interactive.len(),
+                Priority::Background => state.interactive.len() + state.background.len(),
+            };
+            if state.free > 0 && queued == 0 {
+                state.free -= 1;
+                return RequestPermit { state: Some(self.state.clone()) };
+            }
+            
+            let (tx, rx) = oneshot::channel();
+            match priority {
+                Priority::Interactive => state.interactive.push_back(tx),
+                Priority::Background => state.background.push_back(tx),
+            }
+            rx
+        };
+        
+        // A permit handed over after the waiter gave up is dropped along with
+        // the channel and gives its slot back
+        match rx.await {
+            Ok(permit) => permit,
+            Err(_) => RequestPermit { state: Some(self.state.clone()) },
+        }
+    }
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        let Some(limiter) = self.state.take() else {
+            return;
+        };
+        let Ok(mut state) = limiter.lock() else {
+            return;
+        };
+        
+        // Hand the slot to the first waiter still waiting
+        while let Some(waiter) = state.interactive.pop_front().or_else(|| state.background.pop_front()) {
+            let permit = RequestPermit { state: Some(limiter.clone()) };
+            match waiter.send(permit) {
+                Ok(()) => return,
+                // The waiter gave up; the slot is still ours to hand on
+                Err(mut permit) => permit.state = None,
+            }
+        }
+        state.free += 1;
+    }
+}
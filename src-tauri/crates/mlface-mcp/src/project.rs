@@ -0,0 +1,127 @@
+//! Servers a project brings along in its own config.
+//!
+//! A workspace folder can list servers in `.mlface/mcp.json`, in mlFace's own
+//! format, or in `.mcp.json`, with an `mcpServers` map like other clients
+//! use. They are scoped to the folder: it is their working directory and
+//! their root unless they say otherwise. A project config comes with the
+//! code, not from the user, so each server has to be approved before it
+//! runs. `ProjectApprovals` remembers the approval per folder, tied to the
+//! server's config so an edited server is asked about again.
+
+use crate::config::ConfigFile;
+use crate::import::{parse_mcp_servers, SkippedServer};
+use crate::server::McpServerConfig;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Where a project's servers are looked for, in order of preference
+pub const PROJECT_CONFIG_FILES: [&str; 2] = [".mlface/mcp.json", ".mcp.json"];
+
+/// File name approvals are kept under in the app's config directory
+pub const APPROVALS_FILE: &str = "project_approvals.json";
+
+/// The servers of a workspace folder
+#[derive(Debug, Clone)]
+pub struct ProjectConfig {
+    /// The file they were read from
+    pub path: PathBuf,
+    pub servers: Vec<McpServerConfig>,
+    /// Entries that cannot be used
+    pub skipped: Vec<SkippedServer>,
+}
+
+/// The project config of `dir`, if it has one
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+    PROJECT_CONFIG_FILES.iter().map(|file| dir.join(file)).find(|path| path.is_file())
+}
+
+/// Read the servers `dir` configures, scoped to it. `None` when it has no
+/// project config.
+pub fn load(dir: &Path) -> Result<Option<ProjectConfig>> {
+    let Some(path) = find_config(dir) else {
+        return Ok(None);
+    };
+    let content = std::fs::read_to_string(&path)?;
+    let (servers, skipped) = parse(&content).map_err(|e| anyhow!("Invalid project config {}: {}", path.display(), e))?;
+    
+    let folder = dir.to_string_lossy().to_string();
+    let servers = servers
+        .into_iter()
+        .map(|mut config| {
+            config.cwd.get_or_insert_with(|| folder.clone());
+            if config.roots.is_empty() {
+                config.roots.push(folder.clone());
+            }
+            config
+        })
+        .collect();
+    Ok(Some(ProjectConfig { path, servers, skipped }))
+}
+
+/// An `mcpServers` map, or an mlFace config file
+fn parse(content: &str) -> Result<(Vec<McpServerConfig>, Vec<SkippedServer>)> {
+    let document: serde_json::Value = serde_json::from_str(content)?;
+    if document.get("mcpServers").is_some() {
+        return parse_mcp_servers(content);
+    }
+    let mut servers: Vec<McpServerConfig> = ConfigFile::parse(content)?
+        .servers
+        .into_iter()
+        .map(|(name, mut config)| {
+            config.name = name;
+            config
+        })
+        .collect();
+    servers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok((servers, Vec::new()))
+}
+
+/// What an approval of `config` holds on to: a hash of the whole config, so
+/// any change to it needs a new approval
+pub fn fingerprint(config: &McpServerConfig) -> String {
+    let json = serde_json::to_vec(config).unwrap_or_default();
+    format!("{:x}", Sha256::digest(json))
+}
+
+/// The project servers the user allowed to run, by workspace folder
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectApprovals {
+    /// Fingerprints of the approved configs by server name, by folder
+    #[serde(default)]
+    pub projects: HashMap<String, HashMap<String, String>>,
+}
+
+impl ProjectApprovals {
+    /// The approvals saved at `path`; none when it is missing or broken
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+    
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+    
+    /// Whether `config` of the project in `dir` was approved as it is
+    pub fn is_approved(&self, dir: &Path, config: &McpServerConfig) -> bool {
+        self.projects
+            .get(&*dir.to_string_lossy())
+            .and_then(|servers| servers.get(&config.name))
+            .is_some_and(|approved| *approved == fingerprint(config))
+    }
+    
+    pub fn approve(&mut self, dir: &Path, config: &McpServerConfig) {
+        self.projects
+            .entry(dir.to_string_lossy().to_string())
+            .or_default()
+            .insert(config.name.clone(), fingerprint(config));
+    }
+}
@@ -0,0 +1,251 @@
+//! Token-bucket rate limits on tool calls.
+//!
+//! A server's `ServerSettings::rate_limit` caps the calls made to it, and
+//! the manager's global limit caps the calls made to all servers together.
+//! A call needs a token from both buckets. When one is empty the call either
+//! waits for the token or fails with `McpError::RateLimited`, as the limit
+//! that ran out says.
+
+use crate::types::McpError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// What happens to a call over the limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitMode {
+    /// Wait until the call is allowed
+    #[default]
+    Queue,
+    /// Fail the call right away
+    Reject,
+}
+
+impl LimitMode {
+    fn is_queue(&self) -> bool {
+        *self == Self::Queue
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Calls allowed per minute, on average
+    pub per_minute: u32,
+    /// Calls allowed at once after a quiet period; defaults to `per_minute`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+    #[serde(default, skip_serializing_if = "LimitMode::is_queue")]
+    pub when_limited: LimitMode,
+}
+
+impl RateLimit {
+    fn capacity(&self) -> f64 {
+        f64::from(self.burst.unwrap_or(self.per_minute).max(1))
+    }
+    
+    fn tokens_per_sec(&self) -> f64 {
+        f64::from(self.per_minute.max(1)) / 60.0
+    }
+}
+
+/// Current state of one limit
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitUsage {
+    pub limit: RateLimit,
+    /// Calls that can be made right now
+    pub available: u32,
+    /// Calls waiting for a token
+    pub waiting: u32,
+    pub allowed: u64,
+    pub rejected: u64,
+}
+
+/// Usage of the global limit and of every server that has a limit
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitReport {
+    pub global: Option<RateLimitUsage>,
+    pub servers: BTreeMap<String, RateLimitUsage>,
+}
+
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+    waiting: u32,
+    allowed: u64,
+    rejected: u64,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.capacity(),
+            updated: Instant::now(),
+            waiting: 0,
+            allowed: 0,
+            rejected: 0,
+        }
+    }
+    
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.tokens_per_sec()).min(self.limit.capacity());
+        self.updated = now;
+    }
+    
+    /// How long until a token is available; zero if one is
+    fn wait_time(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.limit.tokens_per_sec())
+        }
+    }
+    
+    fn usage(&mut self, now: Instant) -> RateLimitUsage {
+        self.refill(now);
+        RateLimitUsage {
+            limit: self.limit,
+            available: self.tokens.floor() as u32,
+            waiting: self.waiting,
+            allowed: self.allowed,
+            rejected: self.rejected,
+        }
+    }
+}
+
+/// Keeps the buckets of the global limit and of each server
+#[derive(Default)]
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Default)]
+struct LimiterState {
+    global: Option<Bucket>,
+    servers: HashMap<String, Bucket>,
+}
+
+impl LimiterState {
+    /// The buckets that apply to `server`, created or reset to match `limit`
+    fn buckets(&mut self, server: &str, limit: Option<RateLimit>) -> Vec<&mut Bucket> {
+        match limit {
+            Some(limit) => {
+                let bucket = self.servers.entry(server.to_string()).or_insert_with(|| Bucket::new(limit));
+                if bucket.limit != limit {
+                    *bucket = Bucket::new(limit);
+                }
+            }
+            None => {
+                self.servers.remove(server);
+            }
+        }
+        
+        self.global.iter_mut().chain(self.servers.get_mut(server)).collect()
+    }
+}
+
+/// Counts a call as waiting until it gets its token or gives up
+struct Waiting<'a> {
+    limiter: &'a RateLimiter,
+    server: String,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.limiter.state.lock() {
+            let state = &mut *state;
+            for bucket in state.global.iter_mut().chain(state.servers.get_mut(&self.server)) {
+                bucket.waiting = bucket.waiting.saturating_sub(1);
+            }
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Replace the limit on all calls together; `None` removes it
+    pub fn set_global(&self, limit: Option<RateLimit>) {
+        if let Ok(mut state) = self.state.lock() {
+            let unchanged = state.global.as_ref().map(|bucket| bucket.limit) == limit;
+            if !unchanged {
+                state.global = limit.map(Bucket::new);
+            }
+        }
+    }
+    
+    /// Forget the bucket of a server that is no longer registered
+    pub fn remove(&self, server: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            state.servers.remove(server);
+        }
+    }
+    
+    /// Take a token for a call to `server`, which is limited by `limit`,
+    /// waiting for it unless a limit that ran out rejects calls
+    pub async fn acquire(&self, server: &str, limit: Option<RateLimit>) -> Result<(), McpError> {
+        let mut waiting: Option<Waiting> = None;
+        
+        loop {
+            let wait = {
+                let mut state = self.state.lock().map_err(|e| McpError::internal(e.to_string()))?;
+                let now = Instant::now();
+                let mut buckets = state.buckets(server, limit);
+                let waits: Vec<Duration> = buckets.iter_mut().map(|bucket| bucket.wait_time(now)).collect();
+                let wait = waits.iter().copied().max().unwrap_or(Duration::ZERO);
+                
+                if wait.is_zero() {
+                    for bucket in buckets {
+                        bucket.tokens -= 1.0;
+                        bucket.allowed += 1;
+                    }
+                    return Ok(());
+                }
+                
+                let rejects = buckets
+                    .iter()
+                    .zip(&waits)
+                    .any(|(bucket, wait)| !wait.is_zero() && bucket.limit.when_limited == LimitMode::Reject);
+                if rejects {
+                    for bucket in buckets {
+                        bucket.rejected += 1;
+                    }
+                    return Err(McpError::RateLimited(format!(
+                        "too many calls to {}, try again in {} ms",
+                        server,
+                        wait.as_millis()
+                    )));
+                }
+                
+                if waiting.is_none() {
+                    for bucket in buckets {
+                        bucket.waiting += 1;
+                    }
+                }
+                wait
+            };
+            
+            waiting.get_or_insert_with(|| Waiting { limiter: self, server: server.to_string() });
+            tokio::time::sleep(wait).await;
+        }
+    }
+    
+    pub fn usage(&self) -> RateLimitReport {
+        let Ok(mut state) = self.state.lock() else {
+            return RateLimitReport { global: None, servers: BTreeMap::new() };
+        };
+        let now = Instant::now();
+        
+        RateLimitReport {
+            global: state.global.as_mut().map(|bucket| bucket.usage(now)),
+            servers: state
+                .servers
+                .iter_mut()
+                .map(|(server, bucket)| (server.clone(), bucket.usage(now)))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! Searching the public MCP server registry and installing its servers.
+//!
+//! The registry at `registry.modelcontextprotocol.io` lists servers with the
+//! packages and remote endpoints they can be run from. It grows faster than
+//! the curated catalog, so it is browsed live: `Registry::search` and
+//! `Registry::server` ask it directly and cache every server they see. When
+//! the registry can't be reached the cache answers instead, flagged as
+//! offline, so servers seen before can still be found and installed.
+//!
+//! A server runs from its first npm or PyPI package with a stdio transport,
+//! installed like a catalog entry, or else from its first remote endpoint.
+//! A header the endpoint takes is kept in the `SecretStore` like a secret
+//! env var and sent as the server's `auth`.
+
+use crate::catalog::{CatalogEntry, CatalogEnvVar};
+use crate::package::PackageSpec;
+use crate::secrets::{validate_secret_name, SecretStore};
+use crate::server::{McpServerConfig, ServerAuth};
+use crate::types::McpError;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The public registry
+pub const REGISTRY_URL: &str = "https://registry.modelcontextprotocol.io";
+
+/// File name the app caches registry servers under
+pub const REGISTRY_CACHE_FILE: &str = "registry.json";
+
+/// How long `Registry::server` trusts a cached server before asking again
+pub const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// How many servers a search returns unless the caller asks otherwise
+pub const DEFAULT_SEARCH_LIMIT: u32 = 30;
+
+/// A server as the registry describes it; fields mlFace doesn't use are
+/// dropped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryServer {
+    /// Namespaced, like `io.github.user/weather`
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub website_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<RegistryRepository>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<RegistryPackage>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remotes: Vec<RegistryRemote>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryRepository {
+    pub url: String,
+}
+
+/// A package the server is published as
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryPackage {
+    /// `npm`, `pypi`, `oci` and so on
+    pub registry_type: String,
+    pub identifier: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<RegistryTransport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub package_arguments: Vec<RegistryArgument>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub environment_variables: Vec<RegistryInput>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryTransport {
+    /// `stdio`, `sse` or `streamable-http`
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// An argument passed to a package: a `positional` value, or a `named` one
+/// following its name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryArgument {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// What to put in place of an argument without a value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_hint: Option<String>,
+}
+
+/// An environment variable or header the user provides at install
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub is_required: bool,
+    #[serde(default)]
+    pub is_secret: bool,
+}
+
+/// An endpoint the server is hosted at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryRemote {
+    /// `sse` or `streamable-http`
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<RegistryInput>,
+}
+
+impl RegistryServer {
+    /// The part of the name after the namespace, which servers installed
+    /// from it are named after
+    pub fn short_name(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+    
+    /// The name usable as a catalog id and a secret name prefix
+    pub fn id(&self) -> String {
+        self.name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' { c } else { '.' })
+            .collect()
+    }
+    
+    /// The server as a catalog entry, if it has a package mlFace can run
+    pub fn catalog_entry(&self) -> Option<CatalogEntry> {
+        let runs_on_stdio = |package: &&RegistryPackage| package.transport.as_ref().is_none_or(|transport| transport.kind == "stdio");
+        let (package, spec) = self.packages.iter().filter(runs_on_stdio).find_map(|package| {
+            let spec = match package.registry_type.as_str() {
+                "npm" => PackageSpec::npm(&package.identifier),
+                "pypi" => PackageSpec::python(&package.identifier),
+                _ => return None,
+            };
+            Some((package, spec))
+        })?;
+        
+        let mut args = Vec::new();
+        let mut hints = Vec::new();
+        for argument in &package.package_arguments {
+            match (&argument.value, argument.kind.as_str(), &argument.name) {
+                (Some(value), "named", Some(name)) => args.extend([name.clone(), value.clone()]),
+                (Some(value), _, _) => args.push(value.clone()),
+                (None, _, _) => hints.extend(argument.value_hint.clone().or_else(|| argument.name.clone())),
+            }
+        }
+        
+        Some(CatalogEntry {
+            id: self.id(),
+            name: self.short_name().to_string(),
+            description: self.description.clone(),
+            package: spec,
+            args,
+            args_hint: (!hints.is_empty()).then(|| hints.join(" ")),
+            env: package.environment_variables.iter().map(env_var).collect(),
+            homepage: self
+                .website_url
+                .clone()
+                .or_else(|| self.repository.as_ref().map(|repository| repository.url.clone())),
+            tags: Vec::new(),
+        })
+    }
+    
+    /// A config for this server, named `name` or after it. `values` holds
+    /// the user's values for its env vars, or its header, by name; secret
+    /// ones are stored in `secrets`. `args` are added after the package's
+    /// own.
+    pub fn install(
+        &self,
+        name: Option<&str>,
+        args: &[String],
+        values: &HashMap<String, String>,
+        secrets: Option<&dyn SecretStore>,
+    ) -> Result<McpServerConfig> {
+        let name = name.map(str::trim).filter(|name| !name.is_empty()).unwrap_or(self.short_name());
+        if let Some(entry) = self.catalog_entry() {
+            return entry.install(Some(name), args, values, secrets);
+        }
+        
+        let remote = self
+            .remotes
+            .iter()
+            .find(|remote| remote.kind == "sse" || remote.kind == "streamable-http")
+            .ok_or_else(|| anyhow!("{} has no npm or PyPI package or remote endpoint to run", self.name))?;
+        // A config sends one header at most
+        if remote.headers.len() > 1 {
+            return Err(anyhow!("{} needs more headers than mlFace can send", self.name));
+        }
+        let auth = match remote.headers.first() {
+            Some(header) => match values.get(&header.name).map(|value| value.trim()).filter(|value| !value.is_empty()) {
+                Some(value) => {
+                    let secret = format!("{}.{}", self.id(), header.name);
+                    validate_secret_name(&secret)?;
+                    let store = secrets.ok_or_else(|| anyhow!("{} needs a secret store to keep {}", self.name, header.name))?;
+                    store.set(&secret, value)?;
+                    Some(ServerAuth::Header { name: header.name.clone(), value_ref: secret })
+                }
+                None if header.is_required => return Err(anyhow!("{} needs {}", self.short_name(), header.name)),
+                None => None,
+            },
+            None => None,
+        };
+        
+        Ok(McpServerConfig {
+            name: name.to_string(),
+            command: remote.url.clone(),
+            auth,
+            description: Some(self.description.clone()),
+            ..Default::default()
+        })
+    }
+}
+
+fn env_var(input: &RegistryInput) -> CatalogEnvVar {
+    CatalogEnvVar {
+        name: input.name.clone(),
+        description: input.description.clone(),
+        secret: input.is_secret,
+        required: input.is_required,
+    }
+}
+
+/// Registry servers seen before, by name, with when each was fetched
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RegistryCache {
+    pub servers: Vec<CachedServer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedServer {
+    /// Seconds since the epoch
+    pub fetched: u64,
+    pub server: RegistryServer,
+}
+
+impl RegistryCache {
+    /// The cache at `path`; empty when it is missing or broken
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+    
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+    
+    pub fn get(&self, name: &str) -> Option<&CachedServer> {
+        self.servers.iter().find(|cached| cached.server.name == name)
+    }
+    
+    /// Remember `servers` as fetched now, replacing older copies
+    pub fn insert(&mut self, servers: &[RegistryServer]) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or_default();
+        for server in servers {
+            let cached = CachedServer { fetched: now, server: server.clone() };
+            match self.servers.iter_mut().find(|known| known.server.name == server.name) {
+                Some(known) => *known = cached,
+                None => self.servers.push(cached),
+            }
+        }
+    }
+    
+    /// Servers whose name or description contain every word of `query`,
+    /// ignoring case, like `Catalog::search`
+    pub fn search(&self, query: &str, limit: usize) -> Vec<RegistryServer> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        self.servers
+            .iter()
+            .map(|cached| &cached.server)
+            .filter(|server| {
+                let text = format!("{} {}", server.name, server.description).to_lowercase();
+                words.iter().all(|word| text.contains(word.as_str()))
+            })
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+/// The servers a search found
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegistrySearch {
+    pub servers: Vec<RegistryServer>,
+    /// The registry couldn't be reached, so the servers come from the cache
+    pub offline: bool,
+}
+
+/// The registry at `url`, cached at `cache`
+#[derive(Debug, Clone)]
+pub struct Registry {
+    pub url: String,
+    pub cache: Option<PathBuf>,
+}
+
+impl Registry {
+    pub fn new(url: impl Into<String>, cache: Option<PathBuf>) -> Self {
+        Self { url: url.into(), cache }
+    }
+    
+    /// The latest version of the servers matching `query`, up to `limit`
+    pub async fn search(&self, client: &reqwest::Client, query: &str, limit: u32) -> Result<RegistrySearch, McpError> {
+        let request = client
+            .get(format!("{}/v0/servers", self.url.trim_end_matches('/')))
+            .query(&[("search", query), ("version", "latest"), ("limit", &limit.to_string())]);
+        match get(request).await.and_then(servers) {
+            Ok(servers) => {
+                self.remember(&servers).await;
+                Ok(RegistrySearch { servers, offline: false })
+            }
+            Err(e) => {
+                tracing::warn!("Searching the MCP registry offline: {}", e);
+                let query = query.to_string();
+                let servers = self.cached(move |cache| cache.search(&query, limit as usize)).await;
+                Ok(RegistrySearch { servers, offline: true })
+            }
+        }
+    }
+    
+    /// The latest version of the server `name`, from the cache if it was
+    /// fetched within `CACHE_TTL`, or if the registry can't be reached
+    pub async fn server(&self, client: &reqwest::Client, name: &str) -> Result<RegistryServer, McpError> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or_default();
+        let key = name.to_string();
+        let cached = self.cached(move |cache| cache.get(&key).cloned()).await;
+        if let Some(cached) = &cached {
+            if now.saturating_sub(cached.fetched) < CACHE_TTL.as_secs() {
+                return Ok(cached.server.clone());
+            }
+        }
+        
+        let url = format!("{}/v0/servers/{}/versions/latest", self.url.trim_end_matches('/'), name.replace('/', "%2F"));
+        match get(client.get(url)).await.and_then(|document| server(document.get("server").cloned().unwrap_or(document))) {
+            Ok(server) => {
+                self.remember(std::slice::from_ref(&server)).await;
+                Ok(server)
+            }
+            Err(e) => match cached {
+                Some(cached) => {
+                    tracing::warn!("Using the cached {} from the MCP registry: {}", name, e);
+                    Ok(cached.server)
+                }
+                None => Err(e),
+            },
+        }
+    }
+    
+    /// What `read` takes from the cache, read off the async runtime
+    async fn cached<T: Default + Send + 'static>(&self, read: impl FnOnce(&RegistryCache) -> T + Send + 'static) -> T {
+        let path = self.cache.clone();
+        tokio::task::spawn_blocking(move || read(&path.map(|path| RegistryCache::load(&path)).unwrap_or_default()))
+            .await
+            .unwrap_or_default()
+    }
+    
+    async fn remember(&self, servers: &[RegistryServer]) {
+        let Some(path) = self.cache.clone() else {
+            return;
+        };
+        let servers = servers.to_vec();
+        let saved = tokio::task::spawn_blocking(move || {
+            let mut cache = RegistryCache::load(&path);
+            cache.insert(&servers);
+            cache.save(&path)
+        })
+        .await;
+        if let Ok(Err(e)) = saved {
+            tracing::warn!("Failed to cache MCP registry servers: {}", e);
+        }
+    }
+}
+
+async fn get(request: reqwest::RequestBuilder) -> Result<Value, McpError> {
+    let fail = |e: reqwest::Error| McpError::TransportError(format!("Failed to reach the MCP registry: {}", e));
+    request
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(fail)?
+        .json()
+        .await
+        .map_err(fail)
+}
+
+fn server(document: Value) -> Result<RegistryServer, McpError> {
+    serde_json::from_value(document).map_err(|e| McpError::ParseError {
+        message: format!("Invalid MCP registry server: {}", e),
+        data: None,
+    })
+}
+
+/// The servers of a list response, each wrapped with the registry's own
+/// metadata
+fn servers(mut document: Value) -> Result<Vec<RegistryServer>, McpError> {
+    let Some(Value::Array(items)) = document.get_mut("servers").map(Value::take) else {
+        return Err(McpError::ParseError { message: "The MCP registry did not list servers".to_string(), data: None });
+    };
+    items
+        .into_iter()
+        .map(|mut item| match item.get_mut("server").map(Value::take) {
+            Some(server_document) => server(server_document),
+            None => server(item),
+        })
+        .collect()
+}
@@ -0,0 +1,136 @@
+//! Passing large resources on to the frontend piece by piece.
+//!
+//! MCP has no ranged reads: `resources/read` answers with the whole resource
+//! in one JSON-RPC message, and the client holds all of it in memory once the
+//! read is done. Nothing here changes that. What can be avoided is copying it
+//! again on the way out: `chunks` cuts the contents of a finished read into
+//! pieces of a bounded size, in order, so they can be sent to the frontend one
+//! event at a time, and `save` writes them to a file, decoding base64 image
+//! data item by item instead of building the whole file in memory as well.
+
+use crate::types::{Content, ReadResourceResult};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Serialize;
+use std::io;
+use std::iter::Enumerate;
+use std::path::Path;
+use std::vec;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+/// Size of the pieces a resource is cut into unless the caller asks otherwise
+pub const DEFAULT_CHUNK_BYTES: usize = 256 * 1024;
+
+/// Smallest piece size: base64 data is cut at multiples of 4 bytes, and no
+/// character takes more than 4 bytes of UTF-8
+const MIN_CHUNK_BYTES: usize = 4;
+
+/// A piece of one content item of a resource. Joining the pieces of an item
+/// in order gives back its text or base64 data.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceChunk {
+    /// Which content item of the resource the piece belongs to
+    pub index: usize,
+    /// Where the piece starts in the item's text or data, in bytes
+    pub offset: usize,
+    /// The item with its text or data cut down to this piece
+    pub content: Content,
+    /// Whether this is the last piece of the item
+    pub last: bool,
+}
+
+/// Cut the contents of `result` into pieces of at most `chunk_bytes` bytes of
+/// text or base64 data. Text is only cut between characters and base64 data
+/// only between whole groups, so every piece can be used on its own. Each item
+/// is released once all of its pieces were taken.
+pub fn chunks(result: ReadResourceResult, chunk_bytes: usize) -> Chunks {
+    Chunks {
+        contents: result.content.into_iter().enumerate(),
+        current: None,
+        chunk_bytes: chunk_bytes.max(MIN_CHUNK_BYTES),
+    }
+}
+
+/// The item being cut into pieces
+struct Item {
+    index: usize,
+    data: String,
+    // Set for image data, which is base64
+    mime_type: Option<String>,
+    offset: usize,
+}
+
+/// Iterator returned by `chunks`
+pub struct Chunks {
+    contents: Enumerate<vec::IntoIter<Content>>,
+    current: Option<Item>,
+    chunk_bytes: usize,
+}
+
+impl Iterator for Chunks {
+    type Item = ResourceChunk;
+    
+    fn next(&mut self) -> Option<ResourceChunk> {
+        if self.current.is_none() {
+            let (index, content) = self.contents.next()?;
+            let (data, mime_type) = match content {
+                Content::Text { text } => (text, None),
+                Content::Image { mime_type, data } => (data, Some(mime_type)),
+                // Nothing to cut
+                content => return Some(ResourceChunk { index, offset: 0, content, last: true }),
+            };
+            self.current = Some(Item { index, data, mime_type, offset: 0 });
+        }
+        let item = self.current.as_mut()?;
+        
+        let start = item.offset;
+        let mut end = (start + self.chunk_bytes).min(item.data.len());
+        if end < item.data.len() {
+            if item.mime_type.is_some() {
+                end -= (end - start) % 4;
+            }
+            while !item.data.is_char_boundary(end) {
+                end -= 1;
+            }
+        }
+        item.offset = end;
+        
+        let piece = item.data[start..end].to_string();
+        let chunk = ResourceChunk {
+            index: item.index,
+            offset: start,
+            content: match &item.mime_type {
+                Some(mime_type) => Content::Image { mime_type: mime_type.clone(), data: piece },
+                None => Content::Text { text: piece },
+            },
+            last: end == item.data.len(),
+        };
+        if chunk.last {
+            self.current = None;
+        }
+        Some(chunk)
+    }
+}
+
+/// Write the contents of `result` to `path`, one item after the other: text as
+/// UTF-8 and image data decoded from base64. Embedded resources have no data
+/// of their own and are skipped. Returns the number of bytes written.
+pub async fn save(result: ReadResourceResult, path: &Path) -> io::Result<u64> {
+    let mut file = BufWriter::new(tokio::fs::File::create(path).await?);
+    let mut written = 0;
+    
+    for chunk in chunks(result, DEFAULT_CHUNK_BYTES) {
+        let bytes = match chunk.content {
+            Content::Text { text } => text.into_bytes(),
+            Content::Image { data, .. } => STANDARD
+                .decode(data.trim_end())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Content::EmbeddedResource { .. } => continue,
+        };
+        file.write_all(&bytes).await?;
+        written += bytes.len() as u64;
+    }
+    
+    file.flush().await?;
+    Ok(written)
+}
@@ -0,0 +1,208 @@
+//! Filesystem roots a server works in.
+//!
+//! A server configured with roots is told about them through `roots/list`,
+//! and the client holds it to them: `RootsMiddleware` checks the `file://`
+//! URIs of resource reads and the paths in tool arguments before they are
+//! sent, and the URIs of the contents a read returns. Only absolute paths,
+//! `~/` paths and `file://` URIs are recognized in arguments; relative paths
+//! are left to the server. How a violation is handled depends on the
+//! server's `RootsEnforcement`.
+
+use crate::middleware::McpMiddleware;
+use crate::types::{JsonRpcRequest, JsonRpcResponse, McpError};
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+
+/// What happens when a request or result refers to a path outside the roots
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RootsEnforcement {
+    /// Only advertise the roots
+    Off,
+    /// Log the violation and let the request through
+    Warn,
+    /// Fail the request
+    #[default]
+    Strict,
+}
+
+/// Directories a server may refer to
+#[derive(Debug, Clone, Default)]
+pub struct Roots {
+    dirs: Vec<PathBuf>,
+}
+
+impl Roots {
+    pub fn new<P: AsRef<Path>>(dirs: impl IntoIterator<Item = P>) -> Self {
+        Self {
+            dirs: dirs.into_iter().map(|dir| normalize(&expand_home(dir.as_ref()))).collect(),
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.dirs.is_empty()
+    }
+    
+    /// The result of a `roots/list` request
+    pub fn list(&self) -> Value {
+        let roots: Vec<Value> = self
+            .dirs
+            .iter()
+            .filter_map(|dir| {
+                let uri = Url::from_directory_path(dir).ok()?;
+                let name = dir.file_name().map(|name| name.to_string_lossy().into_owned());
+                Some(json!({ "uri": uri.as_str(), "name": name }))
+            })
+            .collect();
+        json!({ "roots": roots })
+    }
+    
+    /// Whether `path` is one of the roots or inside one
+    pub fn contains(&self, path: &Path) -> bool {
+        let path = normalize(&expand_home(path));
+        self.dirs.iter().any(|dir| path.starts_with(dir))
+    }
+    
+    /// Check a resource URI. Only `file://` URIs refer to the filesystem.
+    pub fn check_uri(&self, uri: &str) -> Result<(), String> {
+        let Ok(url) = Url::parse(uri) else {
+            return Ok(());
+        };
+        if url.scheme() != "file" {
+            return Ok(());
+        }
+        
+        match url.to_file_path() {
+            Ok(path) if self.contains(&path) => Ok(()),
+            _ => Err(format!("{} is outside the server's roots", uri)),
+        }
+    }
+    
+    /// Check every path-like string in tool arguments
+    pub fn check_arguments(&self, arguments: &Value) -> Result<(), String> {
+        match arguments {
+            Value::String(value) => self.check_argument(value),
+            Value::Array(values) => values.iter().try_for_each(|value| self.check_arguments(value)),
+            Value::Object(map) => map.values().try_for_each(|value| self.check_arguments(value)),
+            _ => Ok(()),
+        }
+    }
+    
+    fn check_argument(&self, value: &str) -> Result<(), String> {
+        if value.starts_with("file://") {
+            return self.check_uri(value);
+        }
+        
+        let path = Path::new(value);
+        if (path.is_absolute() || value.starts_with("~/")) && !self.contains(path) {
+            return Err(format!("{} is outside the server's roots", value));
+        }
+        Ok(())
+    }
+}
+
+/// Applies a server's roots to its traffic
+pub struct RootsMiddleware {
+    roots: Roots,
+    enforcement: RootsEnforcement,
+    label: String,
+}
+
+impl RootsMiddleware {
+    /// `label` names the server in log lines
+    pub fn new(roots: Roots, enforcement: RootsEnforcement, label: &str) -> Self {
+        Self {
+            roots,
+            enforcement,
+            label: label.to_string(),
+        }
+    }
+    
+    fn enforce(&self, checked: Result<(), String>) -> Result<(), McpError> {
+        let Err(violation) = checked else {
+            return Ok(());
+        };
+        match self.enforcement {
+            RootsEnforcement::Off => Ok(()),
+            RootsEnforcement::Warn => {
+                tracing::warn!("[{}] {}", self.label, violation);
+                Ok(())
+            }
+            RootsEnforcement::Strict => Err(McpError::Denied(violation)),
+        }
+    }
+}
+
+#[async_trait]
+impl McpMiddleware for RootsMiddleware {
+    async fn on_request(&self, request: &mut JsonRpcRequest) -> Result<(), McpError> {
+        let Some(params) = &request.params else {
+            return Ok(());
+        };
+        match request.method.as_str() {
+            "tools/call" => match params.get("arguments") {
+                Some(arguments) => self.enforce(self.roots.check_arguments(arguments)),
+                None => Ok(()),
+            },
+            "resources/read" => match params.get("uri").and_then(Value::as_str) {
+                Some(uri) => self.enforce(self.roots.check_uri(uri)),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+    
+    async fn on_response(&self, method: &str, response: &mut JsonRpcResponse) -> Result<(), McpError> {
+        if method != "resources/read" {
+            return Ok(());
+        }
+        
+        // A server may answer with contents other than the ones asked for.
+        // Answers from dated revisions have been rewritten to `content` by now.
+        let result = response.result.as_ref();
+        let contents = ["contents", "content"]
+            .into_iter()
+            .filter_map(|key| result?.get(key)?.as_array())
+            .flatten();
+        for uri in contents.filter_map(|content| content.get("uri")?.as_str()) {
+            self.enforce(self.roots.check_uri(uri))?;
+        }
+        Ok(())
+    }
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Resolve `.` and `..`, then symlinks in the part of the path that exists
+fn normalize(path: &Path) -> PathBuf {
+    let lexical = normalize_lexically(path);
+    for ancestor in lexical.ancestors() {
+        if let Ok(canonical) = ancestor.canonicalize() {
+            let rest = lexical.strip_prefix(ancestor).unwrap_or(Path::new(""));
+            return canonical.join(rest);
+        }
+    }
+    lexical
+}
+
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
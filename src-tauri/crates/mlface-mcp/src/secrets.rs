@@ -0,0 +1,92 @@
+//! Secrets referenced from server configurations.
+//!
+//! Config values may contain `{{secret:NAME}}`. The reference is stored as is
+//! and the value is looked up in the manager's `SecretStore` only when the
+//! server is launched, so API keys never end up in the config file. The app
+//! provides a store backed by the OS keychain.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const PREFIX: &str = "{{secret:";
+const SUFFIX: &str = "}}";
+
+/// Where secret values are kept
+pub trait SecretStore: Send + Sync {
+    /// The value of a secret, or `None` if it is not set
+    fn get(&self, name: &str) -> Result<Option<String>>;
+    fn set(&self, name: &str, value: &str) -> Result<()>;
+    /// Delete a secret; deleting one that is not set is not an error
+    fn delete(&self, name: &str) -> Result<()>;
+}
+
+/// A store that keeps secrets in memory, for tests and platforms without a
+/// keychain
+#[derive(Default)]
+pub struct MemorySecretStore {
+    secrets: Mutex<HashMap<String, String>>,
+}
+
+impl SecretStore for MemorySecretStore {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        let secrets = self.secrets.lock().map_err(|e| anyhow!(e.to_string()))?;
+        Ok(secrets.get(name).cloned())
+    }
+    
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        let mut secrets = self.secrets.lock().map_err(|e| anyhow!(e.to_string()))?;
+        secrets.insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+    
+    fn delete(&self, name: &str) -> Result<()> {
+        let mut secrets = self.secrets.lock().map_err(|e| anyhow!(e.to_string()))?;
+        secrets.remove(name);
+        Ok(())
+    }
+}
+
+/// Check that `name` can be used in a `{{secret:NAME}}` reference
+pub fn validate_secret_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid secret name {:?}: use letters, digits, _, - and .", name))
+    }
+}
+
+/// Whether `value` refers to a secret
+pub fn has_secret_reference(value: &str) -> bool {
+    value.contains(PREFIX)
+}
+
+/// The value of the secret `name`, which must be set
+pub fn lookup_secret(name: &str, store: Option<&dyn SecretStore>) -> Result<String> {
+    let store = store.ok_or_else(|| anyhow!("Secret {} is used but no secret store is available", name))?;
+    store.get(name)?.ok_or_else(|| anyhow!("Secret {} is not set", name))
+}
+
+/// Replace every `{{secret:NAME}}` in `value` with the secret's value
+pub fn resolve_secrets(value: &str, store: Option<&dyn SecretStore>) -> Result<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    
+    while let Some(start) = rest.find(PREFIX) {
+        result.push_str(&rest[..start]);
+        
+        let reference = &rest[start + PREFIX.len()..];
+        let end = reference
+            .find(SUFFIX)
+            .ok_or_else(|| anyhow!("Unterminated {{{{secret: reference"))?;
+        let name = reference[..end].trim();
+        
+        result.push_str(&lookup_secret(name, store)?);
+        rest = &reference[end + SUFFIX.len()..];
+    }
+    
+    result.push_str(rest);
+    Ok(result)
+}
@@ -0,0 +1,1803 @@
+// unused import: use crate::types::*;
+use crate::transport::{OutputHandler, OutputStream, StdioTransport, SseTransport, Transport};
+use crate::client::{McpClient, SlowCall};
+use crate::config::ConfigFile;
+use crate::crash::CrashRecord;
+use crate::discovery::{self, Candidate};
+use crate::dashboard::{ConnectionType, Dashboard, ServerHealth, ServerSummary};
+use crate::expand;
+use crate::http::{HttpClients, TlsSettings};
+use crate::import::{ConflictPolicy, ImportReport, SkippedServer};
+use crate::integrity::{BinaryCheck, BinaryPin};
+use crate::logs::{LogLine, ServerLog};
+use crate::package::{self, PackageSpec};
+use crate::rate_limit::{RateLimit, RateLimitReport, RateLimiter};
+use crate::roots::{Roots, RootsEnforcement};
+use crate::secrets::{lookup_secret, resolve_secrets, SecretStore};
+use crate::metrics::{MetricsReport, RequestMetrics};
+use crate::priority::{with_priority, Priority};
+use crate::session::{SessionRecorder, SessionTape};
+use crate::single_flight::ListRequests;
+use crate::traffic::{RecordingTransport, TrafficEntry, TrafficFilter, TrafficLog};
+use crate::types::{
+    CallToolResult, InitializeResult, JsonRpcNotification, ListPromptsResult, ListResourcesResult, ListToolsResult, McpError,
+};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tracing::Instrument;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use futures::stream::{self, StreamExt};
+use tokio::sync::{broadcast, watch, Mutex, OwnedMutexGuard, RwLock};
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+const NOTIFICATION_CHANNEL_CAPACITY: usize = 256;
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Requests taking longer than this are reported as slow unless a server's
+/// settings say otherwise
+pub const DEFAULT_SLOW_CALL: Duration = Duration::from_secs(5);
+
+const SLOW_CALL_CHANNEL_CAPACITY: usize = 64;
+
+/// How long to wait for a server process that went away to report its exit
+const EXIT_STATUS_WAIT: Duration = Duration::from_secs(1);
+
+/// How long `dashboard` waits for a server to list its tools
+const DASHBOARD_TOOLS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Servers started at once by `start_all` unless the caller asks otherwise
+pub const DEFAULT_START_PARALLELISM: usize = 4;
+
+/// Configuration for an MCP server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    pub name: String,
+    /// Executable or URL of the server; empty for a server run from `package`
+    #[serde(default)]
+    pub command: String,
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Working directory to launch the server in; defaults to the app's own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Start the server when the app launches
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub autostart: bool,
+    /// A disabled server keeps its configuration but is never started
+    #[serde(default = "enabled_by_default", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "ServerSettings::is_default")]
+    pub settings: ServerSettings,
+    /// Credentials for a remote server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<ServerAuth>,
+    /// Directories the server works in, advertised as its roots and enforced
+    /// as `settings.roots_enforcement` says
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<String>,
+    /// Fingerprint of the executable of a local server, checked before launch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary: Option<BinaryPin>,
+    /// Package to run instead of a `command`, e.g.
+    /// `@modelcontextprotocol/server-filesystem` from npm
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<PackageSpec>,
+    /// What the server does, as its manifest or catalog entry says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Credentials sent with every request to a remote server. `value_ref` names
+/// a secret in the manager's `SecretStore`, looked up at connect time, so the
+/// credential itself never ends up in the config file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ServerAuth {
+    /// `Authorization: Bearer <secret>`
+    Bearer { value_ref: String },
+    /// `<name>: <secret>`, for servers that take an API key header
+    Header { name: String, value_ref: String },
+}
+
+impl ServerAuth {
+    /// The header to send, with its secret looked up in `secrets`
+    pub fn header(&self, secrets: Option<&dyn SecretStore>) -> Result<(String, String)> {
+        match self {
+            Self::Bearer { value_ref } => Ok((
+                "Authorization".to_string(),
+                format!("Bearer {}", lookup_secret(value_ref, secrets)?),
+            )),
+            Self::Header { name, value_ref } => Ok((name.clone(), lookup_secret(value_ref, secrets)?)),
+        }
+    }
+}
+
+/// How much of a server's activity is logged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogVerbosity {
+    /// Discard the server's own output
+    Quiet,
+    /// Record the server's output in its log
+    #[default]
+    Normal,
+    /// Also log every JSON-RPC message exchanged with the server
+    Trace,
+}
+
+/// Operational settings of one server. Unset values fall back to the
+/// client's and the manager's defaults, so a flaky server can get a more
+/// tolerant policy without affecting the others.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerSettings {
+    /// Seconds to wait for a response before a request fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_timeout_secs: Option<u64>,
+    /// Restart attempts after a crash; zero disables restarts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_restarts: Option<u32>,
+    /// Ping the server at this interval and restart it when a ping fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_check_interval_secs: Option<u64>,
+    #[serde(skip_serializing_if = "LogVerbosity::is_normal")]
+    pub log_level: LogVerbosity,
+    /// How strictly the server is held to its roots
+    #[serde(skip_serializing_if = "is_default_enforcement")]
+    pub roots_enforcement: RootsEnforcement,
+    /// Limit on tool calls to this server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Report requests still waiting for a response after this many
+    /// milliseconds as slow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slow_call_ms: Option<u64>,
+    /// Requests allowed in flight at once; those beyond it wait, interactive
+    /// ones ahead of background ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_requests: Option<usize>,
+    /// TLS options for a remote server that differ from the defaults
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsSettings>,
+}
+
+impl ServerSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl LogVerbosity {
+    fn is_normal(&self) -> bool {
+        *self == Self::Normal
+    }
+}
+
+fn is_default_enforcement(enforcement: &RootsEnforcement) -> bool {
+    *enforcement == RootsEnforcement::default()
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+/// A server with no arguments or settings, enabled but not started at launch
+impl Default for McpServerConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            command: String::new(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            cwd: None,
+            autostart: false,
+            enabled: true,
+            settings: ServerSettings::default(),
+            auth: None,
+            roots: Vec::new(),
+            binary: None,
+            package: None,
+            description: None,
+        }
+    }
+}
+
+impl McpServerConfig {
+    /// Whether the server is reached over HTTP/SSE rather than spawned locally
+    pub fn is_remote(&self) -> bool {
+        self.command.starts_with("http://") || self.command.starts_with("https://")
+    }
+    
+    /// A copy with `~`, `${VAR}`, `${env:VAR}` and `${workspaceFolder}` expanded
+    /// and `{{secret:NAME}}` resolved in the command, args and env values
+    pub fn expanded(&self, workspace_folder: Option<&Path>, secrets: Option<&dyn SecretStore>) -> Result<Self> {
+        let expand = |value: &str| {
+            expand::expand(value, workspace_folder)
+                .and_then(|value| resolve_secrets(&value, secrets))
+                .map_err(|e| anyhow::anyhow!("Server {}: {}", self.name, e))
+        };
+        
+        Ok(Self {
+            name: self.name.clone(),
+            command: expand(&self.command)?,
+            args: self.args.iter().map(|arg| expand(arg)).collect::<Result<_>>()?,
+            env: self.env
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), expand(value)?)))
+                .collect::<Result<_>>()?,
+            cwd: self.cwd.as_deref().map(expand).transpose()?,
+            roots: self.roots.iter().map(|root| expand(root)).collect::<Result<_>>()?,
+            autostart: self.autostart,
+            enabled: self.enabled,
+            settings: self.settings.clone(),
+            auth: self.auth.clone(),
+            binary: self.binary.clone(),
+            description: self.description.clone(),
+            package: self.package
+                .as_ref()
+                .map(|package| -> Result<PackageSpec> {
+                    Ok(PackageSpec {
+                        name: expand(&package.name)?,
+                        runtime: package.runtime,
+                        version: package.version.as_deref().map(expand).transpose()?,
+                    })
+                })
+                .transpose()?,
+        })
+    }
+    
+    /// A copy whose command runs its `package`, with the runner found as the
+    /// server starts. `args` are passed on to the package. Fails with what to
+    /// install when the package's runtime is missing.
+    pub fn resolve_package(&self) -> Result<Self> {
+        let Some(package) = &self.package else {
+            if self.command.is_empty() {
+                return Err(anyhow::anyhow!("Server {} has neither a command nor a package", self.name));
+            }
+            return Ok(self.clone());
+        };
+        if !self.command.is_empty() {
+            return Err(anyhow::anyhow!("Server {} has both a command and a package", self.name));
+        }
+        
+        let launch = package::launch(package, &self.args).map_err(|e| anyhow::anyhow!("Server {}: {}", self.name, e))?;
+        let mut resolved = self.clone();
+        resolved.command = launch.command;
+        resolved.args = launch.args;
+        for (key, value) in launch.env {
+            resolved.env.entry(key).or_insert(value);
+        }
+        Ok(resolved)
+    }
+    
+    /// The credential header for a remote server, resolved from `secrets`
+    pub fn auth_header(&self, secrets: Option<&dyn SecretStore>) -> Result<Option<(String, String)>> {
+        self.auth
+            .as_ref()
+            .map(|auth| auth.header(secrets).map_err(|e| anyhow::anyhow!("Server {}: {}", self.name, e)))
+            .transpose()
+    }
+    
+    /// Compare the executable of a local server to its pin. Call this on an
+    /// expanded config, since the command may name a variable.
+    pub fn check_binary(&self) -> Result<BinaryCheck> {
+        match &self.binary {
+            Some(pin) if !self.is_remote() => pin
+                .check(&self.command)
+                .map_err(|e| anyhow::anyhow!("Server {}: {}", self.name, e)),
+            _ => Ok(BinaryCheck::Unpinned),
+        }
+    }
+}
+
+/// File name of a server's log in the log directory
+fn log_file_name(name: &str) -> String {
+    format!("{}.log", file_stem(name))
+}
+
+/// File name of a server's last crash record in the log directory
+fn crash_file_name(name: &str) -> String {
+    format!("{}.crash.json", file_stem(name))
+}
+
+/// Server names are free-form; keep them from escaping the directory
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// Connection state of a server as seen by the manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    /// The previous connection died and a new one is being established
+    Reconnecting,
+    /// The server exited unexpectedly and could not be restarted
+    Crashed,
+}
+
+/// A server changed connection state
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerEvent {
+    pub server: String,
+    pub state: ConnectionState,
+    /// Why the transition happened, for unexpected ones. A server that goes
+    /// `Disconnected` with a reason failed to start.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// How the process ended, when a local server crashed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crash: Option<CrashRecord>,
+}
+
+/// A notification sent by a server
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerNotification {
+    pub server: String,
+    pub notification: JsonRpcNotification,
+}
+
+/// Outcome of starting one server with `start_servers`
+#[derive(Debug, Clone, Serialize)]
+pub struct StartResult {
+    pub server: String,
+    /// Why the server failed to start; `None` on success
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// How `apply_config` changed the registered servers
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// How the manager restarts servers whose connection died unexpectedly
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    /// Restart attempts before giving up; zero disables restarts
+    pub max_restarts: u32,
+    /// Delay before the first attempt, doubled on each further attempt
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// A connection that stayed up this long resets the attempt count
+    pub reset_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            reset_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// Ping a client at `interval` seconds and return the reason once a ping fails.
+/// Without an interval, or once the client is gone, this never returns.
+async fn health_check(client: Weak<McpClient>, interval: Option<u64>) -> String {
+    let Some(interval) = interval.filter(|secs| *secs > 0) else {
+        return std::future::pending().await;
+    };
+    
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        
+        let Some(client) = client.upgrade() else {
+            return std::future::pending().await;
+        };
+        if let Err(e) = with_priority(Priority::Background, client.ping()).await {
+            return format!("failed its health check: {}", e);
+        }
+    }
+}
+
+/// Limits the background reaper enforces; `None` disables a limit
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResourceBudgets {
+    /// Stop local servers unused this long
+    pub idle_timeout: Option<Duration>,
+    /// Close the event streams of remote servers unused this long
+    pub remote_idle_timeout: Option<Duration>,
+    /// Total size of the server log directory. Rotated files and logs of
+    /// servers that are no longer registered are deleted, oldest first.
+    pub max_log_bytes: Option<u64>,
+}
+
+/// Manager for MCP servers. Cloning yields another handle to the same servers.
+#[derive(Clone)]
+pub struct McpServerManager {
+    servers: Arc<RwLock<HashMap<String, McpServerConfig>>>,
+    clients: Arc<RwLock<HashMap<String, Arc<McpClient>>>>,
+    states: Arc<RwLock<HashMap<String, ConnectionState>>>,
+    // What each server reported when it last connected, kept after it stops
+    server_infos: Arc<RwLock<HashMap<String, InitializeResult>>>,
+    // The last crash of each server, also kept in the log directory
+    crashes: Arc<RwLock<HashMap<String, CrashRecord>>>,
+    // Per-server locks serializing connection attempts, so concurrent callers
+    // don't spawn a server twice while different servers connect in parallel
+    connect_locks: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+    events: broadcast::Sender<ServerEvent>,
+    slow_calls: broadcast::Sender<SlowCall>,
+    notifications: broadcast::Sender<ServerNotification>,
+    // Bumped whenever the set of servers or their configs change
+    registry: Arc<watch::Sender<()>>,
+    restart_policy: RestartPolicy,
+    logs: Arc<RwLock<HashMap<String, Arc<ServerLog>>>>,
+    log_dir: Arc<std::sync::RwLock<Option<PathBuf>>>,
+    workspace_folder: Arc<std::sync::RwLock<Option<PathBuf>>>,
+    // Servers of the open workspace, which are never saved to the config file
+    project_servers: Arc<std::sync::RwLock<HashSet<String>>>,
+    config_path: Arc<std::sync::RwLock<Option<PathBuf>>>,
+    secrets: Arc<std::sync::RwLock<Option<Arc<dyn SecretStore>>>>,
+    budgets: Arc<std::sync::RwLock<ResourceBudgets>>,
+    rate_limiter: Arc<RateLimiter>,
+    traffic: Arc<TrafficLog>,
+    metrics: Arc<RequestMetrics>,
+    tapes: Arc<std::sync::RwLock<HashMap<String, SessionTape>>>,
+    health: Arc<ServerHealth>,
+    http: Arc<HttpClients>,
+    lists: Arc<ListRequests>,
+}
+
+impl Default for McpServerManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpServerManager {
+    pub fn new() -> Self {
+        Self::with_restart_policy(RestartPolicy::default())
+    }
+    
+    pub fn with_restart_policy(restart_policy: RestartPolicy) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (notifications, _) = broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+        let (slow_calls, _) = broadcast::channel(SLOW_CALL_CHANNEL_CAPACITY);
+        
+        Self {
+            servers: Arc::new(RwLock::new(HashMap::new())),
+            clients: Arc::new(RwLock::new(HashMap::new())),
+            states: Arc::new(RwLock::new(HashMap::new())),
+            server_infos: Arc::new(RwLock::new(HashMap::new())),
+            crashes: Arc::new(RwLock::new(HashMap::new())),
+            connect_locks: Arc::new(Mutex::new(HashMap::new())),
+            events,
+            slow_calls,
+            notifications,
+            registry: Arc::new(watch::channel(()).0),
+            restart_policy,
+            logs: Arc::new(RwLock::new(HashMap::new())),
+            log_dir: Arc::new(std::sync::RwLock::new(None)),
+            workspace_folder: Arc::new(std::sync::RwLock::new(None)),
+            project_servers: Arc::new(std::sync::RwLock::new(HashSet::new())),
+            config_path: Arc::new(std::sync::RwLock::new(None)),
+            secrets: Arc::new(std::sync::RwLock::new(None)),
+            budgets: Arc::new(std::sync::RwLock::new(ResourceBudgets::default())),
+            rate_limiter: Arc::new(RateLimiter::default()),
+            traffic: Arc::new(TrafficLog::default()),
+            metrics: Arc::new(RequestMetrics::new()),
+            tapes: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            health: Arc::new(ServerHealth::default()),
+            http: Arc::new(HttpClients::default()),
+            lists: Arc::new(ListRequests::default()),
+        }
+    }
+    
+    /// Stop local servers that have not been used for `timeout`; `None` keeps
+    /// them running. A stopped server is started again on its next use.
+    pub fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        if let Ok(mut budgets) = self.budgets.write() {
+            budgets.idle_timeout = timeout;
+        }
+    }
+    
+    /// Replace all limits enforced by `run_reaper`
+    pub fn set_resource_budgets(&self, budgets: ResourceBudgets) {
+        if let Ok(mut current) = self.budgets.write() {
+            *current = budgets;
+        }
+    }
+    
+    pub fn resource_budgets(&self) -> ResourceBudgets {
+        self.budgets.read().map(|budgets| *budgets).unwrap_or_default()
+    }
+    
+    /// Limit tool calls to all servers together; `None` removes the limit
+    pub fn set_global_rate_limit(&self, limit: Option<RateLimit>) {
+        self.rate_limiter.set_global(limit);
+    }
+    
+    /// How much of the global and per-server rate limits is in use
+    pub fn rate_limit_usage(&self) -> RateLimitReport {
+        self.rate_limiter.usage()
+    }
+    
+    /// The last `limit` messages exchanged with `server`, or with all servers,
+    /// that match `filter`, oldest first
+    pub fn traffic(&self, server: Option<&str>, filter: &TrafficFilter, limit: Option<usize>) -> Vec<TrafficEntry> {
+        self.traffic.query(server, filter, limit)
+    }
+    
+    /// Receive messages exchanged with any server as they are recorded
+    pub fn subscribe_traffic(&self) -> broadcast::Receiver<TrafficEntry> {
+        self.traffic.subscribe()
+    }
+    
+    /// Record every message exchanged with a server to `path`, one JSON line
+    /// each, for a `ReplayTransport` to play back. A running server is
+    /// restarted so the recording starts with the initialize handshake.
+    pub async fn start_session_recording(&self, name: &str, path: &Path) -> Result<()> {
+        let _guard = self.connect_lock(name).await;
+        self.ensure_registered(name).await?;
+        
+        // Stopped before recording starts, so the recording holds no shutdown
+        let running = self.live_client(name).await.is_some();
+        if running {
+            self.stop_server(name).await?;
+        }
+        self.session_tape(name).start(path)?;
+        if running {
+            let client = self.connect_locked(name).await?;
+            self.supervise(name, &client);
+        }
+        Ok(())
+    }
+    
+    /// Stop recording a server's messages. Returns the file they were
+    /// recorded to, if they were.
+    pub fn stop_session_recording(&self, name: &str) -> Option<PathBuf> {
+        self.tapes.read().ok()?.get(name)?.stop()
+    }
+    
+    /// The file a server's messages are being recorded to
+    pub fn session_recording(&self, name: &str) -> Option<PathBuf> {
+        self.tapes.read().ok()?.get(name)?.path()
+    }
+    
+    /// Request counts, error rates and latency percentiles of `server`, or of
+    /// all servers, per method
+    pub fn metrics(&self, server: Option<&str>) -> MetricsReport {
+        self.metrics.report(server)
+    }
+    
+    /// Wait until a tool call to `name` is within the rate limits, or fail
+    /// if a limit that ran out rejects calls
+    pub async fn acquire_tool_call(&self, name: &str) -> std::result::Result<(), McpError> {
+        let limit = self.servers.read().await.get(name).and_then(|config| config.settings.rate_limit);
+        self.rate_limiter.acquire(name, limit).await
+    }
+    
+    /// Call a tool on a server, within its rate limits
+    pub async fn call_tool(
+        &self,
+        name: &str,
+        tool: &str,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, McpError> {
+        self.acquire_tool_call(name).await?;
+        let client = self.get_client(name).await?;
+        client.call_tool(tool, args).await
+    }
+    
+    /// List a server's tools, starting it if needed. Callers asking while a
+    /// listing is under way share its request.
+    pub async fn list_tools(&self, name: &str) -> std::result::Result<ListToolsResult, McpError> {
+        let client = self.get_client(name).await?;
+        self.lists.tools.run(name, || async move { client.list_tools().await }).await
+    }
+    
+    /// List a server's resources, sharing a listing under way like `list_tools`
+    pub async fn list_resources(&self, name: &str) -> std::result::Result<ListResourcesResult, McpError> {
+        let client = self.get_client(name).await?;
+        self.lists.resources.run(name, || async move { client.list_resources().await }).await
+    }
+    
+    /// List a server's prompts, sharing a listing under way like `list_tools`
+    pub async fn list_prompts(&self, name: &str) -> std::result::Result<ListPromptsResult, McpError> {
+        let client = self.get_client(name).await?;
+        self.lists.prompts.run(name, || async move { client.list_prompts().await }).await
+    }
+    
+    /// Periodically release idle resources according to the resource budgets.
+    /// Runs until the returned future is dropped.
+    pub async fn run_reaper(&self) {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            
+            let budgets = self.resource_budgets();
+            self.stop_idle_clients(&budgets).await;
+            
+            if let Some(max_bytes) = budgets.max_log_bytes {
+                self.trim_log_dir(max_bytes).await;
+            }
+        }
+    }
+    
+    async fn stop_idle_clients(&self, budgets: &ResourceBudgets) {
+        let idle: Vec<(String, Duration)> = {
+            let servers = self.servers.read().await;
+            let clients = self.clients.read().await;
+            clients
+                .iter()
+                .filter_map(|(name, client)| {
+                    let remote = servers.get(name)?.is_remote();
+                    let timeout = if remote {
+                        budgets.remote_idle_timeout
+                    } else {
+                        budgets.idle_timeout
+                    }?;
+                    let idle = client.is_connected()
+                        && !client.has_pending_requests()
+                        && client.idle_time() >= timeout;
+                    idle.then(|| (name.clone(), timeout))
+                })
+                .collect()
+        };
+        
+        for (name, timeout) in idle {
+            // Re-check under the lock in case a request started meanwhile
+            let client = {
+                let mut clients = self.clients.write().await;
+                let still_idle = clients
+                    .get(&name)
+                    .is_some_and(|client| !client.has_pending_requests() && client.idle_time() >= timeout);
+                if !still_idle {
+                    continue;
+                }
+                clients.remove(&name)
+            };
+            if let Some(client) = client {
+                tracing::info!("Stopping MCP server {} after {} s idle", name, timeout.as_secs());
+                let _ = client.close().await;
+            }
+            self.set_state_with_reason(
+                &name,
+                ConnectionState::Disconnected,
+                Some(format!("stopped after {} s idle", timeout.as_secs())),
+            ).await;
+        }
+    }
+    
+    /// Delete rotated and orphaned log files, oldest first, until the log
+    /// directory fits in `max_bytes`. Logs that are being written are kept,
+    /// and crash records are neither counted nor deleted.
+    async fn trim_log_dir(&self, max_bytes: u64) {
+        let Some(dir) = self.log_dir.read().ok().and_then(|dir| dir.clone()) else {
+            return;
+        };
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return;
+        };
+        
+        let active: Vec<String> = self.logs
+            .read()
+            .await
+            .keys()
+            .map(|name| log_file_name(name))
+            .collect();
+        
+        let mut total = 0;
+        let mut candidates = Vec::new();
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !metadata.is_file() || file_name.ends_with(".crash.json") {
+                continue;
+            }
+            
+            total += metadata.len();
+            if !active.contains(&file_name) {
+                let modified = metadata.modified().unwrap_or(std::time::UNIX_EPOCH);
+                candidates.push((modified, entry.path(), metadata.len()));
+            }
+        }
+        
+        candidates.sort();
+        for (_, path, len) in candidates {
+            if total <= max_bytes {
+                break;
+            }
+            match std::fs::remove_file(&path) {
+                Ok(()) => total -= len,
+                Err(e) => tracing::warn!("Cannot delete log file {}: {}", path.display(), e),
+            }
+        }
+    }
+    
+    /// Set the folder `${workspaceFolder}` expands to when servers are launched
+    pub fn set_workspace_folder(&self, dir: Option<PathBuf>) {
+        if let Ok(mut workspace_folder) = self.workspace_folder.write() {
+            *workspace_folder = dir;
+        }
+    }
+    
+    /// The folder set with `set_workspace_folder`
+    pub fn workspace_folder(&self) -> Option<PathBuf> {
+        self.workspace_folder.read().ok().and_then(|dir| dir.clone())
+    }
+    
+    /// Set the file `load_default_config` and `save_default_config` use
+    pub fn set_config_path(&self, path: Option<PathBuf>) {
+        if let Ok(mut config_path) = self.config_path.write() {
+            *config_path = path;
+        }
+    }
+    
+    pub fn config_path(&self) -> Option<PathBuf> {
+        self.config_path.read().ok().and_then(|path| path.clone())
+    }
+    
+    /// Set the store `{{secret:NAME}}` references are resolved from when servers
+    /// are launched
+    pub fn set_secret_store(&self, store: Option<Arc<dyn SecretStore>>) {
+        if let Ok(mut secrets) = self.secrets.write() {
+            *secrets = store;
+        }
+    }
+    
+    pub fn secret_store(&self) -> Option<Arc<dyn SecretStore>> {
+        self.secrets.read().ok().and_then(|store| store.clone())
+    }
+    
+    /// The HTTP clients remote servers share, for other requests the app makes
+    pub fn http_clients(&self) -> Arc<HttpClients> {
+        self.http.clone()
+    }
+    
+    /// Also write server output to `<dir>/<server>.log`. Applies to servers
+    /// whose log is created afterwards.
+    pub fn set_log_dir(&self, dir: impl Into<PathBuf>) {
+        if let Ok(mut log_dir) = self.log_dir.write() {
+            *log_dir = Some(dir.into());
+        }
+    }
+    
+    /// The last `tail` lines a server wrote to stderr (or non-protocol stdout),
+    /// or all buffered lines
+    pub async fn server_logs(&self, name: &str, tail: Option<usize>) -> Result<Vec<LogLine>> {
+        self.ensure_registered(name).await?;
+        
+        Ok(self.logs
+            .read()
+            .await
+            .get(name)
+            .map(|log| log.tail(tail))
+            .unwrap_or_default())
+    }
+    
+    /// Receive a server's output lines as they are written
+    pub async fn follow_server_logs(&self, name: &str) -> Result<broadcast::Receiver<LogLine>> {
+        self.ensure_registered(name).await?;
+        Ok(self.server_log(name).await.subscribe())
+    }
+    
+    async fn ensure_registered(&self, name: &str) -> Result<()> {
+        if self.servers.read().await.contains_key(name) {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Server {} not found", name))
+        }
+    }
+    
+    /// The output log of a server, created on first use
+    async fn server_log(&self, name: &str) -> Arc<ServerLog> {
+        let mut logs = self.logs.write().await;
+        if let Some(log) = logs.get(name) {
+            return log.clone();
+        }
+        
+        let log = Arc::new(ServerLog::default());
+        let log_dir = self.log_dir.read().ok().and_then(|dir| dir.clone());
+        if let Some(dir) = log_dir {
+            let path = dir.join(log_file_name(name));
+            if let Err(e) = log.persist_to(&path) {
+                tracing::warn!("Cannot write log file {}: {}", path.display(), e);
+            }
+        }
+        
+        logs.insert(name.to_string(), log.clone());
+        log
+    }
+    
+    /// Where a server's session is recorded, created on first use
+    fn session_tape(&self, name: &str) -> SessionTape {
+        match self.tapes.write() {
+            Ok(mut tapes) => tapes.entry(name.to_string()).or_default().clone(),
+            Err(_) => SessionTape::default(),
+        }
+    }
+    
+    /// Receive every connection state transition of every server
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ServerEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Receive requests to any server that are taking longer than its slow-call
+    /// threshold, when they cross it and when they finish
+    pub fn subscribe_slow_calls(&self) -> broadcast::Receiver<SlowCall> {
+        self.slow_calls.subscribe()
+    }
+    
+    /// Receive the notifications of every server, tagged with the server's name.
+    /// Servers that are restarted keep forwarding through the same stream.
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        self.notifications.subscribe()
+    }
+    
+    /// Be woken when servers are registered, removed or reconfigured.
+    /// Connection state changes come through `subscribe_events` instead.
+    pub fn subscribe_registry(&self) -> watch::Receiver<()> {
+        self.registry.subscribe()
+    }
+    
+    fn registry_changed(&self) {
+        self.registry.send_replace(());
+    }
+    
+    /// Register a new server configuration
+    pub async fn register_server(&self, config: McpServerConfig) -> Result<()> {
+        self.servers.write().await.insert(config.name.clone(), config);
+        self.registry_changed();
+        Ok(())
+    }
+    
+    /// Register a server of the open workspace. It runs like any other but is
+    /// left out of the saved config, and it can't replace a saved server.
+    pub async fn register_project_server(&self, config: McpServerConfig) -> Result<()> {
+        if !self.is_project_server(&config.name) && self.servers.read().await.contains_key(&config.name) {
+            return Err(anyhow::anyhow!("A server named {} is already configured", config.name));
+        }
+        if let Ok(mut project_servers) = self.project_servers.write() {
+            project_servers.insert(config.name.clone());
+        }
+        self.register_server(config).await
+    }
+    
+    pub fn is_project_server(&self, name: &str) -> bool {
+        self.project_servers.read().is_ok_and(|project_servers| project_servers.contains(name))
+    }
+    
+    /// Names of the servers registered with `register_project_server`
+    pub fn project_servers(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.project_servers.read().map(|names| names.iter().cloned().collect()).unwrap_or_default();
+        names.sort();
+        names
+    }
+    
+    /// Register imported server configurations, resolving name clashes with
+    /// existing servers according to `policy`. Running servers that get
+    /// overwritten keep running until restarted.
+    pub async fn import_servers(&self, configs: Vec<McpServerConfig>, policy: ConflictPolicy) -> ImportReport {
+        let mut servers = self.servers.write().await;
+        let mut report = ImportReport::default();
+        
+        for mut config in configs {
+            if servers.contains_key(&config.name) {
+                match policy {
+                    ConflictPolicy::Skip => {
+                        report.skipped.push(SkippedServer {
+                            reason: "A server with this name already exists".to_string(),
+                            name: config.name,
+                        });
+                        continue;
+                    }
+                    ConflictPolicy::Overwrite => {}
+                    ConflictPolicy::Rename => {
+                        let name = (2..)
+                            .map(|n| format!("{}-{}", config.name, n))
+                            .find(|name| !servers.contains_key(name))
+                            .unwrap_or_default();
+                        config.name = name;
+                    }
+                }
+            }
+            
+            report.imported.push(config.name.clone());
+            servers.insert(config.name.clone(), config);
+        }
+        
+        if !report.imported.is_empty() {
+            self.registry_changed();
+        }
+        report
+    }
+    
+    /// Make the registered servers match `configs`: servers that are gone are
+    /// stopped and removed, new ones are added, and running servers whose
+    /// configuration changed are restarted. Unchanged servers are left alone.
+    pub async fn apply_config(&self, configs: HashMap<String, McpServerConfig>) -> Result<ConfigDiff> {
+        let current = self.servers.read().await.clone();
+        let kept: Vec<String> = configs.keys().cloned().collect();
+        let mut diff = ConfigDiff::default();
+        
+        for (name, mut config) in configs {
+            config.name = name.clone();
+            
+            match current.get(&name) {
+                None => {
+                    self.register_server(config).await?;
+                    diff.added.push(name);
+                }
+                Some(existing) if *existing != config => {
+                    let running = self.live_client(&name).await.is_some();
+                    let enabled = config.enabled;
+                    self.register_server(config).await?;
+                    
+                    if running && enabled {
+                        if let Err(e) = self.restart_server(&name).await {
+                            tracing::warn!("Restarting MCP server {} with its new config failed: {}", name, e);
+                        }
+                    } else if running {
+                        self.stop_server(&name).await?;
+                    }
+                    diff.changed.push(name);
+                }
+                Some(_) => {}
+            }
+        }
+        
+        // Project servers never come from the config file
+        for name in current.keys() {
+            if !kept.contains(name) && !self.is_project_server(name) {
+                self.unregister_server(name).await?;
+                diff.removed.push(name.clone());
+            }
+        }
+        
+        diff.added.sort();
+        diff.changed.sort();
+        diff.removed.sort();
+        Ok(diff)
+    }
+    
+    /// Unregister a server
+    pub async fn unregister_server(&self, name: &str) -> Result<()> {
+        // Stop the server if running
+        self.stop_server(name).await?;
+        
+        // Remove from registry
+        let mut servers = self.servers.write().await;
+        servers.remove(name);
+        
+        self.states.write().await.remove(name);
+        self.server_infos.write().await.remove(name);
+        self.crashes.write().await.remove(name);
+        if let Some(path) = self.crash_file(name) {
+            // Most servers never crashed
+            let _ = std::fs::remove_file(path);
+        }
+        self.logs.write().await.remove(name);
+        self.rate_limiter.remove(name);
+        self.traffic.forget(name);
+        self.metrics.reset(Some(name));
+        if let Some(tape) = self.tapes.write().ok().and_then(|mut tapes| tapes.remove(name)) {
+            tape.stop();
+        }
+        self.health.forget(name);
+        if let Ok(mut project_servers) = self.project_servers.write() {
+            project_servers.remove(name);
+        }
+        
+        self.registry_changed();
+        Ok(())
+    }
+    
+    /// Enable or disable a server. Disabling stops it if it is running.
+    pub async fn set_server_enabled(&self, name: &str, enabled: bool) -> Result<()> {
+        // Keep a connection attempt from slipping in between the flag and the stop
+        let _guard = self.connect_lock(name).await;
+        
+        self.servers
+            .write()
+            .await
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Server {} not found", name))?
+            .enabled = enabled;
+        self.registry_changed();
+        
+        if !enabled {
+            self.stop_server(name).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Change a server's operational settings. They apply from its next start,
+    /// except the restart limit, which applies at its next crash.
+    pub async fn set_server_settings(&self, name: &str, settings: ServerSettings) -> Result<()> {
+        self.servers
+            .write()
+            .await
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Server {} not found", name))?
+            .settings = settings;
+        self.registry_changed();
+        Ok(())
+    }
+    
+    /// Pin a local server to its executable as it is now, replacing any
+    /// earlier pin. With `enforce`, a changed binary is refused at launch.
+    pub async fn pin_binary(&self, name: &str, enforce: bool) -> Result<BinaryPin> {
+        let config = self.expanded_config(name).await?;
+        if config.is_remote() {
+            return Err(anyhow::anyhow!("Server {} is remote and has no binary", name));
+        }
+        
+        let command = config.command.clone();
+        let pin = tokio::task::spawn_blocking(move || BinaryPin::of(&command, enforce)).await??;
+        self.servers
+            .write()
+            .await
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("Server {} not found", name))?
+            .binary = Some(pin.clone());
+        self.registry_changed();
+        Ok(pin)
+    }
+    
+    /// Compare a server's executable to its pin
+    pub async fn check_binary(&self, name: &str) -> Result<BinaryCheck> {
+        let config = self.expanded_config(name).await?;
+        tokio::task::spawn_blocking(move || config.check_binary()).await?
+    }
+    
+    /// Uptime, restarts, last error, tool count, request volume and connection
+    /// type of every server. Connected servers that have not listed their tools
+    /// since they connected are asked to.
+    pub async fn dashboard(&self) -> Dashboard {
+        let servers = self.get_servers().await;
+        let metrics = &self.metrics.report(None);
+        
+        let summaries = stream::iter(servers)
+            .map(|config| async move {
+                let state = self.connection_state(&config.name).await;
+                if state == ConnectionState::Connected && self.health.snapshot(&config.name).tool_count.is_none() {
+                    self.count_tools(&config.name).await;
+                }
+                
+                let health = self.health.snapshot(&config.name);
+                let total = metrics.servers.get(&config.name).map(|server| &server.total);
+                ServerSummary {
+                    connection_type: if config.is_remote() { ConnectionType::Remote } else { ConnectionType::Stdio },
+                    state,
+                    enabled: config.enabled,
+                    uptime_ms: health.uptime_ms,
+                    restarts: health.restarts,
+                    last_error: health.last_error,
+                    tool_count: health.tool_count,
+                    requests: total.map_or(0, |total| total.requests),
+                    errors: total.map_or(0, |total| total.errors),
+                    name: config.name,
+                }
+            })
+            .buffered(DEFAULT_START_PARALLELISM)
+            .collect()
+            .await;
+        
+        Dashboard { servers: summaries }
+    }
+    
+    /// Ask a connected server for its tools and remember how many there are
+    async fn count_tools(&self, name: &str) {
+        let Some(client) = self.live_client(name).await else {
+            return;
+        };
+        let listing = self.lists.tools.run(name, || async move {
+            with_priority(Priority::Background, client.list_tools()).await
+        });
+        match tokio::time::timeout(DASHBOARD_TOOLS_TIMEOUT, listing).await {
+            Ok(Ok(tools)) => self.health.set_tool_count(name, Some(tools.tools.len())),
+            Ok(Err(e)) => tracing::debug!("MCP server {} did not list its tools: {}", name, e),
+            Err(_) => tracing::debug!("MCP server {} did not list its tools in time", name),
+        }
+    }
+    
+    /// The name, version, instructions and capabilities a server reported when
+    /// it last connected; `None` if it has not connected since it was registered
+    pub async fn server_info(&self, name: &str) -> Result<Option<InitializeResult>> {
+        if !self.servers.read().await.contains_key(name) {
+            return Err(anyhow::anyhow!("Server {} not found", name));
+        }
+        Ok(self.server_infos.read().await.get(name).cloned())
+    }
+    
+    /// How a server's process ended the last time it crashed, and what it
+    /// wrote to stderr before, including crashes before the app restarted
+    pub async fn last_crash(&self, name: &str) -> Option<CrashRecord> {
+        if let Some(crash) = self.crashes.read().await.get(name) {
+            return Some(crash.clone());
+        }
+        
+        let path = self.crash_file(name)?;
+        let crash = tokio::task::spawn_blocking(move || CrashRecord::load(&path)).await.ok()?;
+        let crash = crash.unwrap_or_else(|e| {
+            tracing::warn!("Cannot read the crash record of MCP server {}: {}", name, e);
+            None
+        })?;
+        self.crashes.write().await.insert(name.to_string(), crash.clone());
+        Some(crash)
+    }
+    
+    fn crash_file(&self, name: &str) -> Option<PathBuf> {
+        let dir = self.log_dir.read().ok().and_then(|dir| dir.clone())?;
+        Some(dir.join(crash_file_name(name)))
+    }
+    
+    /// A crash record if the process of a server ended with a failure
+    async fn record_crash(&self, name: &str, log: &ServerLog) -> Option<CrashRecord> {
+        let exit = log.exit_status(EXIT_STATUS_WAIT).await.filter(|exit| !exit.success())?;
+        let crash = CrashRecord::new(name, exit, &log.tail(None));
+        tracing::warn!("MCP server {} crashed: {}", name, crash.summary());
+        
+        if let Some(path) = self.crash_file(name) {
+            if let Err(e) = crash.save(&path) {
+                tracing::warn!("Cannot write crash record {}: {}", path.display(), e);
+            }
+        }
+        self.crashes.write().await.insert(name.to_string(), crash.clone());
+        Some(crash)
+    }
+    
+    /// A server's config with variables and secrets expanded
+    async fn expanded_config(&self, name: &str) -> Result<McpServerConfig> {
+        let config = self.servers.read().await.get(name).cloned().ok_or_else(|| {
+            anyhow::anyhow!("Server {} not found", name)
+        })?;
+        let workspace_folder = self.workspace_folder.read().ok().and_then(|dir| dir.clone());
+        let secrets = self.secret_store();
+        // Keychain lookups may block
+        tokio::task::spawn_blocking(move || config.expanded(workspace_folder.as_deref(), secrets.as_deref())).await?
+    }
+    
+    /// Start an MCP server by name. Local servers are spawned and connected
+    /// over stdio; remote servers are connected over HTTP/SSE.
+    pub async fn start_server(&self, name: &str) -> Result<()> {
+        self.get_client(name).await.map(|_| ())
+    }
+    
+    /// Stop an MCP server by name
+    pub async fn stop_server(&self, name: &str) -> Result<()> {
+        // Closing the client also terminates the process it owns
+        let client = self.clients.write().await.remove(name);
+        if let Some(client) = client {
+            // Try to close gracefully
+            let _ = client.close().await;
+        }
+        
+        self.set_state(name, ConnectionState::Disconnected).await;
+        
+        Ok(())
+    }
+    
+    /// Stop a server and start it again with its current configuration,
+    /// re-running the initialize handshake. Nobody can connect in between.
+    pub async fn restart_server(&self, name: &str) -> Result<()> {
+        let _guard = self.connect_lock(name).await;
+        
+        self.ensure_registered(name).await?;
+        self.stop_server(name).await?;
+        
+        let client = self.connect_locked(name).await?;
+        self.supervise(name, &client);
+        Ok(())
+    }
+    
+    /// Current connection state of a server
+    pub async fn connection_state(&self, name: &str) -> ConnectionState {
+        let state = self.states
+            .read()
+            .await
+            .get(name)
+            .copied()
+            .unwrap_or(ConnectionState::Disconnected);
+        
+        // A client whose transport died is no longer connected, whatever was recorded
+        if state == ConnectionState::Connected && self.live_client(name).await.is_none() {
+            return ConnectionState::Disconnected;
+        }
+        
+        state
+    }
+    
+    /// Get or create a client for a server. If the previous connection was
+    /// closed, the server is re-spawned (or the stream re-opened) and initialized again.
+    pub async fn get_client(&self, name: &str) -> Result<Arc<McpClient>> {
+        // Check if we already have a live client
+        if let Some(client) = self.live_client(name).await {
+            return Ok(client);
+        }
+        
+        let _guard = self.connect_lock(name).await;
+        
+        // Another caller may have connected while we were waiting
+        if let Some(client) = self.live_client(name).await {
+            return Ok(client);
+        }
+        
+        let client = self.connect_locked(name).await?;
+        self.supervise(name, &client);
+        Ok(client)
+    }
+    
+    /// Lock out other connection attempts to a server
+    async fn connect_lock(&self, name: &str) -> OwnedMutexGuard<()> {
+        let lock = self.connect_locks
+            .lock()
+            .await
+            .entry(name.to_string())
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+    
+    /// Start several servers concurrently, at most `max_parallel` at a time.
+    /// Failures don't stop the others; the report lists every server in order.
+    pub async fn start_servers(&self, names: Vec<String>, max_parallel: usize) -> Vec<StartResult> {
+        stream::iter(names)
+            .map(|name| async move {
+                let error = self.start_server(&name).await.err().map(|e| e.to_string());
+                StartResult { server: name, error }
+            })
+            .buffered(max_parallel.max(1))
+            .collect()
+            .await
+    }
+    
+    /// Start every enabled server concurrently, see `start_servers`
+    pub async fn start_all(&self, max_parallel: usize) -> Vec<StartResult> {
+        let mut names: Vec<String> = self.servers
+            .read()
+            .await
+            .values()
+            .filter(|config| config.enabled)
+            .map(|config| config.name.clone())
+            .collect();
+        names.sort();
+        self.start_servers(names, max_parallel).await
+    }
+    
+    /// Connect to a server and record the client. Callers hold its `connect_lock`.
+    #[tracing::instrument(name = "connect", skip_all, fields(server = name))]
+    async fn connect_locked(&self, name: &str) -> Result<Arc<McpClient>> {
+        // Get the server configuration
+        let config = self.servers.read().await.get(name).cloned().ok_or_else(|| {
+            anyhow::anyhow!("Server {} not found", name)
+        })?;
+        
+        if !config.enabled {
+            return Err(anyhow::anyhow!("Server {} is disabled", name));
+        }
+        
+        // A dead client stays in place until the new one replaces it, so a failed
+        // attempt doesn't hide that the server was running before
+        let reconnecting = self.clients.read().await.contains_key(name);
+        if reconnecting {
+            tracing::warn!("Connection to MCP server {} was closed, reconnecting", name);
+        }
+        
+        let state = if reconnecting {
+            ConnectionState::Reconnecting
+        } else {
+            ConnectionState::Connecting
+        };
+        self.set_state(name, state).await;
+        
+        let log = self.server_log(name).await;
+        
+        let workspace_folder = self.workspace_folder.read().ok().and_then(|dir| dir.clone());
+        let secrets = self.secret_store();
+        // Keychain lookups may block
+        let prepared = tokio::task::spawn_blocking(move || {
+            let expanded = config.expanded(workspace_folder.as_deref(), secrets.as_deref())?.resolve_package()?;
+            let auth_header = expanded.auth_header(secrets.as_deref())?;
+            
+            // A binary that can't be read fails to launch anyway
+            let warning = match expanded.check_binary() {
+                Ok(check) => check.warning(),
+                Err(e) => Some(e.to_string()),
+            };
+            let enforced = expanded.binary.as_ref().is_some_and(|pin| pin.enforce);
+            if let (Some(warning), true) = (&warning, enforced) {
+                return Err(anyhow::anyhow!("Server {}: {}, refusing to launch it", expanded.name, warning));
+            }
+            Ok((expanded, auth_header, warning))
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|prepared| prepared);
+        let (expanded, auth_header, warning) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                log.push(OutputStream::Stderr, &e.to_string());
+                self.set_state_with_reason(name, ConnectionState::Disconnected, Some(e.to_string())).await;
+                return Err(e);
+            }
+        };
+        
+        if let Some(warning) = warning {
+            tracing::warn!("MCP server {}: {}", name, warning);
+            log.push(OutputStream::Stderr, &warning);
+            self.set_state_with_reason(name, state, Some(warning)).await;
+        }
+        
+        match self.connect(&expanded, auth_header, &log).await {
+            Ok(client) => {
+                if let Some(info) = client.server_info().await {
+                    self.server_infos.write().await.insert(name.to_string(), info);
+                }
+                self.forward_notifications(name, &client);
+                self.clients.write().await.insert(name.to_string(), client.clone());
+                self.set_state(name, ConnectionState::Connected).await;
+                Ok(client)
+            }
+            Err(e) => {
+                // A server that dies while starting up usually says why
+                let crash = self.record_crash(name, &log).await;
+                self.set_state_with_crash(name, ConnectionState::Disconnected, Some(e.to_string()), crash).await;
+                Err(e)
+            }
+        }
+    }
+    
+    /// Relay a client's notifications to `subscribe_notifications` until the
+    /// client is dropped
+    fn forward_notifications(&self, name: &str, client: &McpClient) {
+        let mut notifications = client.subscribe_notifications();
+        let sender = self.notifications.clone();
+        let health = self.health.clone();
+        let server = name.to_string();
+        
+        tokio::spawn(async move {
+            loop {
+                match notifications.recv().await {
+                    Ok(notification) => {
+                        if notification.method == "notifications/tools/list_changed" {
+                            health.set_tool_count(&server, None);
+                        }
+                        // No subscribers is not an error
+                        let _ = sender.send(ServerNotification {
+                            server: server.clone(),
+                            notification,
+                        });
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!("Dropped {} notifications from MCP server {}", missed, server);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+    
+    /// Return the cached client for a server if its connection is still alive
+    async fn live_client(&self, name: &str) -> Option<Arc<McpClient>> {
+        let clients = self.clients.read().await;
+        clients.get(name).filter(|client| client.is_connected()).cloned()
+    }
+    
+    async fn set_state(&self, name: &str, state: ConnectionState) {
+        self.set_state_with_reason(name, state, None).await;
+    }
+    
+    async fn set_state_with_reason(&self, name: &str, state: ConnectionState, reason: Option<String>) {
+        self.set_state_with_crash(name, state, reason, None).await;
+    }
+    
+    /// Change state because the server's process crashed, describing the crash
+    /// as the reason if there is a record of it
+    async fn set_state_with_crash(
+        &self,
+        name: &str,
+        state: ConnectionState,
+        reason: Option<String>,
+        crash: Option<CrashRecord>,
+    ) {
+        self.states.write().await.insert(name.to_string(), state);
+        let reason = match &crash {
+            Some(crash) => Some(format!("crashed: {}", crash.summary())),
+            None => reason,
+        };
+        self.health.state_changed(name, state, reason.as_deref());
+        
+        // No subscribers is not an error
+        let _ = self.events.send(ServerEvent {
+            server: name.to_string(),
+            state,
+            reason,
+            crash,
+        });
+    }
+    
+    /// Operational settings of a server, or the defaults if it is not registered
+    async fn server_settings(&self, name: &str) -> ServerSettings {
+        self.servers
+            .read()
+            .await
+            .get(name)
+            .map(|config| config.settings.clone())
+            .unwrap_or_default()
+    }
+    
+    /// Whether the manager still holds this exact client for the server. A client
+    /// that was stopped or replaced on purpose is no longer current.
+    async fn is_current(&self, name: &str, client: &Weak<McpClient>) -> bool {
+        let clients = self.clients.read().await;
+        clients.get(name).is_some_and(|current| std::ptr::eq(Arc::as_ptr(current), client.as_ptr()))
+    }
+    
+    /// Watch a client and restart the server with backoff if its connection
+    /// dies without the server being stopped
+    fn supervise(&self, name: &str, client: &Arc<McpClient>) {
+        let manager = self.clone();
+        let name = name.to_string();
+        let mut client = Arc::downgrade(client);
+        let mut closed = match client.upgrade() {
+            Some(client) => Box::pin(client.closed()),
+            None => return,
+        };
+        
+        let span = tracing::info_span!("supervise", server = %name);
+        tokio::spawn(async move {
+            let mut restarts = 0;
+            let mut connected_at = Instant::now();
+            
+            loop {
+                let settings = manager.server_settings(&name).await;
+                let (reason, unresponsive) = tokio::select! {
+                    _ = &mut closed => ("exited unexpectedly".to_string(), false),
+                    failure = health_check(client.clone(), settings.health_check_interval_secs) => (failure, true),
+                };
+                
+                if !manager.is_current(&name, &client).await {
+                    return;
+                }
+                
+                // Settings may have changed while the server was running
+                let mut policy = manager.restart_policy;
+                if let Some(max_restarts) = manager.server_settings(&name).await.max_restarts {
+                    policy.max_restarts = max_restarts;
+                }
+                
+                if connected_at.elapsed() >= policy.reset_after {
+                    restarts = 0;
+                }
+                
+                tracing::warn!("MCP server {} {}", name, reason);
+                
+                // An unresponsive server is still running; make way for the new one
+                let log = manager.server_log(&name).await;
+                let crash = if unresponsive {
+                    log.push(OutputStream::Stderr, &reason);
+                    if let Some(client) = client.upgrade() {
+                        let _ = client.close().await;
+                    }
+                    None
+                } else {
+                    manager.record_crash(&name, &log).await
+                };
+                
+                manager.set_state_with_crash(
+                    &name,
+                    ConnectionState::Crashed,
+                    Some(reason),
+                    crash,
+                ).await;
+                
+                // Restart until a connection comes up or the policy gives up
+                let restarted = loop {
+                    if restarts >= policy.max_restarts {
+                        manager.set_state_with_reason(
+                            &name,
+                            ConnectionState::Crashed,
+                            Some(format!("gave up after {} restart attempts", restarts)),
+                        ).await;
+                        return;
+                    }
+                    
+                    let delay = policy.backoff(restarts);
+                    restarts += 1;
+                    manager.health.restarted(&name);
+                    manager.set_state_with_reason(
+                        &name,
+                        ConnectionState::Reconnecting,
+                        Some(format!("restart {} of {} in {} ms", restarts, policy.max_restarts, delay.as_millis())),
+                    ).await;
+                    tokio::time::sleep(delay).await;
+                    
+                    let _guard = manager.connect_lock(&name).await;
+                    
+                    // The server may have been stopped, unregistered or reconnected meanwhile
+                    if !manager.is_current(&name, &client).await {
+                        return;
+                    }
+                    
+                    match manager.connect_locked(&name).await {
+                        Ok(new_client) => break new_client,
+                        Err(e) => {
+                            tracing::warn!("Restarting MCP server {} failed: {}", name, e);
+                            
+                            // Keep the dead client in place so the next attempt is still ours
+                            manager.set_state_with_reason(
+                                &name,
+                                ConnectionState::Crashed,
+                                Some(e.to_string()),
+                            ).await;
+                        }
+                    }
+                };
+                
+                client = Arc::downgrade(&restarted);
+                closed = Box::pin(restarted.closed());
+                connected_at = Instant::now();
+            }
+        }.instrument(span));
+    }
+    
+    /// Open a transport for the server and run the initialize handshake.
+    /// `auth_header` is sent with every request to a remote server.
+    async fn connect(
+        &self,
+        config: &McpServerConfig,
+        auth_header: Option<(String, String)>,
+        log: &Arc<ServerLog>,
+    ) -> Result<Arc<McpClient>> {
+        // Create the appropriate transport
+        let transport = if config.is_remote() {
+            // HTTP/SSE transport, sharing connections with the other remote servers
+            let headers: Vec<_> = auth_header.into_iter().collect();
+            let tls = config.settings.tls.clone().unwrap_or_default();
+            log.watch_exit(None);
+            let transport = SseTransport::with_client(self.http.client(&tls)?, &config.command, &headers).await?;
+            Arc::new(transport) as Arc<dyn Transport>
+        } else {
+            // Tauri 2.0 compatibility mode
+            tracing::debug!("Starting process in Tauri 2.0 compatibility mode");
+            // Prepare the command
+            let mut cmd = Command::new(&config.command);
+            cmd.args(&config.args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true);
+            
+            // Add environment variables
+            for (key, value) in &config.env {
+                cmd.env(key, value);
+            }
+            
+            if let Some(cwd) = &config.cwd {
+                cmd.current_dir(cwd);
+            }
+            
+            // Start the process; the transport owns it from here on
+            let child = cmd.spawn()?;
+            let output: OutputHandler = match config.settings.log_level {
+                LogVerbosity::Quiet => Arc::new(|_, _| {}),
+                _ => log.handler(),
+            };
+            let transport = StdioTransport::from_child_with_output(child, Some(output))?;
+            log.watch_exit(Some(transport.exit_status()));
+            Arc::new(transport) as Arc<dyn Transport>
+        };
+        
+        let transport = Arc::new(SessionRecorder::new(transport, self.session_tape(&config.name)));
+        let transport = Arc::new(RecordingTransport::new(transport, &config.name, self.traffic.clone()));
+        
+        // Create the client
+        let mut builder = McpClient::builder(transport)
+            .client_info("mlFace", "1.0.0")
+            .label(&config.name)
+            .metrics(self.metrics.clone())
+            .trace(config.settings.log_level == LogVerbosity::Trace);
+        if let Some(secs) = config.settings.request_timeout_secs {
+            builder = builder.request_timeout(Duration::from_secs(secs));
+        }
+        if let Some(limit) = config.settings.max_concurrent_requests {
+            builder = builder.max_concurrent_requests(limit);
+        }
+        let slow_call = config.settings.slow_call_ms.map_or(DEFAULT_SLOW_CALL, Duration::from_millis);
+        let slow_calls = self.slow_calls.clone();
+        builder = builder.on_slow_call(slow_call, move |call| {
+            // No subscribers is not an error
+            let _ = slow_calls.send(call);
+        });
+        if !config.roots.is_empty() {
+            builder = builder.roots(Roots::new(&config.roots), config.settings.roots_enforcement);
+        }
+        let client = builder.build();
+        
+        // Initialize the client
+        client.initialize().await?;
+        
+        Ok(Arc::new(client))
+    }
+    
+    /// Test a connection to a server
+    pub async fn test_connection(&self, url: &str) -> Result<bool> {
+        // For HTTP URLs, try to create an SSE transport
+        if url.starts_with("http://") || url.starts_with("https://") {
+            let transport = SseTransport::with_client(self.http.client(&TlsSettings::default())?, url, &[]).await?;
+            let transport_arc = Arc::new(transport) as Arc<dyn Transport>;
+            
+            // Create a temporary client
+            let client = McpClient::new(transport_arc, "mlFace_test", "1.0.0").await?;
+            
+            // Try to initialize
+            match client.initialize().await {
+                Ok(_) => {
+                    // Clean up
+                    let _ = client.close().await;
+                    Ok(true)
+                }
+                Err(e) => {
+                    // Clean up
+                    let _ = client.close().await;
+                    tracing::warn!("Failed to initialize MCP client: {}", e);
+                    Ok(false)
+                }
+            }
+        } else {
+            // For local commands, try to execute with --help or -h to see if it exists
+            let mut cmd = Command::new(url);
+            cmd.arg("--help")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            
+            match cmd.spawn() {
+                Ok(_) => Ok(true),
+                Err(_) => {
+                    // Try with -h
+                    let mut cmd = Command::new(url);
+                    cmd.arg("-h")
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null());
+                    
+                    match cmd.spawn() {
+                        Ok(_) => Ok(true),
+                        Err(_) => Ok(false),
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Load server configurations from a JSON file, upgrading older formats
+    pub async fn load_from_file(&self, path: &str) -> Result<()> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let file = ConfigFile::parse(&content)?;
+        
+        let mut servers = self.servers.write().await;
+        for (name, mut config) in file.servers {
+            config.name = name.clone();
+            servers.insert(name, config);
+        }
+        
+        self.registry_changed();
+        Ok(())
+    }
+    
+    /// Save server configurations to a JSON file
+    pub async fn save_to_file(&self, path: &str) -> Result<()> {
+        let mut servers = self.servers.read().await.clone();
+        servers.retain(|name, _| !self.is_project_server(name));
+        let file = ConfigFile::new(servers);
+        let json = serde_json::to_string_pretty(&file)?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+    
+    /// Load server configurations from the config file set with
+    /// `set_config_path`. Without one there is nothing to load.
+    pub async fn load_default_config(&self) -> Result<()> {
+        match self.config_path() {
+            Some(path) => self.load_from_file(&path.to_string_lossy()).await,
+            None => Ok(()),
+        }
+    }
+    
+    /// Save server configurations to the config file set with
+    /// `set_config_path`. Without one there is nothing to do.
+    pub async fn save_default_config(&self) -> Result<()> {
+        match self.config_path() {
+            Some(path) => self.save_to_file(&path.to_string_lossy()).await,
+            None => Ok(()),
+        }
+    }
+    
+    /// Get all server configurations
+    pub async fn get_servers(&self) -> Vec<McpServerConfig> {
+        let servers = self.servers.read().await;
+        servers.values().cloned().collect()
+    }
+    
+    /// Discover MCP servers in `dir` and among the executables installed on
+    /// this machine; see `discovery`. Nothing is run unless `probe` is set,
+    /// in which case candidates that fail `test_connection` are left out.
+    pub async fn discover_servers(&self, dir: Option<&Path>, probe: bool) -> Result<Vec<McpServerConfig>> {
+        if let Some(dir) = dir {
+            // A directory that was asked for has to be there
+            tokio::fs::metadata(dir).await?;
+        }
+        let dir = dir.map(Path::to_path_buf);
+        let candidates = tokio::task::spawn_blocking(move || discovery::scan(dir.as_deref())).await?;
+        
+        // Probing spawns each candidate, so try a few at once rather than one
+        // after the other; results keep the scan's order. Only executables
+        // found on disk are probed: a package would be downloaded to do so.
+        let probes: Vec<Result<(Candidate, bool)>> = stream::iter(candidates)
+            .map(|candidate| async move {
+                let Some(path) = candidate.path.as_ref().filter(|_| probe) else {
+                    return Ok((candidate, true));
+                };
+                let found = self.test_connection(&path.to_string_lossy()).await?;
+                Ok((candidate, found))
+            })
+            .buffered(DEFAULT_START_PARALLELISM)
+            .collect()
+            .await;
+        
+        let mut found = Vec::new();
+        for result in probes {
+            let (candidate, ok) = result?;
+            if ok {
+                found.push(candidate);
+            }
+        }
+        
+        // Discovered binaries are pinned, so a later swap doesn't go unnoticed.
+        // Pinning hashes each executable, which is too slow for the runtime.
+        let configs = tokio::task::spawn_blocking(move || {
+            found
+                .into_iter()
+                .map(|Candidate { mut config, path }| {
+                    if let Some(path) = path {
+                        match BinaryPin::of(&path.to_string_lossy(), false) {
+                            Ok(pin) => config.binary = Some(pin),
+                            Err(e) => tracing::warn!("Could not pin {}: {}", path.display(), e),
+                        }
+                    }
+                    config
+                })
+                .collect()
+        })
+        .await?;
+        
+        Ok(configs)
+    }
+}
@@ -0,0 +1,280 @@
+//! Recording a server's session to a file and replaying it without the server.
+//!
+//! The manager wraps every transport in a `SessionRecorder`. While the
+//! server's `SessionTape` is recording, each message sent or received is
+//! appended to the file as one JSON line, so a recording can be attached to a
+//! bug report as it is. A `ReplayTransport` plays a recording back in place of
+//! the server: it accepts the messages the client sent during the recording
+//! and answers with what the server said, without the delays in between.
+
+use crate::traffic::Direction;
+use crate::transport::Transport;
+use crate::types::{JsonRpcMessage, McpError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Notify;
+
+/// One line of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEntry {
+    pub direction: Direction,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub message: Value,
+}
+
+struct Recording {
+    file: File,
+    path: PathBuf,
+}
+
+/// Where a `SessionRecorder` writes its server's messages; nothing is written
+/// until `start` is called
+#[derive(Clone, Default)]
+pub struct SessionTape {
+    recording: Arc<Mutex<Option<Recording>>>,
+}
+
+impl SessionTape {
+    /// Record to `path`, replacing what it holds and any recording in progress
+    pub fn start(&self, path: &Path) -> io::Result<()> {
+        let file = OpenOptions::new().create(true).write(true).truncate(true).open(path)?;
+        if let Ok(mut recording) = self.recording.lock() {
+            *recording = Some(Recording { file, path: path.to_path_buf() });
+        }
+        Ok(())
+    }
+    
+    /// Stop recording. Returns the file that was recorded to, if any.
+    pub fn stop(&self) -> Option<PathBuf> {
+        let recording = self.recording.lock().ok()?.take()?;
+        Some(recording.path)
+    }
+    
+    /// The file being recorded to
+    pub fn path(&self) -> Option<PathBuf> {
+        let recording = self.recording.lock().ok()?;
+        recording.as_ref().map(|recording| recording.path.clone())
+    }
+    
+    fn write(&self, direction: Direction, message: &JsonRpcMessage) {
+        let Ok(mut recording) = self.recording.lock() else {
+            return;
+        };
+        let Some(current) = recording.as_mut() else {
+            return;
+        };
+        
+        let entry = SessionEntry {
+            direction,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            message: serde_json::to_value(message).unwrap_or(Value::Null),
+        };
+        let mut line = serde_json::to_string(&entry).unwrap_or_default();
+        line.push('\n');
+        
+        // One write per line, so a recording cut short by a crash is still readable
+        if let Err(e) = current.file.write_all(line.as_bytes()) {
+            tracing::warn!("Stopped recording to {}: {}", current.path.display(), e);
+            *recording = None;
+        }
+    }
+}
+
+/// A transport that writes its traffic to a `SessionTape`
+pub struct SessionRecorder {
+    inner: Arc<dyn Transport>,
+    tape: SessionTape,
+}
+
+impl SessionRecorder {
+    pub fn new(inner: Arc<dyn Transport>, tape: SessionTape) -> Self {
+        Self { inner, tape }
+    }
+}
+
+#[async_trait]
+impl Transport for SessionRecorder {
+    async fn send(&self, message: JsonRpcMessage) -> Result<(), McpError> {
+        self.tape.write(Direction::Sent, &message);
+        self.inner.send(message).await
+    }
+    
+    async fn receive(&self) -> Result<JsonRpcMessage, McpError> {
+        let message = self.inner.receive().await?;
+        self.tape.write(Direction::Received, &message);
+        Ok(message)
+    }
+    
+    async fn close(&self) -> Result<(), McpError> {
+        self.inner.close().await
+    }
+}
+
+/// Read a recording written by a `SessionRecorder`
+pub fn load(path: &Path) -> Result<Vec<SessionEntry>, McpError> {
+    let file = File::open(path).map_err(|e| McpError::TransportError(format!("Cannot open {}: {}", path.display(), e)))?;
+    
+    let mut entries = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| McpError::TransportError(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line).map_err(|e| McpError::ParseError {
+            message: format!("{} line {}: {}", path.display(), index + 1, e),
+            data: None,
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+struct ReplayState {
+    entries: VecDeque<SessionEntry>,
+    // Ids of the client's requests in the recording, mapped to the ids it
+    // sends now, so the recorded responses answer the requests being made
+    ids: HashMap<String, Value>,
+    closed: bool,
+}
+
+/// A transport that plays a recording back in place of its server.
+///
+/// A recorded server message is received once every client message recorded
+/// before it has been sent. Sending a message the recording does not contain
+/// fails, so a replay stops where the client no longer behaves as it did.
+pub struct ReplayTransport {
+    state: Mutex<ReplayState>,
+    advanced: Notify,
+}
+
+impl ReplayTransport {
+    pub fn new(entries: Vec<SessionEntry>) -> Self {
+        Self {
+            state: Mutex::new(ReplayState {
+                entries: entries.into(),
+                ids: HashMap::new(),
+                closed: false,
+            }),
+            advanced: Notify::new(),
+        }
+    }
+    
+    /// Replay the recording at `path`
+    pub fn open(path: &Path) -> Result<Self, McpError> {
+        Ok(Self::new(load(path)?))
+    }
+    
+    /// Recorded messages not yet sent or received
+    pub fn remaining(&self) -> usize {
+        self.state.lock().map(|state| state.entries.len()).unwrap_or(0)
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn send(&self, message: JsonRpcMessage) -> Result<(), McpError> {
+        let mut state = self.state.lock().map_err(|e| McpError::internal(format!("Failed to lock replay: {}", e)))?;
+        if state.closed {
+            return Err(McpError::ConnectionClosed);
+        }
+        
+        // Messages the client sends on its own, such as pings, may come in a
+        // different order than they were recorded in
+        let entry = state
+            .entries
+            .iter()
+            .position(|entry| entry.direction == Direction::Sent && same_message(&entry.message, &message))
+            .and_then(|position| state.entries.remove(position))
+            .ok_or_else(|| McpError::ProtocolError(format!("Replay diverged: {} was not recorded", describe(&message))))?;
+        
+        if let (JsonRpcMessage::Request(request), Some(recorded)) = (&message, entry.message.get("id")) {
+            state.ids.insert(id_key(recorded), request.id.clone());
+        }
+        drop(state);
+        
+        self.advanced.notify_waiters();
+        Ok(())
+    }
+    
+    async fn receive(&self) -> Result<JsonRpcMessage, McpError> {
+        loop {
+            let advanced = self.advanced.notified();
+            {
+                let mut state = self.state.lock().map_err(|e| McpError::internal(format!("Failed to lock replay: {}", e)))?;
+                if state.closed {
+                    return Err(McpError::ConnectionClosed);
+                }
+                match state.entries.front() {
+                    // The server closed the connection where the recording ends
+                    None => return Err(McpError::ConnectionClosed),
+                    Some(entry) if entry.direction == Direction::Received => {
+                        let mut message = state.entries.pop_front().map(|entry| entry.message).unwrap_or_default();
+                        if message.get("method").is_none() {
+                            if let Some(id) = message.get("id").and_then(|id| state.ids.remove(&id_key(id))) {
+                                message["id"] = id;
+                            }
+                        }
+                        return serde_json::from_value(message).map_err(|e| McpError::ParseError {
+                            message: e.to_string(),
+                            data: None,
+                        });
+                    }
+                    // Waiting for the client to send what it sent before this
+                    Some(_) => {}
+                }
+            }
+            advanced.await;
+        }
+    }
+    
+    async fn close(&self) -> Result<(), McpError> {
+        if let Ok(mut state) = self.state.lock() {
+            state.closed = true;
+        }
+        self.advanced.notify_waiters();
+        Ok(())
+    }
+}
+
+/// Whether `sent` is the message recorded as `recorded`. Requests and
+/// notifications match by method, responses to the server by id.
+fn same_message(recorded: &Value, sent: &JsonRpcMessage) -> bool {
+    match sent {
+        JsonRpcMessage::Request(request) => {
+            recorded.get("id").is_some() && recorded.get("method").and_then(Value::as_str) == Some(&request.method)
+        }
+        JsonRpcMessage::Notification(notification) => {
+            recorded.get("id").is_none() && recorded.get("method").and_then(Value::as_str) == Some(&notification.method)
+        }
+        JsonRpcMessage::Response(response) => {
+            recorded.get("method").is_none() && recorded.get("id") == Some(&response.id)
+        }
+    }
+}
+
+fn describe(message: &JsonRpcMessage) -> String {
+    match message {
+        JsonRpcMessage::Request(request) => format!("request {}", request.method),
+        JsonRpcMessage::Notification(notification) => format!("notification {}", notification.method),
+        JsonRpcMessage::Response(response) => format!("response to {}", response.id),
+    }
+}
+
+/// Request ids may be strings or numbers; `1` and `"1"` are the same request
+fn id_key(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    }
+}
@@ -0,0 +1,69 @@
+//! Sharing one request among callers asking the same thing at once.
+//!
+//! The tools panel and the agent loop often list a server's tools at the same
+//! moment, e.g. right after it connected. A slow stdio server then has to
+//! answer the same `tools/list` twice in a row. `InFlight` lets the second
+//! caller wait for the first one's request instead of sending its own. Only
+//! requests still running are shared; a caller arriving after one finished
+//! sends a new request, so no result is served stale.
+
+use crate::types::{ListPromptsResult, ListResourcesResult, ListToolsResult, McpError};
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+type Flight<T> = Shared<BoxFuture<'static, Result<T, McpError>>>;
+
+/// Requests in flight by key
+pub(crate) struct InFlight<T> {
+    flights: Mutex<HashMap<String, Flight<T>>>,
+}
+
+impl<T> Default for InFlight<T> {
+    fn default() -> Self {
+        Self { flights: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> InFlight<T> {
+    /// The result of the request in flight for `key`, or of the one `request`
+    /// starts if there is none. The request keeps running as long as anyone
+    /// waits for it.
+    pub(crate) async fn run<F>(&self, key: &str, request: impl FnOnce() -> F) -> Result<T, McpError>
+    where
+        F: Future<Output = Result<T, McpError>> + Send + 'static,
+    {
+        // The lock goes before anything is awaited, or the future isn't `Send`
+        let flight = match self.flights.lock() {
+            Ok(mut flights) => Ok(flights
+                .entry(key.to_string())
+                .or_insert_with(|| request().boxed().shared())
+                .clone()),
+            Err(_) => Err(request),
+        };
+        let flight = match flight {
+            Ok(flight) => flight,
+            // Not worth failing over; just don't share
+            Err(request) => return request().await,
+        };
+        
+        let result = flight.clone().await;
+        
+        // Whoever gets here first retires the flight, unless a new one took its place
+        if let Ok(mut flights) = self.flights.lock() {
+            if flights.get(key).is_some_and(|current| current.ptr_eq(&flight)) {
+                flights.remove(key);
+            }
+        }
+        result
+    }
+}
+
+/// The list requests the manager shares, keyed by server
+#[derive(Default)]
+pub(crate) struct ListRequests {
+    pub tools: InFlight<ListToolsResult>,
+    pub resources: InFlight<ListResourcesResult>,
+    pub prompts: InFlight<ListPromptsResult>,
+}
@@ -0,0 +1,155 @@
+//! Keeping large binary content out of the IPC bridge.
+//!
+//! Tool results and resources may carry images or documents as base64 data
+//! several megabytes long. Passing them to the frontend as they are costs a
+//! serialized copy on each side of the bridge. A `SpillDir` decodes such data
+//! into files of its own directory instead and replaces the content item with
+//! an embedded resource pointing at the file, so only its path and a few
+//! properties are sent.
+
+use crate::types::Content;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Directory under the app's cache directory that content is spilled to
+pub const SPILL_DIR: &str = "mcp-content";
+
+/// Base64 data longer than this is written to a file
+pub const DEFAULT_SPILL_BYTES: usize = 256 * 1024;
+
+/// Spilled files not touched for this long are removed by `SpillDir::prune`
+pub const SPILL_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Property set on embedded resources that replaced spilled content
+pub const SPILLED_PROPERTY: &str = "spilled";
+
+/// Where large binary content is written. The default one has no directory
+/// and keeps all content inline.
+#[derive(Debug, Clone)]
+pub struct SpillDir {
+    dir: Option<PathBuf>,
+    threshold: usize,
+}
+
+impl Default for SpillDir {
+    fn default() -> Self {
+        Self { dir: None, threshold: DEFAULT_SPILL_BYTES }
+    }
+}
+
+impl SpillDir {
+    /// Spill into `dir`, creating it if needed
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir: Some(dir), threshold: DEFAULT_SPILL_BYTES })
+    }
+    
+    /// Remove files spilled more than `SPILL_MAX_AGE` ago. Returns how many
+    /// were removed.
+    pub fn prune(&self) -> usize {
+        self.dir.as_deref().map_or(0, |dir| prune(dir, SPILL_MAX_AGE))
+    }
+    
+    /// Spill base64 data longer than `bytes` instead of the default
+    pub fn with_threshold(mut self, bytes: usize) -> Self {
+        self.threshold = bytes;
+        self
+    }
+    
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+    
+    /// Write every image in `content` with more base64 data than the threshold
+    /// to a file and replace it with an embedded resource for the file. Its
+    /// properties hold the `path`, `mime_type` and decoded `size`. Returns how
+    /// many items were replaced; items spilled before an error stay replaced.
+    pub async fn spill(&self, content: &mut [Content]) -> io::Result<usize> {
+        let Some(dir) = &self.dir else {
+            return Ok(0);
+        };
+        
+        let mut spilled = 0;
+        for item in content.iter_mut() {
+            let Content::Image { mime_type, data } = item else {
+                continue;
+            };
+            if data.len() <= self.threshold {
+                continue;
+            }
+            
+            let bytes = STANDARD
+                .decode(data.trim_end())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            
+            // Named after the content, so the same image is only written once
+            let path = dir.join(format!("{:x}.{}", Sha256::digest(&bytes), extension(mime_type)));
+            if !tokio::fs::try_exists(&path).await? {
+                tokio::fs::write(&path, &bytes).await?;
+            }
+            
+            let properties = HashMap::from([
+                (SPILLED_PROPERTY.to_string(), "true".to_string()),
+                ("path".to_string(), path.to_string_lossy().into_owned()),
+                ("mime_type".to_string(), mime_type.clone()),
+                ("size".to_string(), bytes.len().to_string()),
+            ]);
+            *item = Content::EmbeddedResource { uri: file_uri(&path), properties: Some(properties) };
+            spilled += 1;
+        }
+        Ok(spilled)
+    }
+}
+
+/// Remove files in `dir` last modified more than `max_age` ago
+fn prune(dir: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > max_age);
+        if expired {
+            match fs::remove_file(entry.path()) {
+                Ok(()) => removed += 1,
+                Err(e) => tracing::warn!("Failed to remove {}: {}", entry.path().display(), e),
+            }
+        }
+    }
+    removed
+}
+
+fn extension(mime_type: &str) -> &str {
+    match mime_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "application/pdf" => "pdf",
+        _ => "bin",
+    }
+}
+
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        // Windows paths start with the drive letter
+        format!("file:///{}", path)
+    }
+}
@@ -0,0 +1,633 @@
+//! Structured logs of the MCP stack.
+//!
+//! The transports, client and manager report through `tracing`. `init`
+//! installs `JsonLines` as the global subscriber: every event is written as a
+//! JSON object on its own line, together with the spans it happened in, to
+//! `mlface.log` in the log directory. The file is rotated to `mlface.log.1`
+//! and so on once it grows past `MAX_FILE_BYTES`, keeping `KEEP_FILES` of
+//! them. Records of the `log` crate are forwarded to the same file.
+//!
+//! A `SpanSink` passed to `init` is also handed every span once it closes,
+//! which is how the `otlp` feature exports them.
+//!
+//! What gets recorded is decided by the subscriber's `LogLevels`, which can be
+//! changed while the app runs: a global level, and levels for the transports,
+//! the client and the manager that override it, so one part can be made
+//! verbose while a bug is reproduced without flooding the log with the rest.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, ThreadId};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Metadata, Subscriber};
+
+pub use tracing::Level;
+
+/// Directory of the log files, under the app data dir
+pub const LOG_DIR: &str = "logs";
+
+pub const LOG_FILE: &str = "mlface.log";
+
+/// The log file is rotated once it grows past this size
+pub const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Log files kept, counting the current one
+pub const KEEP_FILES: usize = 5;
+
+// Whether `log` records are forwarded to the subscriber, and so follow its level
+static LOG_BRIDGED: AtomicBool = AtomicBool::new(false);
+
+/// Install `JsonLines` writing to `dir` as the global subscriber, and forward
+/// `log` records to it. Closed spans are passed on to `sink`. Returns the
+/// levels the subscriber records at, for changing them later. Fails if a
+/// subscriber is already installed.
+pub fn init(dir: &Path, level: Level, sink: Option<Arc<dyn SpanSink>>) -> io::Result<Arc<LogLevels>> {
+    let mut subscriber = JsonLines::new(RotatingFile::open(dir, MAX_FILE_BYTES, KEEP_FILES)?, level);
+    if let Some(sink) = sink {
+        subscriber = subscriber.with_sink(sink);
+    }
+    let levels = subscriber.levels();
+    tracing::subscriber::set_global_default(subscriber).map_err(io::Error::other)?;
+    
+    // The app may have installed a logger of its own
+    if log::set_logger(&LogBridge).is_ok() {
+        log::set_max_level(log_level_filter(level));
+        LOG_BRIDGED.store(true, Ordering::Relaxed);
+    }
+    Ok(levels)
+}
+
+fn log_level_filter(level: Level) -> log::LevelFilter {
+    match level {
+        Level::ERROR => log::LevelFilter::Error,
+        Level::WARN => log::LevelFilter::Warn,
+        Level::INFO => log::LevelFilter::Info,
+        Level::DEBUG => log::LevelFilter::Debug,
+        Level::TRACE => log::LevelFilter::Trace,
+    }
+}
+
+/// Parts of the MCP stack whose level can be set apart from the global one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogModule {
+    Transport,
+    Client,
+    Manager,
+}
+
+impl LogModule {
+    /// The module whose events `target` names, if any
+    fn of(target: &str) -> Option<Self> {
+        [Self::Transport, Self::Client, Self::Manager].into_iter().find(|module| {
+            let prefix = module.target();
+            target == prefix || target.strip_prefix(prefix).is_some_and(|rest| rest.starts_with("::"))
+        })
+    }
+    
+    fn target(self) -> &'static str {
+        match self {
+            Self::Transport => "mlface_mcp::transport",
+            Self::Client => "mlface_mcp::client",
+            Self::Manager => "mlface_mcp::server",
+        }
+    }
+}
+
+/// The levels in effect, as returned by `LogLevels::report`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogLevelsReport {
+    /// `error`, `warn`, `info`, `debug` or `trace`
+    pub global: String,
+    /// Modules recorded at another level than the global one
+    pub modules: BTreeMap<LogModule, String>,
+}
+
+struct Levels {
+    global: Level,
+    modules: HashMap<LogModule, Level>,
+}
+
+/// The levels a `JsonLines` records events at. Changes apply to events from
+/// then on.
+pub struct LogLevels {
+    levels: RwLock<Levels>,
+}
+
+impl LogLevels {
+    pub fn new(global: Level) -> Self {
+        Self {
+            levels: RwLock::new(Levels {
+                global,
+                modules: HashMap::new(),
+            }),
+        }
+    }
+    
+    /// Record events at `level` and more severe ones, everywhere but in modules
+    /// with a level of their own
+    pub fn set_global(&self, level: Level) {
+        if let Ok(mut levels) = self.levels.write() {
+            levels.global = level;
+        }
+        self.changed();
+    }
+    
+    /// Record events of `module` at `level`, or at the global level again
+    pub fn set_module(&self, module: LogModule, level: Option<Level>) {
+        if let Ok(mut levels) = self.levels.write() {
+            match level {
+                Some(level) => levels.modules.insert(module, level),
+                None => levels.modules.remove(&module),
+            };
+        }
+        self.changed();
+    }
+    
+    pub fn report(&self) -> LogLevelsReport {
+        let Ok(levels) = self.levels.read() else {
+            return LogLevelsReport {
+                global: Level::INFO.as_str().to_lowercase(),
+                modules: BTreeMap::new(),
+            };
+        };
+        
+        LogLevelsReport {
+            global: levels.global.as_str().to_lowercase(),
+            modules: levels
+                .modules
+                .iter()
+                .map(|(module, level)| (*module, level.as_str().to_lowercase()))
+                .collect(),
+        }
+    }
+    
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        let Ok(levels) = self.levels.read() else {
+            return false;
+        };
+        let level = LogModule::of(metadata.target())
+            .and_then(|module| levels.modules.get(&module))
+            .unwrap_or(&levels.global);
+        metadata.level() <= level
+    }
+    
+    /// The most verbose level anything is recorded at
+    fn max(&self) -> Level {
+        let Ok(levels) = self.levels.read() else {
+            return Level::INFO;
+        };
+        // More verbose levels compare as greater
+        levels.modules.values().copied().fold(levels.global, Level::max)
+    }
+    
+    fn changed(&self) {
+        // Callsites remember whether they were enabled and the `log` crate has
+        // a maximum of its own
+        tracing::callsite::rebuild_interest_cache();
+        if LOG_BRIDGED.load(Ordering::Relaxed) {
+            if let Ok(levels) = self.levels.read() {
+                log::set_max_level(log_level_filter(levels.global));
+            }
+        }
+    }
+}
+
+/// The last `limit` entries in the log files of `dir`, oldest first. Lines
+/// that are not JSON, such as a line cut short by a crash, are skipped.
+pub fn recent(dir: &Path, limit: usize) -> io::Result<Vec<Value>> {
+    let mut entries = Vec::new();
+    for path in log_files(dir).iter().rev() {
+        if entries.len() >= limit {
+            break;
+        }
+        let file = BufReader::new(File::open(path)?);
+        let mut lines: Vec<Value> = file
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect();
+        lines.append(&mut entries);
+        entries = lines;
+    }
+    
+    let skip = entries.len().saturating_sub(limit);
+    entries.drain(..skip);
+    Ok(entries)
+}
+
+/// Copy the files of each directory in `sources` into a new folder under
+/// `destination`, one subfolder per source, for attaching to a bug report.
+/// Missing sources are skipped. Returns the new folder.
+pub fn pack(sources: &[PathBuf], destination: &Path) -> io::Result<PathBuf> {
+    let folder = destination.join(format!("mlface-logs-{}", now_millis()));
+    fs::create_dir_all(&folder)?;
+    
+    for source in sources {
+        let Ok(entries) = fs::read_dir(source) else {
+            continue;
+        };
+        let name = source.file_name().unwrap_or(source.as_os_str());
+        let target = folder.join(name);
+        fs::create_dir_all(&target)?;
+        
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                fs::copy(entry.path(), target.join(entry.file_name()))?;
+            }
+        }
+    }
+    Ok(folder)
+}
+
+/// The log files in `dir`, oldest first
+pub fn log_files(dir: &Path) -> Vec<PathBuf> {
+    (0..KEEP_FILES)
+        .rev()
+        .map(|index| rotated_path(dir, index))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+fn rotated_path(dir: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => dir.join(LOG_FILE),
+        index => dir.join(format!("{}.{}", LOG_FILE, index)),
+    }
+}
+
+fn now_millis() -> u64 {
+    (now_nanos() / 1_000_000) as u64
+}
+
+fn now_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
+
+/// `mlface.log` in a directory, rotated by size
+pub struct RotatingFile {
+    dir: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    pub fn open(dir: &Path, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let file = OpenOptions::new().create(true).append(true).open(dir.join(LOG_FILE))?;
+        let written = file.metadata()?.len();
+        
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            keep: keep.max(1),
+            file,
+            written,
+        })
+    }
+    
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.written > 0 && self.written + line.len() as u64 + 1 > self.max_bytes {
+            self.rotate()?;
+        }
+        
+        writeln!(self.file, "{}", line)?;
+        self.written += line.len() as u64 + 1;
+        Ok(())
+    }
+    
+    /// Shift every file up by one, dropping the oldest, and start a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        let _ = fs::remove_file(rotated_path(&self.dir, self.keep - 1));
+        for index in (0..self.keep - 1).rev() {
+            let from = rotated_path(&self.dir, index);
+            if from.exists() {
+                fs::rename(from, rotated_path(&self.dir, index + 1))?;
+            }
+        }
+        
+        self.file = OpenOptions::new().create(true).append(true).open(rotated_path(&self.dir, 0))?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+/// A span that has closed, as handed to a `SpanSink`
+#[derive(Debug, Clone)]
+pub struct FinishedSpan {
+    /// Shared by a span and all its descendants
+    pub trace_id: u128,
+    pub span_id: u64,
+    pub parent_span_id: Option<u64>,
+    pub name: &'static str,
+    pub target: &'static str,
+    pub fields: Map<String, Value>,
+    /// Nanoseconds since the Unix epoch
+    pub start: u128,
+    pub end: u128,
+    /// Whether a warning or error was logged directly in the span
+    pub failed: bool,
+}
+
+/// Receives spans as they close
+pub trait SpanSink: Send + Sync {
+    fn span_closed(&self, span: FinishedSpan);
+}
+
+struct SpanData {
+    name: &'static str,
+    target: &'static str,
+    fields: Map<String, Value>,
+    parent: Option<u64>,
+    refs: usize,
+    trace_id: u128,
+    start: u128,
+    failed: bool,
+}
+
+/// Subscriber writing events as JSON lines to a `RotatingFile`
+pub struct JsonLines {
+    levels: Arc<LogLevels>,
+    file: Mutex<RotatingFile>,
+    spans: Mutex<HashMap<u64, SpanData>>,
+    // The spans entered on each thread, innermost last
+    stacks: Mutex<HashMap<ThreadId, Vec<u64>>>,
+    next_id: AtomicU64,
+    sink: Option<Arc<dyn SpanSink>>,
+    // Mixed into span and trace ids so they differ between runs
+    seed: u64,
+}
+
+impl JsonLines {
+    /// Record events at `level` and more severe ones
+    pub fn new(file: RotatingFile, level: Level) -> Self {
+        Self {
+            levels: Arc::new(LogLevels::new(level)),
+            file: Mutex::new(file),
+            spans: Mutex::new(HashMap::new()),
+            stacks: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            sink: None,
+            seed: now_nanos() as u64,
+        }
+    }
+    
+    /// The levels events are recorded at, for changing them while the
+    /// subscriber is in use
+    pub fn levels(&self) -> Arc<LogLevels> {
+        self.levels.clone()
+    }
+    
+    /// Also hand every span to `sink` once it closes
+    pub fn with_sink(mut self, sink: Arc<dyn SpanSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+    
+    fn current(&self) -> Option<u64> {
+        let stacks = self.stacks.lock().ok()?;
+        stacks.get(&thread::current().id())?.last().copied()
+    }
+    
+    /// The span `id` and its ancestors, outermost first
+    fn scope(&self, id: Option<u64>) -> Vec<Value> {
+        let Ok(spans) = self.spans.lock() else {
+            return Vec::new();
+        };
+        
+        let mut scope = Vec::new();
+        let mut next = id;
+        while let Some(span) = next.and_then(|id| spans.get(&id)) {
+            let mut entry = span.fields.clone();
+            entry.insert("name".to_string(), Value::from(span.name));
+            scope.push(Value::Object(entry));
+            next = span.parent;
+        }
+        scope.reverse();
+        scope
+    }
+}
+
+impl Subscriber for JsonLines {
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // Levels can change, so ask `enabled` every time
+        Interest::sometimes()
+    }
+    
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.levels.enabled(metadata)
+    }
+    
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from_level(self.levels.max()))
+    }
+    
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut fields = Map::new();
+        attributes.record(&mut JsonVisitor(&mut fields));
+        
+        let parent = if attributes.is_contextual() {
+            self.current()
+        } else {
+            attributes.parent().map(Id::into_u64)
+        };
+        if let Ok(mut spans) = self.spans.lock() {
+            // A root span starts a new trace
+            let trace_id = parent
+                .and_then(|parent| spans.get(&parent))
+                .map(|parent| parent.trace_id)
+                .unwrap_or_else(|| (u128::from(self.seed) << 64) | u128::from(id));
+            spans.insert(id, SpanData {
+                name: attributes.metadata().name(),
+                target: attributes.metadata().target(),
+                fields,
+                parent,
+                refs: 1,
+                trace_id,
+                start: now_nanos(),
+                failed: false,
+            });
+        }
+        Id::from_u64(id)
+    }
+    
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(span) = self.spans.lock().ok().as_mut().and_then(|spans| spans.get_mut(&span.into_u64())) {
+            values.record(&mut JsonVisitor(&mut span.fields));
+        }
+    }
+    
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+    
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+        let message = fields.remove("message").unwrap_or(Value::Null);
+        
+        let parent = if event.is_contextual() {
+            self.current()
+        } else {
+            event.parent().map(Id::into_u64)
+        };
+        let metadata = event.metadata();
+        
+        // A warning or error marks the span it happened in as failed
+        if *metadata.level() <= Level::WARN {
+            if let (Some(parent), Ok(mut spans)) = (parent, self.spans.lock()) {
+                if let Some(span) = spans.get_mut(&parent) {
+                    span.failed = true;
+                }
+            }
+        }
+        
+        let mut entry = Map::new();
+        entry.insert("timestamp".to_string(), Value::from(now_millis()));
+        entry.insert("level".to_string(), Value::from(metadata.level().as_str()));
+        entry.insert("target".to_string(), Value::from(metadata.target()));
+        entry.insert("message".to_string(), message);
+        if !fields.is_empty() {
+            entry.insert("fields".to_string(), Value::Object(fields));
+        }
+        let spans = self.scope(parent);
+        if !spans.is_empty() {
+            entry.insert("spans".to_string(), Value::Array(spans));
+        }
+        
+        let line = Value::Object(entry).to_string();
+        if let Ok(mut file) = self.file.lock() {
+            // Nowhere left to report a failure to log
+            let _ = file.write_line(&line);
+        }
+    }
+    
+    fn enter(&self, span: &Id) {
+        if let Ok(mut stacks) = self.stacks.lock() {
+            stacks.entry(thread::current().id()).or_default().push(span.into_u64());
+        }
+    }
+    
+    fn exit(&self, span: &Id) {
+        if let Ok(mut stacks) = self.stacks.lock() {
+            let thread = thread::current().id();
+            if let Some(stack) = stacks.get_mut(&thread) {
+                if let Some(position) = stack.iter().rposition(|id| *id == span.into_u64()) {
+                    stack.remove(position);
+                }
+                if stack.is_empty() {
+                    stacks.remove(&thread);
+                }
+            }
+        }
+    }
+    
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(span) = self.spans.lock().ok().as_mut().and_then(|spans| spans.get_mut(&span.into_u64())) {
+            span.refs += 1;
+        }
+        span.clone()
+    }
+    
+    fn try_close(&self, span: Id) -> bool {
+        let Ok(mut spans) = self.spans.lock() else {
+            return false;
+        };
+        let id = span.into_u64();
+        let Some(data) = spans.get_mut(&id) else {
+            return false;
+        };
+        
+        data.refs -= 1;
+        if data.refs > 0 {
+            return false;
+        }
+        let Some(data) = spans.remove(&id) else {
+            return false;
+        };
+        
+        if let Some(sink) = &self.sink {
+            drop(spans);
+            sink.span_closed(FinishedSpan {
+                trace_id: data.trace_id,
+                span_id: id ^ self.seed,
+                parent_span_id: data.parent.map(|parent| parent ^ self.seed),
+                name: data.name,
+                target: data.target,
+                fields: data.fields,
+                start: data.start,
+                end: now_nanos(),
+                failed: data.failed,
+            });
+        }
+        true
+    }
+}
+
+/// Collects the fields of a span or event as JSON values
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), Value::from(format!("{:?}", value)));
+    }
+    
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+    
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+    
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+    
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+    
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+}
+
+/// Forwards `log` records as `tracing` events
+struct LogBridge;
+
+impl log::Log for LogBridge {
+    fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+        true
+    }
+    
+    fn log(&self, record: &log::Record<'_>) {
+        let target = record.target();
+        let message = record.args();
+        match record.level() {
+            log::Level::Error => tracing::error!(log.target = target, "{}", message),
+            log::Level::Warn => tracing::warn!(log.target = target, "{}", message),
+            log::Level::Info => tracing::info!(log.target = target, "{}", message),
+            log::Level::Debug => tracing::debug!(log.target = target, "{}", message),
+            log::Level::Trace => tracing::trace!(log.target = target, "{}", message),
+        }
+    }
+    
+    fn flush(&self) {}
+}
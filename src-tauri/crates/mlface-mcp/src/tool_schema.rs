@@ -0,0 +1,227 @@
+//! MCP tools as model providers take them for function calling.
+//!
+//! Providers are stricter than MCP: tool names must match
+//! `^[a-zA-Z0-9_-]{1,64}$`, the input schema must be an object schema, some
+//! JSON Schema keywords are rejected, and requests have limits on the number
+//! and size of tools. A `ToolSet` gives each server's tool a name that fits,
+//! unique across servers, and maps the names the model calls back to the
+//! server and tool. Schemas are trimmed to what providers accept and, when
+//! too large, stripped of their descriptions or left as a bare object.
+
+use crate::types::Tool;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+/// Longest tool name providers accept
+pub const MAX_NAME_LEN: usize = 64;
+
+/// Longest tool description sent, in characters
+pub const MAX_DESCRIPTION_LEN: usize = 1024;
+
+/// Largest input schema sent as it is, in bytes of JSON
+pub const MAX_SCHEMA_BYTES: usize = 16 * 1024;
+
+/// Most tools offered in one request
+pub const MAX_TOOLS: usize = 128;
+
+/// Joins server and tool in tool names
+const NAME_SEPARATOR: &str = "__";
+
+/// Keywords dropped from schemas: metadata providers reject or that only
+/// takes up room
+const DROPPED_KEYWORDS: [&str; 5] = ["$schema", "$id", "$comment", "examples", "deprecated"];
+
+/// Keywords whose value maps names to schemas
+const SCHEMA_MAPS: [&str; 4] = ["properties", "patternProperties", "$defs", "definitions"];
+
+/// Keywords whose value is a schema or a list of them
+const SUBSCHEMAS: [&str; 10] = [
+    "items",
+    "prefixItems",
+    "additionalProperties",
+    "not",
+    "anyOf",
+    "oneOf",
+    "allOf",
+    "if",
+    "then",
+    "else",
+];
+
+/// The function-calling format of a provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolFormat {
+    /// `{"type": "function", "function": {name, description, parameters}}`,
+    /// taken by OpenAI-compatible APIs and Ollama
+    OpenAi,
+    /// `{name, description, input_schema}`
+    Anthropic,
+}
+
+/// A tool of `server` under the name the model calls it by
+#[derive(Debug, Clone)]
+pub struct ModelTool {
+    pub name: String,
+    pub server: String,
+    pub tool: Tool,
+}
+
+impl ModelTool {
+    /// The definition of the tool in `format`
+    pub fn definition(&self, format: ToolFormat) -> Value {
+        let description = self.tool.description.as_deref().map(truncate).unwrap_or_default();
+        let schema = trim_schema(&self.tool.input_schema);
+        match format {
+            ToolFormat::OpenAi => json!({
+                "type": "function",
+                "function": { "name": self.name, "description": description, "parameters": schema },
+            }),
+            ToolFormat::Anthropic => json!({ "name": self.name, "description": description, "input_schema": schema }),
+        }
+    }
+}
+
+/// The tools offered to a model, by the names it knows them by
+#[derive(Debug, Clone, Default)]
+pub struct ToolSet {
+    tools: Vec<ModelTool>,
+}
+
+impl ToolSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Offer `tool` of `server`, under `server__tool` made to fit. Returns
+    /// the name, or `None` once the set holds `MAX_TOOLS`.
+    pub fn add(&mut self, server: &str, tool: Tool) -> Option<&str> {
+        if self.tools.len() >= MAX_TOOLS {
+            return None;
+        }
+        let qualified = format!("{}{}{}", server, NAME_SEPARATOR, tool.name);
+        let base = sanitize_name(&qualified);
+        // Sanitizing or shortening may have given two tools the same name, as
+        // may a separator in the server's or the tool's name. The hash takes
+        // the two apart, and an attempt count in case it collides as well.
+        let mut name = base.clone();
+        let mut attempt = 0u32;
+        while self.get(&name).is_some() {
+            name = with_hash(&base, &format!("{}\0{}\0{}", server, tool.name, attempt));
+            attempt += 1;
+        }
+        self.tools.push(ModelTool { name, server: server.to_string(), tool });
+        self.tools.last().map(|tool| tool.name.as_str())
+    }
+    
+    /// The tool the model calls `name`
+    pub fn get(&self, name: &str) -> Option<&ModelTool> {
+        self.tools.iter().find(|tool| tool.name == name)
+    }
+    
+    pub fn iter(&self) -> impl Iterator<Item = &ModelTool> {
+        self.tools.iter()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+    
+    /// The definitions of all tools in `format`
+    pub fn definitions(&self, format: ToolFormat) -> Vec<Value> {
+        self.tools.iter().map(|tool| tool.definition(format)).collect()
+    }
+}
+
+/// `name` with characters providers reject replaced by `_`, shortened to
+/// `MAX_NAME_LEN` with a hash of the whole name to keep it unique
+pub fn sanitize_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    if sanitized.is_empty() {
+        return with_hash("tool", name);
+    }
+    if sanitized.len() > MAX_NAME_LEN {
+        return with_hash(&sanitized, name);
+    }
+    sanitized
+}
+
+/// `name` ending in a hash of `original`, within `MAX_NAME_LEN`
+fn with_hash(name: &str, original: &str) -> String {
+    let hash = format!("{:x}", Sha256::digest(original.as_bytes()));
+    let hash = &hash[..8];
+    // Sanitized names are ASCII, so any byte is a character boundary
+    let keep = name.len().min(MAX_NAME_LEN - hash.len() - 1);
+    format!("{}_{}", &name[..keep], hash)
+}
+
+/// `text` cut to `MAX_DESCRIPTION_LEN` characters
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_DESCRIPTION_LEN {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_DESCRIPTION_LEN - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// `schema` as providers accept it: an object schema, without the keywords
+/// they reject, and within `MAX_SCHEMA_BYTES`, dropping descriptions and
+/// then all but the top level if it is larger
+pub fn trim_schema(schema: &Value) -> Value {
+    let mut schema = match schema {
+        Value::Object(fields) => Value::Object(fields.clone()),
+        _ => json!({}),
+    };
+    trim(&mut schema, false);
+    // Some servers leave out the type or the properties of tools without
+    // arguments; providers insist on both
+    if schema.get("type").is_none() {
+        schema["type"] = json!("object");
+    }
+    if schema["type"] == "object" && schema.get("properties").is_none() {
+        schema["properties"] = Value::Object(Map::new());
+    }
+    
+    if size(&schema) > MAX_SCHEMA_BYTES {
+        trim(&mut schema, true);
+    }
+    if size(&schema) > MAX_SCHEMA_BYTES {
+        schema = json!({ "type": "object", "properties": {} });
+    }
+    schema
+}
+
+fn size(schema: &Value) -> usize {
+    serde_json::to_vec(schema).map(|json| json.len()).unwrap_or(usize::MAX)
+}
+
+/// Drop `DROPPED_KEYWORDS`, and `description`s too if `descriptions`, from
+/// `schema` and the schemas within it
+fn trim(schema: &mut Value, descriptions: bool) {
+    let Some(fields) = schema.as_object_mut() else {
+        return;
+    };
+    fields.retain(|key, _| {
+        let dropped = DROPPED_KEYWORDS.contains(&key.as_str()) || (descriptions && key == "description");
+        !dropped
+    });
+    for (key, value) in fields.iter_mut() {
+        if SCHEMA_MAPS.contains(&key.as_str()) {
+            if let Some(schemas) = value.as_object_mut() {
+                schemas.values_mut().for_each(|schema| trim(schema, descriptions));
+            }
+        } else if SUBSCHEMAS.contains(&key.as_str()) {
+            match value {
+                Value::Array(schemas) => schemas.iter_mut().for_each(|schema| trim(schema, descriptions)),
+                schema => trim(schema, descriptions),
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Correlation ids for MCP requests.
+//!
+//! Every outgoing request gets an internal trace id that is included in all
+//! log lines related to it. The id is kept in a task-local while the request
+//! is dispatched, so transports can tag their own log lines without changing
+//! the `Transport` trait.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_TRACE_ID: AtomicU64 = AtomicU64::new(1);
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// Allocate a new trace id, unique for the lifetime of the process
+pub fn next_trace_id() -> String {
+    format!("{:08x}", NEXT_TRACE_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Run a future with the given trace id visible to `current_trace_id`
+pub async fn with_trace_id<F: Future>(trace_id: String, future: F) -> F::Output {
+    TRACE_ID.scope(trace_id, future).await
+}
+
+/// Trace id of the request being dispatched by the current task, or `-` if none
+pub fn current_trace_id() -> String {
+    TRACE_ID
+        .try_with(|id| id.clone())
+        .unwrap_or_else(|_| "-".to_string())
+}
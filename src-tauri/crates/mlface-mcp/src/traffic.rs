@@ -0,0 +1,271 @@
+//! Recorded JSON-RPC traffic, for inspecting what a server was sent and said.
+//!
+//! The manager wraps every transport in a `RecordingTransport`, which copies
+//! each message into the shared `TrafficLog` before it is sent or after it is
+//! received. The log keeps the most recent messages of all servers in a ring
+//! buffer, pairs responses with their requests to measure latency, and
+//! broadcasts every entry for live views.
+
+use crate::transport::Transport;
+use crate::types::{JsonRpcMessage, McpError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Messages kept across all servers
+pub const DEFAULT_TRAFFIC_CAPACITY: usize = 5000;
+
+const FOLLOW_CHANNEL_CAPACITY: usize = 1024;
+
+/// Requests unanswered this long are no longer expected to be
+const STALE_REQUEST: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageKind {
+    Request,
+    Response,
+    Notification,
+}
+
+/// One recorded message
+#[derive(Debug, Clone, Serialize)]
+pub struct TrafficEntry {
+    /// Increases by one with every message, so a view can ask for what is new
+    pub seq: u64,
+    pub server: String,
+    pub direction: Direction,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub kind: MessageKind,
+    /// For responses, the method of the request they answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    /// For responses, milliseconds since their request was sent or received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Whether the message is an error response
+    pub error: bool,
+    pub message: Value,
+}
+
+/// Which entries `TrafficLog::query` returns; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrafficFilter {
+    pub direction: Option<Direction>,
+    pub kind: Option<MessageKind>,
+    /// Matches methods containing this text
+    pub method: Option<String>,
+    pub errors_only: bool,
+    /// Only entries with a larger `seq`
+    pub after: Option<u64>,
+}
+
+impl TrafficFilter {
+    fn matches(&self, entry: &TrafficEntry) -> bool {
+        self.direction.is_none_or(|direction| entry.direction == direction)
+            && self.kind.is_none_or(|kind| entry.kind == kind)
+            && self.method.as_ref().is_none_or(|method| {
+                entry.method.as_ref().is_some_and(|entry_method| entry_method.contains(method.as_str()))
+            })
+            && (!self.errors_only || entry.error)
+            && self.after.is_none_or(|after| entry.seq > after)
+    }
+}
+
+/// A request waiting for its response, keyed by server and request id
+struct InFlight {
+    method: String,
+    at: Instant,
+}
+
+struct TrafficState {
+    entries: VecDeque<TrafficEntry>,
+    in_flight: HashMap<(String, String), InFlight>,
+    next_seq: u64,
+}
+
+/// Recent traffic of all servers
+pub struct TrafficLog {
+    state: Mutex<TrafficState>,
+    capacity: usize,
+    follow_tx: broadcast::Sender<TrafficEntry>,
+}
+
+impl Default for TrafficLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRAFFIC_CAPACITY)
+    }
+}
+
+impl TrafficLog {
+    pub fn new(capacity: usize) -> Self {
+        let (follow_tx, _) = broadcast::channel(FOLLOW_CHANNEL_CAPACITY);
+        
+        Self {
+            state: Mutex::new(TrafficState {
+                entries: VecDeque::new(),
+                in_flight: HashMap::new(),
+                next_seq: 1,
+            }),
+            capacity: capacity.max(1),
+            follow_tx,
+        }
+    }
+    
+    /// Record a message sent to or received from `server`
+    pub fn record(&self, server: &str, direction: Direction, message: &JsonRpcMessage) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        
+        let (kind, mut method, id, error) = match message {
+            JsonRpcMessage::Request(request) => {
+                (MessageKind::Request, Some(request.method.clone()), Some(request.id.clone()), false)
+            }
+            JsonRpcMessage::Response(response) => {
+                (MessageKind::Response, None, Some(response.id.clone()), response.error.is_some())
+            }
+            JsonRpcMessage::Notification(notification) => {
+                (MessageKind::Notification, Some(notification.method.clone()), None, false)
+            }
+        };
+        
+        // Requests are paired with responses going the other way
+        let mut latency_ms = None;
+        if let Some(id) = &id {
+            let key = (server.to_string(), id_key(id));
+            match kind {
+                MessageKind::Request => {
+                    if state.in_flight.len() >= self.capacity {
+                        state.in_flight.retain(|_, request| request.at.elapsed() < STALE_REQUEST);
+                    }
+                    let method = method.clone().unwrap_or_default();
+                    state.in_flight.insert(key, InFlight { method, at: Instant::now() });
+                }
+                MessageKind::Response => {
+                    if let Some(request) = state.in_flight.remove(&key) {
+                        latency_ms = Some(request.at.elapsed().as_millis() as u64);
+                        method = Some(request.method);
+                    }
+                }
+                MessageKind::Notification => {}
+            }
+        }
+        
+        let entry = TrafficEntry {
+            seq: state.next_seq,
+            server: server.to_string(),
+            direction,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            kind,
+            method,
+            id,
+            latency_ms,
+            error,
+            message: serde_json::to_value(message).unwrap_or(Value::Null),
+        };
+        state.next_seq += 1;
+        
+        if state.entries.len() == self.capacity {
+            state.entries.pop_front();
+        }
+        state.entries.push_back(entry.clone());
+        
+        // Nobody following is not an error
+        let _ = self.follow_tx.send(entry);
+    }
+    
+    /// The last `limit` entries of `server`, or of all servers, that match
+    /// `filter`, oldest first
+    pub fn query(&self, server: Option<&str>, filter: &TrafficFilter, limit: Option<usize>) -> Vec<TrafficEntry> {
+        let Ok(state) = self.state.lock() else {
+            return Vec::new();
+        };
+        
+        let mut entries: Vec<TrafficEntry> = state
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| server.is_none_or(|server| entry.server == server) && filter.matches(entry))
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        entries.reverse();
+        entries
+    }
+    
+    /// Forget a server's traffic, such as when it is unregistered
+    pub fn forget(&self, server: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            state.entries.retain(|entry| entry.server != server);
+            state.in_flight.retain(|(in_flight_server, _), _| in_flight_server != server);
+        }
+    }
+    
+    /// Receive entries as they are recorded
+    pub fn subscribe(&self) -> broadcast::Receiver<TrafficEntry> {
+        self.follow_tx.subscribe()
+    }
+}
+
+/// Request ids may be strings or numbers; `1` and `"1"` are the same request
+fn id_key(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    }
+}
+
+/// A transport that records its traffic in a `TrafficLog`
+pub struct RecordingTransport {
+    inner: Arc<dyn Transport>,
+    server: String,
+    log: Arc<TrafficLog>,
+}
+
+impl RecordingTransport {
+    pub fn new(inner: Arc<dyn Transport>, server: &str, log: Arc<TrafficLog>) -> Self {
+        Self {
+            inner,
+            server: server.to_string(),
+            log,
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for RecordingTransport {
+    async fn send(&self, message: JsonRpcMessage) -> Result<(), McpError> {
+        // Recorded first, so the clock for the response starts before it can arrive
+        self.log.record(&self.server, Direction::Sent, &message);
+        self.inner.send(message).await
+    }
+    
+    async fn receive(&self) -> Result<JsonRpcMessage, McpError> {
+        let message = self.inner.receive().await?;
+        self.log.record(&self.server, Direction::Received, &message);
+        Ok(message)
+    }
+    
+    async fn close(&self) -> Result<(), McpError> {
+        self.inner.close().await
+    }
+}
@@ -0,0 +1,684 @@
+use crate::types::{JsonRpcMessage, McpError};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Client as HttpClient;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fmt, io,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    process::{Child as TokioChild, Command as TokioCommand},
+    sync::{mpsc as tokio_mpsc, oneshot, watch},
+    task::JoinHandle,
+    time::timeout,
+};
+use std::process::Stdio;
+use tracing::Instrument;
+
+const TRANSPORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+type ReceiveSender = oneshot::Sender<Result<JsonRpcMessage, McpError>>;
+
+/// Which output stream of a server process a line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Callback receiving the lines a server process writes that are not protocol
+/// messages: all of stderr, and anything on stdout that isn't JSON-RPC
+pub type OutputHandler = Arc<dyn Fn(OutputStream, &str) + Send + Sync>;
+
+/// How long to keep reading stderr after stdout closed, to catch the last
+/// words of a crashing process
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// How long `close` waits for a killed process to exit
+const EXIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How much is read from a process's output at a time
+const READ_BUFFER_BYTES: usize = 64 * 1024;
+
+/// How a server process ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessExit {
+    /// Exit code, if the process exited by itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<i32>,
+    /// Signal that ended the process, on Unix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<i32>,
+}
+
+impl ProcessExit {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+impl From<std::process::ExitStatus> for ProcessExit {
+    fn from(status: std::process::ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+        
+        Self { code: status.code(), signal }
+    }
+}
+
+impl fmt::Display for ProcessExit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.code, self.signal) {
+            (Some(code), _) => write!(f, "exit {}", code),
+            (None, Some(signal)) => write!(f, "signal {}", signal),
+            (None, None) => write!(f, "exit status unknown"),
+        }
+    }
+}
+
+/// Hand a message to the oldest waiting receiver, skipping receivers that gave
+/// up (e.g. timed out). Returns the message if nobody is waiting for it.
+fn deliver(
+    receivers: &mut VecDeque<ReceiveSender>,
+    mut message: JsonRpcMessage,
+) -> Option<JsonRpcMessage> {
+    while let Some(tx) = receivers.pop_front() {
+        match tx.send(Ok(message)) {
+            Ok(()) => return None,
+            Err(Ok(returned)) => message = returned,
+            Err(Err(_)) => return None,
+        }
+    }
+    
+    Some(message)
+}
+
+/// Splits a process's output into lines. Each line is cut out of the read
+/// buffer as it is, with no copy into a `String`, so messages can be parsed
+/// straight from the bytes.
+struct LineReader<R> {
+    inner: R,
+    buffer: BytesMut,
+    // How far the buffer was searched for a line end
+    scanned: usize,
+    eof: bool,
+}
+
+impl<R: AsyncRead + Unpin> LineReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buffer: BytesMut::with_capacity(READ_BUFFER_BYTES),
+            scanned: 0,
+            eof: false,
+        }
+    }
+    
+    /// The next line without its line ending, or `None` once the stream has
+    /// ended. Cancel safe: nothing read is lost if the future is dropped.
+    async fn next_line(&mut self) -> io::Result<Option<Bytes>> {
+        loop {
+            if let Some(position) = self.buffer[self.scanned..].iter().position(|byte| *byte == b'\n') {
+                let end = self.scanned + position;
+                let mut line = self.buffer.split_to(end + 1);
+                self.scanned = 0;
+                line.truncate(end);
+                if line.last() == Some(&b'\r') {
+                    line.truncate(end - 1);
+                }
+                return Ok(Some(line.freeze()));
+            }
+            self.scanned = self.buffer.len();
+            
+            // A last line without a line end
+            if self.eof {
+                self.scanned = 0;
+                return Ok((!self.buffer.is_empty()).then(|| self.buffer.split().freeze()));
+            }
+            
+            self.buffer.reserve(READ_BUFFER_BYTES);
+            if self.inner.read_buf(&mut self.buffer).await? == 0 {
+                self.eof = true;
+            }
+        }
+    }
+}
+
+/// Parse a line of a process's output as a message. Lines that can't be one,
+/// such as log output, are skipped without trying.
+fn parse_message(line: &[u8]) -> Option<JsonRpcMessage> {
+    let first = line.iter().find(|byte| !byte.is_ascii_whitespace())?;
+    if *first != b'{' {
+        return None;
+    }
+    serde_json::from_slice(line).ok()
+}
+
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, message: JsonRpcMessage) -> Result<(), McpError>;
+    async fn receive(&self) -> Result<JsonRpcMessage, McpError>;
+    async fn close(&self) -> Result<(), McpError>;
+}
+
+/// Stdio transport that uses a spawned process
+pub struct StdioTransport {
+    child: Arc<Mutex<Option<TokioChild>>>,
+    input_tx: tokio_mpsc::Sender<String>,
+    shutdown_tx: tokio_mpsc::Sender<()>,
+    receive_tx: tokio_mpsc::Sender<oneshot::Sender<Result<JsonRpcMessage, McpError>>>,
+    tasks: Vec<JoinHandle<()>>,
+    exit: watch::Receiver<Option<ProcessExit>>,
+}
+
+/// Kill the process if it is still owned by the transport. `start_kill` only
+/// sends the signal; tokio reaps the process in the background.
+fn kill_child(child: &Mutex<Option<TokioChild>>) {
+    if let Ok(mut guard) = child.lock() {
+        if let Some(mut child) = guard.take() {
+            let _ = child.start_kill();
+        }
+    }
+}
+
+impl StdioTransport {
+    pub async fn new(command: &str, args: Vec<&str>) -> Result<Self, McpError> {
+        Self::with_cwd(command, args, None).await
+    }
+    
+    /// Spawn the process in the given working directory
+    pub async fn with_cwd(command: &str, args: Vec<&str>, cwd: Option<&Path>) -> Result<Self, McpError> {
+        // In Tauri 2.0, we don't rely on feature flags for this functionality
+        // Creating a shim to handle process operations in a cross-platform way
+        tracing::debug!(command, "Creating process in Tauri 2.0 compatibility mode");
+        let mut cmd = TokioCommand::new(command);
+        cmd.args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        
+        if let Some(cwd) = cwd {
+            cmd.current_dir(cwd);
+        }
+        
+        let child = cmd.spawn().map_err(|e| {
+            McpError::TransportError(format!("Failed to spawn process: {}", e))
+        })?;
+        
+        Self::from_child(child)
+    }
+    
+    /// Wrap an already spawned process whose stdin, stdout and stderr are piped.
+    /// The transport takes ownership of the process and kills it on close.
+    pub fn from_child(child: TokioChild) -> Result<Self, McpError> {
+        Self::from_child_with_output(child, None)
+    }
+    
+    /// Like `from_child`, but passes the process output that isn't protocol
+    /// traffic to `on_output` instead of printing it
+    pub fn from_child_with_output(
+        mut child: TokioChild,
+        on_output: Option<OutputHandler>,
+    ) -> Result<Self, McpError> {
+        {
+            let stdin = child.stdin.take().ok_or_else(|| {
+                McpError::TransportError("Failed to open stdin".to_string())
+            })?;
+            
+            let stdout = child.stdout.take().ok_or_else(|| {
+                McpError::TransportError("Failed to open stdout".to_string())
+            })?;
+            
+            let stderr = child.stderr.take().ok_or_else(|| {
+                McpError::TransportError("Failed to open stderr".to_string())
+            })?;
+            
+            let (shutdown_tx, mut shutdown_rx) = tokio_mpsc::channel(1);
+            let (input_tx, mut input_rx) = tokio_mpsc::channel::<String>(100);
+            
+            // For the receive operation (tokio oneshot channels)
+            let (receive_tx, mut receive_rx) = tokio_mpsc::channel::<oneshot::Sender<Result<JsonRpcMessage, McpError>>>(10);
+            
+            let child_arc = Arc::new(Mutex::new(Some(child)));
+            let child_clone = child_arc.clone();
+            let (exit_tx, exit) = watch::channel(None);
+            
+            // Spawn a task to handle stdin writes
+            let writer = tokio::spawn(async move {
+                let mut stdin = stdin;
+                while let Some(data) = input_rx.recv().await {
+                    if let Err(e) = stdin.write_all(data.as_bytes()).await {
+                        tracing::warn!("Error writing to stdin: {}", e);
+                        break;
+                    }
+                }
+            }.in_current_span());
+            
+            // Spawn a task to read messages from the process's stdout
+            let reader_task = tokio::spawn(async move {
+                let mut reader = LineReader::new(stdout);
+                let mut stderr_reader = LineReader::new(stderr);
+                let mut stderr_open = true;
+                let mut stdout_closed = false;
+                
+                let output = |stream: OutputStream, line: &[u8]| {
+                    let line = String::from_utf8_lossy(line);
+                    match &on_output {
+                        Some(on_output) => on_output(stream, &line),
+                        None => tracing::info!(?stream, "{}", line),
+                    }
+                };
+                
+                // Pending receive requests, and messages that arrived while nobody was waiting
+                let mut receivers: VecDeque<ReceiveSender> = VecDeque::new();
+                let mut buffered: VecDeque<JsonRpcMessage> = VecDeque::new();
+                
+                loop {
+                    tokio::select! {
+                        _ = shutdown_rx.recv() => {
+                            break;
+                        }
+                        
+                        // Check for new receive requests
+                        Some(response_tx) = receive_rx.recv() => {
+                            receivers.push_back(response_tx);
+                            
+                            // Messages that arrived earlier are delivered first, in order
+                            if let Some(message) = buffered.pop_front() {
+                                if let Some(message) = deliver(&mut receivers, message) {
+                                    buffered.push_front(message);
+                                }
+                            }
+                        }
+                        
+                        // Read stdout
+                        line = reader.next_line() => {
+                            match line {
+                                Ok(Some(line)) => {
+                                    match parse_message(&line) {
+                                        Some(message) => {
+                                            // Respond to the next waiting receiver, or buffer
+                                            // the message if no one is waiting
+                                            if let Some(message) = deliver(&mut receivers, message) {
+                                                buffered.push_back(message);
+                                            }
+                                        }
+                                        None => output(OutputStream::Stdout, &line),
+                                    }
+                                }
+                                Ok(None) => {
+                                    // EOF
+                                    stdout_closed = true;
+                                    break;
+                                }
+                                Err(e) => {
+                                    tracing::warn!("Error reading from stdout: {}", e);
+                                    break;
+                                }
+                            }
+                        }
+                        
+                        // Read stderr until it closes
+                        stderr_line = stderr_reader.next_line(), if stderr_open => {
+                            match stderr_line {
+                                Ok(Some(line)) => output(OutputStream::Stderr, &line),
+                                _ => stderr_open = false,
+                            }
+                        }
+                    }
+                }
+                
+                // A process that exited usually explains why on stderr
+                if stdout_closed && stderr_open {
+                    while let Ok(Ok(Some(line))) = timeout(STDERR_DRAIN_TIMEOUT, stderr_reader.next_line()).await {
+                        output(OutputStream::Stderr, &line);
+                    }
+                }
+                
+                // Record how a process that went away by itself ended
+                let child = stdout_closed.then(|| child_clone.lock().ok().and_then(|mut guard| guard.take())).flatten();
+                if let Some(mut child) = child {
+                    match timeout(EXIT_TIMEOUT, child.wait()).await {
+                        Ok(Ok(status)) => {
+                            let _ = exit_tx.send(Some(status.into()));
+                        }
+                        _ => {
+                            let _ = child.start_kill();
+                        }
+                    }
+                }
+                
+                kill_child(&child_clone);
+            }.in_current_span());
+            
+            Ok(Self {
+                child: child_arc,
+                input_tx,
+                shutdown_tx,
+                receive_tx,
+                tasks: vec![writer, reader_task],
+                exit,
+            })
+        }
+    }
+    
+    /// Becomes how the process ended once it exits by itself. Stays `None`
+    /// if the transport is closed first.
+    pub fn exit_status(&self) -> watch::Receiver<Option<ProcessExit>> {
+        self.exit.clone()
+    }
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    async fn send(&self, message: JsonRpcMessage) -> Result<(), McpError> {
+        let json = serde_json::to_string(&message)
+            .map_err(|e| McpError::TransportError(format!("JSON serialization error: {}", e)))?;
+        
+        // Add a newline to the message
+        let formatted_json = format!("{}\n", json);
+        
+        // Send to the stdin channel
+        self.input_tx.send(formatted_json).await.map_err(|e| {
+            tracing::warn!("stdin closed while sending");
+            McpError::TransportError(format!("Failed to send message to stdin: {}", e))
+        })?;
+        
+        Ok(())
+    }
+    
+    async fn receive(&self) -> Result<JsonRpcMessage, McpError> {
+        // Create a oneshot channel for this receive operation
+        let (tx, rx) = oneshot::channel();
+        
+        // Send the transmitter to the message processing task
+        // The reader task only goes away once the connection is gone
+        self.receive_tx.send(tx).await.map_err(|_| McpError::ConnectionClosed)?;
+        
+        // Wait for response with timeout
+        timeout(TRANSPORT_TIMEOUT, rx).await
+            .map_err(|_| McpError::TimeoutError)?
+            .map_err(|_| McpError::ConnectionClosed)?
+    }
+    
+    async fn close(&self) -> Result<(), McpError> {
+        // Signal the reader task to shut down
+        if let Err(e) = self.shutdown_tx.send(()).await {
+            tracing::debug!("Failed to send shutdown signal: {}", e);
+        }
+        
+        // Wait for the process to exit so a restart doesn't overlap with it
+        let child = self.child.lock().ok().and_then(|mut guard| guard.take());
+        if let Some(mut child) = child {
+            let _ = child.start_kill();
+            if timeout(EXIT_TIMEOUT, child.wait()).await.is_err() {
+                tracing::warn!("Server process did not exit within {} s", EXIT_TIMEOUT.as_secs());
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+impl Drop for StdioTransport {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        
+        kill_child(&self.child);
+    }
+}
+
+/// HTTP/SSE transport that uses Server-Sent Events for server-to-client communication
+/// and HTTP POST for client-to-server communication
+pub struct SseTransport {
+    http_client: HttpClient,
+    headers: HeaderMap,
+    base_url: String,
+    shutdown_tx: tokio_mpsc::Sender<()>,
+    receive_tx: tokio_mpsc::Sender<oneshot::Sender<Result<JsonRpcMessage, McpError>>>,
+    reader: JoinHandle<()>,
+}
+
+impl SseTransport {
+    pub async fn new(url: &str) -> Result<Self, McpError> {
+        Self::with_headers(url, &[]).await
+    }
+    
+    /// Connect to `url` with a client of its own, sending `headers` with the
+    /// event stream request and every message, e.g. to authenticate
+    pub async fn with_headers(url: &str, headers: &[(String, String)]) -> Result<Self, McpError> {
+        let http_client = HttpClient::builder()
+            .build()
+            .map_err(|e| McpError::TransportError(format!("Failed to create HTTP client: {}", e)))?;
+        Self::with_client(http_client, url, headers).await
+    }
+    
+    /// Connect to `url` through `http_client`, which may be shared with other
+    /// transports, sending `headers` with the event stream request and every
+    /// message
+    pub async fn with_client(http_client: HttpClient, url: &str, headers: &[(String, String)]) -> Result<Self, McpError> {
+        let mut request_headers = HeaderMap::new();
+        for (name, value) in headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| McpError::TransportError(format!("Invalid header name {:?}: {}", name, e)))?;
+            // Never include credentials in the error
+            let mut value = HeaderValue::from_str(value)
+                .map_err(|_| McpError::TransportError(format!("Invalid value for header {}", name)))?;
+            value.set_sensitive(true);
+            request_headers.insert(name, value);
+        }
+        
+        let (shutdown_tx, mut shutdown_rx) = tokio_mpsc::channel(1);
+        let (receive_tx, mut receive_rx) = tokio_mpsc::channel::<oneshot::Sender<Result<JsonRpcMessage, McpError>>>(10);
+        
+        let url_clone = url.to_string();
+        let http_client_clone = http_client.clone();
+        let headers_clone = request_headers.clone();
+        
+        // Spawn a task to read SSE events
+        let reader = tokio::spawn(async move {
+            let mut retry_delay = Duration::from_millis(100);
+            let max_retry_delay = Duration::from_secs(5);
+            let mut receivers: Vec<oneshot::Sender<Result<JsonRpcMessage, McpError>>> = Vec::new();
+            
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => {
+                        break;
+                    }
+                    
+                    // Check for new receive requests
+                    Some(response_tx) = receive_rx.recv() => {
+                        receivers.push(response_tx);
+                    }
+                    
+                    _ = async {
+                        let response = match http_client_clone.get(&url_clone).headers(headers_clone.clone()).send().await {
+                            Ok(res) => res,
+                            Err(e) => {
+                                tracing::warn!("Failed to connect to SSE endpoint: {}", e);
+                                tokio::time::sleep(retry_delay).await;
+                                retry_delay = std::cmp::min(retry_delay * 2, max_retry_delay);
+                                return;
+                            }
+                        };
+                        
+                        // Reset retry delay on successful connection
+                        retry_delay = Duration::from_millis(100);
+                        
+                        let mut event_stream = response.bytes_stream().eventsource();
+                        
+                        while let Some(event_result) = event_stream.next().await {
+                            match event_result {
+                                Ok(event) => {
+                                    // event.data contains the data
+                                    let data = event.data;
+                                    match serde_json::from_str::<JsonRpcMessage>(&data) {
+                                        Ok(message) => {
+                                            // Respond to the next waiting receiver if any
+                                            if let Some(tx) = receivers.pop() {
+                                                let _ = tx.send(Ok(message));
+                                            }
+                                        }
+                                        Err(e) => {
+                                            tracing::warn!("Error parsing SSE JSON-RPC message: {}", e);
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    tracing::warn!("SSE event error: {}", e);
+                                    break;
+                                }
+                            }
+                        }
+                        
+                        // If we got here, the connection was closed - attempt to reconnect
+                        tokio::time::sleep(retry_delay).await;
+                        retry_delay = std::cmp::min(retry_delay * 2, max_retry_delay);
+                    } => {}
+                }
+            }
+        }.in_current_span());
+        
+        Ok(Self {
+            http_client,
+            headers: request_headers,
+            base_url: url.to_string(),
+            shutdown_tx,
+            receive_tx,
+            reader,
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for SseTransport {
+    async fn send(&self, message: JsonRpcMessage) -> Result<(), McpError> {
+        let json = serde_json::to_string(&message)
+            .map_err(|e| McpError::TransportError(format!("JSON serialization error: {}", e)))?;
+        
+        // Determine the endpoint for POST requests
+        let post_url = if self.base_url.ends_with("/sse") {
+            self.base_url.replace("/sse", "/messages")
+        } else {
+            // Default to /messages if not specified
+            format!("{}/messages", self.base_url.trim_end_matches('/'))
+        };
+        
+        let response = self.http_client
+            .post(&post_url)
+            .timeout(TRANSPORT_TIMEOUT)
+            .headers(self.headers.clone())
+            .header("Content-Type", "application/json")
+            .body(json)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::warn!("POST {} failed: {}", post_url, e);
+                McpError::TransportError(format!("HTTP request failed: {}", e))
+            })?;
+        
+        if !response.status().is_success() {
+            tracing::warn!("POST {} returned {}", post_url, response.status());
+            return Err(McpError::TransportError(
+                format!("HTTP error: {}", response.status())
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    async fn receive(&self) -> Result<JsonRpcMessage, McpError> {
+        // Create a oneshot channel for this receive operation
+        let (tx, rx) = oneshot::channel();
+        
+        // Send the transmitter to the message processing task
+        // The reader task only goes away once the connection is gone
+        self.receive_tx.send(tx).await.map_err(|_| McpError::ConnectionClosed)?;
+        
+        // Wait for response with timeout
+        timeout(TRANSPORT_TIMEOUT, rx).await
+            .map_err(|_| McpError::TimeoutError)?
+            .map_err(|_| McpError::ConnectionClosed)?
+    }
+    
+    async fn close(&self) -> Result<(), McpError> {
+        // Signal the reader task to shut down
+        if let Err(e) = self.shutdown_tx.send(()).await {
+            tracing::debug!("Failed to send shutdown signal: {}", e);
+        }
+        
+        Ok(())
+    }
+}
+
+impl Drop for SseTransport {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// In-process transport connecting two endpoints through channels, without a
+/// process or socket in between. Used for tests and benchmarks.
+pub struct MemoryTransport {
+    tx: Mutex<Option<tokio_mpsc::UnboundedSender<JsonRpcMessage>>>,
+    rx: tokio::sync::Mutex<tokio_mpsc::UnboundedReceiver<JsonRpcMessage>>,
+}
+
+impl MemoryTransport {
+    /// Create two transports wired to each other: what one sends, the other receives
+    pub fn pair() -> (Self, Self) {
+        let (a_tx, a_rx) = tokio_mpsc::unbounded_channel();
+        let (b_tx, b_rx) = tokio_mpsc::unbounded_channel();
+        
+        let a = Self {
+            tx: Mutex::new(Some(b_tx)),
+            rx: tokio::sync::Mutex::new(a_rx),
+        };
+        let b = Self {
+            tx: Mutex::new(Some(a_tx)),
+            rx: tokio::sync::Mutex::new(b_rx),
+        };
+        
+        (a, b)
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn send(&self, message: JsonRpcMessage) -> Result<(), McpError> {
+        let tx = self.tx.lock()
+            .map_err(|e| McpError::internal(format!("Failed to lock sender: {}", e)))?
+            .clone()
+            .ok_or(McpError::ConnectionClosed)?;
+        
+        tx.send(message).map_err(|_| McpError::ConnectionClosed)
+    }
+    
+    async fn receive(&self) -> Result<JsonRpcMessage, McpError> {
+        self.rx.lock().await.recv().await.ok_or(McpError::ConnectionClosed)
+    }
+    
+    async fn close(&self) -> Result<(), McpError> {
+        // Dropping our sender ends the peer's receive stream
+        if let Ok(mut tx) = self.tx.lock() {
+            tx.take();
+        }
+        
+        Ok(())
+    }
+}
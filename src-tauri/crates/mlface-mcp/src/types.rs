@@ -0,0 +1,456 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+/// MCP Protocol version
+pub const MCP_PROTOCOL_VERSION: &str = "0.1.0";
+
+/// JSON-RPC message types for the MCP protocol
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum JsonRpcMessage {
+    Request(JsonRpcRequest),
+    Response(JsonRpcResponse),
+    Notification(JsonRpcNotification),
+}
+
+/// The fields of any JSON-RPC message. Messages are read into this in one
+/// pass and sorted by which fields are present, where an untagged enum would
+/// buffer every message and try each variant in turn.
+#[derive(Deserialize)]
+struct RawMessage {
+    jsonrpc: String,
+    #[serde(default, deserialize_with = "present")]
+    id: Option<serde_json::Value>,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Option<serde_json::Value>,
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+/// Keeps an explicit `null` apart from a missing field, as `"id": null` is
+/// still an id
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<serde_json::Value>, D::Error> {
+    serde_json::Value::deserialize(deserializer).map(Some)
+}
+
+impl<'de> Deserialize<'de> for JsonRpcMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawMessage::deserialize(deserializer)?;
+        Ok(match (raw.id, raw.method) {
+            (Some(id), Some(method)) => JsonRpcMessage::Request(JsonRpcRequest {
+                jsonrpc: raw.jsonrpc,
+                id,
+                method,
+                params: raw.params,
+            }),
+            (Some(id), None) => JsonRpcMessage::Response(JsonRpcResponse {
+                jsonrpc: raw.jsonrpc,
+                id,
+                result: raw.result,
+                error: raw.error,
+            }),
+            (None, Some(method)) => JsonRpcMessage::Notification(JsonRpcNotification {
+                jsonrpc: raw.jsonrpc,
+                method,
+                params: raw.params,
+            }),
+            (None, None) => return Err(serde::de::Error::custom("message has neither an id nor a method")),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    pub id: serde_json::Value,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    pub id: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i32,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+/// MCP Initialize request parameters
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InitializeParams {
+    pub protocol_version: String,
+    pub name: String,
+    pub version: String,
+    pub capabilities: ClientCapabilities,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourcesClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolsClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<PromptsClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roots: Option<RootsClientCapabilities>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ResourcesClientCapabilities {}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ToolsClientCapabilities {}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PromptsClientCapabilities {}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SamplingClientCapabilities {}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RootsClientCapabilities {}
+
+/// MCP Initialize response
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InitializeResult {
+    pub protocol_version: String,
+    pub name: String,
+    pub version: String,
+    pub capabilities: ServerCapabilities,
+    /// How the server asks to be used, for showing to the user or the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ServerCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourcesServerCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolsServerCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<PromptsServerCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingServerCapabilities>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ResourcesServerCapabilities {
+    /// Whether the server notifies about changes to subscribed resources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscribe: Option<bool>,
+    /// Whether the server notifies when its list of resources changes
+    #[serde(default, alias = "listChanged", skip_serializing_if = "Option::is_none")]
+    pub list_changed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ToolsServerCapabilities {
+    #[serde(default, alias = "listChanged", skip_serializing_if = "Option::is_none")]
+    pub list_changed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PromptsServerCapabilities {
+    #[serde(default, alias = "listChanged", skip_serializing_if = "Option::is_none")]
+    pub list_changed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SamplingServerCapabilities {}
+
+/// MCP Tool types
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tool {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub input_schema: serde_json::Value, // JSON Schema
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ListToolsResult {
+    pub tools: Vec<Tool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CallToolParams {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<serde_json::Value>,
+    /// Request metadata, such as the `progressToken` progress is reported under
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum Content {
+    #[serde(rename = "text")]
+    Text { text: String },
+    #[serde(rename = "image")]
+    Image { 
+        mime_type: String, 
+        data: String 
+    },
+    #[serde(rename = "embedded_resource")]
+    EmbeddedResource { 
+        uri: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        properties: Option<HashMap<String, String>> 
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CallToolResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+    pub content: Vec<Content>,
+}
+
+/// MCP Resource types
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ListResourcesResult {
+    pub resources: Vec<Resource>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadResourceParams {
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReadResourceResult {
+    pub content: Vec<Content>,
+}
+
+/// MCP Prompt types
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Prompt {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter_schema: Option<serde_json::Value>, // JSON Schema
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ListPromptsResult {
+    pub prompts: Vec<Prompt>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPromptParams {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetPromptResult {
+    pub content: Vec<Content>,
+}
+
+/// MCP error codes
+///
+/// Errors returned by a server keep their `data` payload; codes outside the
+/// standard JSON-RPC and MCP set are reported as `ServerError`.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum McpError {
+    #[error("Parse error: {message}")]
+    ParseError { message: String, data: Option<serde_json::Value> },
+    #[error("Invalid request: {message}")]
+    InvalidRequest { message: String, data: Option<serde_json::Value> },
+    #[error("Method not found: {message}")]
+    MethodNotFound { message: String, data: Option<serde_json::Value> },
+    #[error("Invalid params: {message}")]
+    InvalidParams { message: String, data: Option<serde_json::Value> },
+    #[error("Internal error: {message}")]
+    InternalError { message: String, data: Option<serde_json::Value> },
+    #[error("Resource not found: {message}")]
+    ResourceNotFound { message: String, data: Option<serde_json::Value> },
+    #[error("Server error {code}: {message}")]
+    ServerError { code: i32, message: String, data: Option<serde_json::Value> },
+    #[error("Transport error: {0}")]
+    TransportError(String),
+    #[error("Protocol error: {0}")]
+    ProtocolError(String),
+    #[error("Timeout error")]
+    TimeoutError,
+    #[error("Connection closed")]
+    ConnectionClosed,
+    #[error("Request cancelled")]
+    Cancelled,
+    #[error("Denied: {0}")]
+    Denied(String),
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+}
+
+impl McpError {
+    pub fn to_code(&self) -> i32 {
+        match self {
+            McpError::ParseError { .. } => -32700,
+            McpError::InvalidRequest { .. } => -32600,
+            McpError::MethodNotFound { .. } => -32601,
+            McpError::InvalidParams { .. } => -32602,
+            McpError::InternalError { .. } => -32603,
+            McpError::ResourceNotFound { .. } => -32002,
+            McpError::ServerError { code, .. } => *code,
+            McpError::TransportError(_) => -32000,
+            McpError::ProtocolError(_) => -32001,
+            McpError::TimeoutError => -32003,
+            McpError::ConnectionClosed => -32004,
+            McpError::Cancelled => -32800,
+            McpError::Denied(_) => -32801,
+            McpError::RateLimited(_) => -32802,
+        }
+    }
+    
+    /// Stable name of the error kind, for branching on errors in the frontend
+    pub fn kind(&self) -> &'static str {
+        match self {
+            McpError::ParseError { .. } => "parse_error",
+            McpError::InvalidRequest { .. } => "invalid_request",
+            McpError::MethodNotFound { .. } => "method_not_found",
+            McpError::InvalidParams { .. } => "invalid_params",
+            McpError::InternalError { .. } => "internal_error",
+            McpError::ResourceNotFound { .. } => "resource_not_found",
+            McpError::ServerError { .. } => "server_error",
+            McpError::TransportError(_) => "transport_error",
+            McpError::ProtocolError(_) => "protocol_error",
+            McpError::TimeoutError => "timeout",
+            McpError::ConnectionClosed => "connection_closed",
+            McpError::Cancelled => "cancelled",
+            McpError::Denied(_) => "denied",
+            McpError::RateLimited(_) => "rate_limited",
+        }
+    }
+    
+    /// The `data` payload the server attached to the error, if any
+    pub fn data(&self) -> Option<&serde_json::Value> {
+        match self {
+            McpError::ParseError { data, .. }
+            | McpError::InvalidRequest { data, .. }
+            | McpError::MethodNotFound { data, .. }
+            | McpError::InvalidParams { data, .. }
+            | McpError::InternalError { data, .. }
+            | McpError::ResourceNotFound { data, .. }
+            | McpError::ServerError { data, .. } => data.as_ref(),
+            _ => None,
+        }
+    }
+    
+    pub fn internal(message: impl Into<String>) -> Self {
+        McpError::InternalError { message: message.into(), data: None }
+    }
+    
+    pub fn method_not_found(method: impl Into<String>) -> Self {
+        McpError::MethodNotFound { message: method.into(), data: None }
+    }
+    
+    /// Convert to a JSON-RPC error object, e.g. to answer a server request
+    pub fn to_json_rpc_error(&self) -> JsonRpcError {
+        let message = match self {
+            McpError::ParseError { message, .. }
+            | McpError::InvalidRequest { message, .. }
+            | McpError::MethodNotFound { message, .. }
+            | McpError::InvalidParams { message, .. }
+            | McpError::InternalError { message, .. }
+            | McpError::ResourceNotFound { message, .. }
+            | McpError::ServerError { message, .. } => message.clone(),
+            other => other.to_string(),
+        };
+        
+        JsonRpcError {
+            code: self.to_code(),
+            message,
+            data: self.data().cloned(),
+        }
+    }
+}
+
+impl From<JsonRpcError> for McpError {
+    fn from(error: JsonRpcError) -> Self {
+        let JsonRpcError { code, message, data } = error;
+        match code {
+            -32700 => McpError::ParseError { message, data },
+            -32600 => McpError::InvalidRequest { message, data },
+            -32601 => McpError::MethodNotFound { message, data },
+            -32602 => McpError::InvalidParams { message, data },
+            -32603 => McpError::InternalError { message, data },
+            -32002 => McpError::ResourceNotFound { message, data },
+            code => McpError::ServerError { code, message, data },
+        }
+    }
+}
+
+/// Serialized as `{ kind, code, message, data? }` so command errors can be
+/// matched on `kind` instead of parsing the message
+impl Serialize for McpError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        
+        let data = self.data();
+        let mut state = serializer.serialize_struct("McpError", if data.is_some() { 4 } else { 3 })?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("code", &self.to_code())?;
+        state.serialize_field("message", &self.to_string())?;
+        if let Some(data) = data {
+            state.serialize_field("data", data)?;
+        }
+        state.end()
+    }
+}
+
+/// Recover a typed error from a manager failure, falling back to an internal
+/// error for failures that did not come from a server
+impl From<anyhow::Error> for McpError {
+    fn from(err: anyhow::Error) -> Self {
+        err.downcast::<McpError>()
+            .unwrap_or_else(|err| McpError::internal(err.to_string()))
+    }
+}
+
+impl From<serde_json::Error> for McpError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::ParseError { message: err.to_string(), data: None }
+    }
+}
@@ -0,0 +1,162 @@
+//! Update checks for servers run from packages.
+//!
+//! A server runs the version of its package pinned in its config, or else the
+//! one installed with `package::install`. `PackageUpdates::check` asks npm
+//! or PyPI for the latest release of each and remembers which packages are
+//! behind, so the app can show it without asking the registry every time.
+//! A package that is neither pinned nor installed runs whatever its runner
+//! fetched last, which can't be told, so it is never reported as behind.
+
+use crate::package::{self, PackageRuntime, PackageSpec};
+use crate::types::McpError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// How often the app checks for updates
+pub const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Where the latest versions are looked up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageRegistries {
+    pub npm: String,
+    /// The PyPI JSON API
+    pub pypi: String,
+}
+
+impl Default for PackageRegistries {
+    fn default() -> Self {
+        Self {
+            npm: "https://registry.npmjs.org".to_string(),
+            pypi: "https://pypi.org/pypi".to_string(),
+        }
+    }
+}
+
+/// How a package compares to its latest release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageUpdate {
+    /// The version that runs, when it can be told
+    pub current: Option<String>,
+    pub latest: String,
+    pub available: bool,
+}
+
+/// The latest update check of each package
+#[derive(Debug, Default)]
+pub struct PackageUpdates {
+    registries: PackageRegistries,
+    checked: RwLock<HashMap<PackageSpec, PackageUpdate>>,
+}
+
+impl PackageUpdates {
+    pub fn new(registries: PackageRegistries) -> Self {
+        Self { registries, checked: RwLock::new(HashMap::new()) }
+    }
+    
+    /// The result of the last check of `package`
+    pub fn get(&self, package: &PackageSpec) -> Option<PackageUpdate> {
+        self.checked.read().ok()?.get(package).cloned()
+    }
+    
+    /// Check each of `packages` against its registry, keeping the results
+    /// for `get`. Packages that can't be checked are logged and skipped.
+    /// Returns those with an update available.
+    pub async fn check(&self, client: &reqwest::Client, packages: Vec<PackageSpec>) -> Vec<(PackageSpec, PackageUpdate)> {
+        let mut available = Vec::new();
+        for package in packages {
+            let latest = match latest_version(client, &self.registries, &package).await {
+                Ok(latest) => latest,
+                Err(e) => {
+                    tracing::warn!("Could not check {} for updates: {}", package.name, e);
+                    continue;
+                }
+            };
+            let installed = package.clone();
+            let current = match package.version.clone() {
+                Some(version) => Some(version),
+                None => tokio::task::spawn_blocking(move || package::installed_version(&installed)).await.ok().flatten(),
+            };
+            
+            let update = PackageUpdate {
+                available: current.as_deref().is_some_and(|current| is_newer(&latest, current)),
+                current,
+                latest,
+            };
+            if update.available {
+                available.push((package.clone(), update.clone()));
+            }
+            if let Ok(mut checked) = self.checked.write() {
+                checked.insert(package, update);
+            }
+        }
+        available
+    }
+    
+    /// Record that `package`, as a server now has it, runs `version`, the
+    /// latest one
+    pub fn updated(&self, package: &PackageSpec, version: &str) {
+        let update = PackageUpdate { current: Some(version.to_string()), latest: version.to_string(), available: false };
+        if let Ok(mut checked) = self.checked.write() {
+            checked.insert(package.clone(), update);
+        }
+    }
+    
+    /// The latest release of `package`
+    pub async fn latest(&self, client: &reqwest::Client, package: &PackageSpec) -> Result<String, McpError> {
+        latest_version(client, &self.registries, package).await
+    }
+}
+
+/// The latest release of `package` in `registries`: npm's `latest` tag, or
+/// the version PyPI lists for the project
+pub async fn latest_version(
+    client: &reqwest::Client,
+    registries: &PackageRegistries,
+    package: &PackageSpec,
+) -> Result<String, McpError> {
+    let fail = |e: &dyn std::fmt::Display| McpError::TransportError(format!("Failed to look up {}: {}", package.name, e));
+    let request = match package.runtime {
+        // The abbreviated metadata is much smaller than the full document
+        PackageRuntime::Node => client
+            .get(format!("{}/{}", registries.npm.trim_end_matches('/'), package.name.replace('/', "%2F")))
+            .header("Accept", "application/vnd.npm.install-v1+json"),
+        PackageRuntime::Python => client.get(format!("{}/{}/json", registries.pypi.trim_end_matches('/'), package.name)),
+    };
+    let document: Value = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| fail(&e))?
+        .json()
+        .await
+        .map_err(|e| fail(&e))?;
+    
+    let latest = match package.runtime {
+        PackageRuntime::Node => &document["dist-tags"]["latest"],
+        PackageRuntime::Python => &document["info"]["version"],
+    };
+    latest
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| fail(&"the registry did not say which version is the latest"))
+}
+
+/// Whether the version `candidate` comes after `current`, comparing their
+/// numeric parts; anything after the numbers, like a pre-release tag, is
+/// ignored
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    let numbers = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    let (candidate, current) = (numbers(candidate), numbers(current));
+    let len = candidate.len().max(current.len());
+    let padded = |numbers: &[u64]| (0..len).map(|i| numbers.get(i).copied().unwrap_or(0)).collect::<Vec<_>>();
+    padded(&candidate) > padded(&current)
+}
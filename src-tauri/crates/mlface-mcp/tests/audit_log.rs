@@ -0,0 +1,109 @@
+//! The audit log records calls, answers queries and forgets old days.
+
+use mlface_mcp::audit::{hash_args, AuditAction, AuditEntry, AuditLog, AuditOutcome, AuditQuery};
+use mlface_mcp::types::{CallToolResult, Content, McpError};
+use serde_json::json;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+fn tool_call(server: &str, tool: &str, conversation: Option<&str>, result: Result<CallToolResult, McpError>) -> AuditEntry {
+    AuditEntry::tool_call(server, tool, Some(&json!({ "path": "/tmp" })), conversation, Instant::now(), &result)
+}
+
+fn text_result(text: &str) -> CallToolResult {
+    CallToolResult {
+        content: vec![Content::Text { text: text.to_string() }],
+        is_error: None,
+    }
+}
+
+#[test]
+fn entries_describe_the_call() {
+    let entry = tool_call("files", "read", Some("chat-1"), Ok(text_result("hello")));
+    assert_eq!(entry.action, AuditAction::ToolCall);
+    assert_eq!(entry.outcome, AuditOutcome::Success);
+    assert_eq!(entry.args_hash.as_deref(), Some(hash_args(&json!({ "path": "/tmp" }))).as_deref());
+    assert!(entry.result_bytes.unwrap() > 0);
+    assert_eq!(entry.conversation.as_deref(), Some("chat-1"));
+
+    let denied = tool_call("files", "read", None, Err(McpError::Denied("no".into())));
+    assert_eq!(denied.outcome, AuditOutcome::Denied);
+    assert_eq!(denied.result_bytes, None);
+
+    let failed = tool_call("files", "read", None, Err(McpError::TimeoutError));
+    assert_eq!(failed.outcome, AuditOutcome::Failed);
+    assert!(failed.error.is_some());
+
+    // The same arguments always hash the same, different ones do not
+    assert_eq!(hash_args(&json!({ "a": 1 })), hash_args(&json!({ "a": 1 })));
+    assert_ne!(hash_args(&json!({ "a": 1 })), hash_args(&json!({ "a": 2 })));
+}
+
+#[test]
+fn queries_filter_and_return_newest_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = AuditLog::open(dir.path()).unwrap();
+
+    let mut first = tool_call("files", "read", Some("chat-1"), Ok(text_result("a")));
+    first.timestamp = now_ms() - 2000;
+    let mut second = tool_call("search", "query", Some("chat-2"), Ok(text_result("b")));
+    second.timestamp = now_ms() - 1000;
+    let mut yesterday = tool_call("files", "write", Some("chat-1"), Err(McpError::Cancelled));
+    yesterday.timestamp = now_ms() - DAY_MS;
+    for entry in [&first, &second, &yesterday] {
+        log.record(entry).unwrap();
+    }
+
+    let all = log.query(&AuditQuery::default()).unwrap();
+    assert_eq!(all, vec![second.clone(), first.clone(), yesterday.clone()]);
+
+    let files = log.query(&AuditQuery { server: Some("files".into()), ..Default::default() }).unwrap();
+    assert_eq!(files, vec![first.clone(), yesterday.clone()]);
+
+    let chat = log
+        .query(&AuditQuery {
+            conversation: Some("chat-1".into()),
+            outcome: Some(AuditOutcome::Cancelled),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(chat, vec![yesterday.clone()]);
+
+    let recent = log.query(&AuditQuery { since: Some(now_ms() - 5000), limit: Some(1), ..Default::default() }).unwrap();
+    assert_eq!(recent, vec![second]);
+
+    // Reopening reads the same files
+    drop(log);
+    assert_eq!(AuditLog::open(dir.path()).unwrap().query(&AuditQuery::default()).unwrap().len(), 3);
+}
+
+#[test]
+fn old_days_are_removed_after_the_retention_period() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = AuditLog::open(dir.path()).unwrap();
+
+    let mut old = tool_call("files", "read", None, Ok(text_result("old")));
+    old.timestamp = now_ms() - 40 * DAY_MS;
+    let recent = tool_call("files", "read", None, Ok(text_result("recent")));
+    log.record(&old).unwrap();
+    log.record(&recent).unwrap();
+    assert_eq!(log.query(&AuditQuery::default()).unwrap().len(), 2);
+
+    log.set_retention_days(30).unwrap();
+    assert_eq!(log.query(&AuditQuery::default()).unwrap(), vec![recent.clone()]);
+
+    // The setting is kept
+    drop(log);
+    let log = AuditLog::open(dir.path()).unwrap();
+    assert_eq!(log.retention_days(), 30);
+
+    // Without a directory nothing is kept
+    let memoryless = AuditLog::default();
+    memoryless.record(&recent).unwrap();
+    assert!(memoryless.query(&AuditQuery::default()).unwrap().is_empty());
+}
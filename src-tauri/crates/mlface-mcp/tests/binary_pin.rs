@@ -0,0 +1,56 @@
+//! Local servers can be pinned to the executable they were registered with.
+
+use mlface_mcp::integrity::{resolve_executable, BinaryCheck, BinaryPin};
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+
+fn local(command: &str, binary: Option<BinaryPin>) -> McpServerConfig {
+    McpServerConfig {
+        name: "local".to_string(),
+        command: command.to_string(),
+        binary,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn a_changed_binary_no_longer_matches_its_pin() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = dir.path().join("server");
+    std::fs::write(&server, "#!/bin/sh\necho v1\n").unwrap();
+    let command = server.to_str().unwrap();
+
+    let pin = BinaryPin::of(command, false).unwrap();
+    assert_eq!(pin.sha256.len(), 64);
+    let check = pin.check(command).unwrap();
+    assert_eq!(check, BinaryCheck::Unchanged { path: server.clone() });
+    assert!(check.warning().is_none());
+
+    std::fs::write(&server, "#!/bin/sh\necho v2\n").unwrap();
+    let check = pin.check(command).unwrap();
+    assert!(matches!(&check, BinaryCheck::Changed { expected, .. } if *expected == pin.sha256));
+    assert!(check.warning().unwrap().contains("changed since it was pinned"));
+
+    assert!(resolve_executable("surely-not-a-command-on-path").is_err());
+}
+
+#[tokio::test]
+async fn an_enforced_pin_refuses_to_launch_a_changed_binary() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = dir.path().join("server");
+    std::fs::write(&server, "#!/bin/sh\nexit 1\n").unwrap();
+    let command = server.to_str().unwrap();
+
+    let stale = BinaryPin { sha256: "0".repeat(64), enforce: true };
+    let manager = McpServerManager::new();
+    manager.register_server(local(command, Some(stale))).await.unwrap();
+
+    let error = manager.start_server("local").await.unwrap_err();
+    assert!(error.to_string().contains("refusing to launch"), "{}", error);
+    assert!(matches!(manager.check_binary("local").await.unwrap(), BinaryCheck::Changed { .. }));
+
+    // Pinning again accepts the binary as it is now
+    let pin = manager.pin_binary("local", true).await.unwrap();
+    assert!(pin.enforce);
+    assert!(matches!(manager.check_binary("local").await.unwrap(), BinaryCheck::Unchanged { .. }));
+    assert_eq!(manager.get_servers().await[0].binary, Some(pin));
+}
@@ -0,0 +1,67 @@
+//! The server catalog can be searched and its entries installed.
+
+use mlface_mcp::catalog::Catalog;
+use mlface_mcp::package::PackageRuntime;
+use mlface_mcp::secrets::{MemorySecretStore, SecretStore};
+use std::collections::{HashMap, HashSet};
+
+#[test]
+fn the_bundled_catalog_is_searchable() {
+    let catalog = Catalog::bundled();
+    let ids: HashSet<_> = catalog.servers.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids.len(), catalog.servers.len(), "ids are unique");
+
+    assert_eq!(catalog.search("").len(), catalog.servers.len());
+    let web: Vec<_> = catalog.search("WEB fetch").into_iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(web, ["fetch"]);
+    assert_eq!(catalog.get("fetch").unwrap().package.runtime, PackageRuntime::Python);
+    assert!(catalog.search("no such server").is_empty());
+}
+
+#[test]
+fn installing_asks_for_required_values_and_keeps_secrets_out_of_the_config() {
+    let catalog = Catalog::bundled();
+    let slack = catalog.get("slack").unwrap();
+    let store = MemorySecretStore::default();
+
+    let missing = slack.install(None, &[], &HashMap::new(), Some(&store)).unwrap_err();
+    assert!(missing.to_string().contains("SLACK_BOT_TOKEN, SLACK_TEAM_ID"));
+
+    let values = HashMap::from([
+        ("SLACK_BOT_TOKEN".to_string(), " xoxb-123 ".to_string()),
+        ("SLACK_TEAM_ID".to_string(), "T0001".to_string()),
+    ]);
+    let config = slack.install(Some("work chat"), &["--verbose".to_string()], &values, Some(&store)).unwrap();
+    assert_eq!(config.name, "work chat");
+    assert!(config.command.is_empty());
+    assert_eq!(config.package.as_ref(), Some(&slack.package));
+    assert_eq!(config.args, ["--verbose"]);
+    assert_eq!(config.env["SLACK_BOT_TOKEN"], "{{secret:slack.SLACK_BOT_TOKEN}}");
+    assert_eq!(config.env["SLACK_TEAM_ID"], "T0001");
+    assert_eq!(store.get("slack.SLACK_BOT_TOKEN").unwrap().as_deref(), Some("xoxb-123"));
+
+    // Secrets need somewhere to go
+    assert!(slack.install(None, &[], &values, None).is_err());
+}
+
+#[test]
+fn a_cached_catalog_is_used_unless_it_is_older() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("catalog.json");
+    let bundled = Catalog::bundled();
+
+    let mut newer = bundled.clone();
+    newer.version += 1;
+    newer.servers.truncate(1);
+    newer.save(&path).unwrap();
+    assert_eq!(Catalog::load(Some(&path)), newer);
+
+    let mut older = bundled.clone();
+    older.version -= 1;
+    older.save(&path).unwrap();
+    assert_eq!(Catalog::load(Some(&path)), bundled);
+
+    std::fs::write(&path, "not a catalog").unwrap();
+    assert_eq!(Catalog::load(Some(&path)), bundled);
+    assert_eq!(Catalog::load(None), bundled);
+}
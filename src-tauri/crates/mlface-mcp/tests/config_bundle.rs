@@ -0,0 +1,50 @@
+//! Exported bundles carry no secrets and import on another machine.
+
+use mlface_mcp::bundle::{ConfigBundle, BUNDLE_FORMAT};
+use mlface_mcp::server::McpServerConfig;
+use serde_json::json;
+use std::collections::HashMap;
+
+fn server(name: &str, env: &[(&str, &str)]) -> McpServerConfig {
+    McpServerConfig {
+        name: name.to_string(),
+        command: "npx".to_string(),
+        args: vec!["-y".to_string(), format!("@example/{}", name)],
+        env: env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>(),
+        autostart: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn secrets_are_placeheld() {
+    let (bundle, report) = ConfigBundle::export(vec![
+        server("github", &[("GITHUB_TOKEN", "ghp_secret"), ("LOG_LEVEL", "debug")]),
+        server("search", &[("BRAVE_API_KEY", "${env:BRAVE_API_KEY}")]),
+    ]);
+
+    let github = &bundle.servers[0];
+    assert_eq!(github.env["GITHUB_TOKEN"], "${env:GITHUB_TOKEN}");
+    assert_eq!(github.env["LOG_LEVEL"], "debug");
+
+    // Already a reference, so nothing to hide
+    assert_eq!(bundle.servers[1].env["BRAVE_API_KEY"], "${env:BRAVE_API_KEY}");
+
+    assert_eq!(report.servers, vec!["github", "search"]);
+    assert_eq!(report.placeheld.len(), 1);
+    assert_eq!(report.placeheld[0].variable, "GITHUB_TOKEN");
+
+    let json = serde_json::to_string(&bundle).unwrap();
+    assert!(!json.contains("ghp_secret"));
+
+    let parsed = ConfigBundle::parse(&json).unwrap();
+    assert_eq!(parsed.servers, bundle.servers);
+}
+
+#[test]
+fn other_documents_are_rejected() {
+    assert!(ConfigBundle::parse(&json!({ "version": 2, "servers": {} }).to_string()).is_err());
+
+    let future = json!({ "format": BUNDLE_FORMAT, "version": 99, "servers": [] });
+    assert!(ConfigBundle::parse(&future.to_string()).is_err());
+}
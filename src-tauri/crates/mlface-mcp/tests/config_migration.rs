@@ -0,0 +1,41 @@
+//! Config files written by older versions load and are saved in the current format.
+
+use mlface_mcp::config::{ConfigFile, CONFIG_VERSION};
+use mlface_mcp::server::McpServerManager;
+use serde_json::json;
+
+#[test]
+fn flat_map_is_upgraded() {
+    // A server may be called "version" in the flat layout
+    let legacy = json!({
+        "fs": { "name": "fs", "command": "npx", "args": ["server-fs"], "env": {} },
+        "version": { "name": "version", "command": "node", "args": [] }
+    });
+
+    let file = ConfigFile::parse(&legacy.to_string()).unwrap();
+    assert_eq!(file.version, CONFIG_VERSION);
+    assert_eq!(file.servers.len(), 2);
+    assert_eq!(file.servers["fs"].args, vec!["server-fs".to_string()]);
+    assert!(file.servers["version"].enabled);
+}
+
+#[test]
+fn newer_format_is_rejected() {
+    let future = json!({ "version": CONFIG_VERSION + 1, "servers": {} });
+    assert!(ConfigFile::parse(&future.to_string()).is_err());
+}
+
+#[tokio::test]
+async fn legacy_file_is_saved_in_current_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mcp_servers.json");
+    std::fs::write(&path, json!({ "fs": { "name": "fs", "command": "npx", "args": [] } }).to_string()).unwrap();
+
+    let manager = McpServerManager::new();
+    manager.load_from_file(path.to_str().unwrap()).await.unwrap();
+    manager.save_to_file(path.to_str().unwrap()).await.unwrap();
+
+    let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["version"], CONFIG_VERSION);
+    assert_eq!(saved["servers"]["fs"]["name"], "fs");
+}
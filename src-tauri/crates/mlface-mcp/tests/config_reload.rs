@@ -0,0 +1,90 @@
+//! Applying an edited config to a live manager touches only what changed.
+
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+use std::collections::HashMap;
+
+const SCRIPTED_SERVER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/scripted-server.mjs");
+
+fn server(name: &str, args: &[&str]) -> McpServerConfig {
+    McpServerConfig {
+        name: name.to_string(),
+        command: "node".to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn config_of(servers: &[McpServerConfig]) -> HashMap<String, McpServerConfig> {
+    servers.iter().map(|server| (server.name.clone(), server.clone())).collect()
+}
+
+#[tokio::test]
+async fn diff_reports_added_removed_and_changed() {
+    let manager = McpServerManager::new();
+    manager
+        .apply_config(config_of(&[server("kept", &["a.js"]), server("edited", &["a.js"]), server("gone", &[])]))
+        .await
+        .unwrap();
+
+    let diff = manager
+        .apply_config(config_of(&[server("kept", &["a.js"]), server("edited", &["b.js"]), server("new", &[])]))
+        .await
+        .unwrap();
+
+    assert_eq!(diff.added, vec!["new".to_string()]);
+    assert_eq!(diff.removed, vec!["gone".to_string()]);
+    assert_eq!(diff.changed, vec!["edited".to_string()]);
+
+    let mut names: Vec<String> = manager.get_servers().await.into_iter().map(|server| server.name).collect();
+    names.sort();
+    assert_eq!(names, vec!["edited", "kept", "new"]);
+
+    // Applying the same config again is a no-op
+    let diff = manager
+        .apply_config(config_of(&[server("kept", &["a.js"]), server("edited", &["b.js"]), server("new", &[])]))
+        .await
+        .unwrap();
+    assert!(diff.is_empty());
+}
+
+#[tokio::test]
+async fn only_changed_running_servers_are_restarted() {
+    let manager = McpServerManager::new();
+    let kept = server("kept", &[SCRIPTED_SERVER]);
+    let edited = server("edited", &[SCRIPTED_SERVER]);
+    manager.apply_config(config_of(&[kept.clone(), edited.clone()])).await.unwrap();
+
+    let kept_client = manager.get_client("kept").await.unwrap();
+    let edited_client = manager.get_client("edited").await.unwrap();
+
+    let mut edited_env = edited.clone();
+    edited_env.env.insert("EDITED".to_string(), "1".to_string());
+    manager.apply_config(config_of(&[kept, edited_env])).await.unwrap();
+
+    assert!(std::sync::Arc::ptr_eq(&kept_client, &manager.get_client("kept").await.unwrap()));
+    assert!(!std::sync::Arc::ptr_eq(&edited_client, &manager.get_client("edited").await.unwrap()));
+
+    manager.stop_server("kept").await.unwrap();
+    manager.stop_server("edited").await.unwrap();
+}
+
+#[tokio::test]
+async fn registry_changes_wake_subscribers() {
+    let manager = McpServerManager::new();
+    let mut registry = manager.subscribe_registry();
+
+    manager.register_server(server("added", &[])).await.unwrap();
+    assert!(registry.has_changed().unwrap());
+    registry.mark_unchanged();
+
+    // Applying an unchanged config leaves subscribers asleep
+    manager.apply_config(config_of(&[server("added", &[])])).await.unwrap();
+    assert!(!registry.has_changed().unwrap());
+
+    manager.set_server_enabled("added", false).await.unwrap();
+    assert!(registry.has_changed().unwrap());
+    registry.mark_unchanged();
+
+    manager.unregister_server("added").await.unwrap();
+    assert!(registry.has_changed().unwrap());
+}
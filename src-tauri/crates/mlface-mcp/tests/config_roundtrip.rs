@@ -0,0 +1,162 @@
+//! Property tests: any valid server configuration survives save -> load -> save.
+
+use mlface_mcp::http::TlsSettings;
+use mlface_mcp::integrity::BinaryPin;
+use mlface_mcp::package::{PackageRuntime, PackageSpec};
+use mlface_mcp::rate_limit::{LimitMode, RateLimit};
+use mlface_mcp::roots::RootsEnforcement;
+use mlface_mcp::server::{LogVerbosity, McpServerConfig, McpServerManager, ServerAuth, ServerSettings};
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+fn rate_limit_strategy() -> impl Strategy<Value = RateLimit> {
+    (any::<u32>(), proptest::option::of(any::<u32>()), prop_oneof![Just(LimitMode::Queue), Just(LimitMode::Reject)])
+        .prop_map(|(per_minute, burst, when_limited)| RateLimit { per_minute, burst, when_limited })
+}
+
+fn tls_strategy() -> impl Strategy<Value = TlsSettings> {
+    (proptest::option::of("[a-z/]{1,20}\\.pem"), any::<bool>())
+        .prop_map(|(ca_file, accept_invalid_certs)| TlsSettings { ca_file: ca_file.map(Into::into), accept_invalid_certs })
+}
+
+fn settings_strategy() -> impl Strategy<Value = ServerSettings> {
+    (
+        proptest::option::of(any::<u64>()),
+        proptest::option::of(any::<u32>()),
+        proptest::option::of(any::<u64>()),
+        prop_oneof![Just(LogVerbosity::Quiet), Just(LogVerbosity::Normal), Just(LogVerbosity::Trace)],
+        prop_oneof![Just(RootsEnforcement::Off), Just(RootsEnforcement::Warn), Just(RootsEnforcement::Strict)],
+        proptest::option::of(rate_limit_strategy()),
+        proptest::option::of(any::<u64>()),
+        proptest::option::of(1..64usize),
+        proptest::option::of(tls_strategy()),
+    )
+        .prop_map(
+            |(
+                request_timeout_secs,
+                max_restarts,
+                health_check_interval_secs,
+                log_level,
+                roots_enforcement,
+                rate_limit,
+                slow_call_ms,
+                max_concurrent_requests,
+                tls,
+            )| {
+                ServerSettings {
+                    request_timeout_secs,
+                    max_restarts,
+                    health_check_interval_secs,
+                    log_level,
+                    roots_enforcement,
+                    rate_limit,
+                    slow_call_ms,
+                    max_concurrent_requests,
+                    tls,
+                }
+            },
+        )
+}
+
+fn auth_strategy() -> impl Strategy<Value = ServerAuth> {
+    prop_oneof![
+        any::<String>().prop_map(|value_ref| ServerAuth::Bearer { value_ref }),
+        (any::<String>(), any::<String>()).prop_map(|(name, value_ref)| ServerAuth::Header { name, value_ref }),
+    ]
+}
+
+fn binary_strategy() -> impl Strategy<Value = BinaryPin> {
+    ("[0-9a-f]{64}", any::<bool>()).prop_map(|(sha256, enforce)| BinaryPin { sha256, enforce })
+}
+
+fn package_strategy() -> impl Strategy<Value = PackageSpec> {
+    (
+        "@?[a-z]{1,8}(/[a-z-]{1,12})?",
+        prop_oneof![Just(PackageRuntime::Node), Just(PackageRuntime::Python)],
+        proptest::option::of("[0-9]\\.[0-9]{1,2}"),
+    )
+        .prop_map(|(name, runtime, version)| PackageSpec { name, runtime, version })
+}
+
+fn config_strategy() -> impl Strategy<Value = McpServerConfig> {
+    (
+        any::<String>(),
+        prop::collection::vec(any::<String>(), 0..5),
+        prop::collection::hash_map(any::<String>(), any::<String>(), 0..5),
+        proptest::option::of(any::<String>()),
+        any::<bool>(),
+        any::<bool>(),
+        settings_strategy(),
+        proptest::option::of(auth_strategy()),
+        prop::collection::vec(any::<String>(), 0..3),
+        proptest::option::of(binary_strategy()),
+        proptest::option::of(package_strategy()),
+    )
+        .prop_map(|(command, args, env, cwd, autostart, enabled, settings, auth, roots, binary, package)| McpServerConfig {
+            // The name is the map key in the file and is filled in on load
+            name: String::new(),
+            command,
+            args,
+            env,
+            cwd,
+            autostart,
+            enabled,
+            settings,
+            auth,
+            roots,
+            binary,
+            package,
+            ..Default::default()
+        })
+}
+
+fn configs_strategy() -> impl Strategy<Value = HashMap<String, McpServerConfig>> {
+    prop::collection::hash_map("[a-zA-Z0-9_ .-]{1,24}", config_strategy(), 0..8).prop_map(|configs| {
+        configs
+            .into_iter()
+            .map(|(name, mut config)| {
+                config.name = name.clone();
+                (name, config)
+            })
+            .collect()
+    })
+}
+
+fn sorted(mut configs: Vec<McpServerConfig>) -> Vec<McpServerConfig> {
+    configs.sort_by(|a, b| a.name.cmp(&b.name));
+    configs
+}
+
+proptest! {
+    #[test]
+    fn save_load_save_is_lossless(configs in configs_strategy()) {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.json");
+        let second = dir.path().join("second.json");
+
+        runtime.block_on(async {
+            let original = McpServerManager::new();
+            for config in configs.values() {
+                original.register_server(config.clone()).await.unwrap();
+            }
+            original.save_to_file(first.to_str().unwrap()).await.unwrap();
+
+            let reloaded = McpServerManager::new();
+            reloaded.load_from_file(first.to_str().unwrap()).await.unwrap();
+            reloaded.save_to_file(second.to_str().unwrap()).await.unwrap();
+
+            prop_assert_eq!(
+                sorted(reloaded.get_servers().await),
+                sorted(configs.values().cloned().collect())
+            );
+
+            // Map order is not stable across saves, so compare parsed documents
+            let first: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&first).unwrap()).unwrap();
+            let second: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&second).unwrap()).unwrap();
+            prop_assert_eq!(first, second);
+
+            Ok(())
+        })?;
+    }
+}
@@ -0,0 +1,78 @@
+//! A server that exits with a failure leaves a crash record behind.
+
+use mlface_mcp::server::{ConnectionState, McpServerConfig, McpServerManager, ResourceBudgets, ServerEvent};
+use std::time::Duration;
+
+const FAILING_SERVER: &str = r#"
+console.error("node:internal/modules/cjs/loader:1228");
+console.error("Error: Cannot find module '/srv/missing.js'");
+console.error("  code: 'MODULE_NOT_FOUND'");
+process.exit(3);
+"#;
+
+fn failing_server() -> McpServerConfig {
+    McpServerConfig {
+        name: "broken".to_string(),
+        command: "node".to_string(),
+        args: vec!["-e".to_string(), FAILING_SERVER.to_string()],
+        ..Default::default()
+    }
+}
+
+async fn crash(manager: &McpServerManager) -> ServerEvent {
+    manager.register_server(failing_server()).await.unwrap();
+    let mut events = manager.subscribe_events();
+
+    assert!(manager.get_client("broken").await.is_err());
+
+    loop {
+        let event = events.recv().await.unwrap();
+        if event.state == ConnectionState::Disconnected {
+            break event;
+        }
+    }
+}
+
+#[tokio::test]
+async fn a_crash_is_reported_and_persisted() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = McpServerManager::new();
+    manager.set_log_dir(dir.path());
+    let failed = crash(&manager).await;
+    let crash = failed.crash.unwrap();
+    assert_eq!(crash.exit.code, Some(3));
+    assert_eq!(crash.stderr.len(), 3);
+    assert_eq!(crash.summary(), "exit 3 — Error: Cannot find module '/srv/missing.js'");
+    assert_eq!(failed.reason.as_deref(), Some("crashed: exit 3 — Error: Cannot find module '/srv/missing.js'"));
+
+    // Another run of the app still knows
+    let restarted = McpServerManager::new();
+    restarted.set_log_dir(dir.path());
+    restarted.register_server(failing_server()).await.unwrap();
+    assert_eq!(restarted.last_crash("broken").await, Some(crash));
+
+    restarted.unregister_server("broken").await.unwrap();
+    assert!(restarted.last_crash("broken").await.is_none());
+}
+
+#[tokio::test]
+async fn trimming_the_logs_keeps_crash_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = McpServerManager::new();
+    manager.set_log_dir(dir.path());
+    crash(&manager).await;
+    std::fs::write(dir.path().join("gone.log"), "x".repeat(4096)).unwrap();
+
+    manager.set_resource_budgets(ResourceBudgets {
+        max_log_bytes: Some(1),
+        ..Default::default()
+    });
+    tokio::time::pause();
+    let _ = tokio::time::timeout(Duration::from_secs(31), manager.run_reaper()).await;
+
+    assert!(!dir.path().join("gone.log").exists());
+    let restarted = McpServerManager::new();
+    restarted.set_log_dir(dir.path());
+    restarted.register_server(failing_server()).await.unwrap();
+    assert!(restarted.last_crash("broken").await.is_some());
+}
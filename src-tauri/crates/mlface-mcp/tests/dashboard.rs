@@ -0,0 +1,67 @@
+//! The dashboard sums up every server in one payload.
+
+use mlface_mcp::dashboard::ConnectionType;
+use mlface_mcp::server::{ConnectionState, McpServerConfig, McpServerManager};
+use serde_json::json;
+use std::collections::HashMap;
+
+const SCRIPTED_SERVER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/scripted-server.mjs");
+
+fn server(name: &str, command: &str, args: Vec<String>) -> McpServerConfig {
+    McpServerConfig {
+        name: name.to_string(),
+        command: command.to_string(),
+        args,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn every_server_is_summed_up() {
+    let manager = McpServerManager::new();
+    manager
+        .register_server(server("scripted", "node", vec![SCRIPTED_SERVER.to_string()]))
+        .await
+        .unwrap();
+    manager
+        .register_server(server("broken", "node", vec!["-e".to_string(), "process.exit(2)".to_string()]))
+        .await
+        .unwrap();
+    manager
+        .register_server(server("remote", "https://mcp.example.com/sse", Vec::new()))
+        .await
+        .unwrap();
+
+    let client = manager.get_client("scripted").await.unwrap();
+    client.call_tool("echo", Some(json!({ "text": "hi" }))).await.unwrap();
+    assert!(manager.get_client("broken").await.is_err());
+
+    let dashboard = manager.dashboard().await;
+    let summaries: HashMap<_, _> = dashboard.servers.iter().map(|summary| (summary.name.as_str(), summary)).collect();
+    assert_eq!(summaries.len(), 3);
+
+    let scripted = summaries["scripted"];
+    assert_eq!(scripted.connection_type, ConnectionType::Stdio);
+    assert_eq!(scripted.state, ConnectionState::Connected);
+    assert!(scripted.uptime_ms.is_some());
+    assert_eq!(scripted.tool_count, Some(1));
+    // initialize and the tool call; tools/list came after the metrics were read
+    assert_eq!(scripted.requests, 2);
+    assert_eq!(scripted.restarts, 0);
+    assert!(scripted.last_error.is_none());
+
+    let broken = summaries["broken"];
+    assert_eq!(broken.state, ConnectionState::Disconnected);
+    assert!(broken.uptime_ms.is_none());
+    assert!(broken.tool_count.is_none());
+    assert!(broken.last_error.is_some());
+
+    let remote = summaries["remote"];
+    assert_eq!(remote.connection_type, ConnectionType::Remote);
+    assert_eq!(remote.requests, 0);
+
+    // The tool count is remembered, so tools are listed only once
+    let again = manager.dashboard().await;
+    let scripted = again.servers.iter().find(|summary| summary.name == "scripted").unwrap();
+    assert_eq!(scripted.requests, 3);
+}
@@ -0,0 +1,139 @@
+//! Servers are found by name and package manifest without being run.
+#![cfg(unix)]
+
+use mlface_mcp::discovery::looks_like_server;
+use mlface_mcp::server::McpServerManager;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+// Leaves a mark next to itself when run
+const MARKING_SCRIPT: &str = "#!/bin/sh\ntouch \"$0.ran\"\nexit 0\n";
+
+fn install(dir: &Path, name: &str) {
+    let path = dir.join(name);
+    std::fs::write(&path, MARKING_SCRIPT).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn ran(dir: &Path) -> bool {
+    std::fs::read_dir(dir).unwrap().any(|entry| entry.unwrap().path().extension().is_some_and(|ext| ext == "ran"))
+}
+
+// Changes PATH, so the whole scan is one test
+#[tokio::test]
+async fn servers_are_found_without_running_anything() {
+    let picked = tempfile::tempdir().unwrap();
+    for name in ["alpha", "beta", "mcp-server-notes"] {
+        install(picked.path(), name);
+    }
+    std::fs::write(picked.path().join("README"), "not a server").unwrap();
+    std::fs::create_dir(picked.path().join("nested")).unwrap();
+
+    // A prefix as npm lays it out: bins in bin, packages in lib/node_modules
+    let prefix = tempfile::tempdir().unwrap();
+    let bin = prefix.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    for name in ["mcp-server-notes", "github-mcp-server", "context7", "left-pad", "tool"] {
+        install(&bin, name);
+    }
+    let modules = prefix.path().join("lib").join("node_modules");
+    std::fs::create_dir_all(modules.join("@upstash").join("context7")).unwrap();
+    std::fs::write(
+        modules.join("@upstash").join("context7").join("package.json"),
+        r#"{ "name": "@upstash/context7", "keywords": ["MCP"], "bin": { "context7": "dist/index.js" } }"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(modules.join("left-pad")).unwrap();
+    std::fs::write(modules.join("left-pad").join("package.json"), r#"{ "name": "left-pad", "bin": "cli.js" }"#).unwrap();
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let dirs = std::iter::once(bin.clone()).chain(std::env::split_paths(&path));
+    std::env::set_var("PATH", std::env::join_paths(dirs).unwrap());
+
+    let manager = McpServerManager::new();
+    let configs = manager.discover_servers(Some(picked.path()), false).await.unwrap();
+    let names: Vec<_> = configs.iter().map(|config| config.name.as_str()).collect();
+
+    // Everything in the picked directory comes first, and wins over PATH
+    assert_eq!(names[..3], ["alpha", "beta", "mcp-server-notes"]);
+    assert_eq!(configs[2].command, picked.path().join("mcp-server-notes").to_string_lossy());
+    assert!(names.contains(&"github-mcp-server"));
+    assert!(names.contains(&"context7"));
+    assert!(!names.contains(&"left-pad"));
+    assert!(!names.contains(&"tool"));
+    for config in &configs {
+        assert!(config.binary.is_some());
+        assert!(!config.autostart);
+    }
+    assert!(!ran(picked.path()));
+    assert!(!ran(&bin));
+
+    let missing = manager.discover_servers(Some(&picked.path().join("missing")), false).await;
+    assert!(missing.is_err());
+}
+
+#[test]
+fn mcp_has_to_be_a_word_of_the_name() {
+    for name in ["mcp-server-fetch", "github-mcp-server", "mcp_server_time", "Notion-MCP", "mcp"] {
+        assert!(looks_like_server(name), "{}", name);
+    }
+    for name in ["mcpx", "npmcpy", "server-fetch", "ls"] {
+        assert!(!looks_like_server(name), "{}", name);
+    }
+}
+
+#[tokio::test]
+async fn manifests_are_preferred_over_guessing() {
+    let picked = tempfile::tempdir().unwrap();
+    let weather = picked.path().join("forecast");
+    std::fs::create_dir_all(weather.join("bin")).unwrap();
+    install(&weather.join("bin"), "weather");
+    std::fs::write(
+        weather.join("mcp.json"),
+        r#"{
+            "description": "Forecasts for any city",
+            "command": "bin/weather",
+            "args": ["--units", "metric"],
+            "env": [
+                { "name": "WEATHER_KEY", "description": "API key", "secret": true },
+                { "name": "WEATHER_DEBUG", "description": "Log requests", "required": false }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let notes = picked.path().join("notes");
+    std::fs::create_dir(&notes).unwrap();
+    std::fs::write(notes.join("server.json"), r#"{ "package": "@example/notes" }"#).unwrap();
+    let tools = picked.path().join("tools");
+    std::fs::create_dir(&tools).unwrap();
+    std::fs::write(tools.join("mcp.json"), r#"{ "mcpServers": { "search": { "command": "npx", "args": ["-y", "search"] } } }"#).unwrap();
+    let broken = picked.path().join("broken");
+    std::fs::create_dir(&broken).unwrap();
+    std::fs::write(broken.join("mcp.json"), "{").unwrap();
+    // Named like the server the manifest describes, so it is left out
+    install(picked.path(), "weather");
+    install(picked.path(), "other");
+
+    let manager = McpServerManager::new();
+    let configs = manager.discover_servers(Some(picked.path()), false).await.unwrap();
+    let names: Vec<_> = configs.iter().map(|config| config.name.as_str()).collect();
+    assert_eq!(names[..4], ["weather", "notes", "search", "other"]);
+    assert!(!names[4..].contains(&"weather"));
+
+    let notes = &configs[1];
+    assert_eq!(notes.package.as_ref().unwrap().name, "@example/notes");
+    assert!(notes.binary.is_none());
+
+    let search = &configs[2];
+    assert_eq!(search.command, "npx");
+    assert_eq!(search.cwd.as_deref(), Some(&*tools.to_string_lossy()));
+
+    let weather_config = &configs[0];
+    assert_eq!(weather_config.command, weather.join("bin").join("weather").to_string_lossy());
+    assert_eq!(weather_config.args, ["--units", "metric"]);
+    assert_eq!(weather_config.description.as_deref(), Some("Forecasts for any city"));
+    assert_eq!(weather_config.env.len(), 1);
+    assert_eq!(weather_config.env["WEATHER_KEY"], "${WEATHER_KEY}");
+    assert!(weather_config.binary.is_some());
+    assert!(!ran(&weather.join("bin")));
+}
@@ -0,0 +1,37 @@
+//! Remote servers share HTTP clients unless their TLS settings differ.
+
+use mlface_mcp::http::{HttpClients, TlsSettings};
+use mlface_mcp::types::McpError;
+
+#[test]
+fn clients_are_shared_per_tls_settings() {
+    let clients = HttpClients::default();
+    let insecure = TlsSettings { accept_invalid_certs: true, ..Default::default() };
+
+    clients.client(&TlsSettings::default()).unwrap();
+    clients.client(&TlsSettings::default()).unwrap();
+    assert_eq!(clients.len(), 1);
+
+    clients.client(&insecure).unwrap();
+    clients.client(&insecure).unwrap();
+    assert_eq!(clients.len(), 2);
+}
+
+#[test]
+fn a_missing_ca_file_is_an_error() {
+    let clients = HttpClients::default();
+    let tls = TlsSettings { ca_file: Some("/nonexistent/company-ca.pem".into()), ..Default::default() };
+
+    let error = clients.client(&tls).unwrap_err();
+    assert!(matches!(&error, McpError::TransportError(message) if message.contains("company-ca.pem")), "{}", error);
+    assert!(clients.is_empty());
+}
+
+#[test]
+fn tls_settings_are_omitted_when_default() {
+    assert_eq!(serde_json::to_string(&TlsSettings::default()).unwrap(), "{}");
+
+    let tls: TlsSettings = serde_json::from_str(r#"{ "ca_file": "/etc/ssl/company.pem" }"#).unwrap();
+    assert_eq!(tls.ca_file.as_deref(), Some(std::path::Path::new("/etc/ssl/company.pem")));
+    assert!(!tls.accept_invalid_certs);
+}
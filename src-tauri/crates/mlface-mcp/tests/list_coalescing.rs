@@ -0,0 +1,68 @@
+//! Callers listing a server's tools at the same time share one request.
+
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+use std::sync::Arc;
+
+// Answers tools/list only after 200 ms
+const SLOW_LISTING: &str = r#"
+const rl = require("readline").createInterface({ input: process.stdin });
+rl.on("line", (line) => {
+  const message = JSON.parse(line);
+  if (message.id === undefined) return;
+  const result = message.method === "initialize"
+    ? { protocol_version: "0.1.0", name: "slow", version: "1.0.0", capabilities: {} }
+    : { tools: [{ name: "echo", description: "Echo", input_schema: { type: "object" } }] };
+  const reply = () => process.stdout.write(JSON.stringify({ jsonrpc: "2.0", id: message.id, result }) + "\n");
+  if (message.method === "tools/list") setTimeout(reply, 200); else reply();
+});
+"#;
+
+fn tools_list_requests(manager: &McpServerManager) -> u64 {
+    manager.metrics(Some("lister")).servers["lister"].methods["tools/list"].requests
+}
+
+/// A manager connected to the slow server
+async fn lister() -> McpServerManager {
+    let manager = McpServerManager::new();
+    manager
+        .register_server(McpServerConfig {
+            name: "lister".to_string(),
+            command: "node".to_string(),
+            args: vec!["-e".to_string(), SLOW_LISTING.to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    manager.get_client("lister").await.unwrap();
+    manager
+}
+
+#[tokio::test]
+async fn concurrent_listings_share_a_request() {
+    let manager = lister().await;
+    let (first, second, third) =
+        tokio::join!(manager.list_tools("lister"), manager.list_tools("lister"), manager.list_tools("lister"));
+    for listing in [first, second, third] {
+        assert_eq!(listing.unwrap().tools.len(), 1);
+    }
+    assert_eq!(tools_list_requests(&manager), 1);
+
+    // A listing after the shared one finished asks the server again
+    manager.list_tools("lister").await.unwrap();
+    assert_eq!(tools_list_requests(&manager), 2);
+}
+
+#[tokio::test]
+async fn shared_listings_run_on_spawned_tasks() {
+    let manager = Arc::new(lister().await);
+    let tasks: Vec<_> = (0..2)
+        .map(|_| {
+            let manager = manager.clone();
+            tokio::spawn(async move { manager.list_tools("lister").await })
+        })
+        .collect();
+    for task in tasks {
+        assert_eq!(task.await.unwrap().unwrap().tools.len(), 1);
+    }
+    assert_eq!(tools_list_requests(&manager), 1);
+}
@@ -0,0 +1,25 @@
+//! Servers advertised on the local network become remote server configs.
+
+use mdns_sd::ServiceInfo;
+use mlface_mcp::mdns::{config_for, SERVICE_TYPE};
+use std::collections::HashMap;
+
+#[test]
+fn txt_records_say_how_to_reach_the_server() {
+    let properties = HashMap::from([("path".to_string(), "/mcp".to_string()), ("scheme".to_string(), "https".to_string())]);
+    let info = ServiceInfo::new(SERVICE_TYPE, "Home Assistant", "nas.local.", "192.168.1.20,192.168.1.7", 8123, properties).unwrap();
+
+    let config = config_for(&info);
+    assert_eq!(config.name, "Home Assistant");
+    assert_eq!(config.command, "https://192.168.1.7:8123/mcp");
+    assert!(config.is_remote());
+    assert!(!config.autostart);
+}
+
+#[test]
+fn plain_announcements_get_the_default_endpoint() {
+    let info = ServiceInfo::new(SERVICE_TYPE, "notes", "pi.local.", "fe80::1", 3000, None).unwrap();
+
+    // Without an IPv4 address the host name is used
+    assert_eq!(config_for(&info).command, "http://pi.local:3000/sse");
+}
@@ -0,0 +1,71 @@
+//! Messages are told apart by their fields and read straight from a server's
+//! output, whatever else it prints.
+
+use mlface_mcp::transport::{OutputHandler, OutputStream, StdioTransport, Transport};
+use mlface_mcp::types::JsonRpcMessage;
+use serde_json::json;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::process::Command;
+
+#[test]
+fn messages_are_sorted_by_their_fields() {
+    let parse = |value: serde_json::Value| serde_json::from_value::<JsonRpcMessage>(value);
+
+    let request = parse(json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" })).unwrap();
+    assert!(matches!(request, JsonRpcMessage::Request(request) if request.method == "ping" && request.params.is_none()));
+
+    let response = parse(json!({ "jsonrpc": "2.0", "id": "7", "result": { "ok": true } })).unwrap();
+    assert!(matches!(response, JsonRpcMessage::Response(response) if response.result == Some(json!({ "ok": true }))));
+
+    // A null id is still an id
+    let error = parse(json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32700, "message": "Parse error" } })).unwrap();
+    assert!(matches!(error, JsonRpcMessage::Response(response) if response.id.is_null() && response.error.is_some()));
+
+    let notification = parse(json!({ "jsonrpc": "2.0", "method": "notifications/progress", "params": { "progress": 1 } })).unwrap();
+    assert!(matches!(notification, JsonRpcMessage::Notification(notification) if notification.params.is_some()));
+
+    assert!(parse(json!({ "jsonrpc": "2.0", "result": {} })).is_err());
+    assert!(parse(json!({ "id": 1, "method": "ping" })).is_err());
+}
+
+// Prints a log line, a message with a Windows line end, one written in two
+// pieces and a last one without a line end
+const CHATTY_SERVER: &str = r#"
+process.stdout.write("Starting up...\n");
+process.stdout.write('{"jsonrpc":"2.0","method":"first"}\r\n');
+process.stdout.write('{"jsonrpc":"2.0",');
+setTimeout(() => {
+  process.stdout.write('"method":"second"}\n{not json\n');
+  process.stdout.write('{"jsonrpc":"2.0","method":"third"}');
+}, 50);
+"#;
+
+#[tokio::test]
+async fn messages_are_read_between_other_output() {
+    let child = Command::new("node")
+        .args(["-e", CHATTY_SERVER])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let output: OutputHandler = {
+        let lines = lines.clone();
+        Arc::new(move |stream, line| lines.lock().unwrap().push((stream, line.to_string())))
+    };
+    let transport = StdioTransport::from_child_with_output(child, Some(output)).unwrap();
+
+    for expected in ["first", "second", "third"] {
+        match transport.receive().await.unwrap() {
+            JsonRpcMessage::Notification(notification) => assert_eq!(notification.method, expected),
+            other => panic!("Expected a notification, got {:?}", other),
+        }
+    }
+
+    let lines = lines.lock().unwrap();
+    assert!(lines.contains(&(OutputStream::Stdout, "Starting up...".to_string())));
+    assert!(lines.contains(&(OutputStream::Stdout, "{not json".to_string())));
+}
@@ -0,0 +1,70 @@
+//! Requests are counted per server and method, with their errors and latencies.
+
+use mlface_mcp::metrics::RequestMetrics;
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+use serde_json::json;
+use std::time::Duration;
+
+const SCRIPTED_SERVER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/scripted-server.mjs");
+
+#[test]
+fn percentiles_and_error_rates_are_per_method() {
+    let metrics = RequestMetrics::new();
+    for ms in 1..=100 {
+        metrics.record("files", "tools/call", Duration::from_millis(ms), None);
+    }
+    metrics.record("files", "tools/list", Duration::from_millis(500), Some("timeout_error"));
+    metrics.record("files", "tools/list", Duration::from_millis(5), None);
+    metrics.record("search", "tools/call", Duration::from_millis(7), Some("denied"));
+
+    let report = metrics.report(None);
+    let files = &report.servers["files"];
+
+    let call = &files.methods["tools/call"];
+    assert_eq!(call.requests, 100);
+    assert_eq!(call.errors, 0);
+    assert_eq!(call.p50_ms, Some(50.0));
+    assert_eq!(call.p95_ms, Some(95.0));
+    assert_eq!(call.p99_ms, Some(99.0));
+
+    let list = &files.methods["tools/list"];
+    assert_eq!(list.errors_by_kind["timeout_error"], 1);
+    assert_eq!(list.error_rate, 0.5);
+
+    assert_eq!(files.total.requests, 102);
+    assert_eq!(files.total.errors, 1);
+    assert_eq!(files.total.p99_ms, Some(100.0));
+
+    assert_eq!(metrics.report(Some("search")).servers.len(), 1);
+    metrics.reset(Some("files"));
+    assert!(!metrics.report(None).servers.contains_key("files"));
+}
+
+#[tokio::test]
+async fn a_servers_requests_are_measured() {
+    let manager = McpServerManager::new();
+    manager
+        .register_server(McpServerConfig {
+            name: "chatty".to_string(),
+            command: "node".to_string(),
+            args: vec![SCRIPTED_SERVER.to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let client = manager.get_client("chatty").await.unwrap();
+    client.call_tool("echo", Some(json!({ "text": "hi" }))).await.unwrap();
+    let _ = client.read_resource("file:///nowhere").await;
+
+    let report = manager.metrics(Some("chatty"));
+    let chatty = &report.servers["chatty"];
+    assert_eq!(chatty.methods["initialize"].requests, 1);
+    assert_eq!(chatty.methods["tools/call"].requests, 1);
+    assert!(chatty.methods["tools/call"].p50_ms.is_some());
+    assert_eq!(chatty.methods["resources/read"].errors, 1);
+    assert_eq!(chatty.total.requests, 3);
+
+    manager.unregister_server("chatty").await.unwrap();
+    assert!(manager.metrics(None).servers.is_empty());
+}
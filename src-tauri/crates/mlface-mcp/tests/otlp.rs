@@ -0,0 +1,71 @@
+//! Closed spans and request metrics are encoded as OTLP JSON.
+#![cfg(feature = "otlp")]
+
+use mlface_mcp::metrics::RequestMetrics;
+use mlface_mcp::otlp::{OtlpConfig, OtlpExporter};
+use mlface_mcp::server::{ConnectionState, ServerEvent};
+use mlface_mcp::telemetry::{JsonLines, RotatingFile};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::Level;
+
+#[test]
+fn spans_are_exported_with_their_parents() {
+    let dir = tempfile::tempdir().unwrap();
+    let exporter = Arc::new(OtlpExporter::new(OtlpConfig::new("http://localhost:4318/")));
+    let subscriber =
+        JsonLines::new(RotatingFile::open(dir.path(), 1024 * 1024, 3).unwrap(), Level::INFO).with_sink(exporter.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let connect = tracing::info_span!("connect", server = "files");
+        let request = connect.in_scope(|| tracing::info_span!("request", method = "initialize"));
+        request.in_scope(|| tracing::warn!("Request failed: timeout"));
+    });
+
+    exporter.record_event(&ServerEvent {
+        server: "files".to_string(),
+        state: ConnectionState::Crashed,
+        reason: Some("exited with status 1".to_string()),
+        crash: None,
+    });
+
+    let spans_body = exporter.traces_body(&exporter.pending_spans());
+    let spans = spans_body["resourceSpans"][0]["scopeSpans"][0]["spans"].as_array().unwrap();
+    assert_eq!(spans.len(), 3);
+
+    let (request, connect, state) = (&spans[0], &spans[1], &spans[2]);
+    assert_eq!(request["name"], "request");
+    assert_eq!(request["kind"], 3);
+    assert_eq!(request["status"]["code"], 2);
+    assert_eq!(request["traceId"], connect["traceId"]);
+    assert_eq!(request["parentSpanId"], connect["spanId"]);
+    assert_eq!(request["attributes"][0]["key"], "method");
+    assert_eq!(request["attributes"][0]["value"]["stringValue"], "initialize");
+    assert!(connect.get("parentSpanId").is_none());
+    assert!(connect.get("status").is_none());
+
+    assert_eq!(state["name"], "server_state");
+    assert_eq!(state["status"]["code"], 2);
+    assert_eq!(spans_body["resourceSpans"][0]["resource"]["attributes"][0]["value"]["stringValue"], "mlface");
+}
+
+#[test]
+fn metrics_are_sums_and_gauges_per_method() {
+    let exporter = OtlpExporter::new(OtlpConfig::new("http://localhost:4318"));
+    let metrics = RequestMetrics::new();
+    metrics.record("files", "tools/call", Duration::from_millis(20), None);
+    metrics.record("files", "tools/call", Duration::from_millis(40), Some("timeout_error"));
+
+    let body = exporter.metrics_body(&metrics.report(None), 1);
+    let exported = body["resourceMetrics"][0]["scopeMetrics"][0]["metrics"].as_array().unwrap();
+
+    assert_eq!(exported[0]["name"], "mcp.client.requests");
+    assert_eq!(exported[0]["sum"]["dataPoints"][0]["asInt"], "2");
+    assert_eq!(exported[1]["sum"]["dataPoints"][0]["asInt"], "1");
+    assert_eq!(exported[1]["sum"]["dataPoints"][0]["attributes"][2]["value"]["stringValue"], "timeout_error");
+
+    let latency = exported[2]["gauge"]["dataPoints"].as_array().unwrap();
+    assert_eq!(latency.len(), 3);
+    assert_eq!(latency[2]["attributes"][2]["value"]["stringValue"], "p99");
+    assert_eq!(latency[2]["asDouble"], 40.0);
+}
@@ -0,0 +1,81 @@
+//! Packages are installed ahead of their first start, with the installer's
+//! output passed on as it comes.
+#![cfg(unix)]
+
+use mlface_mcp::package::{install, install_command, is_installed, PackageSpec};
+use mlface_mcp::transport::OutputStream;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Stands in for npm: installs @example/server into the prefix it lives in,
+// slowly for the package that never finishes
+const FAKE_NPM: &str = r#"#!/bin/sh
+[ "$1 $2" = "install -g" ] || exit 1
+prefix="$(dirname "$0")/.."
+case "$3" in
+  @example/server@1.2.0)
+    echo "added 1 package"
+    echo "npm warn deprecated glob@7" >&2
+    mkdir -p "$prefix/lib/node_modules/@example/server"
+    echo '{ "name": "@example/server", "version": "1.2.0" }' > "$prefix/lib/node_modules/@example/server/package.json"
+    ;;
+  @example/slow)
+    echo "fetching"
+    sleep 30
+    ;;
+  *)
+    echo "404 Not Found - $3" >&2
+    exit 1
+    ;;
+esac
+"#;
+
+fn install_script(dir: &Path, name: &str, script: &str) {
+    let path = dir.join(name);
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+// Changes PATH, so all cases share one test
+#[tokio::test]
+async fn npm_packages_are_installed_globally() {
+    let prefix = tempfile::tempdir().unwrap();
+    let bin = prefix.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    install_script(&bin, "npm", FAKE_NPM);
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let dirs = std::iter::once(bin.clone()).chain(std::env::split_paths(&path));
+    std::env::set_var("PATH", std::env::join_paths(dirs).unwrap());
+
+    let server = PackageSpec::npm("@example/server").with_version("1.2.0");
+    let command = install_command(&server).unwrap();
+    assert_eq!(command.command, bin.join("npm").to_string_lossy());
+    assert_eq!(command.args, ["install", "-g", "@example/server@1.2.0"]);
+    assert!(!is_installed(&server));
+
+    let output = Mutex::new(Vec::new());
+    install(&server, |stream, line| output.lock().unwrap().push((stream, line.to_string()))).await.unwrap();
+    let mut output = output.into_inner().unwrap();
+    output.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(
+        output,
+        [
+            (OutputStream::Stdout, "added 1 package".to_string()),
+            (OutputStream::Stderr, "npm warn deprecated glob@7".to_string()),
+        ]
+    );
+    assert!(is_installed(&server));
+    assert!(is_installed(&PackageSpec::npm("@example/server")));
+    assert!(!is_installed(&server.clone().with_version("2.0.0")));
+
+    let missing = install(&PackageSpec::npm("@example/missing"), |_, _| {}).await.unwrap_err();
+    assert!(missing.to_string().contains("Installing @example/missing failed"));
+
+    // Dropping the install stops the installer
+    let slow = PackageSpec::npm("@example/slow");
+    let cancelled = tokio::time::timeout(Duration::from_millis(500), install(&slow, |_, _| {})).await;
+    assert!(cancelled.is_err());
+    assert!(!is_installed(&slow));
+}
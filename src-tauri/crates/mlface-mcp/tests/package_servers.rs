@@ -0,0 +1,121 @@
+//! Servers configured by package are run through npx, uvx or pipx.
+#![cfg(unix)]
+
+use mlface_mcp::package::{PackageRuntime, PackageSpec};
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+
+// Reports the args it was started with as its name
+const ARGS_SERVER: &str = r#"
+const rl = require("readline").createInterface({ input: process.stdin });
+rl.on("line", (line) => {
+  const message = JSON.parse(line);
+  if (message.id === undefined) return;
+  const result = { protocol_version: "0.1.0", name: process.argv.slice(1).join(" "), version: "1.0.0", capabilities: {} };
+  process.stdout.write(JSON.stringify({ jsonrpc: "2.0", id: message.id, result }) + "\n");
+});
+"#;
+
+// Stands in for npx: runs the server if it was asked for the right package
+const FAKE_NPX: &str = r#"#!/bin/sh
+[ "$1" = "-y" ] && [ "$2" = "@example/server-args@1.0.0" ] || exit 1
+shift 2
+exec node -e "$ARGS_SERVER" -- "$@"
+"#;
+
+// Stands in for pipx: runs the server if it was asked for the pinned package
+// in an environment free of the user's Python setup
+const FAKE_PIPX: &str = r#"#!/bin/sh
+[ "$1 $2 $3 $4" = "run --spec mcp-server-args==2.1 mcp-server-args" ] || exit 1
+[ "$PYTHONNOUSERSITE" = "1" ] && [ -z "$PYTHONPATH" ] || exit 1
+shift 4
+exec node -e "$ARGS_SERVER" -- "$@"
+"#;
+
+fn install(dir: &std::path::Path, name: &str, script: &str) -> std::path::PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn config(command: &str, package: Option<PackageSpec>) -> McpServerConfig {
+    McpServerConfig {
+        name: "packaged".to_string(),
+        command: command.to_string(),
+        args: vec!["--root".to_string(), "/data".to_string()],
+        env: HashMap::from([("ARGS_SERVER".to_string(), ARGS_SERVER.to_string())]),
+        package,
+        ..Default::default()
+    }
+}
+
+// Both cases change PATH, so they share one test
+#[tokio::test]
+async fn packages_are_run_with_their_runner() {
+    let dir = tempfile::tempdir().unwrap();
+    let npx = install(dir.path(), "npx", FAKE_NPX);
+    let pipx = install(dir.path(), "pipx", FAKE_PIPX);
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let dirs = std::iter::once(dir.path().to_path_buf()).chain(std::env::split_paths(&path));
+    std::env::set_var("PATH", std::env::join_paths(dirs).unwrap());
+
+    let npm = PackageSpec::npm("@example/server-args").with_version("1.0.0");
+    let resolved = config("", Some(npm.clone())).resolve_package().unwrap();
+    assert_eq!(resolved.command, npx.to_string_lossy());
+    assert_eq!(resolved.args, ["-y", "@example/server-args@1.0.0", "--root", "/data"]);
+
+    let manager = McpServerManager::new();
+    manager.register_server(config("", Some(npm))).await.unwrap();
+    manager.get_client("packaged").await.unwrap();
+    let info = manager.server_info("packaged").await.unwrap().unwrap();
+    assert_eq!(info.name, "--root /data");
+
+    let runtime = mlface_mcp::package::detect_node().unwrap();
+    assert!(runtime.version.starts_with('v'));
+    assert_eq!(runtime.npx, npx);
+
+    // Without uvx on PATH, Python packages fall back to pipx
+    let python = PackageSpec::python("mcp-server-args").with_version("2.1");
+    let mut packaged = config("", Some(python));
+    packaged.name = "python".to_string();
+    let resolved = packaged.resolve_package().unwrap();
+    if resolved.command == pipx.to_string_lossy() {
+        assert_eq!(resolved.args, ["run", "--spec", "mcp-server-args==2.1", "mcp-server-args", "--root", "/data"]);
+        assert_eq!(resolved.env["PYTHONNOUSERSITE"], "1");
+
+        manager.register_server(packaged).await.unwrap();
+        manager.get_client("python").await.unwrap();
+        let info = manager.server_info("python").await.unwrap().unwrap();
+        assert_eq!(info.name, "--root /data");
+    }
+}
+
+#[test]
+fn package_specs_are_written_as_short_as_possible() {
+    let npm: PackageSpec = serde_json::from_str(r#""@modelcontextprotocol/server-filesystem""#).unwrap();
+    assert_eq!(npm, PackageSpec::npm("@modelcontextprotocol/server-filesystem"));
+    assert_eq!(serde_json::to_string(&npm).unwrap(), r#""@modelcontextprotocol/server-filesystem""#);
+
+    let python: PackageSpec =
+        serde_json::from_str(r#"{ "name": "mcp-server-fetch", "runtime": "python", "version": "2025.1.17" }"#).unwrap();
+    assert_eq!(python.runtime, PackageRuntime::Python);
+    assert_eq!(python.version.as_deref(), Some("2025.1.17"));
+    assert_eq!(
+        serde_json::to_value(&python).unwrap(),
+        serde_json::json!({ "name": "mcp-server-fetch", "runtime": "python", "version": "2025.1.17" })
+    );
+}
+
+#[test]
+fn a_server_needs_exactly_one_of_command_and_package() {
+    let neither = config("", None).resolve_package().unwrap_err();
+    assert!(neither.to_string().contains("neither a command nor a package"));
+
+    let both = config("node", Some(PackageSpec::npm("@example/server-args"))).resolve_package().unwrap_err();
+    assert!(both.to_string().contains("both a command and a package"));
+
+    let command = config("node", None);
+    assert_eq!(command.resolve_package().unwrap(), command);
+}
@@ -0,0 +1,83 @@
+//! Pinned and installed packages are compared with the registry's latest
+//! release.
+
+use mlface_mcp::package::PackageSpec;
+use mlface_mcp::updates::{is_newer, latest_version, PackageRegistries, PackageUpdates};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// Answers npm's abbreviated metadata and PyPI's JSON for a couple of packages
+async fn registry() -> PackageRegistries {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = vec![0; 4096];
+            let read = socket.read(&mut request).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&request[..read]).to_string();
+            let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+            let body = match path.as_str() {
+                "/npm/@example%2Fserver" => r#"{ "name": "@example/server", "dist-tags": { "latest": "1.10.0", "next": "2.0.0-rc.1" } }"#,
+                "/pypi/example-server/json" => r#"{ "info": { "name": "example-server", "version": "0.4.2" } }"#,
+                "/npm/@example%2Funtagged" => r#"{ "name": "@example/untagged" }"#,
+                _ => "",
+            };
+            let status = if body.is_empty() { "404 Not Found" } else { "200 OK" };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    PackageRegistries { npm: format!("{}/npm", base), pypi: format!("{}/pypi", base) }
+}
+
+#[tokio::test]
+async fn latest_versions_come_from_the_registry() {
+    let registries = registry().await;
+    let client = reqwest::Client::new();
+
+    let npm = latest_version(&client, &registries, &PackageSpec::npm("@example/server")).await.unwrap();
+    assert_eq!(npm, "1.10.0");
+    let pypi = latest_version(&client, &registries, &PackageSpec::python("example-server")).await.unwrap();
+    assert_eq!(pypi, "0.4.2");
+
+    assert!(latest_version(&client, &registries, &PackageSpec::npm("@example/missing")).await.is_err());
+    assert!(latest_version(&client, &registries, &PackageSpec::npm("@example/untagged")).await.is_err());
+}
+
+#[tokio::test]
+async fn pinned_packages_behind_the_latest_release_are_reported() {
+    let updates = PackageUpdates::new(registry().await);
+    let client = reqwest::Client::new();
+    let behind = PackageSpec::npm("@example/server").with_version("1.9.3");
+    let current = PackageSpec::python("example-server").with_version("0.4.2");
+    let missing = PackageSpec::npm("@example/missing").with_version("1.0.0");
+
+    let available = updates.check(&client, vec![behind.clone(), current.clone(), missing.clone()]).await;
+    assert_eq!(available.len(), 1);
+    assert_eq!(available[0].0, behind);
+
+    let update = updates.get(&behind).unwrap();
+    assert_eq!(update.current.as_deref(), Some("1.9.3"));
+    assert_eq!(update.latest, "1.10.0");
+    assert!(update.available);
+    assert!(!updates.get(&current).unwrap().available);
+    assert!(updates.get(&missing).is_none());
+
+    updates.updated(&behind, "1.10.0");
+    assert!(!updates.get(&behind).unwrap().available);
+}
+
+#[test]
+fn versions_compare_by_their_numbers() {
+    assert!(is_newer("1.10.0", "1.9.3"));
+    assert!(is_newer("2.0", "1.99.99"));
+    assert!(is_newer("v0.4.3", "0.4.2"));
+    assert!(!is_newer("1.2.0", "1.2"));
+    assert!(!is_newer("1.2.0-rc.1", "1.2.0"));
+    assert!(!is_newer("0.4.2", "0.4.10"));
+}
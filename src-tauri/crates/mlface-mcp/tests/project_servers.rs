@@ -0,0 +1,93 @@
+//! A workspace folder's own servers are read scoped to it, need approval,
+//! and stay out of the saved config.
+
+use mlface_mcp::config::ConfigFile;
+use mlface_mcp::project::{self, ProjectApprovals};
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+use std::collections::HashMap;
+
+fn server(name: &str) -> McpServerConfig {
+    McpServerConfig {
+        name: name.to_string(),
+        command: "node".to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn project_configs_are_scoped_to_their_folder() {
+    let workspace = tempfile::tempdir().unwrap();
+    assert!(project::load(workspace.path()).unwrap().is_none());
+
+    std::fs::write(
+        workspace.path().join(".mcp.json"),
+        r#"{ "mcpServers": { "lint": { "command": "npx", "args": ["-y", "lint-mcp"] } } }"#,
+    )
+    .unwrap();
+    let config = project::load(workspace.path()).unwrap().unwrap();
+    assert_eq!(config.path, workspace.path().join(".mcp.json"));
+    let folder = workspace.path().to_string_lossy().to_string();
+    assert_eq!(config.servers.len(), 1);
+    assert_eq!(config.servers[0].name, "lint");
+    assert_eq!(config.servers[0].cwd.as_deref(), Some(folder.as_str()));
+    assert_eq!(config.servers[0].roots, [folder]);
+
+    // mlFace's own file wins, and servers keep the scope they ask for
+    std::fs::create_dir(workspace.path().join(".mlface")).unwrap();
+    let mut docs = server("docs");
+    docs.cwd = Some("/srv/docs".to_string());
+    docs.roots = vec!["/srv/docs".to_string()];
+    let file = ConfigFile::new(HashMap::from([("docs".to_string(), docs.clone()), ("build".to_string(), server("build"))]));
+    std::fs::write(workspace.path().join(".mlface").join("mcp.json"), serde_json::to_string(&file).unwrap()).unwrap();
+    let config = project::load(workspace.path()).unwrap().unwrap();
+    let names: Vec<_> = config.servers.iter().map(|server| server.name.as_str()).collect();
+    assert_eq!(names, ["build", "docs"]);
+    assert_eq!(config.servers[1].roots, docs.roots);
+    assert_eq!(config.servers[1].cwd, docs.cwd);
+
+    std::fs::write(workspace.path().join(".mlface").join("mcp.json"), "{").unwrap();
+    assert!(project::load(workspace.path()).is_err());
+}
+
+#[test]
+fn approvals_lapse_when_the_config_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("approvals.json");
+    let workspace = dir.path().join("workspace");
+    let mut lint = server("lint");
+
+    let mut approvals = ProjectApprovals::load(&path);
+    assert!(!approvals.is_approved(&workspace, &lint));
+    approvals.approve(&workspace, &lint);
+    approvals.save(&path).unwrap();
+
+    let approvals = ProjectApprovals::load(&path);
+    assert!(approvals.is_approved(&workspace, &lint));
+    assert!(!approvals.is_approved(&dir.path().join("elsewhere"), &lint));
+    lint.args.push("--fix".to_string());
+    assert!(!approvals.is_approved(&workspace, &lint));
+}
+
+#[tokio::test]
+async fn project_servers_are_not_saved() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mcp_servers.json");
+    let manager = McpServerManager::new();
+    manager.register_server(server("saved")).await.unwrap();
+    manager.register_project_server(server("lint")).await.unwrap();
+    assert!(manager.register_project_server(server("saved")).await.is_err());
+    assert_eq!(manager.project_servers(), ["lint"]);
+
+    manager.save_to_file(path.to_str().unwrap()).await.unwrap();
+    let file = ConfigFile::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(file.servers.keys().collect::<Vec<_>>(), ["saved"]);
+
+    // Reloading the config file leaves them alone
+    let diff = manager.apply_config(file.servers).await.unwrap();
+    assert!(diff.removed.is_empty());
+    assert!(manager.is_project_server("lint"));
+
+    manager.unregister_server("lint").await.unwrap();
+    assert!(manager.project_servers().is_empty());
+    assert_eq!(manager.get_servers().await.len(), 1);
+}
@@ -0,0 +1,73 @@
+//! Tool calls are held to the per-server and global rate limits.
+
+use mlface_mcp::rate_limit::{LimitMode, RateLimit, RateLimiter};
+use mlface_mcp::types::McpError;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+fn limit(per_minute: u32, burst: u32, when_limited: LimitMode) -> Option<RateLimit> {
+    Some(RateLimit {
+        per_minute,
+        burst: Some(burst),
+        when_limited,
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn calls_over_a_rejecting_limit_fail() {
+    let limiter = RateLimiter::default();
+    let search = limit(60, 2, LimitMode::Reject);
+
+    limiter.acquire("search", search).await.unwrap();
+    limiter.acquire("search", search).await.unwrap();
+    let err = limiter.acquire("search", search).await.unwrap_err();
+    assert!(matches!(err, McpError::RateLimited(_)));
+
+    // Other servers have their own bucket
+    limiter.acquire("files", limit(60, 1, LimitMode::Reject)).await.unwrap();
+    limiter.acquire("unlimited", None).await.unwrap();
+
+    // A token comes back every second
+    tokio::time::advance(Duration::from_secs(1)).await;
+    limiter.acquire("search", search).await.unwrap();
+
+    let usage = limiter.usage();
+    assert!(usage.global.is_none());
+    assert_eq!(usage.servers["search"].allowed, 3);
+    assert_eq!(usage.servers["search"].rejected, 1);
+    assert!(!usage.servers.contains_key("unlimited"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn calls_over_a_queueing_limit_wait() {
+    let limiter = Arc::new(RateLimiter::default());
+    let search = limit(30, 1, LimitMode::Queue);
+
+    let started = Instant::now();
+    limiter.acquire("search", search).await.unwrap();
+
+    let waiting = tokio::spawn({
+        let limiter = limiter.clone();
+        async move { limiter.acquire("search", search).await }
+    });
+    tokio::task::yield_now().await;
+    assert_eq!(limiter.usage().servers["search"].waiting, 1);
+
+    waiting.await.unwrap().unwrap();
+    assert!(started.elapsed() >= Duration::from_secs(2));
+    assert_eq!(limiter.usage().servers["search"].waiting, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_global_limit_covers_all_servers() {
+    let limiter = RateLimiter::default();
+    limiter.set_global(limit(60, 2, LimitMode::Reject));
+
+    limiter.acquire("search", None).await.unwrap();
+    limiter.acquire("files", None).await.unwrap();
+    assert!(limiter.acquire("weather", None).await.is_err());
+    assert_eq!(limiter.usage().global.unwrap().allowed, 2);
+
+    limiter.set_global(None);
+    limiter.acquire("weather", None).await.unwrap();
+}
@@ -0,0 +1,161 @@
+//! Registry servers are searched live, cached, and installed from their
+//! package or remote endpoint.
+
+use mlface_mcp::package::PackageRuntime;
+use mlface_mcp::registry::{Registry, RegistryCache, RegistryServer};
+use mlface_mcp::secrets::{MemorySecretStore, SecretStore};
+use mlface_mcp::server::ServerAuth;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const WEATHER: &str = r#"{
+    "name": "io.github.example/weather",
+    "description": "Forecasts for any city",
+    "version": "1.2.0",
+    "repository": { "url": "https://github.com/example/weather", "source": "github" },
+    "packages": [
+        { "registryType": "oci", "identifier": "example/weather", "transport": { "type": "stdio" } },
+        {
+            "registryType": "npm",
+            "identifier": "@example/weather",
+            "version": "1.2.0",
+            "transport": { "type": "stdio" },
+            "packageArguments": [
+                { "type": "named", "name": "--units", "value": "metric" },
+                { "type": "positional", "valueHint": "city" }
+            ],
+            "environmentVariables": [
+                { "name": "WEATHER_KEY", "description": "API key", "isRequired": true, "isSecret": true }
+            ]
+        }
+    ]
+}"#;
+
+const SEARCH: &str = r#"{
+    "name": "com.example/search",
+    "description": "Search the web",
+    "remotes": [
+        { "type": "streamable-http", "url": "https://search.example.com/mcp", "headers": [
+            { "name": "X-Api-Key", "description": "API key", "isRequired": true, "isSecret": true }
+        ] }
+    ]
+}"#;
+
+// Answers list and lookup requests like the registry, counting them
+async fn registry() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counted = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            counted.fetch_add(1, Ordering::SeqCst);
+            let mut request = vec![0; 4096];
+            let read = socket.read(&mut request).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&request[..read]).to_string();
+            let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+            let body = if path.starts_with("/v0/servers?") {
+                format!(
+                    r#"{{ "servers": [ {{ "server": {}, "_meta": {{}} }}, {{ "server": {} }} ], "metadata": {{ "count": 2 }} }}"#,
+                    WEATHER, SEARCH
+                )
+            } else if path == "/v0/servers/io.github.example%2Fweather/versions/latest" {
+                format!(r#"{{ "server": {} }}"#, WEATHER)
+            } else {
+                String::new()
+            };
+            let status = if body.is_empty() { "404 Not Found" } else { "200 OK" };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (url, requests)
+}
+
+// Nothing listens on the port once the listener is dropped
+async fn unreachable() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+#[tokio::test]
+async fn searches_fall_back_to_the_cache_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = dir.path().join("registry.json");
+    let client = reqwest::Client::new();
+    let (url, requests) = registry().await;
+
+    let online = Registry::new(&url, Some(cache.clone()));
+    let found = online.search(&client, "weather", 10).await.unwrap();
+    assert!(!found.offline);
+    let names: Vec<_> = found.servers.iter().map(|server| server.name.as_str()).collect();
+    assert_eq!(names, ["io.github.example/weather", "com.example/search"]);
+    assert_eq!(RegistryCache::load(&cache).servers.len(), 2);
+
+    // Fetched just now, so the cache answers
+    let seen = requests.load(Ordering::SeqCst);
+    let weather = online.server(&client, "io.github.example/weather").await.unwrap();
+    assert_eq!(weather.version.as_deref(), Some("1.2.0"));
+    assert_eq!(requests.load(Ordering::SeqCst), seen);
+
+    let offline = Registry::new(unreachable().await, Some(cache.clone()));
+    let found = offline.search(&client, "SEARCH web", 10).await.unwrap();
+    assert!(found.offline);
+    assert_eq!(found.servers.len(), 1);
+    assert_eq!(found.servers[0].name, "com.example/search");
+    assert!(offline.server(&client, "io.github.example/weather").await.is_ok());
+    assert!(offline.server(&client, "com.example/missing").await.is_err());
+
+    let uncached = Registry::new(&url, None);
+    assert!(uncached.server(&client, "io.github.example/weather").await.is_ok());
+    assert!(uncached.server(&client, "com.example/missing").await.is_err());
+}
+
+#[test]
+fn packages_install_like_catalog_entries() {
+    let weather: RegistryServer = serde_json::from_str(WEATHER).unwrap();
+    let entry = weather.catalog_entry().unwrap();
+    assert_eq!(entry.package.runtime, PackageRuntime::Node);
+    assert_eq!(entry.package.name, "@example/weather");
+    assert_eq!(entry.args, ["--units", "metric"]);
+    assert_eq!(entry.args_hint.as_deref(), Some("city"));
+    assert_eq!(entry.homepage.as_deref(), Some("https://github.com/example/weather"));
+
+    let store = MemorySecretStore::default();
+    assert!(weather.install(None, &[], &HashMap::new(), Some(&store)).is_err());
+    let values = HashMap::from([("WEATHER_KEY".to_string(), "k3y".to_string())]);
+    let config = weather.install(None, &["Berlin".to_string()], &values, Some(&store)).unwrap();
+    assert_eq!(config.name, "weather");
+    assert_eq!(config.args, ["--units", "metric", "Berlin"]);
+    assert_eq!(config.env["WEATHER_KEY"], "{{secret:io.github.example.weather.WEATHER_KEY}}");
+    assert_eq!(store.get("io.github.example.weather.WEATHER_KEY").unwrap().as_deref(), Some("k3y"));
+}
+
+#[test]
+fn remotes_install_with_their_header_as_auth() {
+    let search: RegistryServer = serde_json::from_str(SEARCH).unwrap();
+    assert!(search.catalog_entry().is_none());
+
+    let store = MemorySecretStore::default();
+    let missing = search.install(None, &[], &HashMap::new(), Some(&store)).unwrap_err();
+    assert!(missing.to_string().contains("X-Api-Key"));
+
+    let values = HashMap::from([("X-Api-Key".to_string(), " s3cret ".to_string())]);
+    let config = search.install(Some("web"), &[], &values, Some(&store)).unwrap();
+    assert_eq!(config.name, "web");
+    assert_eq!(config.command, "https://search.example.com/mcp");
+    assert_eq!(config.description.as_deref(), Some("Search the web"));
+    assert_eq!(
+        config.auth,
+        Some(ServerAuth::Header { name: "X-Api-Key".into(), value_ref: "com.example.search.X-Api-Key".into() })
+    );
+    assert_eq!(store.get("com.example.search.X-Api-Key").unwrap().as_deref(), Some("s3cret"));
+}
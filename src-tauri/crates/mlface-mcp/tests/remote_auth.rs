@@ -0,0 +1,72 @@
+//! Credentials of remote servers come from the secret store and are sent with
+//! every request.
+
+use mlface_mcp::secrets::{MemorySecretStore, SecretStore};
+use mlface_mcp::server::{McpServerConfig, McpServerManager, ServerAuth};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+
+fn remote(url: &str, auth: ServerAuth) -> McpServerConfig {
+    McpServerConfig {
+        name: "remote".to_string(),
+        command: url.to_string(),
+        auth: Some(auth),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn auth_is_configured_by_type() {
+    let bearer: ServerAuth = serde_json::from_str(r#"{ "type": "bearer", "value_ref": "github" }"#).unwrap();
+    assert_eq!(bearer, ServerAuth::Bearer { value_ref: "github".into() });
+
+    let header: ServerAuth =
+        serde_json::from_str(r#"{ "type": "header", "name": "X-Api-Key", "value_ref": "search" }"#).unwrap();
+    assert_eq!(header, ServerAuth::Header { name: "X-Api-Key".into(), value_ref: "search".into() });
+
+    let store = MemorySecretStore::default();
+    store.set("github", "t0ken").unwrap();
+    store.set("search", "k3y").unwrap();
+    assert_eq!(bearer.header(Some(&store)).unwrap(), ("Authorization".into(), "Bearer t0ken".into()));
+    assert_eq!(header.header(Some(&store)).unwrap(), ("X-Api-Key".into(), "k3y".into()));
+}
+
+#[tokio::test]
+async fn connecting_fails_without_the_secret() {
+    let manager = McpServerManager::new();
+    manager.set_secret_store(Some(Arc::new(MemorySecretStore::default())));
+    let auth = ServerAuth::Bearer { value_ref: "github".into() };
+    manager.register_server(remote("http://127.0.0.1:9/sse", auth)).await.unwrap();
+
+    let error = manager.start_server("remote").await.unwrap_err();
+    assert!(error.to_string().contains("Secret github is not set"));
+}
+
+#[tokio::test]
+async fn the_credential_is_sent_to_the_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/sse", listener.local_addr().unwrap());
+
+    let store = MemorySecretStore::default();
+    store.set("search", "k3y").unwrap();
+    let manager = Arc::new(McpServerManager::new());
+    manager.set_secret_store(Some(Arc::new(store)));
+    let auth = ServerAuth::Header { name: "X-Api-Key".into(), value_ref: "search".into() };
+    manager.register_server(remote(&url, auth)).await.unwrap();
+
+    // Nothing answers the handshake; the request is all this test needs
+    let starting = tokio::spawn({
+        let manager = manager.clone();
+        async move { manager.start_server("remote").await }
+    });
+
+    let (mut socket, _) = tokio::time::timeout(Duration::from_secs(5), listener.accept()).await.unwrap().unwrap();
+    let mut request = vec![0; 4096];
+    let read = socket.read(&mut request).await.unwrap();
+    let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+    assert!(request.contains("x-api-key: k3y"), "{}", request);
+
+    starting.abort();
+}
@@ -0,0 +1,66 @@
+//! Interactive requests go ahead of background ones waiting for a slot.
+
+use mlface_mcp::priority::{with_priority, Priority};
+use mlface_mcp::server::{McpServerConfig, McpServerManager, ServerSettings};
+use mlface_mcp::types::Content;
+use serde_json::json;
+use std::time::Duration;
+
+// Answers tool calls after 100 ms with their text and the order they arrived in
+const ORDERED_SERVER: &str = r#"
+let arrived = 0;
+const rl = require("readline").createInterface({ input: process.stdin });
+rl.on("line", (line) => {
+  const message = JSON.parse(line);
+  if (message.id === undefined) return;
+  const reply = (result) => process.stdout.write(JSON.stringify({ jsonrpc: "2.0", id: message.id, result }) + "\n");
+  if (message.method === "initialize") {
+    reply({ protocol_version: "0.1.0", name: "ordered", version: "1.0.0", capabilities: {} });
+  } else {
+    const text = message.params.arguments.text + " " + arrived++;
+    setTimeout(() => reply({ content: [{ type: "text", text }] }), 100);
+  }
+});
+"#;
+
+fn arrival(text: Vec<Content>) -> String {
+    match &text[0] {
+        Content::Text { text } => text.clone(),
+        other => panic!("Expected text, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn interactive_requests_jump_the_queue() {
+    let manager = McpServerManager::new();
+    manager
+        .register_server(McpServerConfig {
+            name: "ordered".to_string(),
+            command: "node".to_string(),
+            args: vec!["-e".to_string(), ORDERED_SERVER.to_string()],
+            settings: ServerSettings {
+                max_concurrent_requests: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let client = manager.get_client("ordered").await.unwrap();
+
+    let call = |text: &'static str| {
+        let client = client.clone();
+        async move { arrival(client.call_tool("echo", Some(json!({ "text": text }))).await.unwrap().content) }
+    };
+
+    // The first call takes the only slot; the others wait, the refresh first
+    let first = tokio::spawn(call("first"));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let refresh = tokio::spawn(with_priority(Priority::Background, call("refresh")));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let click = tokio::spawn(call("click"));
+
+    assert_eq!(first.await.unwrap(), "first 0");
+    assert_eq!(click.await.unwrap(), "click 1");
+    assert_eq!(refresh.await.unwrap(), "refresh 2");
+}
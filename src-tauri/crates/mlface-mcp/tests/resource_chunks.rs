@@ -0,0 +1,70 @@
+//! Large resources are cut into usable pieces and saved without a detour.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use mlface_mcp::resource::{chunks, save};
+use mlface_mcp::types::{Content, ReadResourceResult};
+
+fn resource() -> ReadResourceResult {
+    ReadResourceResult {
+        content: vec![
+            Content::Text { text: "größer als ein Stück".to_string() },
+            Content::EmbeddedResource { uri: "file:///notes.md".to_string(), properties: None },
+            Content::Image {
+                mime_type: "image/png".to_string(),
+                data: STANDARD.encode(b"\x89PNG\r\n\x1a\n pixels"),
+            },
+        ],
+    }
+}
+
+#[test]
+fn pieces_rejoin_to_the_original() {
+    let pieces: Vec<_> = chunks(resource(), 7).collect();
+
+    let text: String = pieces
+        .iter()
+        .filter(|piece| piece.index == 0)
+        .map(|piece| match &piece.content {
+            Content::Text { text } => {
+                assert!(text.len() <= 7);
+                text.as_str()
+            }
+            other => panic!("Expected text, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(text, "größer als ein Stück");
+
+    let embedded: Vec<_> = pieces.iter().filter(|piece| piece.index == 1).collect();
+    assert_eq!(embedded.len(), 1);
+    assert!(embedded[0].last);
+
+    // Every piece of base64 data decodes on its own
+    let mut image = Vec::new();
+    let mut offset = 0;
+    for piece in pieces.iter().filter(|piece| piece.index == 2) {
+        let Content::Image { mime_type, data } = &piece.content else {
+            panic!("Expected image data");
+        };
+        assert_eq!(mime_type, "image/png");
+        assert_eq!(data.len(), 4);
+        assert_eq!(piece.offset, offset);
+        offset += data.len();
+        image.extend(STANDARD.decode(data).unwrap());
+    }
+    assert_eq!(image, b"\x89PNG\r\n\x1a\n pixels");
+    assert!(pieces.last().unwrap().last);
+}
+
+#[tokio::test]
+async fn saving_decodes_image_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("resource.bin");
+
+    let written = save(resource(), &path).await.unwrap();
+
+    let mut expected = "größer als ein Stück".as_bytes().to_vec();
+    expected.extend_from_slice(b"\x89PNG\r\n\x1a\n pixels");
+    assert_eq!(written, expected.len() as u64);
+    assert_eq!(std::fs::read(&path).unwrap(), expected);
+}
@@ -0,0 +1,114 @@
+//! Servers with roots are held to them on the client side.
+
+use mlface_mcp::client::McpClient;
+use mlface_mcp::middleware::McpMiddleware;
+use mlface_mcp::roots::{Roots, RootsEnforcement, RootsMiddleware};
+use mlface_mcp::session::{ReplayTransport, SessionEntry};
+use mlface_mcp::traffic::Direction;
+use mlface_mcp::types::{JsonRpcRequest, JsonRpcResponse, McpError};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn request(method: &str, params: Value) -> JsonRpcRequest {
+    JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: json!(1),
+        method: method.to_string(),
+        params: Some(params),
+    }
+}
+
+#[test]
+fn paths_are_checked_against_the_roots() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("project");
+    std::fs::create_dir(&root).unwrap();
+    let roots = Roots::new([&root]);
+
+    assert!(roots.contains(&root.join("src/main.rs")));
+    assert!(!roots.contains(&root.join("../secrets.txt")));
+    assert!(!roots.contains(&dir.path().join("project-other")));
+
+    let inside = format!("file://{}", root.join("notes.md").display());
+    let outside = format!("file://{}", dir.path().join("notes.md").display());
+    assert!(roots.check_uri(&inside).is_ok());
+    assert!(roots.check_uri(&outside).is_err());
+    assert!(roots.check_uri("https://example.com/notes.md").is_ok());
+
+    let args = json!({ "query": "hello", "paths": [root.join("a.txt"), dir.path().join("b.txt")] });
+    assert!(roots.check_arguments(&args).is_err());
+    assert!(roots.check_arguments(&json!({ "path": "relative/a.txt", "count": 3 })).is_ok());
+
+    let listed = roots.list();
+    assert_eq!(listed["roots"].as_array().unwrap().len(), 1);
+    assert!(listed["roots"][0]["uri"].as_str().unwrap().starts_with("file://"));
+}
+
+#[tokio::test]
+async fn strictness_decides_what_happens_to_violations() {
+    let dir = tempfile::tempdir().unwrap();
+    let outside = json!({ "name": "read_file", "arguments": { "path": dir.path().join("../etc/passwd") } });
+    let roots = || Roots::new([dir.path().join("project")]);
+
+    let strict = RootsMiddleware::new(roots(), RootsEnforcement::Strict, "files");
+    let err = strict.on_request(&mut request("tools/call", outside.clone())).await.unwrap_err();
+    assert!(matches!(err, McpError::Denied(_)));
+
+    let warn = RootsMiddleware::new(roots(), RootsEnforcement::Warn, "files");
+    assert!(warn.on_request(&mut request("tools/call", outside.clone())).await.is_ok());
+
+    let off = RootsMiddleware::new(roots(), RootsEnforcement::Off, "files");
+    assert!(off.on_request(&mut request("tools/call", outside)).await.is_ok());
+
+    // A read may not return contents from elsewhere either
+    let mut response = JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id: json!(1),
+        result: Some(json!({ "contents": [{ "uri": "file:///etc/passwd", "text": "root:x:0:0" }] })),
+        error: None,
+    };
+    assert!(strict.on_response("resources/read", &mut response).await.is_err());
+    assert!(strict.on_response("resources/list", &mut response).await.is_ok());
+}
+
+#[tokio::test]
+async fn reads_from_dated_revisions_are_held_to_the_roots() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("project");
+    let asked = format!("file://{}", root.join("notes.md").display());
+    let answered = format!("file://{}", dir.path().join("secrets.txt").display());
+
+    let entry = |direction, message| SessionEntry { direction, timestamp: 0, message };
+    let server = ReplayTransport::new(vec![
+        entry(Direction::Sent, json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" })),
+        entry(
+            Direction::Received,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": { "resources": {} },
+                    "serverInfo": { "name": "dated", "version": "1.0.0" }
+                }
+            }),
+        ),
+        entry(Direction::Sent, json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })),
+        entry(Direction::Sent, json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/read" })),
+        entry(
+            Direction::Received,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "result": { "contents": [{ "uri": answered, "mimeType": "text/plain", "text": "hunter2" }] }
+            }),
+        ),
+    ]);
+    let client = McpClient::builder(Arc::new(server))
+        .roots(Roots::new([&root]), RootsEnforcement::Strict)
+        .build();
+    client.initialize().await.unwrap();
+
+    let err = client.read_resource(&asked).await.unwrap_err();
+    assert!(matches!(err, McpError::Denied(_)));
+}
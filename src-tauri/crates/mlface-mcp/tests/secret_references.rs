@@ -0,0 +1,49 @@
+//! `{{secret:NAME}}` references are resolved from the secret store at launch.
+
+use mlface_mcp::secrets::{MemorySecretStore, SecretStore};
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn server(env: &[(&str, &str)]) -> McpServerConfig {
+    McpServerConfig {
+        name: "search".to_string(),
+        command: "node".to_string(),
+        args: vec!["server.js".to_string()],
+        env: env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn references_are_resolved() {
+    let store = MemorySecretStore::default();
+    store.set("brave", "s3cret").unwrap();
+
+    let config = server(&[("BRAVE_API_KEY", "{{secret:brave}}"), ("HEADER", "Bearer {{secret:brave}}")]);
+    let expanded = config.expanded(None, Some(&store)).unwrap();
+    assert_eq!(expanded.env["BRAVE_API_KEY"], "s3cret");
+    assert_eq!(expanded.env["HEADER"], "Bearer s3cret");
+
+    // The stored config keeps the reference
+    assert_eq!(config.env["BRAVE_API_KEY"], "{{secret:brave}}");
+}
+
+#[test]
+fn missing_secrets_are_errors() {
+    let store = MemorySecretStore::default();
+    let config = server(&[("BRAVE_API_KEY", "{{secret:brave}}")]);
+
+    assert!(config.expanded(None, Some(&store)).is_err());
+    assert!(config.expanded(None, None).is_err());
+}
+
+#[tokio::test]
+async fn launch_fails_without_the_secret() {
+    let manager = McpServerManager::new();
+    manager.set_secret_store(Some(Arc::new(MemorySecretStore::default())));
+    manager.register_server(server(&[("BRAVE_API_KEY", "{{secret:brave}}")])).await.unwrap();
+
+    let error = manager.start_server("search").await.unwrap_err();
+    assert!(error.to_string().contains("Secret brave is not set"));
+}
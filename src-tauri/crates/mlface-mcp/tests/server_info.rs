@@ -0,0 +1,33 @@
+//! What a server reports during initialize is kept for the UI.
+
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+
+const SCRIPTED_SERVER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/scripted-server.mjs");
+
+#[tokio::test]
+async fn server_info_outlives_the_connection() {
+    let manager = McpServerManager::new();
+    manager
+        .register_server(McpServerConfig {
+            name: "scripted".to_string(),
+            command: "node".to_string(),
+            args: vec![SCRIPTED_SERVER.to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(manager.server_info("scripted").await.unwrap().is_none());
+    assert!(manager.server_info("missing").await.is_err());
+
+    manager.get_client("scripted").await.unwrap();
+    manager.stop_server("scripted").await.unwrap();
+
+    let info = manager.server_info("scripted").await.unwrap().unwrap();
+    assert_eq!(info.name, "scripted-server");
+    assert_eq!(info.instructions.as_deref(), Some("Call echo to hear yourself."));
+    assert_eq!(info.capabilities.tools.unwrap().list_changed, Some(true));
+    assert!(info.capabilities.sampling.is_none());
+
+    manager.unregister_server("scripted").await.unwrap();
+    assert!(manager.server_info("scripted").await.is_err());
+}
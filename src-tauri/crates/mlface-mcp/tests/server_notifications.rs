@@ -0,0 +1,38 @@
+//! Notifications of every server reach the manager's subscribers, tagged with
+//! the server they came from.
+
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+use serde_json::json;
+use std::time::Duration;
+
+const SCRIPTED_SERVER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/scripted-server.mjs");
+
+#[tokio::test]
+async fn notifications_are_tagged_with_their_server() {
+    let manager = McpServerManager::new();
+    manager
+        .register_server(McpServerConfig {
+            name: "chatty".to_string(),
+            command: "node".to_string(),
+            args: vec![SCRIPTED_SERVER.to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let mut notifications = manager.subscribe_notifications();
+
+    // The fixture sends ten log messages before answering a tool call
+    let client = manager.get_client("chatty").await.unwrap();
+    client.call_tool("echo", Some(json!({ "text": "hi" }))).await.unwrap();
+
+    for _ in 0..10 {
+        let received = tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.server, "chatty");
+        assert_eq!(received.notification.method, "notifications/message");
+    }
+
+    manager.stop_server("chatty").await.unwrap();
+}
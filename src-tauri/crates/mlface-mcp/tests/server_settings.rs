@@ -0,0 +1,60 @@
+//! Per-server settings are applied when the server is launched and supervised.
+
+use mlface_mcp::server::{LogVerbosity, McpServerConfig, McpServerManager, ServerSettings};
+use std::sync::Arc;
+use std::time::Duration;
+
+const SCRIPTED_SERVER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/scripted-server.mjs");
+
+fn server(name: &str, settings: ServerSettings) -> McpServerConfig {
+    McpServerConfig {
+        name: name.to_string(),
+        command: "node".to_string(),
+        args: vec![SCRIPTED_SERVER.to_string()],
+        settings,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn quiet_servers_are_not_logged() {
+    let manager = McpServerManager::new();
+    manager.register_server(server("normal", ServerSettings::default())).await.unwrap();
+    manager
+        .register_server(server("quiet", ServerSettings {
+            log_level: LogVerbosity::Quiet,
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+
+    manager.start_server("normal").await.unwrap();
+    manager.start_server("quiet").await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let logged = |lines: Vec<mlface_mcp::logs::LogLine>| lines.iter().any(|line| line.line.contains("ready"));
+    assert!(logged(manager.server_logs("normal", None).await.unwrap()));
+    assert!(!logged(manager.server_logs("quiet", None).await.unwrap()));
+
+    manager.stop_server("normal").await.unwrap();
+    manager.stop_server("quiet").await.unwrap();
+}
+
+#[tokio::test]
+async fn healthy_servers_pass_their_health_check() {
+    let manager = McpServerManager::new();
+    manager
+        .register_server(server("checked", ServerSettings {
+            health_check_interval_secs: Some(1),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+
+    let client = manager.get_client("checked").await.unwrap();
+    client.ping().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+
+    assert!(Arc::ptr_eq(&client, &manager.get_client("checked").await.unwrap()));
+    manager.stop_server("checked").await.unwrap();
+}
@@ -0,0 +1,74 @@
+//! A recorded session plays back without the server.
+
+use mlface_mcp::client::McpClient;
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+use mlface_mcp::session::{self, ReplayTransport};
+use mlface_mcp::traffic::Direction;
+use mlface_mcp::types::McpError;
+use serde_json::json;
+use std::sync::Arc;
+
+const SCRIPTED_SERVER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/scripted-server.mjs");
+
+fn scripted_server() -> McpServerConfig {
+    McpServerConfig {
+        name: "scripted".to_string(),
+        command: "node".to_string(),
+        args: vec![SCRIPTED_SERVER.to_string()],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn a_recording_replays_the_servers_answers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("scripted.jsonl");
+    let manager = McpServerManager::new();
+    manager.register_server(scripted_server()).await.unwrap();
+    manager.start_session_recording("scripted", &path).await.unwrap();
+    assert_eq!(manager.session_recording("scripted"), Some(path.clone()));
+
+    let client = manager.get_client("scripted").await.unwrap();
+    let echoed = client.call_tool("echo", Some(json!({ "text": "hi" }))).await.unwrap();
+    let missing = client.read_resource("file:///nowhere").await.unwrap_err();
+    assert_eq!(manager.stop_session_recording("scripted"), Some(path.clone()));
+
+    let recorded = session::load(&path).unwrap();
+    assert_eq!(recorded[0].direction, Direction::Sent);
+    assert_eq!(recorded[0].message["method"], "initialize");
+    // Ten notifications come before the tool call's result
+    assert!(recorded.len() > 15);
+
+    let replay = Arc::new(ReplayTransport::open(&path).unwrap());
+    let replayed = McpClient::builder(replay.clone()).build();
+    assert_eq!(replayed.initialize().await.unwrap().name, "scripted-server");
+    let echoed_again = replayed.call_tool("echo", Some(json!({ "text": "hi" }))).await.unwrap();
+    assert_eq!(
+        serde_json::to_value(&echoed_again.content).unwrap(),
+        serde_json::to_value(&echoed.content).unwrap(),
+    );
+    let missing_again = replayed.read_resource("file:///nowhere").await.unwrap_err();
+    assert_eq!(missing_again.to_string(), missing.to_string());
+    assert_eq!(replay.remaining(), 0);
+
+    // The server never said anything about prompts
+    let diverged = replayed.list_prompts().await.unwrap_err();
+    assert!(matches!(diverged, McpError::ProtocolError(_)));
+}
+
+#[tokio::test]
+async fn recording_a_running_server_restarts_it() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("scripted.jsonl");
+    let manager = McpServerManager::new();
+    manager.register_server(scripted_server()).await.unwrap();
+    manager.get_client("scripted").await.unwrap();
+
+    manager.start_session_recording("scripted", &path).await.unwrap();
+    manager.stop_session_recording("scripted");
+
+    let recorded = session::load(&path).unwrap();
+    let methods: Vec<_> = recorded.iter().filter_map(|entry| entry.message["method"].as_str()).collect();
+    assert_eq!(methods, ["initialize", "initialized"]);
+    assert!(manager.stop_session_recording("scripted").is_none());
+}
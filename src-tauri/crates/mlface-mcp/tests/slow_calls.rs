@@ -0,0 +1,54 @@
+//! Requests waiting longer than a server's slow-call threshold are reported.
+
+use mlface_mcp::server::{McpServerConfig, McpServerManager, ServerSettings};
+use serde_json::json;
+
+// Answers every request, but tool calls only after 300 ms
+const SLOW_SERVER: &str = r#"
+const rl = require("readline").createInterface({ input: process.stdin });
+rl.on("line", (line) => {
+  const message = JSON.parse(line);
+  if (message.id === undefined) return;
+  const result = message.method === "initialize"
+    ? { protocol_version: "0.1.0", name: "slow", version: "1.0.0", capabilities: {} }
+    : { content: [{ type: "text", text: "done" }] };
+  const reply = () => process.stdout.write(JSON.stringify({ jsonrpc: "2.0", id: message.id, result }) + "\n");
+  if (message.method === "tools/call") setTimeout(reply, 300); else reply();
+});
+"#;
+
+#[tokio::test]
+async fn a_slow_request_is_reported_when_it_crosses_the_threshold_and_when_it_finishes() {
+    let manager = McpServerManager::new();
+    manager
+        .register_server(McpServerConfig {
+            name: "sluggish".to_string(),
+            command: "node".to_string(),
+            args: vec!["-e".to_string(), SLOW_SERVER.to_string()],
+            settings: ServerSettings {
+                slow_call_ms: Some(100),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    // Starting node can take longer than the threshold, so only listen once
+    // the server is up
+    let client = manager.get_client("sluggish").await.unwrap();
+    let mut slow_calls = manager.subscribe_slow_calls();
+
+    client.call_tool("wait", Some(json!({}))).await.unwrap();
+
+    let waiting = slow_calls.recv().await.unwrap();
+    assert_eq!(waiting.server, "sluggish");
+    assert_eq!(waiting.method, "tools/call");
+    assert!(!waiting.finished);
+    assert!(waiting.elapsed_ms >= 100);
+
+    let finished = slow_calls.recv().await.unwrap();
+    assert!(finished.finished);
+    assert!(finished.elapsed_ms >= 300);
+    assert!(slow_calls.try_recv().is_err());
+}
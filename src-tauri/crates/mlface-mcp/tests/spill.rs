@@ -0,0 +1,45 @@
+//! Large binary content is written to files instead of being passed inline.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use mlface_mcp::spill::{SpillDir, SPILLED_PROPERTY};
+use mlface_mcp::types::Content;
+
+#[tokio::test]
+async fn large_images_are_replaced_by_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let spill_dir = SpillDir::open(dir.path()).unwrap().with_threshold(16);
+    let image = vec![7u8; 64];
+    let mut content = vec![
+        Content::Text { text: "a".repeat(100) },
+        Content::Image { mime_type: "image/png".to_string(), data: STANDARD.encode(b"tiny") },
+        Content::Image { mime_type: "image/png".to_string(), data: STANDARD.encode(&image) },
+        Content::Image { mime_type: "image/png".to_string(), data: STANDARD.encode(&image) },
+    ];
+
+    assert_eq!(spill_dir.spill(&mut content).await.unwrap(), 2);
+
+    assert!(matches!(&content[0], Content::Text { .. }));
+    assert!(matches!(&content[1], Content::Image { .. }));
+    let Content::EmbeddedResource { uri, properties: Some(properties) } = &content[2] else {
+        panic!("Expected the image to be spilled, got {:?}", content[2]);
+    };
+    assert_eq!(properties[SPILLED_PROPERTY], "true");
+    assert_eq!(properties["mime_type"], "image/png");
+    assert_eq!(properties["size"], "64");
+    assert!(properties["path"].ends_with(".png"));
+    assert!(uri.starts_with("file:///"));
+    assert_eq!(std::fs::read(&properties["path"]).unwrap(), image);
+
+    // The same image is stored once
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn nothing_is_spilled_without_a_directory() {
+    let data = STANDARD.encode(vec![0u8; 1024 * 1024]);
+    let mut content = vec![Content::Image { mime_type: "image/png".to_string(), data }];
+
+    assert_eq!(SpillDir::default().spill(&mut content).await.unwrap(), 0);
+    assert!(matches!(&content[0], Content::Image { .. }));
+}
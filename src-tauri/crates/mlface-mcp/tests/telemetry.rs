@@ -0,0 +1,83 @@
+//! Events are written as JSON lines with their spans, and the files rotate.
+
+use mlface_mcp::telemetry::{self, JsonLines, LogModule, RotatingFile};
+use tracing::Level;
+
+#[test]
+fn events_are_written_with_their_spans() {
+    let dir = tempfile::tempdir().unwrap();
+    let subscriber = JsonLines::new(RotatingFile::open(dir.path(), 1024 * 1024, 3).unwrap(), Level::INFO);
+
+    tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("request", server = "files", trace = "0000002a");
+        span.in_scope(|| tracing::warn!(attempt = 2, "Request failed: {}", "timeout"));
+        tracing::debug!("below the level");
+        tracing::info!("outside any span");
+    });
+
+    let entries = telemetry::recent(dir.path(), 10).unwrap();
+    assert_eq!(entries.len(), 2);
+
+    let failed = &entries[0];
+    assert_eq!(failed["level"], "WARN");
+    assert_eq!(failed["message"], "Request failed: timeout");
+    assert_eq!(failed["fields"]["attempt"], 2);
+    assert_eq!(failed["spans"][0]["name"], "request");
+    assert_eq!(failed["spans"][0]["server"], "files");
+    assert_eq!(failed["spans"][0]["trace"], "0000002a");
+
+    assert_eq!(entries[1]["message"], "outside any span");
+    assert!(entries[1].get("spans").is_none());
+}
+
+#[test]
+fn old_files_are_rotated_out() {
+    let dir = tempfile::tempdir().unwrap();
+    let subscriber = JsonLines::new(RotatingFile::open(dir.path(), 512, 3).unwrap(), Level::INFO);
+
+    tracing::subscriber::with_default(subscriber, || {
+        for line in 0..100 {
+            tracing::info!(line, "a line long enough to fill the file quickly");
+        }
+    });
+
+    assert_eq!(telemetry::log_files(dir.path()).len(), 3);
+    let entries = telemetry::recent(dir.path(), 5).unwrap();
+    let lines: Vec<_> = entries.iter().map(|entry| entry["fields"]["line"].as_u64().unwrap()).collect();
+    assert_eq!(lines, vec![95, 96, 97, 98, 99]);
+
+    // A bug report gets a copy of every file
+    let report = tempfile::tempdir().unwrap();
+    let folder = telemetry::pack(&[dir.path().to_path_buf()], report.path()).unwrap();
+    let copied = std::fs::read_dir(folder.join(dir.path().file_name().unwrap())).unwrap().count();
+    assert_eq!(copied, 3);
+}
+
+#[test]
+fn levels_change_while_recording() {
+    let dir = tempfile::tempdir().unwrap();
+    let subscriber = JsonLines::new(RotatingFile::open(dir.path(), 1024 * 1024, 3).unwrap(), Level::INFO);
+    let levels = subscriber.levels();
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::debug!(target: "mlface_mcp::transport", "hidden");
+
+        levels.set_module(LogModule::Transport, Some(Level::TRACE));
+        tracing::trace!(target: "mlface_mcp::transport::stdio", "transport trace");
+        tracing::debug!(target: "mlface_mcp::client", "client debug");
+        tracing::debug!(target: "mlface_mcp::transports", "not the transport module");
+
+        levels.set_global(Level::WARN);
+        levels.set_module(LogModule::Transport, None);
+        tracing::info!(target: "mlface_mcp::transport", "quiet again");
+        tracing::warn!(target: "mlface_mcp::server", "manager warning");
+    });
+
+    let entries = telemetry::recent(dir.path(), 10).unwrap();
+    let messages: Vec<_> = entries.iter().map(|entry| entry["message"].as_str().unwrap()).collect();
+    assert_eq!(messages, ["transport trace", "manager warning"]);
+
+    let report = levels.report();
+    assert_eq!(report.global, "warn");
+    assert!(report.modules.is_empty());
+}
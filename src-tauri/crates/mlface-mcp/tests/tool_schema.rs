@@ -0,0 +1,120 @@
+//! MCP tools are renamed and trimmed to what function calling accepts.
+
+use mlface_mcp::tool_schema::{sanitize_name, trim_schema, ToolFormat, ToolSet, MAX_NAME_LEN, MAX_SCHEMA_BYTES, MAX_TOOLS};
+use mlface_mcp::types::Tool;
+use serde_json::json;
+
+fn tool(name: &str, input_schema: serde_json::Value) -> Tool {
+    Tool { name: name.to_string(), description: Some(format!("Does {}", name)), input_schema }
+}
+
+#[test]
+fn names_are_qualified_by_server_and_sanitized() {
+    let mut tools = ToolSet::new();
+    assert_eq!(tools.add("files", tool("read_file", json!({}))), Some("files__read_file"));
+    assert_eq!(tools.add("my server", tool("fetch.url", json!({}))), Some("my_server__fetch_url"));
+    
+    let called = tools.get("my_server__fetch_url").unwrap();
+    assert_eq!(called.server, "my server");
+    assert_eq!(called.tool.name, "fetch.url");
+    assert!(tools.get("fetch.url").is_none());
+}
+
+#[test]
+fn names_that_collide_or_run_long_stay_unique() {
+    let mut tools = ToolSet::new();
+    let first = tools.add("a", tool("b.c", json!({}))).unwrap().to_string();
+    let second = tools.add("a", tool("b_c", json!({}))).unwrap().to_string();
+    assert_eq!(first, "a__b_c");
+    assert_ne!(first, second);
+    assert!(second.starts_with("a__b_c_"));
+    
+    let long = "x".repeat(100);
+    let one = sanitize_name(&format!("{}1", long));
+    let other = sanitize_name(&format!("{}2", long));
+    assert!(one.len() <= MAX_NAME_LEN && other.len() <= MAX_NAME_LEN);
+    assert_ne!(one, other);
+    assert!(sanitize_name("é").chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+}
+
+#[test]
+fn names_with_the_separator_in_them_stay_apart() {
+    let mut tools = ToolSet::new();
+    let first = tools.add("a__b", tool("c", json!({}))).unwrap().to_string();
+    let second = tools.add("a", tool("b__c", json!({}))).unwrap().to_string();
+    assert_eq!(first, "a__b__c");
+    assert_ne!(first, second);
+    assert_eq!(tools.get(&first).unwrap().server, "a__b");
+    assert_eq!(tools.get(&second).unwrap().server, "a");
+    assert_eq!(tools.get(&second).unwrap().tool.name, "b__c");
+}
+
+#[test]
+fn sets_stop_growing_at_the_limit() {
+    let mut tools = ToolSet::new();
+    for i in 0..MAX_TOOLS {
+        assert!(tools.add("s", tool(&format!("t{}", i), json!({}))).is_some());
+    }
+    assert_eq!(tools.add("s", tool("one_more", json!({}))), None);
+    assert_eq!(tools.len(), MAX_TOOLS);
+}
+
+#[test]
+fn definitions_follow_each_format() {
+    let mut tools = ToolSet::new();
+    let schema = json!({ "type": "object", "properties": { "path": { "type": "string" } }, "required": ["path"] });
+    tools.add("files", tool("read", schema.clone()));
+    
+    assert_eq!(tools.definitions(ToolFormat::OpenAi), vec![json!({
+        "type": "function",
+        "function": { "name": "files__read", "description": "Does read", "parameters": schema },
+    })]);
+    assert_eq!(tools.definitions(ToolFormat::Anthropic), vec![json!({
+        "name": "files__read",
+        "description": "Does read",
+        "input_schema": schema,
+    })]);
+}
+
+#[test]
+fn schemas_lose_rejected_keywords_but_not_properties_named_like_them() {
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "properties": {
+            "examples": { "type": "array", "items": { "type": "string", "examples": ["a"] } },
+            "mode": { "anyOf": [{ "type": "string", "$comment": "legacy" }, { "type": "null" }] },
+        },
+        "examples": [{ "mode": "fast" }],
+    });
+    assert_eq!(trim_schema(&schema), json!({
+        "type": "object",
+        "properties": {
+            "examples": { "type": "array", "items": { "type": "string" } },
+            "mode": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+        },
+    }));
+}
+
+#[test]
+fn schemas_without_type_or_properties_become_object_schemas() {
+    assert_eq!(trim_schema(&json!(null)), json!({ "type": "object", "properties": {} }));
+    assert_eq!(trim_schema(&json!({ "type": "object" })), json!({ "type": "object", "properties": {} }));
+}
+
+#[test]
+fn large_schemas_lose_descriptions_first() {
+    let properties: serde_json::Map<String, serde_json::Value> = (0..200)
+        .map(|i| (format!("field{}", i), json!({ "type": "string", "description": "d".repeat(100) })))
+        .collect();
+    let schema = json!({ "type": "object", "properties": properties });
+    
+    let trimmed = trim_schema(&schema);
+    assert!(serde_json::to_vec(&trimmed).unwrap().len() <= MAX_SCHEMA_BYTES);
+    assert_eq!(trimmed["properties"]["field0"], json!({ "type": "string" }));
+    
+    let properties: serde_json::Map<String, serde_json::Value> =
+        (0..2000).map(|i| (format!("field{}", i), json!({ "type": "string" }))).collect();
+    let huge = trim_schema(&json!({ "type": "object", "properties": properties }));
+    assert_eq!(huge, json!({ "type": "object", "properties": {} }));
+}
@@ -0,0 +1,95 @@
+//! Every message exchanged with a server is recorded, and responses are paired
+//! with their requests.
+
+use mlface_mcp::server::{McpServerConfig, McpServerManager};
+use mlface_mcp::traffic::{Direction, MessageKind, TrafficFilter, TrafficLog};
+use mlface_mcp::types::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use serde_json::json;
+
+const SCRIPTED_SERVER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/fixtures/scripted-server.mjs");
+
+fn request(id: u64, method: &str) -> JsonRpcMessage {
+    JsonRpcMessage::Request(JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: json!(id),
+        method: method.to_string(),
+        params: None,
+    })
+}
+
+fn response(id: &str) -> JsonRpcMessage {
+    JsonRpcMessage::Response(JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id: json!(id),
+        result: Some(json!({})),
+        error: None,
+    })
+}
+
+#[test]
+fn the_log_is_bounded_and_filterable() {
+    let log = TrafficLog::new(3);
+    log.record("files", Direction::Sent, &request(1, "tools/list"));
+    log.record("search", Direction::Sent, &request(1, "tools/call"));
+    log.record("files", Direction::Received, &response("1"));
+    log.record(
+        "files",
+        Direction::Received,
+        &JsonRpcMessage::Notification(JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/tools/list_changed".to_string(),
+            params: None,
+        }),
+    );
+
+    // The first request fell out, but its response still knows what it answered
+    let all = log.query(None, &TrafficFilter::default(), None);
+    assert_eq!(all.iter().map(|entry| entry.seq).collect::<Vec<_>>(), vec![2, 3, 4]);
+    assert_eq!(all[1].method.as_deref(), Some("tools/list"));
+    assert!(all[1].latency_ms.is_some());
+
+    let files = log.query(Some("files"), &TrafficFilter::default(), Some(1));
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].kind, MessageKind::Notification);
+
+    let filter = TrafficFilter { method: Some("tools/".into()), direction: Some(Direction::Sent), ..Default::default() };
+    let sent = log.query(None, &filter, None);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].server, "search");
+}
+
+#[tokio::test]
+async fn a_servers_traffic_is_recorded() {
+    let manager = McpServerManager::new();
+    manager
+        .register_server(McpServerConfig {
+            name: "chatty".to_string(),
+            command: "node".to_string(),
+            args: vec![SCRIPTED_SERVER.to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let mut live = manager.subscribe_traffic();
+
+    let client = manager.get_client("chatty").await.unwrap();
+    client.call_tool("echo", Some(json!({ "text": "hi" }))).await.unwrap();
+
+    let first = live.recv().await.unwrap();
+    assert_eq!(first.direction, Direction::Sent);
+    assert_eq!(first.method.as_deref(), Some("initialize"));
+
+    let calls = TrafficFilter { method: Some("tools/call".into()), ..Default::default() };
+    let traffic = manager.traffic(Some("chatty"), &calls, None);
+    assert_eq!(traffic.len(), 2);
+    assert_eq!(traffic[0].direction, Direction::Sent);
+    assert_eq!(traffic[1].direction, Direction::Received);
+    assert_eq!(traffic[1].kind, MessageKind::Response);
+    assert!(traffic[1].latency_ms.is_some());
+
+    let notifications = TrafficFilter { kind: Some(MessageKind::Notification), ..Default::default() };
+    assert!(manager.traffic(Some("chatty"), &notifications, None).len() >= 10);
+
+    manager.unregister_server("chatty").await.unwrap();
+    assert!(manager.traffic(Some("chatty"), &TrafficFilter::default(), None).is_empty());
+}
@@ -0,0 +1,68 @@
+//! Background mode: keep servers alive with the window closed.
+//!
+//! When enabled, closing the main window hides it instead of exiting, so the
+//! server manager, its clients and scheduled work such as backups and the
+//! reaper keep running. The window comes back from the tray, from the dock
+//! on macOS, or by launching the app again, and shows the servers as they
+//! are rather than starting them over. The setting applies immediately.
+
+use crate::paths::config_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, Manager, Runtime, Window, WindowEvent};
+
+const SETTINGS_FILE: &str = "background.json";
+const MAIN_WINDOW: &str = "main";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct BackgroundSettings {
+    enabled: bool,
+}
+
+pub fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    config_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+        .and_then(|content| serde_json::from_str::<BackgroundSettings>(&content).ok())
+        .unwrap_or_default()
+        .enabled
+}
+
+fn save<R: Runtime>(app: &AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let dir = config_dir(app)?;
+    let json = serde_json::to_string_pretty(&BackgroundSettings { enabled }).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    fs::write(dir.join(SETTINGS_FILE), json).map_err(|e| e.to_string())
+}
+
+/// Bring the main window back, whether it was hidden, minimized or behind others
+pub fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Hide the main window instead of closing it while background mode is on
+pub fn on_window_event<R: Runtime>(window: &Window<R>, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        if window.label() == MAIN_WINDOW && is_enabled(window.app_handle()) {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+}
+
+/// Command to get whether closing the window keeps the app running
+#[tauri::command]
+pub async fn app_get_background_mode<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    Ok(is_enabled(&app))
+}
+
+/// Command to choose whether closing the window keeps the app running
+#[tauri::command]
+pub async fn app_set_background_mode<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    save(&app, enabled)
+}
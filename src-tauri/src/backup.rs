@@ -0,0 +1,194 @@
+//! Scheduled backups of the app configuration.
+//!
+//! A backup is a copy of the config directory (`<data>/config`) in a
+//! timestamped folder under the destination directory. Chats, prompts and
+//! automations are kept by the frontend and are not part of it yet.
+
+use crate::paths::{config_dir, paths};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Runtime};
+
+const BACKUP_PREFIX: &str = "mlface-backup-";
+const SETTINGS_FILE: &str = "backup.json";
+
+/// How often the scheduler checks whether a backup is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub interval_hours: u64,
+    /// Defaults to `<data>/backups`
+    pub destination: Option<PathBuf>,
+    /// Number of backups to keep; older ones are deleted
+    pub keep: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: 24,
+            destination: None,
+            keep: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub name: String,
+    pub path: PathBuf,
+    /// Seconds since the Unix epoch
+    pub created: u64,
+}
+
+fn destination<R: Runtime>(app: &AppHandle<R>, settings: &BackupSettings) -> Result<PathBuf, String> {
+    match &settings.destination {
+        Some(dir) => Ok(dir.clone()),
+        None => paths(app).map(|paths| paths.data_dir.join("backups")),
+    }
+}
+
+pub fn load_settings<R: Runtime>(app: &AppHandle<R>) -> BackupSettings {
+    config_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings<R: Runtime>(app: &AppHandle<R>, settings: &BackupSettings) -> Result<(), String> {
+    let dir = config_dir(app)?;
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    fs::write(dir.join(SETTINGS_FILE), json).map_err(|e| e.to_string())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Backups in `dir`, newest first
+fn list_in(dir: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let created = name.strip_prefix(BACKUP_PREFIX)?.parse().ok()?;
+            Some(BackupInfo { path: entry.path(), name, created })
+        })
+        .collect();
+    
+    backups.sort_by_key(|backup| Reverse(backup.created));
+    backups
+}
+
+/// Snapshot the config directory and prune backups beyond `keep`
+pub fn create_backup<R: Runtime>(app: &AppHandle<R>, settings: &BackupSettings) -> Result<BackupInfo, String> {
+    let dest = destination(app, settings)?;
+    let created = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    
+    let name = format!("{}{}", BACKUP_PREFIX, created);
+    let path = dest.join(&name);
+    copy_dir(&config_dir(app)?, &path).map_err(|e| e.to_string())?;
+    
+    for old in list_in(&dest).into_iter().skip(settings.keep.max(1)) {
+        if let Err(e) = fs::remove_dir_all(&old.path) {
+            log::warn!("Failed to prune backup {}: {}", old.path.display(), e);
+        }
+    }
+    
+    Ok(BackupInfo { name, path, created })
+}
+
+/// Run scheduled backups for the lifetime of the app. Settings are re-read on
+/// every check, so changes apply without a restart.
+pub fn spawn_scheduler<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = load_settings(&app);
+            if settings.enabled {
+                let newest = destination(&app, &settings)
+                    .map(|dest| list_in(&dest).first().map(|b| b.created).unwrap_or(0))
+                    .unwrap_or(0);
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                
+                if now.saturating_sub(newest) >= settings.interval_hours.max(1) * 3600 {
+                    if let Err(e) = create_backup(&app, &settings) {
+                        log::warn!("Scheduled backup failed: {}", e);
+                    }
+                }
+            }
+            
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Command to get the backup settings
+#[tauri::command]
+pub async fn backup_get_settings<R: Runtime>(app: AppHandle<R>) -> Result<BackupSettings, String> {
+    Ok(load_settings(&app))
+}
+
+/// Command to change the backup settings
+#[tauri::command]
+pub async fn backup_set_settings<R: Runtime>(app: AppHandle<R>, settings: BackupSettings) -> Result<(), String> {
+    save_settings(&app, &settings)
+}
+
+/// Command to take a backup right away
+#[tauri::command]
+pub async fn backup_now<R: Runtime>(app: AppHandle<R>) -> Result<BackupInfo, String> {
+    create_backup(&app, &load_settings(&app))
+}
+
+/// Command to list existing backups, newest first
+#[tauri::command]
+pub async fn backup_list<R: Runtime>(app: AppHandle<R>) -> Result<Vec<BackupInfo>, String> {
+    let settings = load_settings(&app);
+    Ok(list_in(&destination(&app, &settings)?))
+}
+
+/// Command to restore a backup over the current config and reload the servers
+#[tauri::command]
+pub async fn backup_restore<R: Runtime>(app: AppHandle<R>, name: String) -> Result<(), String> {
+    let settings = load_settings(&app);
+    let backup = list_in(&destination(&app, &settings)?)
+        .into_iter()
+        .find(|b| b.name == name)
+        .ok_or_else(|| format!("Backup {} not found", name))?;
+    
+    copy_dir(&backup.path, &config_dir(&app)?).map_err(|e| e.to_string())?;
+    
+    crate::mcp::manager(&app).load_default_config()
+        .await
+        .map_err(|e| e.to_string())
+}
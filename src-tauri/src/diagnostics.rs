@@ -0,0 +1,126 @@
+//! The app's own logs, for troubleshooting and bug reports.
+//!
+//! `init` sends the MCP stack's `tracing` output, and everything logged
+//! through `log`, to rotating JSON-lines files in `<data>/logs`. A bug report
+//! packs those together with the servers' output logs.
+//!
+//! Built with the `otlp` feature, spans and request metrics are also sent to
+//! the OpenTelemetry collector named by `OTEL_EXPORTER_OTLP_ENDPOINT`.
+//!
+//! `mcp_set_debug_mode` changes how verbose the log is while the app runs,
+//! for all of it or for the transports, client or manager alone.
+
+use crate::mcp::telemetry::{self, Level, LogLevels, LogLevelsReport, LogModule, SpanSink, LOG_DIR};
+use crate::paths::paths;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Entries returned by `app_get_logs` unless the caller asks otherwise
+const DEFAULT_LOG_ENTRIES: usize = 500;
+
+/// Start writing the app log. `MLFACE_LOG=debug` or `trace` makes it more verbose.
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let Ok(paths) = paths(app) else {
+        return;
+    };
+    let level = match std::env::var("MLFACE_LOG").as_deref() {
+        Ok("trace") => Level::TRACE,
+        Ok("debug") => Level::DEBUG,
+        _ => Level::INFO,
+    };
+    
+    match telemetry::init(&paths.data_dir.join(LOG_DIR), level, exporter(app)) {
+        Ok(levels) => {
+            app.manage(levels);
+        }
+        Err(e) => eprintln!("Failed to open the app log: {}", e),
+    }
+}
+
+/// Start exporting to the configured collector, if any
+#[cfg(feature = "otlp")]
+fn exporter<R: Runtime>(app: &AppHandle<R>) -> Option<Arc<dyn SpanSink>> {
+    use crate::mcp::otlp::{OtlpConfig, OtlpExporter};
+    use crate::mcp::server::McpServerManager;
+    
+    let exporter = Arc::new(OtlpExporter::new(OtlpConfig::from_env()?));
+    let manager = app.state::<Arc<McpServerManager>>().inner().clone();
+    tauri::async_runtime::spawn(exporter.clone().run(manager));
+    Some(exporter)
+}
+
+#[cfg(not(feature = "otlp"))]
+fn exporter<R: Runtime>(_app: &AppHandle<R>) -> Option<Arc<dyn SpanSink>> {
+    None
+}
+
+fn log_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    Ok(paths(app)?.data_dir.join(LOG_DIR))
+}
+
+fn log_levels<R: Runtime>(app: &AppHandle<R>) -> Result<Arc<LogLevels>, String> {
+    app.try_state::<Arc<LogLevels>>()
+        .map(|levels| levels.inner().clone())
+        .ok_or_else(|| "The app log is not open".to_string())
+}
+
+/// Command to get the last `limit` entries of the app log, oldest first
+#[tauri::command]
+pub async fn app_get_logs<R: Runtime>(app: AppHandle<R>, limit: Option<usize>) -> Result<Vec<Value>, String> {
+    let dir = log_dir(&app)?;
+    tauri::async_runtime::spawn_blocking(move || telemetry::recent(&dir, limit.unwrap_or(DEFAULT_LOG_ENTRIES)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Command to set how verbose the app log is: `error`, `warn`, `info`, `debug`
+/// or `trace`. With `module`, only for the transports, the client or the
+/// manager, and no `level` makes that module follow the global level again.
+/// Without, no `level` goes back to `info`. Takes effect right away.
+#[tauri::command]
+pub async fn mcp_set_debug_mode<R: Runtime>(
+    app: AppHandle<R>,
+    level: Option<String>,
+    module: Option<LogModule>,
+) -> Result<LogLevelsReport, String> {
+    let levels = log_levels(&app)?;
+    let level = level
+        .map(|level| level.parse::<Level>().map_err(|_| format!("Unknown log level {}", level)))
+        .transpose()?;
+    
+    match module {
+        Some(module) => levels.set_module(module, level),
+        None => levels.set_global(level.unwrap_or(Level::INFO)),
+    }
+    log::info!("Log levels changed to {:?}", levels.report());
+    Ok(levels.report())
+}
+
+/// Command to get the levels the app log records at
+#[tauri::command]
+pub async fn mcp_get_debug_mode<R: Runtime>(app: AppHandle<R>) -> Result<LogLevelsReport, String> {
+    Ok(log_levels(&app)?.report())
+}
+
+/// Command to copy the app log and the server logs into a new folder under
+/// `destination`, or the downloads folder, and return its path
+#[tauri::command]
+pub async fn app_pack_logs<R: Runtime>(app: AppHandle<R>, destination: Option<String>) -> Result<String, String> {
+    let mut sources = vec![log_dir(&app)?];
+    if let Ok(dir) = app.path().app_log_dir() {
+        sources.push(dir.join("mcp"));
+    }
+    let destination = match destination {
+        Some(destination) => PathBuf::from(destination),
+        None => app.path().download_dir().map_err(|e| e.to_string())?,
+    };
+    
+    tauri::async_runtime::spawn_blocking(move || telemetry::pack(&sources, &destination))
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|folder| folder.to_string_lossy().into_owned())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,627 @@
+//! Event payloads emitted from the backend to the frontend.
+//!
+//! Every Tauri event is declared here as a typed payload so the TypeScript
+//! definitions in `src/bindings` are generated by ts-rs (run `cargo test` in
+//! `src-tauri`) instead of being kept in sync by hand. Emit through the helpers
+//! in this module rather than calling `Emitter::emit` directly.
+//!
+//! Most events describe shared state and go to every window. Output that
+//! belongs to one request, such as a streamed tool call or model tokens, goes
+//! only to the window that made it, so two chats never see each other's.
+
+use mlface_mcp::client::SlowCall;
+use mlface_mcp::crash::CrashRecord;
+use mlface_mcp::traffic::{Direction, MessageKind, TrafficEntry};
+use mlface_mcp::transport::{OutputStream, ProcessExit};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Runtime};
+use ts_rs::TS;
+
+/// An MCP server changed lifecycle state
+pub const MCP_SERVER_STATUS: &str = "mcp:server-status";
+/// An MCP server is being started or reconnected
+pub const MCP_SERVER_CONNECTING: &str = "mcp:server-connecting";
+/// An MCP server is connected and ready for requests
+pub const MCP_SERVER_STARTED: &str = "mcp:server-started";
+/// An MCP server was stopped
+pub const MCP_SERVER_STOPPED: &str = "mcp:server-stopped";
+/// An MCP server failed to start or exited unexpectedly
+pub const MCP_SERVER_CRASHED: &str = "mcp:server-crashed";
+/// A notification sent by an MCP server
+pub const MCP_NOTIFICATION: &str = "mcp:notification";
+/// A progress update for a long-running MCP operation
+pub const MCP_PROGRESS: &str = "mcp:progress";
+/// A log message sent by an MCP server
+pub const MCP_LOG_MESSAGE: &str = "mcp:log-message";
+/// An MCP server's tools, resources or prompts changed
+pub const MCP_LIST_CHANGED: &str = "mcp:list-changed";
+/// A resource an MCP server was subscribed to changed
+pub const MCP_RESOURCE_UPDATED: &str = "mcp:resource-updated";
+/// A line of output from a followed MCP server
+pub const MCP_SERVER_LOG: &str = "mcp:server-log";
+/// `mcp_servers.json` was edited outside the app and reloaded
+pub const MCP_CONFIG_RELOADED: &str = "mcp:config-reloaded";
+/// The saved MCP server config was loaded after launch
+pub const MCP_CONFIG_LOADED: &str = "mcp:config-loaded";
+/// A discovery started with `mcp_start_discovery` finished
+pub const MCP_DISCOVERY_FINISHED: &str = "mcp:discovery-finished";
+/// A piece of a streamed tool call
+pub const MCP_TOOL_STREAM: &str = "mcp:tool-stream";
+/// A piece of a resource read in chunks
+pub const MCP_RESOURCE_CHUNK: &str = "mcp:resource-chunk";
+/// A line of output from a package being installed
+pub const MCP_PACKAGE_INSTALL: &str = "mcp:package-install";
+/// A newer release was found for packages servers run
+pub const MCP_UPDATES_AVAILABLE: &str = "mcp:updates-available";
+/// An `mlface://add-server` link was opened and awaits confirmation
+pub const MCP_INSTALL_REQUEST: &str = "mcp:install-request";
+/// A tool call is waiting for the user's consent
+pub const MCP_TOOL_APPROVAL: &str = "mcp:tool-approval";
+/// A JSON-RPC message exchanged with an MCP server, while traffic is followed
+pub const MCP_TRAFFIC: &str = "mcp:traffic";
+/// A request to an MCP server is taking longer than its slow-call threshold
+pub const MCP_SLOW_CALL: &str = "mcp:slow-call";
+/// A token streamed from a model
+pub const LLM_TOKEN: &str = "llm:token";
+/// Tokens a streamed chat used, when the provider counted them
+pub const LLM_USAGE: &str = "llm:usage";
+/// How a chat measures up to the model's context window, before it is sent
+pub const LLM_CONTEXT: &str = "llm:context";
+/// A streamed chat ended
+pub const LLM_FINISH: &str = "llm:finish";
+/// A step of an agent run: a reply of the model, or a tool it called
+pub const LLM_AGENT_STEP: &str = "llm:agent-step";
+/// Progress of a model being pulled
+pub const LLM_PULL_PROGRESS: &str = "llm:pull-progress";
+
+/// Lifecycle state of an MCP server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum ServerState {
+    Connecting,
+    Running,
+    Stopped,
+    Crashed,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ServerStatusEvent {
+    pub server: String,
+    pub state: ServerState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub crash: Option<ServerCrash>,
+    /// Milliseconds since the Unix epoch
+    #[ts(type = "number")]
+    pub timestamp: u64,
+}
+
+/// Payload of the `mcp:server-*` lifecycle events
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ServerLifecycleEvent {
+    pub server: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub reason: Option<String>,
+    /// How the process ended, when a local server crashed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub crash: Option<ServerCrash>,
+    /// Milliseconds since the Unix epoch
+    #[ts(type = "number")]
+    pub timestamp: u64,
+}
+
+/// How a local server's process ended when it crashed, and the last lines it
+/// wrote to stderr
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ServerCrash {
+    pub server: String,
+    /// Milliseconds since the Unix epoch
+    #[ts(type = "number")]
+    pub timestamp: u64,
+    #[ts(type = "{ code?: number, signal?: number }")]
+    pub exit: ProcessExit,
+    pub stderr: Vec<String>,
+    /// Exit status and the stderr line most likely to explain it
+    pub summary: String,
+}
+
+impl From<CrashRecord> for ServerCrash {
+    fn from(crash: CrashRecord) -> Self {
+        Self {
+            summary: crash.summary(),
+            server: crash.server,
+            timestamp: crash.timestamp,
+            exit: crash.exit,
+            stderr: crash.stderr,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct NotificationEvent {
+    pub server: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub params: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ProgressEvent {
+    pub server: String,
+    pub progress_token: Value,
+    pub progress: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub total: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct LogMessageEvent {
+    pub server: String,
+    /// Syslog severity as sent by the server, e.g. `info` or `error`
+    pub level: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub logger: Option<String>,
+    pub data: Value,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ListChangedEvent {
+    pub server: String,
+    /// Which list changed: `tools`, `resources`, `prompts` or `roots`
+    pub list: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ResourceUpdatedEvent {
+    pub server: String,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ServerLogEvent {
+    pub server: String,
+    #[ts(type = "\"stdout\" | \"stderr\"")]
+    pub stream: OutputStream,
+    pub line: String,
+    /// Milliseconds since the Unix epoch
+    #[ts(type = "number")]
+    pub timestamp: u64,
+}
+
+/// A recorded JSON-RPC message, as returned by `mcp_get_traffic`
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TrafficEvent {
+    #[ts(type = "number")]
+    pub seq: u64,
+    pub server: String,
+    #[ts(type = "\"sent\" | \"received\"")]
+    pub direction: Direction,
+    /// Milliseconds since the Unix epoch
+    #[ts(type = "number")]
+    pub timestamp: u64,
+    #[ts(type = "\"request\" | \"response\" | \"notification\"")]
+    pub kind: MessageKind,
+    /// For responses, the method of the request they answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub id: Option<Value>,
+    /// For responses, milliseconds since their request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub latency_ms: Option<u64>,
+    pub error: bool,
+    pub message: Value,
+}
+
+impl From<TrafficEntry> for TrafficEvent {
+    fn from(entry: TrafficEntry) -> Self {
+        Self {
+            seq: entry.seq,
+            server: entry.server,
+            direction: entry.direction,
+            timestamp: entry.timestamp,
+            kind: entry.kind,
+            method: entry.method,
+            id: entry.id,
+            latency_ms: entry.latency_ms,
+            error: entry.error,
+            message: entry.message,
+        }
+    }
+}
+
+/// A request still waiting for its response past the server's slow-call
+/// threshold. Sent once when the threshold is crossed and again, with
+/// `finished` set, when the response arrives or the request fails.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SlowCallEvent {
+    pub server: String,
+    pub method: String,
+    /// Milliseconds since the request was sent
+    #[ts(type = "number")]
+    pub elapsed_ms: u64,
+    pub finished: bool,
+}
+
+impl From<SlowCall> for SlowCallEvent {
+    fn from(call: SlowCall) -> Self {
+        Self {
+            server: call.server,
+            method: call.method,
+            elapsed_ms: call.elapsed_ms,
+            finished: call.finished,
+        }
+    }
+}
+
+/// Servers affected by a config reload
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ConfigReloadedEvent {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+/// The servers listed once the saved config was loaded at launch
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ConfigLoadedEvent {
+    pub servers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
+/// Servers found by a discovery run in the background
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct DiscoveryFinishedEvent {
+    /// The directory searched
+    pub path: String,
+    /// Configs for the servers found, as `mcp_discover_servers` returns them
+    pub servers: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
+/// One piece of a streamed tool call. A stream ends with `result` or `error`.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+#[ts(export)]
+pub enum ToolStreamChunk {
+    Progress {
+        progress: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        total: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        message: Option<String>,
+    },
+    /// One content item of the result, sent in order
+    Content { index: usize, content: Value },
+    /// The call finished and all of its content has been sent
+    Result { is_error: bool },
+    Error { code: i32, message: String },
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ToolStreamEvent {
+    /// Identifies the call the chunk belongs to
+    pub channel: String,
+    pub chunk: ToolStreamChunk,
+}
+
+/// A piece of one content item of a resource read with
+/// `mcp_read_resource_streamed`. Joining the pieces of an item in order gives
+/// back its text or base64 data.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ResourceChunkEvent {
+    /// Identifies the read the piece belongs to
+    pub channel: String,
+    /// Which content item of the resource the piece belongs to
+    pub index: usize,
+    /// Where the piece starts in the item's text or data, in bytes
+    pub offset: usize,
+    /// The item with its text or data cut down to this piece
+    pub content: Value,
+    /// Whether this is the last piece of the item
+    pub last: bool,
+}
+
+/// A server offered by an `mlface://add-server` link. Nothing is registered
+/// until the user accepts it with `mcp_resolve_install`.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct InstallRequestEvent {
+    pub id: u32,
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+/// A line the installer printed during `mcp_install_package`
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PackageInstallEvent {
+    /// The `install_id` the install was started with
+    pub id: String,
+    #[ts(type = "\"stdout\" | \"stderr\"")]
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// The servers an update check found behind their package's latest release
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct UpdatesAvailableEvent {
+    pub servers: Vec<String>,
+}
+
+/// A tool call held until the user answers it with `mcp_resolve_tool_approval`
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ToolApprovalEvent {
+    pub id: u32,
+    pub server: String,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub arguments: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TokenEvent {
+    /// Identifies the generation the token belongs to
+    pub channel: String,
+    pub delta: String,
+}
+
+/// The tokens a chat of `llm_chat_stream` used
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct UsageEvent {
+    pub channel: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub prompt_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub completion_tokens: Option<u64>,
+}
+
+/// The tokens a chat of `llm_chat_stream` or a step of `llm_run_agent` is
+/// about to send, after fitting it into the context window
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ContextEvent {
+    pub channel: String,
+    #[ts(type = "number")]
+    pub prompt_tokens: u64,
+    /// Whether the model's own tokenizer counted them, rather than an estimate
+    pub exact: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub context_window: Option<u64>,
+    /// Tokens kept for the reply
+    #[ts(type = "number")]
+    pub reply_tokens: u64,
+    /// Messages left out or summarized to fit
+    pub removed: usize,
+}
+
+/// The end of a chat of `llm_chat_stream`, the last event on its channel
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct FinishEvent {
+    pub channel: String,
+    /// The provider's reason, such as `stop`, `length` or `tool_use`, or
+    /// `cancelled` or `error`
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
+/// What happened in a step of an agent run
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[ts(export)]
+pub enum AgentStep {
+    /// The model replied, with `tool_calls` calls to follow
+    Reply { content: String, tool_calls: usize },
+    /// A tool the model asked for is about to be called, once approved
+    ToolCall { id: String, server: String, tool: String, arguments: Value },
+    /// A tool call finished. Calls that were denied or failed are errors.
+    ToolResult { id: String, server: String, tool: String, content: String, is_error: bool },
+}
+
+/// A step of `llm_run_agent`
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AgentStepEvent {
+    pub channel: String,
+    /// Counts the model's replies, from 1
+    pub step: u32,
+    pub detail: AgentStep,
+}
+
+/// A progress update of `llm_pull_model`
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PullProgressEvent {
+    /// The `pull_id` the pull was started with
+    pub id: String,
+    pub status: String,
+    /// The layer being downloaded, which `total` and `completed` count bytes of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub digest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub total: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub completed: Option<u64>,
+}
+
+/// Milliseconds since the Unix epoch, for event timestamps
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+pub fn emit_server_status<R: Runtime>(app: &AppHandle<R>, payload: ServerStatusEvent) {
+    emit(app, MCP_SERVER_STATUS, payload);
+}
+
+/// Emit the lifecycle event for `state`
+pub fn emit_server_lifecycle<R: Runtime>(app: &AppHandle<R>, state: ServerState, payload: ServerLifecycleEvent) {
+    let event = match state {
+        ServerState::Connecting => MCP_SERVER_CONNECTING,
+        ServerState::Running => MCP_SERVER_STARTED,
+        ServerState::Stopped => MCP_SERVER_STOPPED,
+        ServerState::Crashed => MCP_SERVER_CRASHED,
+    };
+    emit(app, event, payload);
+}
+
+pub fn emit_notification<R: Runtime>(app: &AppHandle<R>, payload: NotificationEvent) {
+    emit(app, MCP_NOTIFICATION, payload);
+}
+
+pub fn emit_progress<R: Runtime>(app: &AppHandle<R>, payload: ProgressEvent) {
+    emit(app, MCP_PROGRESS, payload);
+}
+
+pub fn emit_log_message<R: Runtime>(app: &AppHandle<R>, payload: LogMessageEvent) {
+    emit(app, MCP_LOG_MESSAGE, payload);
+}
+
+pub fn emit_list_changed<R: Runtime>(app: &AppHandle<R>, payload: ListChangedEvent) {
+    emit(app, MCP_LIST_CHANGED, payload);
+}
+
+pub fn emit_resource_updated<R: Runtime>(app: &AppHandle<R>, payload: ResourceUpdatedEvent) {
+    emit(app, MCP_RESOURCE_UPDATED, payload);
+}
+
+pub fn emit_server_log<R: Runtime>(app: &AppHandle<R>, payload: ServerLogEvent) {
+    emit(app, MCP_SERVER_LOG, payload);
+}
+
+pub fn emit_traffic<R: Runtime>(app: &AppHandle<R>, payload: TrafficEvent) {
+    emit(app, MCP_TRAFFIC, payload);
+}
+
+pub fn emit_slow_call<R: Runtime>(app: &AppHandle<R>, payload: SlowCallEvent) {
+    emit(app, MCP_SLOW_CALL, payload);
+}
+
+pub fn emit_config_reloaded<R: Runtime>(app: &AppHandle<R>, payload: ConfigReloadedEvent) {
+    emit(app, MCP_CONFIG_RELOADED, payload);
+}
+
+pub fn emit_config_loaded<R: Runtime>(app: &AppHandle<R>, payload: ConfigLoadedEvent) {
+    emit(app, MCP_CONFIG_LOADED, payload);
+}
+
+pub fn emit_discovery_finished<R: Runtime>(app: &AppHandle<R>, payload: DiscoveryFinishedEvent) {
+    emit(app, MCP_DISCOVERY_FINISHED, payload);
+}
+
+pub fn emit_tool_stream<R: Runtime>(app: &AppHandle<R>, window: &str, payload: ToolStreamEvent) {
+    emit_to(app, window, MCP_TOOL_STREAM, payload);
+}
+
+pub fn emit_resource_chunk<R: Runtime>(app: &AppHandle<R>, window: &str, payload: ResourceChunkEvent) {
+    emit_to(app, window, MCP_RESOURCE_CHUNK, payload);
+}
+
+pub fn emit_install_request<R: Runtime>(app: &AppHandle<R>, payload: InstallRequestEvent) {
+    emit(app, MCP_INSTALL_REQUEST, payload);
+}
+
+pub fn emit_package_install<R: Runtime>(app: &AppHandle<R>, window: &str, payload: PackageInstallEvent) {
+    emit_to(app, window, MCP_PACKAGE_INSTALL, payload);
+}
+
+pub fn emit_updates_available<R: Runtime>(app: &AppHandle<R>, payload: UpdatesAvailableEvent) {
+    emit(app, MCP_UPDATES_AVAILABLE, payload);
+}
+
+pub fn emit_tool_approval<R: Runtime>(app: &AppHandle<R>, window: &str, payload: ToolApprovalEvent) {
+    emit_to(app, window, MCP_TOOL_APPROVAL, payload);
+}
+
+pub fn emit_token<R: Runtime>(app: &AppHandle<R>, window: &str, payload: TokenEvent) {
+    emit_to(app, window, LLM_TOKEN, payload);
+}
+
+pub fn emit_usage<R: Runtime>(app: &AppHandle<R>, window: &str, payload: UsageEvent) {
+    emit_to(app, window, LLM_USAGE, payload);
+}
+
+pub fn emit_context<R: Runtime>(app: &AppHandle<R>, window: &str, payload: ContextEvent) {
+    emit_to(app, window, LLM_CONTEXT, payload);
+}
+
+pub fn emit_finish<R: Runtime>(app: &AppHandle<R>, window: &str, payload: FinishEvent) {
+    emit_to(app, window, LLM_FINISH, payload);
+}
+
+pub fn emit_agent_step<R: Runtime>(app: &AppHandle<R>, window: &str, payload: AgentStepEvent) {
+    emit_to(app, window, LLM_AGENT_STEP, payload);
+}
+
+pub fn emit_pull_progress<R: Runtime>(app: &AppHandle<R>, window: &str, payload: PullProgressEvent) {
+    emit_to(app, window, LLM_PULL_PROGRESS, payload);
+}
+
+fn emit<R: Runtime, P: Serialize + Clone>(app: &AppHandle<R>, event: &str, payload: P) {
+    if let Err(e) = app.emit(event, payload) {
+        log::warn!("Failed to emit {} event: {}", event, e);
+    }
+}
+
+/// Emit to the window labelled `window` only
+fn emit_to<R: Runtime, P: Serialize + Clone>(app: &AppHandle<R>, window: &str, event: &str, payload: P) {
+    if let Err(e) = app.emit_to(window, event, payload) {
+        log::warn!("Failed to emit {} event to {}: {}", event, window, e);
+    }
+}
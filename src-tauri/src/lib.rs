@@ -1,9 +1,46 @@
+// Keeping the app running with the window closed
+pub mod background;
+// Scheduled config backups
+pub mod backup;
+// Rotating app log files and bug report bundles
+pub mod diagnostics;
+// Typed events emitted to the frontend
+pub mod events;
+// Model backends
+pub mod llm;
 // MCP integration module
-mod mcp;
-use tauri::Manager;
+pub mod mcp;
+// Locations of the app's data and config files
+pub mod paths;
+// Startup mode and the background work it starts
+pub mod startup;
+// Tray icon with per-server controls
+#[cfg(desktop)]
+pub mod tray;
+// Detects a blocked async runtime
+pub mod watchdog;
+use mcp::context::McpContext;
+use mcp::server::McpServerManager;
+use mlface_mcp::updates::PackageUpdates;
+use paths::ConfigPaths;
+use std::sync::Arc;
+use tauri::ipc::Invoke;
+use tauri::{Manager, Runtime};
 
-// Re-export the MCP commands for use in the app
+// Commands registered by `invoke_handler`
+use llm::commands::*;
+use mcp::approval::*;
+use mcp::catalog::*;
 use mcp::commands::*;
+use mcp::deep_link::*;
+use mcp::keychain::*;
+use mcp::profiles::*;
+use mcp::project::*;
+use mcp::registry::*;
+use background::*;
+use diagnostics::*;
+use backup::*;
+use startup::*;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -11,56 +48,191 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    let mut builder = tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
-        .setup(|app| {
-            // Setup the MCP config directory
-            let app_data_dir_result = app.path().app_data_dir();
-            if let Ok(app_data_dir) = app_data_dir_result {
-                let config_dir = app_data_dir.join("config");
-                let server_config_path = config_dir.join("mcp_servers.json");
-                
-                // Create config directory if it doesn't exist
-                if !config_dir.exists() {
-                    let _ = std::fs::create_dir_all(&config_dir); // Ignore error
-                }
-                
-                // Create an empty server config file if it doesn't exist yet
-                if !server_config_path.exists() {
-                    let empty_config = serde_json::json!({});
-                    let _ = std::fs::write(&server_config_path, empty_config.to_string()); // Ignore error
-                }
-                
-                // Set MCP_CONFIG_PATH environment variable for the Rust backend to access
-                std::env::set_var("MCP_CONFIG_PATH", server_config_path.to_string_lossy().to_string());
-            }
-            Ok(())
-        });
-        
-    // Register MCP commands
-    builder = builder.invoke_handler(tauri::generate_handler![
+/// Every command the frontend can invoke. A builder keeps only the last
+/// handler it is given, so they are all registered here together.
+pub fn invoke_handler<R: Runtime>() -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    tauri::generate_handler![
+        greet,
         mcp_register_server,
         mcp_unregister_server,
         mcp_start_server,
+        mcp_start_all,
         mcp_stop_server,
+        mcp_restart_server,
+        mcp_set_server_enabled,
+        mcp_set_server_settings,
+        mcp_pin_server_binary,
+        mcp_check_server_binary,
+        mcp_check_node,
+        mcp_check_python,
+        mcp_check_package,
+        mcp_install_package,
+        mcp_cancel_package_install,
+        mcp_check_server_updates,
+        mcp_update_server_package,
         mcp_get_servers,
         mcp_test_connection,
         mcp_discover_servers,
+        mcp_start_discovery,
         mcp_list_tools,
         mcp_call_tool,
+        mcp_call_tool_streamed,
+        mcp_call_tools_batch,
+        mcp_cancel_tool_call,
         mcp_list_resources,
         mcp_read_resource,
+        mcp_read_resource_streamed,
+        mcp_read_resource_to_file,
         mcp_list_prompts,
         mcp_get_prompt,
         mcp_get_server_status,
+        mcp_get_server_info,
+        mcp_get_server_logs,
+        mcp_get_traffic,
+        mcp_unfollow_traffic,
+        mcp_start_session_recording,
+        mcp_stop_session_recording,
+        mcp_query_audit_log,
+        mcp_get_audit_retention,
+        mcp_set_audit_retention,
+        mcp_set_idle_timeout,
+        mcp_set_resource_budgets,
+        mcp_set_rate_limit,
+        mcp_get_rate_limit_usage,
+        mcp_get_metrics,
+        mcp_get_dashboard,
+        mcp_import_claude_config,
+        mcp_preview_import,
+        mcp_import_servers,
+        mcp_export_config,
+        mcp_import_config,
         mcp_save_config,
-        mcp_load_config
-    ]);
+        mcp_load_config,
+        mcp_set_secret,
+        mcp_delete_secret,
+        mcp_pending_installs,
+        mcp_resolve_install,
+        mcp_get_catalog,
+        mcp_refresh_catalog,
+        mcp_install_catalog_entry,
+        mcp_search_registry,
+        mcp_get_registry_server,
+        mcp_install_registry_server,
+        mcp_open_workspace,
+        mcp_approve_project_servers,
+        mcp_close_workspace,
+        llm_list_models,
+        llm_server_health,
+        llm_pull_model,
+        llm_chat,
+        llm_chat_stream,
+        llm_count_tokens,
+        llm_run_agent,
+        llm_cancel,
+        mcp_resolve_tool_approval,
+        mcp_list_tool_approvals,
+        mcp_set_tool_approval,
+        mcp_list_permissions,
+        mcp_revoke_permission,
+        mcp_list_profiles,
+        mcp_create_profile,
+        mcp_switch_profile,
+        mcp_delete_profile,
+        backup_get_settings,
+        backup_set_settings,
+        backup_now,
+        backup_list,
+        backup_restore,
+        app_get_startup_mode,
+        app_set_startup_mode,
+        app_get_prewarm,
+        app_set_prewarm,
+        app_get_background_mode,
+        app_set_background_mode,
+        app_get_logs,
+        app_pack_logs,
+        mcp_set_debug_mode,
+        mcp_get_debug_mode,
+        watchdog::runtime_health
+    ]
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    let builder = tauri::Builder::default();
+    
+    // A second launch, or a link opened on Windows and Linux, goes to the
+    // running app instead of starting another set of servers. This plugin
+    // must be registered first.
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        background::show_main_window(app);
+    }));
     
     builder
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .invoke_handler(invoke_handler())
+        .manage(Arc::new(McpServerManager::new()))
+        .manage(PackageUpdates::default())
+        .on_window_event(background::on_window_event)
+        .setup(|app| {
+            // Before anything else, so the rest of setup is logged
+            diagnostics::init(app.handle());
+            
+            let manager = mcp::manager(app.handle());
+            
+            // Setup the config directory. A config registered before setup,
+            // by tests or an embedding app, takes precedence.
+            let paths = match app.try_state::<ConfigPaths>() {
+                Some(paths) => Some(paths.inner().clone()),
+                None => ConfigPaths::resolve(app.handle())
+                    .map_err(|e| log::error!("Failed to locate the app data directory: {}", e))
+                    .ok(),
+            };
+            // Approvals, the audit log and large content are opened in the
+            // background by `startup`
+            let cache_dir = app.path().app_cache_dir().ok();
+            app.manage(McpContext::open_later(manager.clone(), paths.clone(), cache_dir));
+            
+            // The config file is created and watched in the background
+            if let Some(paths) = paths {
+                manager.set_config_path(Some(paths.server_config.clone()));
+                app.manage(paths);
+            }
+            
+            // Resolve {{secret:NAME}} references from the OS keychain
+            mcp::keychain::install(&manager);
+            
+            // Subscribe before anything is started so no transition is missed
+            mcp::lifecycle::spawn(app.handle().clone());
+            mcp::notifications::spawn(app.handle().clone());
+            
+            // Let "Add to mlFace" links offer servers for installation
+            mcp::deep_link::install(app.handle());
+            
+            startup::start_background(app.handle());
+            
+            backup::spawn_scheduler(app.handle().clone());
+            
+            #[cfg(desktop)]
+            if let Err(e) = tray::create(app.handle()) {
+                log::error!("Failed to create the tray icon: {}", e);
+            }
+            
+            // Persist server output next to the app's own logs
+            if let Ok(log_dir) = app.path().app_log_dir() {
+                manager.set_log_dir(log_dir.join("mcp"));
+            }
+            Ok(())
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, _event| {
+            // Clicking the dock icon brings back a window hidden by background mode
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Reopen { .. } = _event {
+                background::show_main_window(_app);
+            }
+        });
 }
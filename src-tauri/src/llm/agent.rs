@@ -0,0 +1,232 @@
+//! The loop that lets a model use MCP tools.
+//!
+//! The conversation goes to the model along with the tools of the connected
+//! servers. When the reply asks for tools, each call is approved like any
+//! other tool call, made through the server manager, and its result added to
+//! the conversation, which goes back to the model. That repeats until the
+//! model replies without calling tools, or the run reaches its step limit.
+//! Every reply and call is reported as an `llm:agent-step` event. With the
+//! `context` of the request, the conversation is fitted into the model's
+//! context window before each step.
+
+use crate::events::{self, AgentStep, AgentStepEvent};
+use crate::llm::commands::{chat, fitted};
+use crate::llm::{ChatMessage, ChatRequest, Provider};
+use crate::mcp::approval::ToolApprovals;
+use crate::mcp::audit::{AuditEntry, AuditLog};
+use crate::mcp::server::{ConnectionState, McpServerManager};
+use crate::mcp::types::{CallToolResult, Content, McpError};
+use mlface_mcp::tool_schema::{ModelTool, ToolFormat, ToolSet};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::time::Instant;
+use tauri::{AppHandle, Runtime};
+
+/// Replies of the model a run allows unless it sets `max_steps`
+pub const DEFAULT_MAX_STEPS: u32 = 10;
+
+/// How an agent run goes
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AgentOptions {
+    /// The most replies of the model to ask for
+    #[serde(default)]
+    pub max_steps: Option<u32>,
+    /// The servers whose tools are offered; by default all connected ones
+    #[serde(default)]
+    pub servers: Option<Vec<String>>,
+    /// Recorded with the tool calls in the audit log
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+}
+
+/// What an agent run added to the conversation
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentRun {
+    /// The model's replies and the tool results, in order
+    pub messages: Vec<ChatMessage>,
+    pub steps: u32,
+    /// The provider's reason for the last reply, or `max_steps` when the run
+    /// stopped at its limit
+    pub done_reason: String,
+}
+
+/// Where a run reports to
+pub(crate) struct RunContext<'a, R: Runtime> {
+    pub app: &'a AppHandle<R>,
+    /// The window asked for approvals and sent the events
+    pub window: &'a str,
+    pub channel: &'a str,
+    pub manager: &'a McpServerManager,
+    pub approvals: &'a ToolApprovals,
+    pub audit_log: &'a AuditLog,
+}
+
+impl<R: Runtime> RunContext<'_, R> {
+    fn step(&self, step: u32, detail: AgentStep) {
+        events::emit_agent_step(self.app, self.window, AgentStepEvent { channel: self.channel.to_string(), step, detail });
+    }
+}
+
+/// The tools of `servers`, or of every connected server. Servers that fail
+/// to list theirs are left out, as are tools past `MAX_TOOLS`.
+async fn available_tools(manager: &McpServerManager, servers: Option<&[String]>) -> ToolSet {
+    let names: Vec<String> = match servers {
+        Some(servers) => servers.to_vec(),
+        None => {
+            let mut names = Vec::new();
+            for config in manager.get_servers().await {
+                if config.enabled && manager.connection_state(&config.name).await == ConnectionState::Connected {
+                    names.push(config.name);
+                }
+            }
+            names
+        }
+    };
+    
+    let mut tools = ToolSet::new();
+    for server in names {
+        match manager.list_tools(&server).await {
+            Ok(list) => {
+                for tool in list.tools {
+                    let name = tool.name.clone();
+                    if tools.add(&server, tool).is_none() {
+                        log::warn!("Leaving out {} of {} from the agent run: too many tools", name, server);
+                    }
+                }
+            }
+            Err(e) => log::warn!("Leaving out the tools of {} from the agent run: {}", server, e),
+        }
+    }
+    tools
+}
+
+/// The function-calling format `provider` takes tools in
+fn tool_format(provider: &Provider) -> ToolFormat {
+    match provider {
+        Provider::Anthropic { .. } => ToolFormat::Anthropic,
+        _ => ToolFormat::OpenAi,
+    }
+}
+
+/// The text of a tool result for the model. Content other than text is
+/// described rather than passed on.
+fn result_text(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .map(|content| match content {
+            Content::Text { text } => text.clone(),
+            Content::Image { mime_type, .. } => format!("[{} image]", mime_type),
+            Content::EmbeddedResource { uri, .. } => format!("[resource {}]", uri),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Run `request` until the model stops calling tools or `max_steps` replies
+/// were asked for, passing on each piece of each reply
+pub(crate) async fn run<R: Runtime>(
+    context: &RunContext<'_, R>,
+    request: ChatRequest,
+    options: AgentOptions,
+    mut on_delta: impl FnMut(&str),
+) -> Result<AgentRun, String> {
+    let provider = request.provider.clone().unwrap_or_default();
+    let tools = available_tools(context.manager, options.servers.as_deref()).await;
+    let mut chat_options = match request.options.clone() {
+        Some(Value::Object(options)) => options,
+        _ => Map::new(),
+    };
+    if !tools.is_empty() {
+        chat_options.insert("tools".to_string(), Value::Array(tools.definitions(tool_format(&provider))));
+    }
+    let mut request = ChatRequest { options: Some(Value::Object(chat_options)), ..request };
+    // Fitting only removes messages before the last user message, so the run's
+    // own are the last `added`
+    let mut added = 0;
+    let max_steps = options.max_steps.unwrap_or(DEFAULT_MAX_STEPS).max(1);
+    
+    for step in 1..=max_steps {
+        request = fitted(context.app, context.window, context.channel, context.manager, request).await?;
+        let reply = chat(context.app, context.manager, &request, &mut on_delta).await?;
+        let calls = reply.message.tool_calls.clone();
+        context.step(step, AgentStep::Reply { content: reply.message.content.clone(), tool_calls: calls.len() });
+        request.messages.push(reply.message);
+        added += 1;
+        if calls.is_empty() {
+            return Ok(AgentRun {
+                messages: request.messages.split_off(request.messages.len() - added),
+                steps: step,
+                done_reason: reply.done_reason.unwrap_or_else(|| "stop".to_string()),
+            });
+        }
+        
+        for call in calls {
+            let (content, is_error) = match tools.get(&call.name) {
+                Some(tool) => {
+                    context.step(step, AgentStep::ToolCall {
+                        id: call.id.clone(),
+                        server: tool.server.clone(),
+                        tool: tool.tool.name.clone(),
+                        arguments: call.arguments.clone(),
+                    });
+                    let result = call_tool(context, tool, &call.arguments, options.conversation_id.as_deref()).await;
+                    let (content, is_error) = match result {
+                        Ok(result) => (result_text(&result), result.is_error == Some(true)),
+                        Err(e) => (e.to_string(), true),
+                    };
+                    context.step(step, AgentStep::ToolResult {
+                        id: call.id.clone(),
+                        server: tool.server.clone(),
+                        tool: tool.tool.name.clone(),
+                        content: content.clone(),
+                        is_error,
+                    });
+                    (content, is_error)
+                }
+                // Models sometimes make names up; tell it so it can correct itself
+                None => (format!("There is no tool named {}", call.name), true),
+            };
+            // Not every provider has a way to mark a result as an error
+            let content = if is_error { format!("Error: {}", content) } else { content };
+            request.messages.push(ChatMessage {
+                role: "tool".to_string(),
+                content,
+                tool_calls: Vec::new(),
+                tool_call_id: Some(call.id),
+            });
+            added += 1;
+        }
+    }
+    
+    Ok(AgentRun {
+        messages: request.messages.split_off(request.messages.len() - added),
+        steps: max_steps,
+        done_reason: "max_steps".to_string(),
+    })
+}
+
+/// Call `tool` once the user approves, recording the call in the audit log
+async fn call_tool<R: Runtime>(
+    context: &RunContext<'_, R>,
+    tool: &ModelTool,
+    arguments: &Value,
+    conversation: Option<&str>,
+) -> Result<CallToolResult, McpError> {
+    let started = Instant::now();
+    let arguments = Some(arguments.clone()).filter(|arguments| !arguments.is_null());
+    let result = async {
+        context
+            .approvals
+            .ask(context.app, context.window, &tool.server, &tool.tool.name, arguments.as_ref())
+            .await?;
+        context.manager.call_tool(&tool.server, &tool.tool.name, arguments.clone()).await
+    }
+    .await;
+    
+    let entry = AuditEntry::tool_call(&tool.server, &tool.tool.name, arguments.as_ref(), conversation, started, &result);
+    if let Err(e) = context.audit_log.record(&entry) {
+        log::warn!("Failed to record {} on {} in the audit log: {}", entry.target, entry.server, e);
+    }
+    result
+}
@@ -0,0 +1,211 @@
+//! Client for Anthropic's Messages API.
+//!
+//! Its messages differ from the chat completions shape: the system prompt is
+//! a parameter of its own, and content is a list of blocks, where tool calls
+//! are `tool_use` blocks of the assistant and their results `tool_result`
+//! blocks of the user. Messages are converted both ways so tool calls keep
+//! their ids and arguments as they are. Replies stream as server-sent events,
+//! one per block start, delta and stop.
+
+use crate::llm::api::{checked, model_list, read_events, unreachable};
+use crate::llm::{ChatMessage, ChatReply, ModelInfo, ToolCall};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Where the API is served unless configured otherwise
+pub const DEFAULT_URL: &str = "https://api.anthropic.com/v1";
+
+/// The API version requests are made against
+pub const API_VERSION: &str = "2023-06-01";
+
+/// The API requires a limit on the reply; this one is used unless the
+/// options give `max_tokens`
+pub const DEFAULT_MAX_TOKENS: u64 = 4096;
+
+/// Anthropic's API at `base_url`
+#[derive(Debug, Clone)]
+pub struct Anthropic {
+    base_url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Anthropic {
+    pub fn new(base_url: Option<&str>, api_key: Option<String>, client: reqwest::Client) -> Self {
+        let base_url = base_url.map(str::trim).filter(|url| !url.is_empty()).unwrap_or(DEFAULT_URL);
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            client,
+        }
+    }
+    
+    fn request(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let request = request.header("anthropic-version", API_VERSION);
+        match &self.api_key {
+            Some(key) => request.header("x-api-key", key.trim()),
+            None => request,
+        }
+    }
+    
+    /// The models the API offers
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let response = self
+            .request(self.client.get(format!("{}/models", self.base_url)).query(&[("limit", "1000")]))
+            .send()
+            .await
+            .map_err(|e| unreachable(&self.base_url, e))?;
+        let document = checked(response).await?.json::<Value>().await.map_err(|e| e.to_string())?;
+        Ok(model_list(document))
+    }
+    
+    /// Send `messages` to `model`, passing on each piece of the text of the
+    /// reply as it is generated. `options`, such as `temperature`, `tools` or
+    /// `max_tokens`, are added to the request as they are.
+    pub async fn chat(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        options: Option<Value>,
+        mut on_delta: impl FnMut(&str),
+    ) -> Result<ChatReply, String> {
+        let (system, messages) = request_messages(messages);
+        let mut body = json!({
+            "model": model,
+            "messages": messages,
+            "max_tokens": DEFAULT_MAX_TOKENS,
+            "stream": true,
+        });
+        if let Some(system) = system {
+            body["system"] = Value::String(system);
+        }
+        if let Some(Value::Object(options)) = options {
+            for (key, value) in options {
+                body[key] = value;
+            }
+        }
+        let response = self
+            .request(self.client.post(format!("{}/messages", self.base_url)))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| unreachable(&self.base_url, e))?;
+        let response = checked(response).await?;
+        
+        let mut reply = ChatReply::default();
+        // Tool calls by the index of their block, with the JSON of their
+        // arguments as it arrives
+        let mut tool_calls: BTreeMap<u64, (ToolCall, String)> = BTreeMap::new();
+        read_events(response, |event| {
+            match event["type"].as_str().unwrap_or_default() {
+                "message_start" => {
+                    reply.prompt_tokens = event["message"]["usage"]["input_tokens"].as_u64();
+                }
+                "content_block_start" => {
+                    let block = &event["content_block"];
+                    if block["type"] == "tool_use" {
+                        let call = ToolCall {
+                            id: block["id"].as_str().unwrap_or_default().to_string(),
+                            name: block["name"].as_str().unwrap_or_default().to_string(),
+                            arguments: Value::Object(Map::new()),
+                        };
+                        tool_calls.insert(event["index"].as_u64().unwrap_or_default(), (call, String::new()));
+                    }
+                }
+                "content_block_delta" => {
+                    let delta = &event["delta"];
+                    match delta["type"].as_str().unwrap_or_default() {
+                        "text_delta" => {
+                            if let Some(text) = delta["text"].as_str().filter(|text| !text.is_empty()) {
+                                reply.message.content.push_str(text);
+                                on_delta(text);
+                            }
+                        }
+                        "input_json_delta" => {
+                            if let Some((_, json)) = tool_calls.get_mut(&event["index"].as_u64().unwrap_or_default()) {
+                                json.push_str(delta["partial_json"].as_str().unwrap_or_default());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                "message_delta" => {
+                    if let Some(reason) = event["delta"]["stop_reason"].as_str() {
+                        reply.done_reason = Some(reason.to_string());
+                    }
+                    if let Some(tokens) = event["usage"]["output_tokens"].as_u64() {
+                        reply.completion_tokens = Some(tokens);
+                    }
+                }
+                _ => {}
+            }
+            Ok(())
+        })
+        .await?;
+        
+        for (mut call, json) in tool_calls.into_values() {
+            // A call without arguments sends none
+            if !json.trim().is_empty() {
+                call.arguments = serde_json::from_str(&json).map_err(|e| format!("Invalid arguments for tool {}: {}", call.name, e))?;
+            }
+            reply.message.tool_calls.push(call);
+        }
+        Ok(reply)
+    }
+}
+
+/// The system prompt and the messages of a request for `messages`. System
+/// messages are joined into the prompt; tool results become `tool_result`
+/// blocks of the user, and consecutive messages of one role are merged, as
+/// the API expects roles to alternate.
+fn request_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<Value>) {
+    let mut system = Vec::new();
+    let mut request: Vec<(&str, Vec<Value>)> = Vec::new();
+    for message in messages {
+        let (role, blocks) = match message.role.as_str() {
+            "system" => {
+                system.push(message.content.as_str());
+                continue;
+            }
+            "tool" => (
+                "user",
+                vec![json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.tool_call_id.as_deref().unwrap_or_default(),
+                    "content": message.content,
+                })],
+            ),
+            "assistant" => {
+                let mut blocks = text_block(&message.content);
+                blocks.extend(message.tool_calls.iter().map(|call| {
+                    json!({ "type": "tool_use", "id": call.id, "name": call.name, "input": call.arguments })
+                }));
+                ("assistant", blocks)
+            }
+            _ => ("user", text_block(&message.content)),
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+        match request.last_mut() {
+            Some((last, content)) if *last == role => content.extend(blocks),
+            _ => request.push((role, blocks)),
+        }
+    }
+    
+    let system = Some(system.join("\n\n")).filter(|system| !system.is_empty());
+    let request = request
+        .into_iter()
+        .map(|(role, content)| json!({ "role": role, "content": content }))
+        .collect();
+    (system, request)
+}
+
+/// The API rejects empty text blocks
+fn text_block(text: &str) -> Vec<Value> {
+    if text.is_empty() {
+        Vec::new()
+    } else {
+        vec![json!({ "type": "text", "text": text })]
+    }
+}
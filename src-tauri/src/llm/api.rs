@@ -0,0 +1,81 @@
+//! Helpers shared by the clients of hosted APIs, which answer with JSON
+//! errors and stream replies as server-sent events.
+
+use crate::llm::ModelInfo;
+use futures::StreamExt;
+use serde_json::Value;
+
+pub(crate) fn unreachable(url: &str, e: reqwest::Error) -> String {
+    format!("Failed to reach {}: {}", url, e)
+}
+
+/// The response if it succeeded, otherwise the error the API explained
+pub(crate) async fn checked(response: reqwest::Response) -> Result<reqwest::Response, String> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let message = response.json::<Value>().await.ok().and_then(|body| error_message(&body));
+    Err(match message {
+        Some(message) => format!("{}: {}", status, message),
+        None => status.to_string(),
+    })
+}
+
+/// `{"error": {"message": ...}}`, or a bare string some servers send instead
+pub(crate) fn error_message(body: &Value) -> Option<String> {
+    let error = body.get("error")?;
+    error["message"].as_str().or_else(|| error.as_str()).map(str::to_string)
+}
+
+/// Hand the JSON of each `data:` event of a streamed response to `on_event`,
+/// until `[DONE]` or the end of the stream
+pub(crate) async fn read_events(response: reqwest::Response, mut on_event: impl FnMut(Value) -> Result<(), String>) -> Result<(), String> {
+    let mut stream = response.bytes_stream();
+    let mut buffer = Vec::new();
+    // Whether `[DONE]` was seen
+    let mut handle = |line: &[u8]| -> Result<bool, String> {
+        let line = String::from_utf8_lossy(line);
+        let Some(data) = line.trim_end_matches(['\r', '\n']).strip_prefix("data:") else {
+            // Comments, event names and blank lines between events
+            return Ok(false);
+        };
+        let data = data.trim();
+        if data == "[DONE]" {
+            return Ok(true);
+        }
+        let event: Value = serde_json::from_str(data).map_err(|e| format!("Unexpected event in the stream: {}", e))?;
+        // Errors in the middle of a stream come as an event
+        if let Some(message) = error_message(&event) {
+            return Err(message);
+        }
+        on_event(event).map(|()| false)
+    };
+    
+    while let Some(chunk) = stream.next().await {
+        buffer.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+        while let Some(end) = buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            if handle(&line)? {
+                return Ok(());
+            }
+        }
+    }
+    handle(&buffer).map(|_| ())
+}
+
+/// The list models endpoints answer with, `{"data": [{"id": ...}]}`, with
+/// whatever else is said about each model as its details
+pub(crate) fn model_list(document: Value) -> Vec<ModelInfo> {
+    let models = document["data"].as_array().cloned().unwrap_or_default();
+    models
+        .into_iter()
+        .filter_map(|mut model| {
+            let id = model["id"].as_str()?.to_string();
+            if let Some(fields) = model.as_object_mut() {
+                fields.remove("id");
+            }
+            Some(ModelInfo { id, size: None, details: model })
+        })
+        .collect()
+}
@@ -0,0 +1,421 @@
+use crate::events::{self, ContextEvent, FinishEvent, PullProgressEvent, TokenEvent, UsageEvent};
+use crate::llm::agent::{self, AgentOptions, AgentRun, RunContext};
+use crate::llm::anthropic::Anthropic;
+use crate::llm::embedded::{self, Device, Embedded};
+use crate::llm::local::{LocalServer, LocalServerKind, ServerHealth};
+use crate::llm::ollama::Ollama;
+use crate::llm::openai::OpenAi;
+use crate::llm::tokens::{self, ContextOptions, Encoding, Overflow, TokenCount, TokenEstimate};
+use crate::llm::{ChatMessage, ChatReply, ChatRequest, ModelInfo, Provider};
+use crate::mcp::context::McpContext;
+use crate::mcp::http::TlsSettings;
+use crate::mcp::secrets::resolve_secrets;
+use crate::mcp::server::McpServerManager;
+use crate::paths::paths;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime, State};
+use tokio::sync::oneshot;
+
+lazy_static::lazy_static! {
+    // Chats and pulls that can be cancelled, by window label and id
+    static ref REQUESTS: Mutex<HashMap<String, oneshot::Sender<()>>> = Mutex::new(HashMap::new());
+}
+
+fn request_key(window: &str, id: &str) -> String {
+    format!("{}/{}", window, id)
+}
+
+fn http_client(manager: &McpServerManager) -> Result<reqwest::Client, String> {
+    manager.http_clients().client(&TlsSettings::default()).map_err(|e| e.to_string())
+}
+
+fn ollama(manager: &McpServerManager, url: Option<&str>) -> Result<Ollama, String> {
+    Ok(Ollama::new(url, http_client(manager)?))
+}
+
+/// `api_key` with the secrets it refers to looked up
+async fn api_key(manager: &McpServerManager, api_key: Option<String>) -> Result<Option<String>, String> {
+    let Some(key) = api_key else {
+        return Ok(None);
+    };
+    // Keychain reads may wait on an unlock prompt
+    let secrets = manager.secret_store();
+    let key = tauri::async_runtime::spawn_blocking(move || resolve_secrets(&key, secrets.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(Some(key))
+}
+
+fn local(manager: &McpServerManager, kind: LocalServerKind, url: Option<&str>) -> Result<LocalServer, String> {
+    Ok(LocalServer::new(kind, url, http_client(manager)?))
+}
+
+fn embedded<R: Runtime>(
+    app: &AppHandle<R>,
+    models_dir: Option<String>,
+    device: Device,
+    context_size: Option<u32>,
+) -> Result<Embedded, String> {
+    let dir = match models_dir {
+        Some(dir) => dir.into(),
+        None => paths(app)?.models_dir,
+    };
+    Ok(Embedded::new(dir, device, context_size))
+}
+
+async fn openai(manager: &McpServerManager, base_url: &str, key: Option<String>) -> Result<OpenAi, String> {
+    Ok(OpenAi::new(base_url, api_key(manager, key).await?, http_client(manager)?))
+}
+
+async fn anthropic(manager: &McpServerManager, base_url: Option<&str>, key: Option<String>) -> Result<Anthropic, String> {
+    Ok(Anthropic::new(base_url, api_key(manager, key).await?, http_client(manager)?))
+}
+
+/// Make the request under `key` cancellable, unless one is running under it
+/// already. The receiver fires when `llm_cancel` stops it.
+fn register(key: &str) -> Result<oneshot::Receiver<()>, String> {
+    let mut requests = REQUESTS.lock().map_err(|e| e.to_string())?;
+    if requests.contains_key(key) {
+        return Err(format!("{} is in use by a running request", key));
+    }
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    requests.insert(key.to_string(), cancel_tx);
+    Ok(cancel_rx)
+}
+
+fn unregister(key: &str) {
+    if let Ok(mut requests) = REQUESTS.lock() {
+        requests.remove(key);
+    }
+}
+
+/// Run `request` until it finishes or `llm_cancel` stops it. Dropping the
+/// request closes the connection, or ends the inference, which stops the
+/// model generating.
+async fn cancellable<T>(key: String, what: &str, request: impl Future<Output = Result<T, String>>) -> Result<T, String> {
+    let cancel_rx = register(&key)?;
+    let result = tokio::select! {
+        result = request => result,
+        _ = cancel_rx => Err(format!("{} was cancelled", what)),
+    };
+    unregister(&key);
+    result
+}
+
+/// Send `request` to its provider, passing on each piece of the reply
+pub(crate) async fn chat<R: Runtime>(
+    app: &AppHandle<R>,
+    manager: &McpServerManager,
+    request: &ChatRequest,
+    on_delta: impl FnMut(&str),
+) -> Result<ChatReply, String> {
+    let ChatRequest { provider, model, messages, options, .. } = request;
+    let options = options.clone();
+    match provider.clone().unwrap_or_default() {
+        Provider::Ollama { url } => ollama(manager, url.as_deref())?.chat(model, messages, options, on_delta).await,
+        Provider::LlamaCpp { url } => {
+            local(manager, LocalServerKind::LlamaCpp, url.as_deref())?.chat(model, messages, options, on_delta).await
+        }
+        Provider::LmStudio { url } => {
+            local(manager, LocalServerKind::LmStudio, url.as_deref())?.chat(model, messages, options, on_delta).await
+        }
+        Provider::Embedded { models_dir, device, context_size } => {
+            embedded(app, models_dir, device, context_size)?.chat(model, messages, options, on_delta).await
+        }
+        Provider::OpenAi { base_url, api_key } => openai(manager, &base_url, api_key).await?.chat(model, messages, options, on_delta).await,
+        Provider::Anthropic { base_url, api_key } => {
+            anthropic(manager, base_url.as_deref(), api_key).await?.chat(model, messages, options, on_delta).await
+        }
+    }
+}
+
+/// The tokens of `request`, counted by the model's tokenizer where the
+/// provider has one, and estimated otherwise
+pub(crate) async fn count_tokens<R: Runtime>(
+    app: &AppHandle<R>,
+    manager: &McpServerManager,
+    request: &ChatRequest,
+) -> Result<TokenCount, String> {
+    let texts = tokens::texts(request);
+    let model = request.model.as_str();
+    let (counts, exact) = match request.provider.clone().unwrap_or_default() {
+        Provider::LlamaCpp { url } => (local(manager, LocalServerKind::LlamaCpp, url.as_deref())?.count_tokens(&texts).await?, true),
+        Provider::Embedded { models_dir, device, context_size } => {
+            (embedded(app, models_dir, device, context_size)?.count_tokens(model, &texts).await?, true)
+        }
+        Provider::OpenAi { .. } => match Encoding::for_model(model) {
+            Some(encoding) => (encoding.count(&texts), true),
+            None => (Encoding::O200k.count(&texts), false),
+        },
+        Provider::Ollama { .. } | Provider::LmStudio { .. } | Provider::Anthropic { .. } => (Encoding::O200k.count(&texts), false),
+    };
+    Ok(TokenCount::new(request, counts, exact))
+}
+
+/// The tokens of context the model of `request` has, when the provider
+/// tells or the model is known
+pub(crate) async fn context_window<R: Runtime>(
+    app: &AppHandle<R>,
+    manager: &McpServerManager,
+    request: &ChatRequest,
+) -> Result<Option<u64>, String> {
+    let model = request.model.as_str();
+    match request.provider.clone().unwrap_or_default() {
+        Provider::Ollama { url } => Ok(Some(ollama(manager, url.as_deref())?.context_window(model, request.options.as_ref()).await?)),
+        Provider::LlamaCpp { url } => local(manager, LocalServerKind::LlamaCpp, url.as_deref())?.context_window(model).await,
+        Provider::LmStudio { url } => local(manager, LocalServerKind::LmStudio, url.as_deref())?.context_window(model).await,
+        Provider::Embedded { models_dir, device, context_size } => {
+            Ok(Some(embedded(app, models_dir, device, context_size)?.context_window()))
+        }
+        Provider::OpenAi { base_url, api_key } => openai(manager, &base_url, api_key).await?.context_window(model).await,
+        Provider::Anthropic { .. } => Ok(tokens::known_context_window(model)),
+    }
+}
+
+/// `request` fitted into its model's context window if it asks to be, with
+/// how it measures up reported as an `llm:context` event on `channel`
+pub(crate) async fn fitted<R: Runtime>(
+    app: &AppHandle<R>,
+    window: &str,
+    channel: &str,
+    manager: &McpServerManager,
+    request: ChatRequest,
+) -> Result<ChatRequest, String> {
+    let Some(context) = request.context.clone() else {
+        return Ok(request);
+    };
+    let (request, estimate) = tokens::fit(app, manager, request, &context).await?;
+    events::emit_context(app, window, ContextEvent {
+        channel: channel.to_string(),
+        prompt_tokens: estimate.prompt_tokens,
+        exact: estimate.exact,
+        context_window: estimate.context_window,
+        reply_tokens: estimate.reply_tokens,
+        removed: estimate.removed,
+    });
+    Ok(request)
+}
+
+/// Command to list the models `provider` offers, by default those of the
+/// local Ollama instance
+#[tauri::command]
+pub async fn llm_list_models<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    provider: Option<Provider>,
+) -> Result<Vec<ModelInfo>, String> {
+    match provider.unwrap_or_default() {
+        Provider::Ollama { url } => {
+            let models = ollama(&manager, url.as_deref())?.list_models().await?;
+            Ok(models
+                .into_iter()
+                .map(|model| ModelInfo { id: model.name, size: Some(model.size), details: model.details })
+                .collect())
+        }
+        Provider::LlamaCpp { url } => local(&manager, LocalServerKind::LlamaCpp, url.as_deref())?.list_models().await,
+        Provider::LmStudio { url } => local(&manager, LocalServerKind::LmStudio, url.as_deref())?.list_models().await,
+        Provider::Embedded { models_dir, device, context_size } => {
+            embedded(&app, models_dir, device, context_size)?.list_models()
+        }
+        Provider::OpenAi { base_url, api_key } => openai(&manager, &base_url, api_key).await?.list_models().await,
+        Provider::Anthropic { base_url, api_key } => {
+            anthropic(&manager, base_url.as_deref(), api_key).await?.list_models().await
+        }
+    }
+}
+
+/// Command to check whether the local server of `provider` is running and
+/// ready to chat, by default the local Ollama instance
+#[tauri::command]
+pub async fn llm_server_health(
+    manager: State<'_, Arc<McpServerManager>>,
+    provider: Option<Provider>,
+) -> Result<ServerHealth, String> {
+    match provider.unwrap_or_default() {
+        Provider::Ollama { url } => {
+            let health = match ollama(&manager, url.as_deref())?.list_models().await {
+                Ok(_) => ServerHealth { running: true, ready: true, message: None },
+                Err(e) => ServerHealth { running: false, ready: false, message: Some(e) },
+            };
+            Ok(health)
+        }
+        Provider::LlamaCpp { url } => Ok(local(&manager, LocalServerKind::LlamaCpp, url.as_deref())?.health().await),
+        Provider::LmStudio { url } => Ok(local(&manager, LocalServerKind::LmStudio, url.as_deref())?.health().await),
+        Provider::Embedded { .. } if embedded::AVAILABLE => Ok(ServerHealth { running: true, ready: true, message: None }),
+        Provider::Embedded { .. } => Ok(ServerHealth {
+            running: false,
+            ready: false,
+            message: Some("mlFace was built without embedded inference".to_string()),
+        }),
+        Provider::OpenAi { .. } | Provider::Anthropic { .. } => Err("Only local servers have a health check".to_string()),
+    }
+}
+
+/// Command to pull `model` into the Ollama instance at `url`, sending its
+/// progress to the calling window as `llm:pull-progress` events with
+/// `pull_id`. Stop it with `llm_cancel(pull_id)`.
+#[tauri::command]
+pub async fn llm_pull_model<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    model: String,
+    pull_id: String,
+    url: Option<String>,
+) -> Result<(), String> {
+    let ollama = ollama(&manager, url.as_deref())?;
+    let app = window.app_handle().clone();
+    let label = window.label().to_string();
+    let pull = ollama.pull(&model, |status| {
+        events::emit_pull_progress(&app, &label, PullProgressEvent {
+            id: pull_id.clone(),
+            status: status.status,
+            digest: status.digest,
+            total: status.total,
+            completed: status.completed,
+        });
+    });
+    cancellable(request_key(&label, &pull_id), &format!("Pulling {}", model), pull).await
+}
+
+/// Command to chat with `model` of `provider`, by default the local Ollama
+/// instance. Each piece of the reply is sent to the calling window as an
+/// `llm:token` event on `channel` as it is generated; the whole reply is
+/// returned at the end. Stop it with `llm_cancel(channel)`.
+#[tauri::command]
+pub async fn llm_chat<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    model: String,
+    messages: Vec<ChatMessage>,
+    channel: String,
+    options: Option<Value>,
+    provider: Option<Provider>,
+) -> Result<ChatReply, String> {
+    let app = window.app_handle().clone();
+    let label = window.label().to_string();
+    let request = ChatRequest { provider, model, messages, options, context: None };
+    let on_delta = |delta: &str| {
+        events::emit_token(&app, &label, TokenEvent {
+            channel: channel.clone(),
+            delta: delta.to_string(),
+        });
+    };
+    cancellable(request_key(&label, &channel), "The chat", chat(&app, &manager, &request, on_delta)).await
+}
+
+/// Command to start a chat streamed to the calling window on `channel`:
+/// `llm:token` events for each piece of the reply, then an `llm:usage` event
+/// if the provider counted tokens, and last an `llm:finish` event saying how
+/// it ended. A request with `context` is first fitted into the model's
+/// context window, reported by an `llm:context` event. It returns once the
+/// chat started. `llm_cancel(channel)` stops
+/// the request or the inference behind it, and finishes it as `cancelled`.
+#[tauri::command]
+pub async fn llm_chat_stream<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    request: ChatRequest,
+    channel: String,
+) -> Result<(), String> {
+    let app = window.app_handle().clone();
+    let label = window.label().to_string();
+    let manager = manager.inner().clone();
+    let key = request_key(&label, &channel);
+    let cancel_rx = register(&key)?;
+    
+    tauri::async_runtime::spawn(async move {
+        let on_delta = |delta: &str| {
+            events::emit_token(&app, &label, TokenEvent {
+                channel: channel.clone(),
+                delta: delta.to_string(),
+            });
+        };
+        let send = async {
+            let request = fitted(&app, &label, &channel, &manager, request).await?;
+            chat(&app, &manager, &request, on_delta).await
+        };
+        let result = tokio::select! {
+            result = send => Some(result),
+            _ = cancel_rx => None,
+        };
+        unregister(&key);
+        
+        let finish = |reason: &str, error: Option<String>| FinishEvent { channel: channel.clone(), reason: reason.to_string(), error };
+        match result {
+            Some(Ok(reply)) => {
+                if reply.prompt_tokens.is_some() || reply.completion_tokens.is_some() {
+                    events::emit_usage(&app, &label, UsageEvent {
+                        channel: channel.clone(),
+                        prompt_tokens: reply.prompt_tokens,
+                        completion_tokens: reply.completion_tokens,
+                    });
+                }
+                events::emit_finish(&app, &label, finish(reply.done_reason.as_deref().unwrap_or("stop"), None));
+            }
+            Some(Err(e)) => events::emit_finish(&app, &label, finish("error", Some(e))),
+            None => events::emit_finish(&app, &label, finish("cancelled", None)),
+        }
+    });
+    Ok(())
+}
+
+/// Command to count the tokens `request` would send and compare them with
+/// its model's context window, without sending it. With the `context` of the
+/// request, the window and the room for the reply are taken from there.
+#[tauri::command]
+pub async fn llm_count_tokens<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    request: ChatRequest,
+) -> Result<TokenEstimate, String> {
+    let context = ContextOptions { overflow: Overflow::Keep, ..request.context.clone().unwrap_or_default() };
+    let (_, estimate) = tokens::fit(&app, &manager, request, &context).await?;
+    Ok(estimate)
+}
+
+/// Command to run an agent: chat with the model of `request`, letting it call
+/// the tools of connected MCP servers until it is done. Each tool call waits
+/// for approval like any other. Replies stream as `llm:token` events on
+/// `channel` and each reply and call is reported as an `llm:agent-step`
+/// event, all to the calling window. Returns what the run added to the
+/// conversation. Stop it with `llm_cancel(channel)`.
+#[tauri::command]
+pub async fn llm_run_agent<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    mcp: State<'_, McpContext>,
+    request: ChatRequest,
+    channel: String,
+    options: Option<AgentOptions>,
+) -> Result<AgentRun, String> {
+    let app = window.app_handle().clone();
+    let label = window.label().to_string();
+    let stores = mcp.stores().await;
+    let context = RunContext {
+        app: &app,
+        window: &label,
+        channel: &channel,
+        manager: &mcp.manager,
+        approvals: &stores.approvals,
+        audit_log: &stores.audit_log,
+    };
+    let on_delta = |delta: &str| {
+        events::emit_token(&app, &label, TokenEvent {
+            channel: channel.clone(),
+            delta: delta.to_string(),
+        });
+    };
+    let run = agent::run(&context, request, options.unwrap_or_default(), on_delta);
+    cancellable(request_key(&label, &channel), "The agent run", run).await
+}
+
+/// Command to stop a chat, agent run or pull the calling window started, by
+/// its channel or pull id
+#[tauri::command]
+pub async fn llm_cancel<R: Runtime>(window: tauri::WebviewWindow<R>, id: String) -> Result<bool, String> {
+    let cancel_tx = REQUESTS.lock().map_err(|e| e.to_string())?.remove(&request_key(window.label(), &id));
+    Ok(cancel_tx.is_some_and(|cancel_tx| cancel_tx.send(()).is_ok()))
+}
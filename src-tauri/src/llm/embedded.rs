@@ -0,0 +1,352 @@
+//! GGUF models run in-process with llama.cpp.
+//!
+//! Models are `.gguf` files in the app's `models` folder, or another folder
+//! the provider names; a chat names one by its file name. Inference needs the
+//! `local-inference` feature, and offloading to the GPU the
+//! `local-inference-metal` or `local-inference-cuda` one. Without them the
+//! models can still be listed, but chats fail saying how mlFace was built.
+//!
+//! The last model used stays loaded for the next chat. Generation runs on a
+//! blocking thread and hands each piece to the chat as it is produced; when
+//! the chat is dropped, as `llm_cancel` does, generation stops with it.
+
+use crate::llm::{ChatMessage, ChatReply, ModelInfo};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Tokens of context unless the provider sets `context_size`. Models
+/// trained on less get what they were trained on.
+pub const DEFAULT_CONTEXT_SIZE: u32 = 4096;
+
+/// What the model runs on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Device {
+    /// The GPU if mlFace was built for one, the CPU otherwise
+    #[default]
+    Auto,
+    Cpu,
+    Metal,
+    Cuda,
+}
+
+impl Device {
+    /// The layers of the model to offload to the GPU
+    #[cfg_attr(not(feature = "local-inference"), allow(dead_code))]
+    fn gpu_layers(self) -> Result<u32, String> {
+        // More than any model has, so all are offloaded
+        const ALL: u32 = 999;
+        let gpu = cfg!(any(feature = "local-inference-metal", feature = "local-inference-cuda"));
+        match self {
+            Self::Auto if gpu => Ok(ALL),
+            Self::Auto | Self::Cpu => Ok(0),
+            Self::Metal if cfg!(feature = "local-inference-metal") => Ok(ALL),
+            Self::Cuda if cfg!(feature = "local-inference-cuda") => Ok(ALL),
+            Self::Metal => Err("mlFace was built without Metal support".to_string()),
+            Self::Cuda => Err("mlFace was built without CUDA support".to_string()),
+        }
+    }
+}
+
+/// Whether this build can run models
+pub const AVAILABLE: bool = cfg!(feature = "local-inference");
+
+fn not_built() -> String {
+    "mlFace was built without embedded inference; enable the local-inference feature".to_string()
+}
+
+/// The models in `dir`
+#[derive(Debug, Clone)]
+pub struct Embedded {
+    dir: PathBuf,
+    device: Device,
+    context_size: Option<u32>,
+}
+
+impl Embedded {
+    pub fn new(dir: PathBuf, device: Device, context_size: Option<u32>) -> Self {
+        Self { dir, device, context_size }
+    }
+    
+    /// The `.gguf` files in the models folder, by file name
+    pub fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.dir.display(), e)),
+        };
+        let mut models: Vec<ModelInfo> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| is_gguf(&entry.path()))
+            .map(|entry| ModelInfo {
+                id: entry.file_name().to_string_lossy().to_string(),
+                size: entry.metadata().ok().map(|metadata| metadata.len()),
+                details: Value::Null,
+            })
+            .collect();
+        models.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(models)
+    }
+    
+    /// The file of `model`, a file name in the models folder or a path
+    fn model_path(&self, model: &str) -> Result<PathBuf, String> {
+        let path = Path::new(model);
+        let path = if path.is_absolute() { path.to_path_buf() } else { self.dir.join(path) };
+        if !is_gguf(&path) {
+            return Err(format!("No GGUF model at {}", path.display()));
+        }
+        Ok(path)
+    }
+    
+    /// The tokens of context chats run with, unless the model was trained on
+    /// fewer
+    pub fn context_window(&self) -> u64 {
+        u64::from(self.context_size.unwrap_or(DEFAULT_CONTEXT_SIZE))
+    }
+    
+    /// The tokens of each of `texts`, by the tokenizer of `model`, which is
+    /// loaded for it
+    pub async fn count_tokens(&self, model: &str, texts: &[String]) -> Result<Vec<u64>, String> {
+        if !AVAILABLE {
+            return Err(not_built());
+        }
+        let path = self.model_path(model)?;
+        let device = self.device;
+        let texts = texts.to_vec();
+        tauri::async_runtime::spawn_blocking(move || inference::count_tokens(&path, device, &texts))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+    
+    /// Send `messages` to `model`, passing on each piece of the reply as it
+    /// is generated. `options` may set `temperature`, `max_tokens` and
+    /// `seed`.
+    pub async fn chat(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        options: Option<Value>,
+        mut on_delta: impl FnMut(&str),
+    ) -> Result<ChatReply, String> {
+        if !AVAILABLE {
+            return Err(not_built());
+        }
+        let path = self.model_path(model)?;
+        let settings = inference::Settings::new(self.device, self.context_size, options.as_ref());
+        let messages = messages.to_vec();
+        let (piece_tx, mut piece_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        // Sending fails once the chat is dropped, which stops generating
+        let generation = tauri::async_runtime::spawn_blocking(move || {
+            inference::generate(&path, &settings, &messages, |piece| piece_tx.send(piece.to_string()).is_ok())
+        });
+        while let Some(piece) = piece_rx.recv().await {
+            on_delta(&piece);
+        }
+        generation.await.map_err(|e| e.to_string())?
+    }
+}
+
+fn is_gguf(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("gguf"))
+}
+
+#[cfg(feature = "local-inference")]
+mod inference {
+    use super::{Device, DEFAULT_CONTEXT_SIZE};
+    use crate::llm::{ChatMessage, ChatReply};
+    use llama_cpp_2::context::params::LlamaContextParams;
+    use llama_cpp_2::llama_backend::LlamaBackend;
+    use llama_cpp_2::llama_batch::LlamaBatch;
+    use llama_cpp_2::model::params::LlamaModelParams;
+    use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel, Special};
+    use llama_cpp_2::sampling::LlamaSampler;
+    use serde_json::Value;
+    use std::num::NonZeroU32;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex, OnceLock};
+    
+    // llama.cpp is set up once per process
+    static BACKEND: OnceLock<Result<LlamaBackend, String>> = OnceLock::new();
+    // The model last used, with the device it was loaded for
+    static LOADED: Mutex<Option<(PathBuf, Device, Arc<LlamaModel>)>> = Mutex::new(None);
+    
+    /// How a reply is generated
+    pub struct Settings {
+        device: Device,
+        context_size: Option<u32>,
+        temperature: f32,
+        max_tokens: Option<u32>,
+        seed: u32,
+    }
+    
+    impl Settings {
+        pub fn new(device: Device, context_size: Option<u32>, options: Option<&Value>) -> Self {
+            let option = |key: &str| options.and_then(|options| options.get(key));
+            Self {
+                device,
+                context_size,
+                temperature: option("temperature").and_then(Value::as_f64).unwrap_or(0.8) as f32,
+                max_tokens: option("max_tokens").and_then(Value::as_u64).map(|tokens| tokens as u32),
+                seed: option("seed").and_then(Value::as_u64).unwrap_or_else(rand_seed) as u32,
+            }
+        }
+    }
+    
+    fn rand_seed() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default()
+    }
+    
+    fn backend() -> Result<&'static LlamaBackend, String> {
+        BACKEND.get_or_init(|| LlamaBackend::init().map_err(|e| e.to_string())).as_ref().map_err(Clone::clone)
+    }
+    
+    fn model(path: &Path, device: Device) -> Result<Arc<LlamaModel>, String> {
+        let mut loaded = LOADED.lock().map_err(|e| e.to_string())?;
+        if let Some((loaded_path, loaded_device, model)) = loaded.as_ref() {
+            if loaded_path == path && *loaded_device == device {
+                return Ok(model.clone());
+            }
+        }
+        // Free the model before loading the next, they rarely fit together
+        *loaded = None;
+        let params = LlamaModelParams::default().with_n_gpu_layers(device.gpu_layers()?);
+        let model = LlamaModel::load_from_file(backend()?, path, &params)
+            .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+        let model = Arc::new(model);
+        *loaded = Some((path.to_path_buf(), device, model.clone()));
+        Ok(model)
+    }
+    
+    /// The tokens of each of `texts` by the model at `path`
+    pub fn count_tokens(path: &Path, device: Device, texts: &[String]) -> Result<Vec<u64>, String> {
+        let model = model(path, device)?;
+        texts
+            .iter()
+            .map(|text| model.str_to_token(text, AddBos::Never).map(|tokens| tokens.len() as u64).map_err(|e| e.to_string()))
+            .collect()
+    }
+    
+    /// Generate the reply of the model at `path` to `messages`, handing each
+    /// piece to `on_piece` until it returns false
+    pub fn generate(
+        path: &Path,
+        settings: &Settings,
+        messages: &[ChatMessage],
+        mut on_piece: impl FnMut(&str) -> bool,
+    ) -> Result<ChatReply, String> {
+        let model = model(path, settings.device)?;
+        let template = model.chat_template(None).map_err(|e| format!("The model has no chat template: {}", e))?;
+        let chat = messages
+            .iter()
+            .map(|message| LlamaChatMessage::new(message.role.clone(), message.content.clone()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        let prompt = model.apply_chat_template(&template, &chat, true).map_err(|e| e.to_string())?;
+        let tokens = model.str_to_token(&prompt, AddBos::Always).map_err(|e| e.to_string())?;
+        
+        let context_size = settings.context_size.unwrap_or(DEFAULT_CONTEXT_SIZE).min(model.n_ctx_train());
+        if tokens.len() >= context_size as usize {
+            return Err(format!(
+                "The conversation is {} tokens, which leaves no room for a reply in a context of {}",
+                tokens.len(),
+                context_size
+            ));
+        }
+        let params = LlamaContextParams::default()
+            .with_n_ctx(NonZeroU32::new(context_size))
+            .with_n_batch(context_size);
+        let mut context = model.new_context(backend()?, params).map_err(|e| e.to_string())?;
+        
+        let mut batch = LlamaBatch::new(context_size as usize, 1);
+        let last = tokens.len() - 1;
+        for (position, token) in tokens.iter().enumerate() {
+            batch.add(*token, position as i32, &[0], position == last).map_err(|e| e.to_string())?;
+        }
+        context.decode(&mut batch).map_err(|e| e.to_string())?;
+        
+        let mut sampler = if settings.temperature <= 0.0 {
+            LlamaSampler::greedy()
+        } else {
+            LlamaSampler::chain_simple([LlamaSampler::temp(settings.temperature), LlamaSampler::dist(settings.seed)])
+        };
+        let room = context_size - tokens.len() as u32;
+        let max_tokens = settings.max_tokens.map_or(room, |max_tokens| max_tokens.min(room));
+        let mut reply = ChatReply { prompt_tokens: Some(tokens.len() as u64), ..ChatReply::default() };
+        let mut position = tokens.len() as i32;
+        let mut generated = 0;
+        // Bytes of a character split across tokens
+        let mut pending = Vec::new();
+        let done_reason = loop {
+            let token = sampler.sample(&context, batch.n_tokens() - 1);
+            if model.is_eog_token(token) {
+                break "stop";
+            }
+            generated += 1;
+            pending.extend(model.token_to_bytes(token, Special::Tokenize).map_err(|e| e.to_string())?);
+            let piece = complete_text(&mut pending);
+            if !piece.is_empty() {
+                reply.message.content.push_str(&piece);
+                if !on_piece(&piece) {
+                    break "cancelled";
+                }
+            }
+            if generated >= max_tokens {
+                break "length";
+            }
+            
+            batch.clear();
+            batch.add(token, position, &[0], true).map_err(|e| e.to_string())?;
+            position += 1;
+            context.decode(&mut batch).map_err(|e| e.to_string())?;
+        };
+        reply.done_reason = Some(done_reason.to_string());
+        reply.completion_tokens = Some(generated as u64);
+        Ok(reply)
+    }
+    
+    /// The text of `bytes` up to a character that is still incomplete, which
+    /// is left for the next token
+    fn complete_text(bytes: &mut Vec<u8>) -> String {
+        let complete = match std::str::from_utf8(bytes) {
+            Ok(_) => bytes.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            // Not UTF-8 at all; pass it on as well as it goes
+            Err(_) => bytes.len(),
+        };
+        let text: Vec<u8> = bytes.drain(..complete).collect();
+        String::from_utf8_lossy(&text).to_string()
+    }
+}
+
+#[cfg(not(feature = "local-inference"))]
+mod inference {
+    use super::{not_built, Device};
+    use crate::llm::{ChatMessage, ChatReply};
+    use serde_json::Value;
+    use std::path::Path;
+    
+    pub struct Settings;
+    
+    impl Settings {
+        pub fn new(_device: Device, _context_size: Option<u32>, _options: Option<&Value>) -> Self {
+            Self
+        }
+    }
+    
+    pub fn count_tokens(_path: &Path, _device: Device, _texts: &[String]) -> Result<Vec<u64>, String> {
+        Err(not_built())
+    }
+    
+    pub fn generate(
+        _path: &Path,
+        _settings: &Settings,
+        _messages: &[ChatMessage],
+        _on_piece: impl FnMut(&str) -> bool,
+    ) -> Result<ChatReply, String> {
+        Err(not_built())
+    }
+}
@@ -0,0 +1,198 @@
+//! Local inference servers: llama.cpp's `llama-server` and LM Studio.
+//!
+//! Both serve OpenAI's chat completions API under `/v1`, so chats and model
+//! lists go through the OpenAI client. What they add is a notion of health:
+//! llama.cpp answers `/health` with 503 while it loads its model, and a
+//! server that is not running gets an error saying how to start it, rather
+//! than a refused connection.
+
+use crate::llm::api::{checked, error_message};
+use crate::llm::openai::OpenAi;
+use crate::llm::{ChatMessage, ChatReply, ModelInfo};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Where `llama-server` listens unless configured otherwise
+pub const LLAMA_CPP_URL: &str = "http://localhost:8080";
+
+/// Where LM Studio's server listens unless configured otherwise
+pub const LM_STUDIO_URL: &str = "http://localhost:1234";
+
+/// Which local server is spoken to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalServerKind {
+    LlamaCpp,
+    LmStudio,
+}
+
+impl LocalServerKind {
+    pub fn default_url(self) -> &'static str {
+        match self {
+            Self::LlamaCpp => LLAMA_CPP_URL,
+            Self::LmStudio => LM_STUDIO_URL,
+        }
+    }
+    
+    fn name(self) -> &'static str {
+        match self {
+            Self::LlamaCpp => "llama.cpp server",
+            Self::LmStudio => "LM Studio server",
+        }
+    }
+    
+    fn start_hint(self) -> &'static str {
+        match self {
+            Self::LlamaCpp => "Start it with `llama-server -m <model>.gguf`",
+            Self::LmStudio => "Start it in LM Studio's Developer tab or with `lms server start`",
+        }
+    }
+}
+
+/// What a health check found
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerHealth {
+    /// Whether the server answered at all
+    pub running: bool,
+    /// Whether it is ready to chat, with its model loaded
+    pub ready: bool,
+    /// Why it is not ready, or how to start it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// A local server of `kind` at `url`
+#[derive(Debug, Clone)]
+pub struct LocalServer {
+    kind: LocalServerKind,
+    url: String,
+    api: OpenAi,
+    client: reqwest::Client,
+}
+
+impl LocalServer {
+    pub fn new(kind: LocalServerKind, url: Option<&str>, client: reqwest::Client) -> Self {
+        let url = url.map(str::trim).filter(|url| !url.is_empty()).unwrap_or(kind.default_url());
+        // Accept the API's URL as well as the server's
+        let url = url.trim_end_matches('/').trim_end_matches("/v1").to_string();
+        let api = OpenAi::new(&format!("{}/v1", url), None, client.clone());
+        Self { kind, url, api, client }
+    }
+    
+    /// Whether the server is up and has a model loaded
+    pub async fn health(&self) -> ServerHealth {
+        if self.kind == LocalServerKind::LlamaCpp {
+            match self.client.get(format!("{}/health", self.url)).send().await {
+                Ok(response) if response.status().is_success() => {
+                    return ServerHealth { running: true, ready: true, message: None };
+                }
+                // 503 while the model loads
+                Ok(response) if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE => {
+                    let message = response.json::<Value>().await.ok().and_then(|body| error_message(&body));
+                    return ServerHealth {
+                        running: true,
+                        ready: false,
+                        message: Some(message.unwrap_or_else(|| "Loading model".to_string())),
+                    };
+                }
+                // Older servers have no health endpoint; try the API
+                Ok(_) => {}
+                Err(_) => return self.not_running(),
+            }
+        }
+        match self.client.get(format!("{}/v1/models", self.url)).send().await {
+            Ok(response) if response.status().is_success() => ServerHealth { running: true, ready: true, message: None },
+            Ok(response) => ServerHealth {
+                running: true,
+                ready: false,
+                message: Some(format!("The {} answered {}", self.kind.name(), response.status())),
+            },
+            Err(_) => self.not_running(),
+        }
+    }
+    
+    fn not_running(&self) -> ServerHealth {
+        ServerHealth { running: false, ready: false, message: Some(self.not_running_message()) }
+    }
+    
+    fn not_running_message(&self) -> String {
+        format!("No {} is running at {}. {}.", self.kind.name(), self.url, self.kind.start_hint())
+    }
+    
+    /// The models the server offers
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let result = self.api.list_models().await;
+        self.explained(result).await
+    }
+    
+    /// The tokens of each of `texts`, by the server's model. Only llama.cpp
+    /// has a tokenizer endpoint.
+    pub async fn count_tokens(&self, texts: &[String]) -> Result<Vec<u64>, String> {
+        if self.kind != LocalServerKind::LlamaCpp {
+            return Err(format!("The {} does not count tokens", self.kind.name()));
+        }
+        let mut counts = Vec::with_capacity(texts.len());
+        for text in texts {
+            let result = async {
+                let response = self
+                    .client
+                    .post(format!("{}/tokenize", self.url))
+                    .json(&json!({ "content": text }))
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let document = checked(response).await?.json::<Value>().await.map_err(|e| e.to_string())?;
+                document["tokens"].as_array().map(|tokens| tokens.len() as u64).ok_or_else(|| "Unexpected answer to /tokenize".to_string())
+            }
+            .await;
+            counts.push(self.explained(result).await?);
+        }
+        Ok(counts)
+    }
+    
+    /// The tokens of context `model` has, as the server reports it:
+    /// llama.cpp the size of its slots, LM Studio the length `model` is
+    /// loaded with or, if it is not loaded, the most it takes
+    pub async fn context_window(&self, model: &str) -> Result<Option<u64>, String> {
+        let url = match self.kind {
+            LocalServerKind::LlamaCpp => format!("{}/props", self.url),
+            LocalServerKind::LmStudio => format!("{}/api/v0/models/{}", self.url, model),
+        };
+        let result = async {
+            let response = self.client.get(url).send().await.map_err(|e| e.to_string())?;
+            checked(response).await?.json::<Value>().await.map_err(|e| e.to_string())
+        }
+        .await;
+        let document = self.explained(result).await?;
+        let window = match self.kind {
+            LocalServerKind::LlamaCpp => document["default_generation_settings"]["n_ctx"].as_u64().or(document["n_ctx"].as_u64()),
+            LocalServerKind::LmStudio => document["loaded_context_length"].as_u64().or(document["max_context_length"].as_u64()),
+        };
+        Ok(window)
+    }
+    
+    /// Send `messages` to `model` like `OpenAi::chat`
+    pub async fn chat(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        options: Option<Value>,
+        on_delta: impl FnMut(&str),
+    ) -> Result<ChatReply, String> {
+        let result = self.api.chat(model, messages, options, on_delta).await;
+        self.explained(result).await
+    }
+    
+    /// `result`, with a failure put down to the server not running or still
+    /// loading when that is why
+    async fn explained<T>(&self, result: Result<T, String>) -> Result<T, String> {
+        let Err(error) = result else {
+            return result;
+        };
+        let health = self.health().await;
+        match health.message {
+            Some(message) if !health.running => Err(message),
+            Some(message) if !health.ready => Err(format!("The {} is not ready: {}", self.kind.name(), message)),
+            _ => Err(error),
+        }
+    }
+}
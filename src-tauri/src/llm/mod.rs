@@ -0,0 +1,155 @@
+//! Model backends.
+//!
+//! Each provider module talks to one kind of API. They share the chat types
+//! here, so the commands and the frontend handle every provider alike.
+
+mod api;
+pub mod agent;
+pub mod anthropic;
+pub mod commands;
+pub mod embedded;
+pub mod local;
+pub mod ollama;
+pub mod openai;
+pub mod tokens;
+
+use embedded::Device;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokens::ContextOptions;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// `system`, `user`, `assistant` or `tool`
+    pub role: String,
+    #[serde(default)]
+    pub content: String,
+    /// Tools the assistant asked to call
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For `tool` messages, the call `content` is the result of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+/// A call of a tool a model asked for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// The finished reply of a chat
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatReply {
+    pub message: ChatMessage,
+    /// Why generation stopped, such as `stop`, `length` or `tool_use`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done_reason: Option<String>,
+    /// Tokens in the prompt and in the reply, when the provider reports them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_tokens: Option<u64>,
+}
+
+impl Default for ChatReply {
+    fn default() -> Self {
+        Self {
+            message: ChatMessage {
+                role: "assistant".to_string(),
+                content: String::new(),
+                tool_calls: Vec::new(),
+                tool_call_id: None,
+            },
+            done_reason: None,
+            prompt_tokens: None,
+            completion_tokens: None,
+        }
+    }
+}
+
+/// A chat to send: `messages` for `model` of `provider`, by default the
+/// local Ollama instance. `options` are passed to the provider, such as
+/// `temperature`. With `context`, the messages are fitted into the model's
+/// context window before they are sent.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatRequest {
+    #[serde(default)]
+    pub provider: Option<Provider>,
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub options: Option<Value>,
+    #[serde(default)]
+    pub context: Option<ContextOptions>,
+}
+
+/// A model a provider offers
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelInfo {
+    pub id: String,
+    /// Bytes on disk, for local models
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Whatever else the provider says about it
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub details: Value,
+}
+
+/// Where a chat is sent
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Provider {
+    /// An Ollama instance, the local one unless `url` is given
+    Ollama {
+        #[serde(default)]
+        url: Option<String>,
+    },
+    /// Any endpoint speaking OpenAI's chat completions API, such as OpenAI,
+    /// Groq, OpenRouter or vLLM. `api_key` may be a `{{secret:NAME}}`
+    /// reference to a key in the keychain.
+    #[serde(rename = "openai")]
+    OpenAi {
+        base_url: String,
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+    /// A llama.cpp `llama-server`, the local one on its default port unless
+    /// `url` is given
+    LlamaCpp {
+        #[serde(default)]
+        url: Option<String>,
+    },
+    /// LM Studio's server, the local one unless `url` is given
+    LmStudio {
+        #[serde(default)]
+        url: Option<String>,
+    },
+    /// GGUF models run in-process, from `models_dir` or the app's models
+    /// folder. `context_size` is in tokens.
+    Embedded {
+        #[serde(default)]
+        models_dir: Option<String>,
+        #[serde(default)]
+        device: Device,
+        #[serde(default)]
+        context_size: Option<u32>,
+    },
+    /// Anthropic's Messages API, at `base_url` if it is proxied. `api_key`
+    /// may be a `{{secret:NAME}}` reference too.
+    Anthropic {
+        #[serde(default)]
+        base_url: Option<String>,
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+}
+
+impl Default for Provider {
+    fn default() -> Self {
+        Self::Ollama { url: None }
+    }
+}
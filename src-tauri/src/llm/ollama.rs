@@ -0,0 +1,235 @@
+//! Client for a local Ollama instance.
+//!
+//! Ollama answers streaming endpoints with one JSON object per line: `pull`
+//! passes on each progress update and `chat` each piece of the reply as they
+//! arrive. A line carrying an `error` ends the stream with that error. Tool
+//! calls come whole, without ids, so they are given ids here.
+
+use crate::llm::{ChatMessage, ChatReply, ToolCall};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Where Ollama listens unless configured otherwise
+pub const DEFAULT_URL: &str = "http://localhost:11434";
+
+/// Tokens of context Ollama runs models with unless `num_ctx` is set
+pub const DEFAULT_NUM_CTX: u64 = 4096;
+
+/// A model available locally
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    /// Bytes on disk
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub digest: String,
+    #[serde(default)]
+    pub modified_at: String,
+    #[serde(default)]
+    pub details: Value,
+}
+
+/// One progress update of a pull. `total` and `completed` count the bytes of
+/// the layer named by `digest` while it downloads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullStatus {
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed: Option<u64>,
+}
+
+/// An Ollama instance at `url`
+#[derive(Debug, Clone)]
+pub struct Ollama {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl Ollama {
+    pub fn new(url: Option<&str>, client: reqwest::Client) -> Self {
+        let url = url.map(str::trim).filter(|url| !url.is_empty()).unwrap_or(DEFAULT_URL);
+        Self { url: url.trim_end_matches('/').to_string(), client }
+    }
+    
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/api/{}", self.url, path)
+    }
+    
+    /// The models pulled so far
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>, String> {
+        let document: Value = self
+            .client
+            .get(self.endpoint("tags"))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| unreachable(&self.url, e))?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        serde_json::from_value(document.get("models").cloned().unwrap_or(Value::Array(Vec::new())))
+            .map_err(|e| format!("Unexpected model list from Ollama: {}", e))
+    }
+    
+    /// Download `model`, passing on each progress update
+    pub async fn pull(&self, model: &str, mut on_status: impl FnMut(PullStatus)) -> Result<(), String> {
+        let response = self.post("pull", serde_json::json!({ "model": model, "stream": true })).await?;
+        read_lines(response, |line| {
+            let status: PullStatus = serde_json::from_value(line).map_err(|e| format!("Unexpected pull status from Ollama: {}", e))?;
+            on_status(status);
+            Ok(())
+        })
+        .await
+    }
+    
+    /// The tokens of context `model` runs with given `options`: their
+    /// `num_ctx`, or the model's, or Ollama's default if the model was
+    /// trained on more
+    pub async fn context_window(&self, model: &str, options: Option<&Value>) -> Result<u64, String> {
+        if let Some(num_ctx) = options.and_then(|options| options["num_ctx"].as_u64()) {
+            return Ok(num_ctx);
+        }
+        let show: Value = self
+            .post("show", serde_json::json!({ "model": model }))
+            .await?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        // The Modelfile's parameters, one `name value` per line
+        let parameters = show["parameters"].as_str().unwrap_or_default();
+        let num_ctx = parameters.lines().find_map(|line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["num_ctx", value] => value.parse().ok(),
+            _ => None,
+        });
+        if let Some(num_ctx) = num_ctx {
+            return Ok(num_ctx);
+        }
+        // `<architecture>.context_length`
+        let trained = show["model_info"]
+            .as_object()
+            .and_then(|info| info.iter().find(|(key, _)| key.ends_with(".context_length")))
+            .and_then(|(_, length)| length.as_u64());
+        Ok(trained.map_or(DEFAULT_NUM_CTX, |trained| trained.min(DEFAULT_NUM_CTX)))
+    }
+    
+    /// Send `messages` to `model`, passing on each piece of the reply as it
+    /// is generated. `options` are Ollama's model parameters, such as
+    /// `temperature`, except for `tools`, which are offered to the model.
+    pub async fn chat(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        options: Option<Value>,
+        mut on_delta: impl FnMut(&str),
+    ) -> Result<ChatReply, String> {
+        let mut body = serde_json::json!({ "model": model, "messages": request_messages(messages), "stream": true });
+        if let Some(mut options) = options {
+            if let Some(tools) = options.as_object_mut().and_then(|options| options.remove("tools")) {
+                body["tools"] = tools;
+            }
+            body["options"] = options;
+        }
+        let response = self.post("chat", body).await?;
+        
+        let mut reply = ChatReply::default();
+        read_lines(response, |line| {
+            if let Some(delta) = line["message"]["content"].as_str().filter(|delta| !delta.is_empty()) {
+                reply.message.content.push_str(delta);
+                on_delta(delta);
+            }
+            for call in line["message"]["tool_calls"].as_array().into_iter().flatten() {
+                let id = format!("call_{}", reply.message.tool_calls.len());
+                reply.message.tool_calls.push(ToolCall {
+                    id,
+                    name: call["function"]["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: call["function"]["arguments"].clone(),
+                });
+            }
+            if line["done"].as_bool() == Some(true) {
+                reply.done_reason = line["done_reason"].as_str().map(str::to_string);
+                reply.prompt_tokens = line["prompt_eval_count"].as_u64();
+                reply.completion_tokens = line["eval_count"].as_u64();
+            }
+            Ok(())
+        })
+        .await?;
+        Ok(reply)
+    }
+    
+    async fn post(&self, path: &str, body: Value) -> Result<reqwest::Response, String> {
+        let response = self
+            .client
+            .post(self.endpoint(path))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| unreachable(&self.url, e))?;
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        // Ollama explains failures, such as an unknown model, in the body
+        let status = response.status();
+        let message = response
+            .json::<Value>()
+            .await
+            .ok()
+            .and_then(|body| body["error"].as_str().map(str::to_string))
+            .unwrap_or_else(|| status.to_string());
+        Err(format!("Ollama: {}", message))
+    }
+}
+
+/// `messages` as Ollama takes them, with the arguments of tool calls as
+/// objects and tool results without the id of their call
+fn request_messages(messages: &[ChatMessage]) -> Vec<Value> {
+    messages
+        .iter()
+        .map(|message| {
+            let mut request = serde_json::json!({ "role": message.role, "content": message.content });
+            if !message.tool_calls.is_empty() {
+                let calls: Vec<Value> = message
+                    .tool_calls
+                    .iter()
+                    .map(|call| serde_json::json!({ "function": { "name": call.name, "arguments": call.arguments } }))
+                    .collect();
+                request["tool_calls"] = Value::Array(calls);
+            }
+            request
+        })
+        .collect()
+}
+
+fn unreachable(url: &str, e: reqwest::Error) -> String {
+    format!("Failed to reach Ollama at {}: {}", url, e)
+}
+
+/// Hand each JSON line of a streamed response to `on_line`
+async fn read_lines(response: reqwest::Response, mut on_line: impl FnMut(Value) -> Result<(), String>) -> Result<(), String> {
+    let mut stream = response.bytes_stream();
+    let mut buffer = Vec::new();
+    let mut handle = |line: &[u8]| -> Result<(), String> {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return Ok(());
+        }
+        let line: Value = serde_json::from_slice(line).map_err(|e| format!("Unexpected response from Ollama: {}", e))?;
+        if let Some(error) = line["error"].as_str() {
+            return Err(format!("Ollama: {}", error));
+        }
+        on_line(line)
+    };
+    
+    while let Some(chunk) = stream.next().await {
+        buffer.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+        while let Some(end) = buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            handle(&line)?;
+        }
+    }
+    handle(&buffer)
+}
@@ -0,0 +1,162 @@
+//! Client for endpoints speaking OpenAI's chat completions API.
+//!
+//! OpenAI, Groq, OpenRouter, vLLM and many others accept the same requests,
+//! so one client serves them all given the base URL, like
+//! `https://api.openai.com/v1`. Replies are streamed as server-sent events,
+//! each `data:` line a chunk of the completion, until `data: [DONE]`. Tool
+//! calls stream in pieces too, their arguments as a JSON string in parts.
+
+use crate::llm::api::{checked, model_list, read_events, unreachable};
+use crate::llm::tokens::{known_context_window, listed_context_length};
+use crate::llm::{ChatMessage, ChatReply, ModelInfo, ToolCall};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// An OpenAI-compatible API at `base_url`
+#[derive(Debug, Clone)]
+pub struct OpenAi {
+    base_url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl OpenAi {
+    pub fn new(base_url: &str, api_key: Option<String>, client: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim().trim_end_matches('/').to_string(),
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            client,
+        }
+    }
+    
+    fn request(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key.trim()),
+            None => request,
+        }
+    }
+    
+    /// The models the endpoint offers
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let response = self
+            .request(self.client.get(format!("{}/models", self.base_url)))
+            .send()
+            .await
+            .map_err(|e| unreachable(&self.base_url, e))?;
+        let document = checked(response).await?.json::<Value>().await.map_err(|e| e.to_string())?;
+        Ok(model_list(document))
+    }
+    
+    /// The tokens of context `model` has: known for OpenAI's models, and
+    /// for others what the endpoint lists with the model, if anything
+    pub async fn context_window(&self, model: &str) -> Result<Option<u64>, String> {
+        if let Some(window) = known_context_window(model) {
+            return Ok(Some(window));
+        }
+        let models = self.list_models().await?;
+        Ok(models.iter().find(|info| info.id == model).and_then(|info| listed_context_length(&info.details)))
+    }
+    
+    /// Send `messages` to `model`, passing on each piece of the reply as it
+    /// is generated. `options`, such as `temperature` or `max_tokens`, are
+    /// added to the request as they are.
+    pub async fn chat(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        options: Option<Value>,
+        mut on_delta: impl FnMut(&str),
+    ) -> Result<ChatReply, String> {
+        let mut body = json!({
+            "model": model,
+            "messages": request_messages(messages),
+            "stream": true,
+            // Usage comes in a last chunk of its own
+            "stream_options": { "include_usage": true },
+        });
+        if let Some(Value::Object(options)) = options {
+            for (key, value) in options {
+                body[key] = value;
+            }
+        }
+        let response = self
+            .request(self.client.post(format!("{}/chat/completions", self.base_url)))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| unreachable(&self.base_url, e))?;
+        let response = checked(response).await?;
+        
+        let mut reply = ChatReply::default();
+        // Tool calls by their index, with the JSON of their arguments as it
+        // arrives
+        let mut tool_calls: BTreeMap<u64, (ToolCall, String)> = BTreeMap::new();
+        read_events(response, |chunk| {
+            let choice = &chunk["choices"][0];
+            if let Some(delta) = choice["delta"]["content"].as_str().filter(|delta| !delta.is_empty()) {
+                reply.message.content.push_str(delta);
+                on_delta(delta);
+            }
+            for call in choice["delta"]["tool_calls"].as_array().into_iter().flatten() {
+                let (tool_call, arguments) = tool_calls.entry(call["index"].as_u64().unwrap_or_default()).or_insert_with(|| {
+                    let call = ToolCall { id: String::new(), name: String::new(), arguments: Value::Object(Map::new()) };
+                    (call, String::new())
+                });
+                if let Some(id) = call["id"].as_str() {
+                    tool_call.id = id.to_string();
+                }
+                if let Some(name) = call["function"]["name"].as_str() {
+                    tool_call.name.push_str(name);
+                }
+                arguments.push_str(call["function"]["arguments"].as_str().unwrap_or_default());
+            }
+            if let Some(reason) = choice["finish_reason"].as_str() {
+                reply.done_reason = Some(reason.to_string());
+            }
+            if let Some(usage) = chunk.get("usage").filter(|usage| usage.is_object()) {
+                reply.prompt_tokens = usage["prompt_tokens"].as_u64();
+                reply.completion_tokens = usage["completion_tokens"].as_u64();
+            }
+            Ok(())
+        })
+        .await?;
+        
+        for (mut call, arguments) in tool_calls.into_values() {
+            if !arguments.trim().is_empty() {
+                call.arguments = serde_json::from_str(&arguments).map_err(|e| format!("Invalid arguments for tool {}: {}", call.name, e))?;
+            }
+            reply.message.tool_calls.push(call);
+        }
+        Ok(reply)
+    }
+}
+
+/// `messages` as the API takes them: the tool calls of the assistant as
+/// functions with their arguments in a JSON string, and tool results by the
+/// id of their call
+fn request_messages(messages: &[ChatMessage]) -> Vec<Value> {
+    messages
+        .iter()
+        .map(|message| {
+            let mut request = json!({ "role": message.role, "content": message.content });
+            if !message.tool_calls.is_empty() {
+                let calls: Vec<Value> = message
+                    .tool_calls
+                    .iter()
+                    .map(|call| {
+                        json!({
+                            "id": call.id,
+                            "type": "function",
+                            "function": { "name": call.name, "arguments": call.arguments.to_string() },
+                        })
+                    })
+                    .collect();
+                request["tool_calls"] = Value::Array(calls);
+            }
+            if let Some(id) = &message.tool_call_id {
+                request["tool_call_id"] = Value::String(id.clone());
+            }
+            request
+        })
+        .collect()
+}
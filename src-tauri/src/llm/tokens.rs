@@ -0,0 +1,308 @@
+//! Counting tokens and fitting conversations into context windows.
+//!
+//! OpenAI's models are counted with the tiktoken encodings they use. Local
+//! models are counted by what runs them where it can tell: a llama.cpp server
+//! tokenizes with its model, and embedded models with theirs. The rest,
+//! Anthropic's models among them as their tokenizer is not published, are
+//! estimated with `o200k_base`, and their counts are marked as estimates.
+//!
+//! A count covers the text and tool calls of each message, a few tokens per
+//! message for the role and separators of the chat format, and the tools
+//! offered. A conversation too long for the window, less the room kept for
+//! the reply, loses its oldest messages, or has them summarized by the model.
+//! Leading system messages and the last turn, from the last user message on,
+//! are always kept.
+
+use crate::llm::commands::{chat, context_window, count_tokens};
+use crate::llm::{ChatMessage, ChatRequest};
+use crate::mcp::server::McpServerManager;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::ops::Range;
+use tauri::{AppHandle, Runtime};
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::CoreBPE;
+
+/// Tokens of the chat format around each message, for its role and
+/// separators
+pub const MESSAGE_OVERHEAD: u64 = 4;
+
+/// Tokens of the chat format that start the reply
+pub const REPLY_OVERHEAD: u64 = 3;
+
+/// Tokens kept for the reply unless the context options or `max_tokens` set
+/// them
+pub const DEFAULT_REPLY_TOKENS: u64 = 1024;
+
+/// Instructions for summarizing the messages that no longer fit
+const SUMMARY_PROMPT: &str = "Summarize the conversation below in a few short paragraphs. Keep the facts, \
+    decisions, results of tool calls and open questions the rest of the conversation may depend on. \
+    Reply with the summary only.";
+
+/// A tiktoken encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// GPT-4o, GPT-4.1 and the o-series
+    O200k,
+    /// GPT-4 and GPT-3.5
+    Cl100k,
+}
+
+impl Encoding {
+    /// The encoding of OpenAI's `model`, also under a router's prefix such as
+    /// `openai/gpt-4o`, or `None` if it is not one of OpenAI's
+    pub fn for_model(model: &str) -> Option<Self> {
+        let model = model.rsplit('/').next().unwrap_or(model);
+        match get_tokenizer(model)? {
+            Tokenizer::O200kBase => Some(Self::O200k),
+            Tokenizer::Cl100kBase => Some(Self::Cl100k),
+            // Models from before chat completions
+            _ => None,
+        }
+    }
+    
+    fn bpe(self) -> &'static CoreBPE {
+        match self {
+            Self::O200k => tiktoken_rs::o200k_base_singleton(),
+            Self::Cl100k => tiktoken_rs::cl100k_base_singleton(),
+        }
+    }
+    
+    /// The tokens of each of `texts`
+    pub fn count(self, texts: &[String]) -> Vec<u64> {
+        let bpe = self.bpe();
+        texts.iter().map(|text| bpe.encode_ordinary(text).len() as u64).collect()
+    }
+}
+
+/// The text of `request` to count: that of each message, then the tools it
+/// offers, if any, as JSON
+pub fn texts(request: &ChatRequest) -> Vec<String> {
+    let mut texts: Vec<String> = request.messages.iter().map(message_text).collect();
+    if let Some(tools) = request.options.as_ref().and_then(|options| options.get("tools")) {
+        texts.push(tools.to_string());
+    }
+    texts
+}
+
+/// The content of `message` with the names and arguments of its tool calls
+fn message_text(message: &ChatMessage) -> String {
+    let mut text = message.content.clone();
+    for call in &message.tool_calls {
+        text.push('\n');
+        text.push_str(&call.name);
+        text.push_str(&call.arguments.to_string());
+    }
+    text
+}
+
+/// The tokens of a request
+#[derive(Debug, Clone)]
+pub struct TokenCount {
+    /// Of each message, with its `MESSAGE_OVERHEAD`
+    pub messages: Vec<u64>,
+    /// Of the tools offered
+    pub tools: u64,
+    /// Whether the model's own tokenizer counted them, rather than another
+    /// one estimating
+    pub exact: bool,
+}
+
+impl TokenCount {
+    /// The count of `request` from the counts of its `texts`
+    pub fn new(request: &ChatRequest, counts: Vec<u64>, exact: bool) -> Self {
+        let mut messages = counts;
+        let tools = messages.split_off(request.messages.len().min(messages.len()));
+        Self {
+            messages: messages.into_iter().map(|tokens| tokens + MESSAGE_OVERHEAD).collect(),
+            tools: tools.into_iter().sum(),
+            exact,
+        }
+    }
+    
+    /// Tokens of the whole prompt, up to the start of the reply
+    pub fn total(&self) -> u64 {
+        self.messages.iter().sum::<u64>() + self.tools + REPLY_OVERHEAD
+    }
+}
+
+/// What happens to a conversation too long for the context window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overflow {
+    /// It is sent as it is, for the provider to reject or cut
+    #[default]
+    Keep,
+    /// Its oldest messages are left out
+    Truncate,
+    /// Its oldest messages are replaced by a summary the model writes
+    Summarize,
+}
+
+/// How a chat is fitted into the model's context window
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ContextOptions {
+    /// Tokens of context, in place of what the provider says the model has
+    #[serde(default)]
+    pub window: Option<u64>,
+    /// Tokens kept for the reply; by default the `max_tokens` of the options
+    /// or `DEFAULT_REPLY_TOKENS`
+    #[serde(default)]
+    pub reply_tokens: Option<u64>,
+    #[serde(default)]
+    pub overflow: Overflow,
+}
+
+/// How a chat measures up to the model's context window
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenEstimate {
+    /// Tokens of the prompt: the messages, the tools and the start of the
+    /// reply
+    pub prompt_tokens: u64,
+    /// Whether the model's own tokenizer counted them
+    pub exact: bool,
+    /// Tokens of context the model has, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    /// Tokens kept for the reply
+    pub reply_tokens: u64,
+    /// Messages left out or summarized to fit
+    pub removed: usize,
+}
+
+/// The messages to remove from `messages`, counted in `count`, for the rest
+/// to fit in `budget` tokens: the oldest after the leading system messages
+/// and before the last turn, up to a user message for the rest to start
+/// with, so no tool result is left without its call
+pub fn overflowing(messages: &[ChatMessage], count: &TokenCount, budget: u64) -> Result<Range<usize>, String> {
+    let mut total = count.total();
+    let first = messages.iter().take_while(|message| message.role == "system").count();
+    let last = messages.iter().rposition(|message| message.role == "user").unwrap_or(messages.len().saturating_sub(1)).max(first);
+    let mut end = first;
+    while end < last && (total > budget || (end > first && messages[end].role != "user")) {
+        total -= count.messages[end];
+        end += 1;
+    }
+    if total > budget {
+        return Err(format!(
+            "The conversation is {} tokens without its older messages, more than the {} the context window has room for",
+            total, budget
+        ));
+    }
+    Ok(first..end)
+}
+
+/// The tokens to keep for the reply to `request`
+fn reply_tokens(request: &ChatRequest, context: &ContextOptions) -> u64 {
+    let max_tokens = request.options.as_ref().and_then(|options| options["max_tokens"].as_u64());
+    context.reply_tokens.or(max_tokens).unwrap_or(DEFAULT_REPLY_TOKENS)
+}
+
+/// `request` made to fit its model's context window as `context` says, with
+/// an estimate of the tokens it takes
+pub(crate) async fn fit<R: Runtime>(
+    app: &AppHandle<R>,
+    manager: &McpServerManager,
+    mut request: ChatRequest,
+    context: &ContextOptions,
+) -> Result<(ChatRequest, TokenEstimate), String> {
+    let window = match context.window {
+        Some(window) => Some(window),
+        None => context_window(app, manager, &request).await?,
+    };
+    let reply_tokens = reply_tokens(&request, context);
+    let mut count = count_tokens(app, manager, &request).await?;
+    let mut removed = 0;
+    
+    if let Some(window) = window.filter(|_| context.overflow != Overflow::Keep) {
+        let budget = window.saturating_sub(reply_tokens);
+        let range = overflowing(&request.messages, &count, budget)?;
+        if !range.is_empty() {
+            removed = range.len();
+            let left_out: Vec<ChatMessage> = request.messages.drain(range.clone()).collect();
+            if context.overflow == Overflow::Summarize {
+                let summary = summarize(app, manager, &request, &left_out, budget).await?;
+                request.messages.insert(range.start, summary);
+            }
+            count = count_tokens(app, manager, &request).await?;
+            // A summary too long to fit goes as well
+            if context.overflow == Overflow::Summarize && count.total() > budget {
+                request.messages.remove(range.start);
+                count = count_tokens(app, manager, &request).await?;
+            }
+        }
+    }
+    
+    let estimate = TokenEstimate { prompt_tokens: count.total(), exact: count.exact, context_window: window, reply_tokens, removed };
+    Ok((request, estimate))
+}
+
+/// A system message summarizing `messages`, written by the model of
+/// `request`. The newest of them that fit in `budget` tokens are summarized.
+async fn summarize<R: Runtime>(
+    app: &AppHandle<R>,
+    manager: &McpServerManager,
+    request: &ChatRequest,
+    messages: &[ChatMessage],
+    budget: u64,
+) -> Result<ChatMessage, String> {
+    let lines: Vec<String> = messages.iter().map(|message| format!("{}: {}", message.role, message_text(message))).collect();
+    // Tokens are only estimated here; keep a margin for the prompt
+    let counts = Encoding::O200k.count(&lines);
+    let mut room = budget.saturating_sub(budget / 10 + MESSAGE_OVERHEAD * 2);
+    let mut start = lines.len();
+    while start > 0 && counts[start - 1] <= room {
+        start -= 1;
+        room -= counts[start];
+    }
+    
+    let summary_request = ChatRequest {
+        provider: request.provider.clone(),
+        model: request.model.clone(),
+        messages: vec![
+            ChatMessage { role: "system".to_string(), content: SUMMARY_PROMPT.to_string(), tool_calls: Vec::new(), tool_call_id: None },
+            ChatMessage { role: "user".to_string(), content: lines[start..].join("\n\n"), tool_calls: Vec::new(), tool_call_id: None },
+        ],
+        options: request.options.clone().map(|mut options| {
+            // The summary is text only
+            if let Some(options) = options.as_object_mut() {
+                options.remove("tools");
+            }
+            options
+        }),
+        context: None,
+    };
+    let reply = chat(app, manager, &summary_request, |_| {}).await?;
+    Ok(ChatMessage {
+        role: "system".to_string(),
+        content: format!("Summary of the earlier conversation:\n\n{}", reply.message.content.trim()),
+        tool_calls: Vec::new(),
+        tool_call_id: None,
+    })
+}
+
+/// The context window of OpenAI's and Anthropic's `model`, where it is known
+pub fn known_context_window(model: &str) -> Option<u64> {
+    // Longer prefixes first
+    const WINDOWS: [(&str, u64); 11] = [
+        ("gpt-4.1", 1_047_576),
+        ("gpt-4o", 128_000),
+        ("chatgpt-4o", 128_000),
+        ("gpt-4-turbo", 128_000),
+        ("gpt-4-32k", 32_768),
+        ("gpt-4", 8_192),
+        ("gpt-3.5-turbo", 16_385),
+        ("o1", 200_000),
+        ("o3", 200_000),
+        ("o4", 200_000),
+        ("claude", 200_000),
+    ];
+    let model = model.rsplit('/').next().unwrap_or(model);
+    WINDOWS.iter().find(|(prefix, _)| model.starts_with(prefix)).map(|(_, window)| *window)
+}
+
+/// The context length in a model's details: the `context_length`,
+/// `context_window` or `max_model_len` hosted APIs list with their models
+pub fn listed_context_length(details: &Value) -> Option<u64> {
+    ["context_length", "context_window", "max_model_len"].iter().find_map(|key| details[*key].as_u64())
+}
@@ -0,0 +1,371 @@
+//! Consent before a tool runs.
+//!
+//! Every tool call is held until the user answers an `mcp:tool-approval`
+//! event, sent to the window that made the call, with "allow once", "allow
+//! for this session", "always allow this tool", "always allow this server",
+//! "deny" or "never allow". Session grants last until the app quits. The
+//! other decisions are kept in `<config>/tool_approvals.json` and answer the
+//! question from then on; a grant for the whole server comes before any
+//! decision about one of its tools. All of them can be listed and revoked.
+
+use crate::events::{self, ToolApprovalEvent};
+use crate::mcp::context::McpContext;
+use crate::mcp::types::McpError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Runtime, State};
+use tokio::sync::oneshot;
+
+pub const APPROVALS_FILE: &str = "tool_approvals.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    AllowOnce,
+    /// Allow this tool of this server until the app quits
+    Session,
+    /// Allow this tool of this server from now on
+    Always,
+    /// Allow every tool of this server from now on
+    AlwaysServer,
+    Deny,
+    /// Deny this tool of this server from now on, without asking
+    Never,
+}
+
+/// How long a remembered decision lasts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionScope {
+    /// Allowed until the app quits
+    Session,
+    /// Allowed for good
+    Always,
+    /// Allowed for good, along with every other tool of the server
+    Server,
+    /// Denied for good
+    Never,
+}
+
+/// A remembered decision about a tool, or about all tools of a server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolPermission {
+    pub server: String,
+    /// `None` for a grant covering the whole server
+    pub tool: Option<String>,
+    pub scope: PermissionScope,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovedTool {
+    pub server: String,
+    pub tool: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ApprovalsFile {
+    /// Servers whose tools are all always allowed
+    servers: BTreeSet<String>,
+    /// Tools that are always allowed, by server
+    always: BTreeMap<String, BTreeSet<String>>,
+    /// Tools that are never allowed, by server
+    never: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Tools by server
+type ToolSets = BTreeMap<String, BTreeSet<String>>;
+
+fn contains(sets: &ToolSets, server: &str, tool: &str) -> bool {
+    sets.get(server).is_some_and(|tools| tools.contains(tool))
+}
+
+/// Add `tool` of `server` to `sets`, or take it out. Returns whether that
+/// changed anything.
+fn set(sets: &mut ToolSets, server: &str, tool: &str, present: bool) -> bool {
+    if present {
+        return sets.entry(server.to_string()).or_default().insert(tool.to_string());
+    }
+    let Some(tools) = sets.get_mut(server) else {
+        return false;
+    };
+    let removed = tools.remove(tool);
+    if tools.is_empty() {
+        sets.remove(server);
+    }
+    removed
+}
+
+/// Tool calls waiting for consent and the tools that no longer need it
+#[derive(Default)]
+pub struct ToolApprovals {
+    /// Where decisions other than session grants are saved; kept in memory
+    /// only without one
+    path: Option<PathBuf>,
+    /// Saved decisions
+    saved: Mutex<ApprovalsFile>,
+    /// Tools allowed until the app quits
+    session: Mutex<ToolSets>,
+    next_id: AtomicU32,
+    pending: Mutex<HashMap<u32, PendingCall>>,
+}
+
+struct PendingCall {
+    server: String,
+    tool: String,
+    decision_tx: oneshot::Sender<ApprovalDecision>,
+}
+
+/// Forgets a pending call when its caller stops waiting, e.g. on cancellation
+struct PendingGuard<'a> {
+    approvals: &'a ToolApprovals,
+    id: u32,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut pending) = self.approvals.pending.lock() {
+            pending.remove(&self.id);
+        }
+    }
+}
+
+impl ToolApprovals {
+    /// Approvals saved in `path`. A missing or unreadable file starts empty.
+    pub fn load(path: PathBuf) -> Self {
+        let file: ApprovalsFile = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        
+        Self {
+            path: Some(path),
+            saved: Mutex::new(file),
+            ..Default::default()
+        }
+    }
+    
+    /// The remembered decision about `tool` of `server`, if any
+    fn permission(&self, server: &str, tool: &str) -> Option<PermissionScope> {
+        let saved = self.saved.lock().ok()?;
+        if saved.servers.contains(server) {
+            return Some(PermissionScope::Server);
+        }
+        if contains(&saved.never, server, tool) {
+            return Some(PermissionScope::Never);
+        }
+        if contains(&saved.always, server, tool) {
+            return Some(PermissionScope::Always);
+        }
+        let session = self.session.lock().ok()?;
+        contains(&session, server, tool).then_some(PermissionScope::Session)
+    }
+    
+    /// Remember `scope` for `tool` of `server` in place of any earlier
+    /// decision about the tool, or forget them all with `None`. Returns
+    /// whether a decision was forgotten. The file is only written when a
+    /// saved decision changed.
+    fn remember(&self, server: &str, tool: &str, scope: Option<PermissionScope>) -> Result<bool, String> {
+        let mut saved = self.saved.lock().map_err(|e| e.to_string())?;
+        let mut session = self.session.lock().map_err(|e| e.to_string())?;
+        let changed_always = set(&mut saved.always, server, tool, scope == Some(PermissionScope::Always));
+        let changed_never = set(&mut saved.never, server, tool, scope == Some(PermissionScope::Never));
+        let changed_session = set(&mut session, server, tool, scope == Some(PermissionScope::Session));
+        if changed_always || changed_never {
+            self.save(&saved)?;
+        }
+        Ok(scope.is_none() && (changed_always || changed_never || changed_session))
+    }
+    
+    /// Allow every tool of `server` from now on, or take that back. Returns
+    /// whether that changed anything.
+    fn remember_server(&self, server: &str, allowed: bool) -> Result<bool, String> {
+        let mut saved = self.saved.lock().map_err(|e| e.to_string())?;
+        let changed = if allowed {
+            saved.servers.insert(server.to_string())
+        } else {
+            saved.servers.remove(server)
+        };
+        if changed {
+            self.save(&saved)?;
+        }
+        Ok(changed)
+    }
+    
+    fn save(&self, file: &ApprovalsFile) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+    
+    /// Wait until the user allows `tool` to run, asking the window labelled
+    /// `window` unless a remembered decision answers
+    pub async fn ask<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        window: &str,
+        server: &str,
+        tool: &str,
+        arguments: Option<&Value>,
+    ) -> Result<(), McpError> {
+        match self.permission(server, tool) {
+            Some(PermissionScope::Session | PermissionScope::Always | PermissionScope::Server) => return Ok(()),
+            Some(PermissionScope::Never) => {
+                return Err(McpError::Denied(format!("{} on {} is never allowed", tool, server)));
+            }
+            None => {}
+        }
+        
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (decision_tx, decision_rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|e| McpError::internal(e.to_string()))?
+            .insert(id, PendingCall {
+                server: server.to_string(),
+                tool: tool.to_string(),
+                decision_tx,
+            });
+        let _guard = PendingGuard { approvals: self, id };
+        
+        events::emit_tool_approval(app, window, ToolApprovalEvent {
+            id,
+            server: server.to_string(),
+            tool: tool.to_string(),
+            arguments: arguments.cloned(),
+        });
+        
+        match decision_rx.await {
+            Ok(
+                ApprovalDecision::AllowOnce
+                | ApprovalDecision::Session
+                | ApprovalDecision::Always
+                | ApprovalDecision::AlwaysServer,
+            ) => Ok(()),
+            Ok(ApprovalDecision::Deny | ApprovalDecision::Never) | Err(_) => Err(McpError::Denied(format!(
+                "the call to {} on {} was not approved",
+                tool, server
+            ))),
+        }
+    }
+}
+
+/// Command to answer a pending tool call
+#[tauri::command]
+pub async fn mcp_resolve_tool_approval(
+    context: State<'_, McpContext>,
+    id: u32,
+    decision: ApprovalDecision,
+) -> Result<(), String> {
+    let approvals = &context.stores().await.approvals;
+    let call = approvals
+        .pending
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&id)
+        .ok_or_else(|| format!("Tool call {} is no longer waiting for approval", id))?;
+    
+    let scope = match decision {
+        ApprovalDecision::Session => Some(PermissionScope::Session),
+        ApprovalDecision::Always => Some(PermissionScope::Always),
+        ApprovalDecision::Never => Some(PermissionScope::Never),
+        ApprovalDecision::AlwaysServer => {
+            approvals.remember_server(&call.server, true)?;
+            None
+        }
+        ApprovalDecision::AllowOnce | ApprovalDecision::Deny => None,
+    };
+    if scope.is_some() {
+        approvals.remember(&call.server, &call.tool, scope)?;
+    }
+    
+    // The caller may have been cancelled in the meantime
+    let _ = call.decision_tx.send(decision);
+    Ok(())
+}
+
+/// Command to list the tools that run without asking, sorted by server
+#[tauri::command]
+pub async fn mcp_list_tool_approvals(context: State<'_, McpContext>) -> Result<Vec<ApprovedTool>, String> {
+    let approvals = &context.stores().await.approvals;
+    let saved = approvals.saved.lock().map_err(|e| e.to_string())?;
+    Ok(saved
+        .always
+        .iter()
+        .flat_map(|(server, tools)| {
+            tools.iter().map(move |tool| ApprovedTool {
+                server: server.clone(),
+                tool: tool.clone(),
+            })
+        })
+        .collect())
+}
+
+/// Command to always allow a tool, or to go back to asking for it
+#[tauri::command]
+pub async fn mcp_set_tool_approval(
+    context: State<'_, McpContext>,
+    server: String,
+    tool: String,
+    always: bool,
+) -> Result<(), String> {
+    let approvals = &context.stores().await.approvals;
+    let scope = always.then_some(PermissionScope::Always);
+    approvals.remember(&server, &tool, scope).map(|_| ())
+}
+
+/// Command to list every remembered decision: servers whose tools are all
+/// allowed, tools allowed for the session or always, and tools never allowed,
+/// sorted by server and tool. A server's grant comes before its tools.
+#[tauri::command]
+pub async fn mcp_list_permissions(context: State<'_, McpContext>) -> Result<Vec<ToolPermission>, String> {
+    let approvals = &context.stores().await.approvals;
+    let saved = approvals.saved.lock().map_err(|e| e.to_string())?;
+    let session = approvals.session.lock().map_err(|e| e.to_string())?;
+    let servers = saved.servers.iter().map(|server| ToolPermission {
+        server: server.clone(),
+        tool: None,
+        scope: PermissionScope::Server,
+    });
+    let mut permissions: Vec<ToolPermission> = [
+        (&*session, PermissionScope::Session),
+        (&saved.always, PermissionScope::Always),
+        (&saved.never, PermissionScope::Never),
+    ]
+    .into_iter()
+    .flat_map(|(sets, scope)| {
+        sets.iter().flat_map(move |(server, tools)| {
+            tools.iter().map(move |tool| ToolPermission { server: server.clone(), tool: Some(tool.clone()), scope })
+        })
+    })
+    .chain(servers)
+    .collect();
+    permissions.sort_by(|a, b| (&a.server, &a.tool).cmp(&(&b.server, &b.tool)));
+    Ok(permissions)
+}
+
+/// Command to forget the remembered decision about a tool, or without `tool`
+/// the grant for the whole server, so it is asked about again. Returns false
+/// if there was none.
+#[tauri::command]
+pub async fn mcp_revoke_permission(
+    context: State<'_, McpContext>,
+    server: String,
+    tool: Option<String>,
+) -> Result<bool, String> {
+    let approvals = &context.stores().await.approvals;
+    match tool {
+        Some(tool) => approvals.remember(&server, &tool, None),
+        None => approvals.remember_server(&server, false),
+    }
+}
@@ -0,0 +1,39 @@
+//! Launch the servers marked `autostart` when the app starts. Disabled servers
+//! are skipped. Their progress reaches the frontend through the lifecycle
+//! events the manager's state changes are forwarded as.
+//!
+//! With prewarming on, every enabled server is started this way, so even the
+//! first tool call to a server does not wait for it to launch and initialize.
+
+use crate::mcp::server::{McpServerManager, DEFAULT_START_PARALLELISM};
+use std::sync::Arc;
+
+/// Load the saved config and start the autostart servers concurrently, or all
+/// enabled servers with `prewarm`
+pub async fn start_autostart_servers(manager: Arc<McpServerManager>, prewarm: bool) {
+    if let Err(e) = manager.load_default_config().await {
+        log::error!("Failed to load MCP server config: {}", e);
+        return;
+    }
+    
+    start_configured_servers(manager, prewarm).await;
+}
+
+/// Start the autostart servers of the config already loaded, or all enabled
+/// servers with `prewarm`
+pub async fn start_configured_servers(manager: Arc<McpServerManager>, prewarm: bool) {
+    let names: Vec<String> = manager
+        .get_servers()
+        .await
+        .into_iter()
+        .filter(|server| (server.autostart || prewarm) && server.enabled)
+        .map(|server| server.name)
+        .collect();
+    
+    let report = manager.start_servers(names, DEFAULT_START_PARALLELISM).await;
+    for result in report {
+        if let Some(e) = result.error {
+            log::warn!("Failed to autostart MCP server {}: {}", result.server, e);
+        }
+    }
+}
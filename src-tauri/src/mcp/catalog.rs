@@ -0,0 +1,86 @@
+//! Browsing the curated server catalog and installing its entries.
+//!
+//! The catalog bundled with the app is used until `mcp_refresh_catalog`
+//! downloads a newer one, which is cached in the app's cache directory from
+//! then on. Installing an entry stores the secrets it asks for in the
+//! keychain, then registers and saves the server like an accepted install
+//! link; it is not started.
+
+use crate::mcp::http::TlsSettings;
+use crate::mcp::import::{ConflictPolicy, ImportReport};
+use crate::mcp::server::McpServerManager;
+use mlface_mcp::catalog::{Catalog, CatalogEntry, CATALOG_FILE, CATALOG_URL};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+fn cache_path<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    app.path().app_cache_dir().ok().map(|dir| dir.join(CATALOG_FILE))
+}
+
+/// The cached catalog, or the bundled one
+async fn load<R: Runtime>(app: &AppHandle<R>) -> Result<Catalog, String> {
+    let path = cache_path(app);
+    tauri::async_runtime::spawn_blocking(move || Catalog::load(path.as_deref()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Command to list the catalog's servers matching `query`, or all of them
+#[tauri::command]
+pub async fn mcp_get_catalog<R: Runtime>(app: AppHandle<R>, query: Option<String>) -> Result<Vec<CatalogEntry>, String> {
+    let catalog = load(&app).await?;
+    Ok(catalog.search(query.as_deref().unwrap_or_default()).into_iter().cloned().collect())
+}
+
+/// Command to download the latest catalog and use it from now on. Returns
+/// how many servers it offers.
+#[tauri::command]
+pub async fn mcp_refresh_catalog<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+) -> Result<usize, String> {
+    let client = manager.http_clients().client(&TlsSettings::default()).map_err(|e| e.to_string())?;
+    let catalog = Catalog::fetch(&client, CATALOG_URL).await.map_err(|e| e.to_string())?;
+    
+    let path = cache_path(&app).ok_or_else(|| "The app cache directory is not available".to_string())?;
+    let servers = catalog.servers.len();
+    tauri::async_runtime::spawn_blocking(move || catalog.save(&path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(servers)
+}
+
+/// Command to install the catalog entry `id` as a server named `name`, or
+/// after the entry, renamed if the name is taken. `values` holds the entry's
+/// env vars by name; the command fails naming the required ones missing.
+#[tauri::command]
+pub async fn mcp_install_catalog_entry<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    id: String,
+    name: Option<String>,
+    args: Option<Vec<String>>,
+    values: Option<HashMap<String, String>>,
+) -> Result<ImportReport, String> {
+    let catalog = load(&app).await?;
+    let entry = catalog
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| format!("{} is not in the server catalog", id))?;
+    
+    // Keychain writes may wait on an unlock prompt
+    let secrets = manager.secret_store();
+    let config = tauri::async_runtime::spawn_blocking(move || {
+        entry.install(name.as_deref(), &args.unwrap_or_default(), &values.unwrap_or_default(), secrets.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+    
+    let report = manager.import_servers(vec![config], ConflictPolicy::Rename).await;
+    manager.save_default_config().await.map_err(|e| e.to_string())?;
+    Ok(report)
+}
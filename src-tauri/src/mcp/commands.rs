@@ -1,158 +1,821 @@
-use crate::mcp::server::{McpServerConfig, SERVER_MANAGER};
+use crate::events::{self, DiscoveryFinishedEvent, PackageInstallEvent, ResourceChunkEvent, ServerCrash, ServerLogEvent, ToolStreamChunk, ToolStreamEvent, TrafficEvent};
+use crate::mcp::audit::{AuditEntry, AuditLog, AuditQuery};
+use crate::mcp::bundle::{ConfigBundle, ExportReport};
+use crate::mcp::context::{McpContext, McpStores};
+use crate::mcp::dashboard::Dashboard;
+use crate::mcp::http::TlsSettings;
+use crate::mcp::import::{ConflictPolicy, ImportPreview, ImportReport, ImportSource, SkippedServer};
+use crate::mcp::integrity::{BinaryCheck, BinaryPin};
+use crate::mcp::logs::LogLine;
+use crate::mcp::mdns;
+use crate::mcp::metrics::MetricsReport;
+use crate::mcp::package::{self, NodeRuntime, PackageSpec, PythonRuntime};
+use crate::mcp::rate_limit::{RateLimit, RateLimitReport};
+use crate::mcp::resource::{self, DEFAULT_CHUNK_BYTES};
+use crate::mcp::traffic::TrafficFilter;
+use crate::mcp::spill::SpillDir;
+use crate::mcp::updates;
+use crate::mcp::server::{
+    ConnectionState, McpServerConfig, McpServerManager, ResourceBudgets, ServerSettings, StartResult,
+    DEFAULT_START_PARALLELISM,
+};
 use crate::mcp::types::*;
+use futures::stream::{self, StreamExt};
+use mlface_mcp::transport::OutputStream;
+use mlface_mcp::updates::{PackageUpdate, PackageUpdates};
 use serde::{Serialize, Deserialize};
 use serde_json::Value;
-use std::collections::HashMap;
-use tauri::{Runtime, Manager};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Runtime, Manager, State};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::oneshot;
+
+lazy_static::lazy_static! {
+    // Servers whose output is currently forwarded as `mcp:server-log` events
+    static ref FOLLOWED_LOGS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+    // Tool calls that can be cancelled, by `call_key`
+    static ref TOOL_CALLS: Mutex<HashMap<String, oneshot::Sender<()>>> = Mutex::new(HashMap::new());
+    // Package installs that can be cancelled, by `call_key`
+    static ref PACKAGE_INSTALLS: Mutex<HashMap<String, oneshot::Sender<()>>> = Mutex::new(HashMap::new());
+    // Forwards traffic as `mcp:traffic` events while an inspector follows it
+    static ref TRAFFIC_FOLLOWER: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::new(None);
+}
+
+/// Identifies a cancellable call. Ids are chosen by the window that started
+/// the call, so one window cannot cancel another's calls.
+fn call_key(window: &str, call_id: &str) -> String {
+    format!("{}/{}", window, call_id)
+}
+
+/// Append a finished call to the audit log. Failing to record it is logged
+/// rather than failing the call.
+fn audit(log: &AuditLog, entry: AuditEntry) {
+    if let Err(e) = log.record(&entry) {
+        log::warn!("Failed to record {} on {} in the audit log: {}", entry.target, entry.server, e);
+    }
+}
+
+/// Move large binary content to files before it is passed to the frontend;
+/// content that could not be moved is passed as it is
+async fn spill(spill_dir: &SpillDir, content: &mut [Content]) {
+    if let Err(e) = spill_dir.spill(content).await {
+        log::warn!("Failed to move large content to a file: {}", e);
+    }
+}
 
 /// Command to register an MCP server
 #[tauri::command]
 pub async fn mcp_register_server(
-    name: String,
-    command: String,
-    args: Vec<String>,
-    env: Option<HashMap<String, String>>,
+    manager: State<'_, Arc<McpServerManager>>,
+    mut config: McpServerConfig,
 ) -> Result<(), String> {
-    let config = McpServerConfig {
-        name: name.clone(),
-        command,
-        args,
-        env: env.unwrap_or_default(),
-        process: None,
-    };
+    // Fields left empty in the form are unset
+    config.cwd = config.cwd.filter(|cwd| !cwd.is_empty());
+    config.package = config.package.filter(|package| !package.name.is_empty());
+    config.description = config.description.filter(|description| !description.is_empty());
     
-    SERVER_MANAGER.0.register_server(config)
+    manager.register_server(config)
         .await
         .map_err(|e| e.to_string())
 }
 
 /// Command to unregister an MCP server
 #[tauri::command]
-pub async fn mcp_unregister_server(name: String) -> Result<(), String> {
-    SERVER_MANAGER.0.unregister_server(&name)
+pub async fn mcp_unregister_server(manager: State<'_, Arc<McpServerManager>>, name: String) -> Result<(), String> {
+    manager.unregister_server(&name)
         .await
         .map_err(|e| e.to_string())
 }
 
 /// Command to start an MCP server
 #[tauri::command]
-pub async fn mcp_start_server(name: String) -> Result<(), String> {
-    SERVER_MANAGER.0.start_server(&name)
+pub async fn mcp_start_server(manager: State<'_, Arc<McpServerManager>>, name: String) -> Result<(), String> {
+    manager.start_server(&name)
         .await
         .map_err(|e| e.to_string())
 }
 
+/// Command to start every enabled MCP server concurrently. Returns whether
+/// each one started instead of failing on the first error.
+#[tauri::command]
+pub async fn mcp_start_all(
+    manager: State<'_, Arc<McpServerManager>>,
+    max_parallel: Option<usize>,
+) -> Result<Vec<StartResult>, String> {
+    Ok(manager
+        .start_all(max_parallel.unwrap_or(DEFAULT_START_PARALLELISM))
+        .await)
+}
+
 /// Command to stop an MCP server
 #[tauri::command]
-pub async fn mcp_stop_server(name: String) -> Result<(), String> {
-    SERVER_MANAGER.0.stop_server(&name)
+pub async fn mcp_stop_server(manager: State<'_, Arc<McpServerManager>>, name: String) -> Result<(), String> {
+    manager.stop_server(&name)
         .await
         .map_err(|e| e.to_string())
 }
 
-/// Command to get all registered MCP servers
+/// Command to restart an MCP server with its current configuration
 #[tauri::command]
-pub async fn mcp_get_servers() -> Result<Vec<McpServerConfig>, String> {
-    Ok(SERVER_MANAGER.0.get_servers().await)
+pub async fn mcp_restart_server(manager: State<'_, Arc<McpServerManager>>, name: String) -> Result<(), String> {
+    manager.restart_server(&name)
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// Command to test connection to an MCP server or endpoint
+/// Command to enable or disable an MCP server without removing its configuration
 #[tauri::command]
-pub async fn mcp_test_connection(url: String) -> Result<bool, String> {
-    SERVER_MANAGER.0.test_connection(&url)
+pub async fn mcp_set_server_enabled(
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+    enabled: bool,
+) -> Result<(), String> {
+    manager.set_server_enabled(&name, enabled)
         .await
         .map_err(|e| e.to_string())
 }
 
-/// Command to discover MCP servers
+/// Command to change the timeouts, restart limit, health checks and log
+/// verbosity of an MCP server. They apply from its next start.
 #[tauri::command]
-pub async fn mcp_discover_servers<R: Runtime>(app: tauri::AppHandle<R>, path: Option<String>) -> Result<Vec<McpServerConfig>, String> {
-    // If path is None, use default paths
-    let search_path = if let Some(p) = path {
-        p
-    } else {
-        // Use the app handle's environment path
-        let app_dir = app.path().app_data_dir()
-            .map_err(|e| e.to_string())?;
-        
-        app_dir.to_string_lossy().to_string()
-    };
-    
-    SERVER_MANAGER.0.discover_servers(&search_path)
+pub async fn mcp_set_server_settings(
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+    settings: ServerSettings,
+) -> Result<(), String> {
+    manager.set_server_settings(&name, settings)
         .await
         .map_err(|e| e.to_string())
 }
 
-/// Command to list tools from an MCP server
+/// Command to pin a local server to its executable as it is now. With
+/// `enforce`, a server whose binary changed since is refused at launch rather
+/// than started with a warning.
 #[tauri::command]
-pub async fn mcp_list_tools(server_name: String) -> Result<ListToolsResult, String> {
-    let client = SERVER_MANAGER.0.get_client(&server_name)
-        .await
-        .map_err(|e| e.to_string())?;
-    
-    client.list_tools()
+pub async fn mcp_pin_server_binary(
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+    enforce: Option<bool>,
+) -> Result<BinaryPin, String> {
+    manager.pin_binary(&name, enforce.unwrap_or(false))
         .await
         .map_err(|e| e.to_string())
 }
 
-/// Command to call a tool on an MCP server
+/// Command to compare a server's executable to its pin
 #[tauri::command]
-pub async fn mcp_call_tool(server_name: String, tool_name: String, args: Option<Value>) -> Result<CallToolResult, String> {
-    let client = SERVER_MANAGER.0.get_client(&server_name)
+pub async fn mcp_check_server_binary(
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+) -> Result<BinaryCheck, String> {
+    manager.check_binary(&name)
         .await
-        .map_err(|e| e.to_string())?;
-    
-    client.call_tool(&tool_name, args)
+        .map_err(|e| e.to_string())
+}
+
+/// Command to find the Node.js installation servers run from npm packages
+/// need. The error says what to install when it is missing.
+#[tauri::command]
+pub async fn mcp_check_node() -> Result<NodeRuntime, String> {
+    tauri::async_runtime::spawn_blocking(package::detect_node)
         .await
+        .map_err(|e| e.to_string())?
         .map_err(|e| e.to_string())
 }
 
-/// Command to list resources from an MCP server
+/// Command to find `uvx` or `pipx`, which servers run from Python packages
+/// need. The error says what to install when neither is there.
 #[tauri::command]
-pub async fn mcp_list_resources(server_name: String) -> Result<ListResourcesResult, String> {
-    let client = SERVER_MANAGER.0.get_client(&server_name)
+pub async fn mcp_check_python() -> Result<PythonRuntime, String> {
+    tauri::async_runtime::spawn_blocking(package::detect_python)
         .await
-        .map_err(|e| e.to_string())?;
-    
-    client.list_resources()
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Command to check whether `package` is installed already, so the first
+/// start of its server doesn't have to download it. Nothing is run.
+#[tauri::command]
+pub async fn mcp_check_package(package: PackageSpec) -> Result<bool, String> {
+    tauri::async_runtime::spawn_blocking(move || package::is_installed(&package))
         .await
         .map_err(|e| e.to_string())
 }
 
-/// Command to read a resource from an MCP server
+/// Command to install `package` with npm, uv or pipx, streaming the
+/// installer's output to the calling window as `mcp:package-install` events
+/// tagged with `install_id`. Only that window can stop it, with
+/// `mcp_cancel_package_install(install_id)`.
+#[tauri::command]
+pub async fn mcp_install_package<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    window: tauri::WebviewWindow<R>,
+    package: PackageSpec,
+    install_id: String,
+) -> Result<(), String> {
+    install_package(&app, window.label(), &package, &install_id).await
+}
+
+/// Install `package`, streaming the output to the window `label` and letting
+/// it cancel the install by `install_id`
+async fn install_package<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    label: &str,
+    package: &PackageSpec,
+    install_id: &str,
+) -> Result<(), String> {
+    let key = call_key(label, install_id);
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    if let Ok(mut installs) = PACKAGE_INSTALLS.lock() {
+        installs.insert(key.clone(), cancel_tx);
+    }
+    
+    let output = |stream: OutputStream, line: &str| {
+        events::emit_package_install(app, label, PackageInstallEvent {
+            id: install_id.to_string(),
+            stream,
+            line: line.to_string(),
+        });
+    };
+    // Dropping the install stops the installer
+    let result = tokio::select! {
+        result = package::install(package, output) => result.map_err(|e| e.to_string()),
+        _ = cancel_rx => Err(format!("Installing {} was cancelled", package.name)),
+    };
+    
+    if let Ok(mut installs) = PACKAGE_INSTALLS.lock() {
+        installs.remove(&key);
+    }
+    result
+}
+
+/// Command to look for newer releases of the packages servers run. Returns
+/// the servers with an update available, which `mcp_get_server_status` shows
+/// from then on.
 #[tauri::command]
-pub async fn mcp_read_resource(server_name: String, uri: String) -> Result<ReadResourceResult, String> {
-    let client = SERVER_MANAGER.0.get_client(&server_name)
+pub async fn mcp_check_server_updates<R: Runtime>(app: tauri::AppHandle<R>) -> Result<Vec<String>, String> {
+    updates::check_for_updates(&app).await
+}
+
+/// Command to update the package server `name` runs to its latest release,
+/// streaming the installer's output like `mcp_install_package`. A pinned
+/// version is moved to the new one and saved. A running server is restarted
+/// on the new version. Returns the version installed.
+#[tauri::command]
+pub async fn mcp_update_server_package<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    window: tauri::WebviewWindow<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    package_updates: State<'_, PackageUpdates>,
+    name: String,
+    install_id: String,
+) -> Result<String, String> {
+    let mut config = manager
+        .get_servers()
         .await
-        .map_err(|e| e.to_string())?;
+        .into_iter()
+        .find(|server| server.name == name)
+        .ok_or_else(|| format!("Server {} not found", name))?;
+    let package = config.package.clone().ok_or_else(|| format!("Server {} does not run a package", name))?;
+    
+    let client = manager.http_clients().client(&TlsSettings::default()).map_err(|e| e.to_string())?;
+    let latest = package_updates.latest(&client, &package).await.map_err(|e| e.to_string())?;
+    install_package(&app, window.label(), &package.clone().with_version(latest.clone()), &install_id).await?;
+    
+    if package.version.is_some() {
+        config.package = Some(package.with_version(latest.clone()));
+        manager.register_server(config.clone()).await.map_err(|e| e.to_string())?;
+        manager.save_default_config().await.map_err(|e| e.to_string())?;
+    }
+    if let Some(package) = &config.package {
+        package_updates.updated(package, &latest);
+    }
     
-    client.read_resource(&uri)
+    if manager.connection_state(&name).await == ConnectionState::Connected {
+        manager.restart_server(&name).await.map_err(|e| e.to_string())?;
+    }
+    Ok(latest)
+}
+
+/// Command to stop a package install the calling window started
+#[tauri::command]
+pub async fn mcp_cancel_package_install<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    install_id: String,
+) -> Result<bool, String> {
+    let key = call_key(window.label(), &install_id);
+    let cancel_tx = PACKAGE_INSTALLS.lock().map_err(|e| e.to_string())?.remove(&key);
+    Ok(cancel_tx.is_some_and(|cancel_tx| cancel_tx.send(()).is_ok()))
+}
+
+/// Command to get all registered MCP servers
+#[tauri::command]
+pub async fn mcp_get_servers(manager: State<'_, Arc<McpServerManager>>) -> Result<Vec<McpServerConfig>, String> {
+    Ok(manager.get_servers().await)
+}
+
+/// Command to test connection to an MCP server or endpoint
+#[tauri::command]
+pub async fn mcp_test_connection(manager: State<'_, Arc<McpServerManager>>, url: String) -> Result<bool, String> {
+    manager.test_connection(&url)
         .await
         .map_err(|e| e.to_string())
 }
 
-/// Command to list prompts from an MCP server
+/// Servers in `dir` and installed locally, run to check them only with
+/// `probe`, followed with `network` by those advertised on the local network.
+/// The network is searched while the machine is.
+async fn discover(manager: &McpServerManager, dir: Option<&Path>, network: bool, probe: bool) -> Result<Vec<McpServerConfig>, String> {
+    let browse = async {
+        if !network {
+            return Vec::new();
+        }
+        // A machine without a usable network still has its local servers
+        mdns::browse(mdns::DEFAULT_BROWSE_TIME).await.unwrap_or_else(|e| {
+            log::warn!("Failed to look for MCP servers on the network: {}", e);
+            Vec::new()
+        })
+    };
+    let (local, advertised) = tokio::join!(manager.discover_servers(dir, probe), browse);
+    
+    let mut configs = local.map_err(|e| e.to_string())?;
+    configs.extend(advertised);
+    Ok(configs)
+}
+
+/// The directory whose executables are all offered as servers, `path` or the
+/// app data dir, and whether to search it: the app data dir may not have
+/// been created yet
+fn discovery_dir<R: Runtime>(app: &tauri::AppHandle<R>, path: Option<String>) -> Result<(PathBuf, bool), String> {
+    match path {
+        Some(path) => Ok((PathBuf::from(path), true)),
+        None => {
+            let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+            let exists = app_dir.is_dir();
+            Ok((app_dir, exists))
+        }
+    }
+}
+
+/// Command to discover MCP servers, in `path`, on `PATH` and in known install
+/// locations and, unless `network` is false, advertised on the local network.
+/// Candidates are only run to check that they are servers when `probe` is set.
 #[tauri::command]
-pub async fn mcp_list_prompts(server_name: String) -> Result<ListPromptsResult, String> {
-    let client = SERVER_MANAGER.0.get_client(&server_name)
-        .await
-        .map_err(|e| e.to_string())?;
+pub async fn mcp_discover_servers<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    path: Option<String>,
+    network: Option<bool>,
+    probe: Option<bool>,
+) -> Result<Vec<McpServerConfig>, String> {
+    let (dir, search) = discovery_dir(&app, path)?;
+    discover(&manager, Some(dir.as_path()).filter(|_| search), network.unwrap_or(true), probe.unwrap_or(false)).await
+}
+
+/// Command to discover MCP servers like `mcp_discover_servers`, in the
+/// background. Returns the directory searched; the servers found follow as
+/// an `mcp:discovery-finished` event.
+#[tauri::command]
+pub async fn mcp_start_discovery<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    path: Option<String>,
+    network: Option<bool>,
+    probe: Option<bool>,
+) -> Result<String, String> {
+    let (dir, search) = discovery_dir(&app, path)?;
+    let search_path = dir.to_string_lossy().to_string();
+    
+    let manager = manager.inner().clone();
+    let path = search_path.clone();
+    tauri::async_runtime::spawn(async move {
+        let payload = match discover(&manager, Some(dir.as_path()).filter(|_| search), network.unwrap_or(true), probe.unwrap_or(false)).await {
+            Ok(configs) => DiscoveryFinishedEvent {
+                path,
+                servers: configs.iter().filter_map(|config| serde_json::to_value(config).ok()).collect(),
+                error: None,
+            },
+            Err(error) => DiscoveryFinishedEvent { path, servers: Vec::new(), error: Some(error) },
+        };
+        events::emit_discovery_finished(&app, payload);
+    });
+    
+    Ok(search_path)
+}
+
+/// Command to list tools from an MCP server
+#[tauri::command]
+pub async fn mcp_list_tools(
+    manager: State<'_, Arc<McpServerManager>>,
+    server_name: String,
+) -> Result<ListToolsResult, McpError> {
+    manager.list_tools(&server_name).await
+}
+
+/// Command to call a tool on an MCP server once the user has approved it.
+/// Calls given a `call_id` can be stopped with `mcp_cancel_tool_call` from
+/// the same window, also while they wait for approval. Every call is
+/// recorded in the audit log under `conversation_id`.
+#[tauri::command]
+pub async fn mcp_call_tool<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    context: State<'_, McpContext>,
+    server_name: String,
+    tool_name: String,
+    args: Option<Value>,
+    call_id: Option<String>,
+    conversation_id: Option<String>,
+) -> Result<CallToolResult, McpError> {
+    let stores = context.stores().await;
+    let started = Instant::now();
+    let record = |result: &Result<CallToolResult, McpError>| {
+        let conversation = conversation_id.as_deref();
+        audit(&stores.audit_log, AuditEntry::tool_call(&server_name, &tool_name, args.as_ref(), conversation, started, result));
+    };
+    
+    let call = async {
+        stores
+            .approvals
+            .ask(window.app_handle(), window.label(), &server_name, &tool_name, args.as_ref())
+            .await?;
+        context.manager.call_tool(&server_name, &tool_name, args.clone()).await
+    };
+    
+    let Some(call_id) = call_id else {
+        let mut result = call.await;
+        record(&result);
+        if let Ok(call_result) = &mut result {
+            spill(&stores.spill_dir, &mut call_result.content).await;
+        }
+        return result;
+    };
+    let key = call_key(window.label(), &call_id);
     
-    client.list_prompts()
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    if let Ok(mut calls) = TOOL_CALLS.lock() {
+        calls.insert(key.clone(), cancel_tx);
+    }
+    
+    // Dropping the call tells the server to cancel it
+    let mut result = tokio::select! {
+        result = call => result,
+        _ = cancel_rx => Err(McpError::Cancelled),
+    };
+    
+    if let Ok(mut calls) = TOOL_CALLS.lock() {
+        calls.remove(&key);
+    }
+    
+    record(&result);
+    if let Ok(call_result) = &mut result {
+        spill(&stores.spill_dir, &mut call_result.content).await;
+    }
+    result
+}
+
+/// Command to call a tool, once approved, and stream its progress and result
+/// as `mcp:tool-stream` events tagged with `channel_id`. Only the calling
+/// window receives them, and only it can stop the call with
+/// `mcp_cancel_tool_call(channel_id)`. The result is also returned, and the
+/// call recorded in the audit log under `conversation_id`.
+#[tauri::command]
+pub async fn mcp_call_tool_streamed<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    context: State<'_, McpContext>,
+    server: String,
+    tool: String,
+    args: Option<Value>,
+    channel_id: String,
+    conversation_id: Option<String>,
+) -> Result<CallToolResult, McpError> {
+    let stores = context.stores().await;
+    let app = window.app_handle();
+    let manager = &context.manager;
+    let started = Instant::now();
+    let record = |result: &Result<CallToolResult, McpError>| {
+        let conversation = conversation_id.as_deref();
+        audit(&stores.audit_log, AuditEntry::tool_call(&server, &tool, args.as_ref(), conversation, started, result));
+    };
+    
+    let label = window.label().to_string();
+    let send = |chunk: ToolStreamChunk| {
+        events::emit_tool_stream(app, &label, ToolStreamEvent {
+            channel: channel_id.clone(),
+            chunk,
+        });
+    };
+    
+    let fail = |e: McpError| {
+        send(ToolStreamChunk::Error { code: e.to_code(), message: e.to_string() });
+        let result = Err(e);
+        record(&result);
+        result
+    };
+    
+    // Cancellable from here on, including while the user decides
+    let key = call_key(&label, &channel_id);
+    let (cancel_tx, mut cancel_rx) = oneshot::channel();
+    if let Ok(mut calls) = TOOL_CALLS.lock() {
+        calls.insert(key.clone(), cancel_tx);
+    }
+    let forget_call = || {
+        if let Ok(mut calls) = TOOL_CALLS.lock() {
+            calls.remove(&key);
+        }
+    };
+    
+    // Waiting for the rate limit counts as part of getting approval
+    let approve = async {
+        stores.approvals.ask(app, &label, &server, &tool, args.as_ref()).await?;
+        manager.acquire_tool_call(&server).await
+    };
+    let approved = tokio::select! {
+        approved = approve => approved,
+        _ = &mut cancel_rx => Err(McpError::Cancelled),
+    };
+    if let Err(e) = approved {
+        forget_call();
+        return fail(e);
+    }
+    
+    let client = match manager.get_client(&server).await {
+        Ok(client) => client,
+        Err(e) => {
+            forget_call();
+            return fail(McpError::from(e));
+        }
+    };
+    
+    // The channel doubles as the progress token, so only this call's progress is relayed
+    let progress_token = Value::String(channel_id.clone());
+    let mut notifications = client.subscribe_notifications();
+    let progress_app = app.clone();
+    let progress_label = label.clone();
+    let progress_channel = channel_id.clone();
+    let token = progress_token.clone();
+    let progress = tauri::async_runtime::spawn(async move {
+        loop {
+            let notification = match notifications.recv().await {
+                Ok(notification) => notification,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            };
+            let Some(params) = notification.params.filter(|_| notification.method == "notifications/progress") else {
+                continue;
+            };
+            if params.get("progressToken") != Some(&token) {
+                continue;
+            }
+            let Some(progress) = params.get("progress").and_then(Value::as_f64) else {
+                continue;
+            };
+            
+            events::emit_tool_stream(&progress_app, &progress_label, ToolStreamEvent {
+                channel: progress_channel.clone(),
+                chunk: ToolStreamChunk::Progress {
+                    progress,
+                    total: params.get("total").and_then(Value::as_f64),
+                    message: params.get("message").and_then(Value::as_str).map(str::to_string),
+                },
+            });
+        }
+    });
+    
+    let mut result = tokio::select! {
+        result = client.call_tool_with_progress(&tool, args.clone(), progress_token) => result,
+        _ = cancel_rx => Err(McpError::Cancelled),
+    };
+    
+    forget_call();
+    progress.abort();
+    
+    record(&result);
+    if let Ok(call_result) = &mut result {
+        spill(&stores.spill_dir, &mut call_result.content).await;
+    }
+    
+    match &result {
+        Ok(call_result) => {
+            for (index, content) in call_result.content.iter().enumerate() {
+                send(ToolStreamChunk::Content {
+                    index,
+                    content: serde_json::to_value(content).unwrap_or(Value::Null),
+                });
+            }
+            send(ToolStreamChunk::Result { is_error: call_result.is_error.unwrap_or(false) });
+        }
+        Err(e) => send(ToolStreamChunk::Error { code: e.to_code(), message: e.to_string() }),
+    }
+    result
+}
+
+/// Calls a batch runs at once unless the caller asks otherwise
+const DEFAULT_BATCH_PARALLELISM: usize = 4;
+
+/// One call of `mcp_call_tools_batch`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchToolCall {
+    pub server: String,
+    pub tool: String,
+    #[serde(default)]
+    pub args: Option<Value>,
+}
+
+/// Outcome of one call of `mcp_call_tools_batch`: its `result`, or the `error`
+/// it failed with
+#[derive(Debug, Serialize)]
+pub struct BatchToolResult {
+    pub server: String,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<CallToolResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<McpError>,
+}
+
+/// Command to run several tool calls, possibly on different servers, at most
+/// `max_parallel` at a time. Each call is approved, rate limited and recorded
+/// in the audit log like one made with `mcp_call_tool`. A failed call doesn't
+/// stop the others; the results are in the order of `calls`.
+#[tauri::command]
+pub async fn mcp_call_tools_batch<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    context: State<'_, McpContext>,
+    calls: Vec<BatchToolCall>,
+    max_parallel: Option<usize>,
+    conversation_id: Option<String>,
+) -> Result<Vec<BatchToolResult>, McpError> {
+    let app = window.app_handle();
+    let label = window.label();
+    let conversation = conversation_id.as_deref();
+    let manager = &context.manager;
+    let McpStores { approvals, audit_log, spill_dir } = context.stores().await;
+    
+    let results = stream::iter(calls)
+        .map(|call| {
+            async move {
+                let started = Instant::now();
+                let mut result = async {
+                    approvals.ask(app, label, &call.server, &call.tool, call.args.as_ref()).await?;
+                    manager.call_tool(&call.server, &call.tool, call.args.clone()).await
+                }
+                .await;
+                
+                audit(audit_log, AuditEntry::tool_call(&call.server, &call.tool, call.args.as_ref(), conversation, started, &result));
+                if let Ok(call_result) = &mut result {
+                    spill(spill_dir, &mut call_result.content).await;
+                }
+                
+                let (result, error) = match result {
+                    Ok(result) => (Some(result), None),
+                    Err(e) => (None, Some(e)),
+                };
+                BatchToolResult { server: call.server, tool: call.tool, result, error }
+            }
+        })
+        .buffered(max_parallel.unwrap_or(DEFAULT_BATCH_PARALLELISM).max(1))
+        .collect()
+        .await;
+    Ok(results)
+}
+
+/// Command to cancel a tool call the calling window started with a `call_id`,
+/// or a streamed call by its `channel_id`
+#[tauri::command]
+pub async fn mcp_cancel_tool_call<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    call_id: String,
+) -> Result<bool, String> {
+    let key = call_key(window.label(), &call_id);
+    let cancel_tx = TOOL_CALLS.lock().map_err(|e| e.to_string())?.remove(&key);
+    Ok(cancel_tx.is_some_and(|cancel_tx| cancel_tx.send(()).is_ok()))
+}
+
+/// Command to list resources from an MCP server
+#[tauri::command]
+pub async fn mcp_list_resources(
+    manager: State<'_, Arc<McpServerManager>>,
+    server_name: String,
+) -> Result<ListResourcesResult, McpError> {
+    manager.list_resources(&server_name).await
+}
+
+/// Command to read a resource from an MCP server
+#[tauri::command]
+pub async fn mcp_read_resource(
+    context: State<'_, McpContext>,
+    server_name: String,
+    uri: String,
+    conversation_id: Option<String>,
+) -> Result<ReadResourceResult, McpError> {
+    let stores = context.stores().await;
+    let started = Instant::now();
+    let mut result = async {
+        let client = context.manager.get_client(&server_name).await?;
+        client.read_resource(&uri).await
+    }
+    .await;
+    
+    let conversation = conversation_id.as_deref();
+    audit(&stores.audit_log, AuditEntry::resource_read(&server_name, &uri, conversation, started, &result));
+    if let Ok(read_result) = &mut result {
+        spill(&stores.spill_dir, &mut read_result.content).await;
+    }
+    result
+}
+
+/// Command to read a resource and send it to the calling window in pieces of
+/// at most `chunk_bytes` bytes of text or base64 data, as `mcp:resource-chunk`
+/// events tagged with `channel_id`. The read itself still holds the whole
+/// resource in memory; only its delivery to the frontend is chunked, so no
+/// single IPC message carries all of it. Returns the number of pieces sent.
+#[tauri::command]
+pub async fn mcp_read_resource_streamed<R: Runtime>(
+    window: tauri::WebviewWindow<R>,
+    context: State<'_, McpContext>,
+    server_name: String,
+    uri: String,
+    channel_id: String,
+    chunk_bytes: Option<usize>,
+    conversation_id: Option<String>,
+) -> Result<usize, McpError> {
+    let stores = context.stores().await;
+    let started = Instant::now();
+    let result = async {
+        let client = context.manager.get_client(&server_name).await?;
+        client.read_resource(&uri).await
+    }
+    .await;
+    
+    let conversation = conversation_id.as_deref();
+    audit(&stores.audit_log, AuditEntry::resource_read(&server_name, &uri, conversation, started, &result));
+    
+    let label = window.label().to_string();
+    let mut sent = 0;
+    for chunk in resource::chunks(result?, chunk_bytes.unwrap_or(DEFAULT_CHUNK_BYTES)) {
+        events::emit_resource_chunk(window.app_handle(), &label, ResourceChunkEvent {
+            channel: channel_id.clone(),
+            index: chunk.index,
+            offset: chunk.offset,
+            content: serde_json::to_value(&chunk.content).unwrap_or(Value::Null),
+            last: chunk.last,
+        });
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+/// Command to read a resource and write its contents to `path`: text as
+/// UTF-8 and image data decoded from base64. The resource is read whole
+/// first, but never sent to the frontend. Returns the number of bytes
+/// written.
+#[tauri::command]
+pub async fn mcp_read_resource_to_file(
+    context: State<'_, McpContext>,
+    server_name: String,
+    uri: String,
+    path: PathBuf,
+    conversation_id: Option<String>,
+) -> Result<u64, McpError> {
+    let stores = context.stores().await;
+    let started = Instant::now();
+    let result = async {
+        let client = context.manager.get_client(&server_name).await?;
+        client.read_resource(&uri).await
+    }
+    .await;
+    
+    let conversation = conversation_id.as_deref();
+    audit(&stores.audit_log, AuditEntry::resource_read(&server_name, &uri, conversation, started, &result));
+    
+    resource::save(result?, &path)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| McpError::internal(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Command to list prompts from an MCP server
+#[tauri::command]
+pub async fn mcp_list_prompts(
+    manager: State<'_, Arc<McpServerManager>>,
+    server_name: String,
+) -> Result<ListPromptsResult, McpError> {
+    manager.list_prompts(&server_name).await
 }
 
 /// Command to get a prompt from an MCP server
 #[tauri::command]
-pub async fn mcp_get_prompt(server_name: String, prompt_id: String, params: Option<Value>) -> Result<GetPromptResult, String> {
-    let client = SERVER_MANAGER.0.get_client(&server_name)
-        .await
-        .map_err(|e| e.to_string())?;
+pub async fn mcp_get_prompt(
+    manager: State<'_, Arc<McpServerManager>>,
+    server_name: String,
+    prompt_id: String,
+    params: Option<Value>,
+) -> Result<GetPromptResult, McpError> {
+    let client = manager.get_client(&server_name).await?;
     
-    client.get_prompt(&prompt_id, params)
-        .await
-        .map_err(|e| e.to_string())
+    client.get_prompt(&prompt_id, params).await
 }
 
 /// Wrapper type for MCP server configuration with additional connection status
@@ -162,21 +825,39 @@ pub struct McpServerStatus {
     pub command: String,
     pub args: Vec<String>,
     pub env: HashMap<String, String>,
+    pub cwd: Option<String>,
+    pub autostart: bool,
+    pub enabled: bool,
+    pub settings: ServerSettings,
     pub is_running: bool,
+    pub connection_state: ConnectionState,
     pub url: Option<String>,
+    /// The last time the server's process crashed, if it ever did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_crash: Option<ServerCrash>,
+    /// How the package the server runs compares to its latest release, once
+    /// it was checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<PackageUpdate>,
 }
 
 /// Command to get status of all MCP servers
 #[tauri::command]
-pub async fn mcp_get_server_status() -> Result<Vec<McpServerStatus>, String> {
-    let servers = SERVER_MANAGER.0.get_servers().await;
+pub async fn mcp_get_server_status(
+    manager: State<'_, Arc<McpServerManager>>,
+    package_updates: State<'_, PackageUpdates>,
+) -> Result<Vec<McpServerStatus>, String> {
+    let servers = manager.get_servers().await;
     
     let mut result = Vec::new();
     for server in servers {
-        let is_running = SERVER_MANAGER.0.get_client(&server.name).await.is_ok();
+        let connection_state = manager.connection_state(&server.name).await;
+        let is_running = connection_state == ConnectionState::Connected;
+        let last_crash = manager.last_crash(&server.name).await.map(ServerCrash::from);
+        let update = server.package.as_ref().and_then(|package| package_updates.get(package));
         
         // Determine URL for HTTP endpoints
-        let url = if server.command.starts_with("http://") || server.command.starts_with("https://") {
+        let url = if server.is_remote() {
             Some(server.command.clone())
         } else {
             None
@@ -187,26 +868,351 @@ pub async fn mcp_get_server_status() -> Result<Vec<McpServerStatus>, String> {
             command: server.command,
             args: server.args,
             env: server.env,
+            cwd: server.cwd,
+            autostart: server.autostart,
+            enabled: server.enabled,
+            settings: server.settings,
             is_running,
+            connection_state,
             url,
+            last_crash,
+            update,
         });
     }
     
     Ok(result)
 }
 
+/// Command to get the name, version, instructions and capabilities a server
+/// reported when it last connected; `None` if it has not connected yet
+#[tauri::command]
+pub async fn mcp_get_server_info(
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+) -> Result<Option<InitializeResult>, String> {
+    manager.server_info(&name).await.map_err(|e| e.to_string())
+}
+
+/// Command to stop local servers after `seconds` without use; `None` disables it
+#[tauri::command]
+pub async fn mcp_set_idle_timeout(
+    manager: State<'_, Arc<McpServerManager>>,
+    seconds: Option<u64>,
+) -> Result<(), String> {
+    manager.set_idle_timeout(seconds.map(Duration::from_secs));
+    Ok(())
+}
+
+/// Command to set the limits the background reaper enforces; `None` disables a limit
+#[tauri::command]
+pub async fn mcp_set_resource_budgets(
+    manager: State<'_, Arc<McpServerManager>>,
+    idle_seconds: Option<u64>,
+    remote_idle_seconds: Option<u64>,
+    max_log_bytes: Option<u64>,
+) -> Result<(), String> {
+    manager.set_resource_budgets(ResourceBudgets {
+        idle_timeout: idle_seconds.map(Duration::from_secs),
+        remote_idle_timeout: remote_idle_seconds.map(Duration::from_secs),
+        max_log_bytes,
+    });
+    Ok(())
+}
+
+/// Command to limit the tool calls made to all servers together; `None` removes the limit
+#[tauri::command]
+pub async fn mcp_set_rate_limit(
+    manager: State<'_, Arc<McpServerManager>>,
+    limit: Option<RateLimit>,
+) -> Result<(), String> {
+    manager.set_global_rate_limit(limit);
+    Ok(())
+}
+
+/// Command to get how much of the global and per-server rate limits is in use
+#[tauri::command]
+pub async fn mcp_get_rate_limit_usage(manager: State<'_, Arc<McpServerManager>>) -> Result<RateLimitReport, String> {
+    Ok(manager.rate_limit_usage())
+}
+
+/// Command to get request counts, error rates and latency percentiles per
+/// method of `server`, or of all servers
+#[tauri::command]
+pub async fn mcp_get_metrics(
+    manager: State<'_, Arc<McpServerManager>>,
+    server: Option<String>,
+) -> Result<MetricsReport, String> {
+    Ok(manager.metrics(server.as_deref()))
+}
+
+/// Command to get uptime, restarts, last error, tool count, request volume and
+/// connection type of every server at once, for the overview page
+#[tauri::command]
+pub async fn mcp_get_dashboard(manager: State<'_, Arc<McpServerManager>>) -> Result<Dashboard, String> {
+    Ok(manager.dashboard().await)
+}
+
+/// Command to get the recent output of an MCP server. With `follow`, new lines
+/// are also emitted as `mcp:server-log` events until the server is unregistered.
+#[tauri::command]
+pub async fn mcp_get_server_logs<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+    tail: Option<usize>,
+    follow: Option<bool>,
+) -> Result<Vec<LogLine>, String> {
+    let lines = manager.server_logs(&name, tail)
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    if follow.unwrap_or(false) && FOLLOWED_LOGS.lock().map_err(|e| e.to_string())?.insert(name.clone()) {
+        let mut log_rx = match manager.follow_server_logs(&name).await {
+            Ok(log_rx) => log_rx,
+            Err(e) => {
+                if let Ok(mut followed) = FOLLOWED_LOGS.lock() {
+                    followed.remove(&name);
+                }
+                return Err(e.to_string());
+            }
+        };
+        
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match log_rx.recv().await {
+                    Ok(line) => events::emit_server_log(&app, ServerLogEvent {
+                        server: name.clone(),
+                        stream: line.stream,
+                        line: line.line,
+                        timestamp: line.timestamp,
+                    }),
+                    // Dropping lines is fine for a live view
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            
+            if let Ok(mut followed) = FOLLOWED_LOGS.lock() {
+                followed.remove(&name);
+            }
+        });
+    }
+    
+    Ok(lines)
+}
+
+/// Command to get the last `limit` JSON-RPC messages exchanged with `server`,
+/// or with all servers, that match `filter`, oldest first. With `follow`, every
+/// new message is also emitted as an `mcp:traffic` event until
+/// `mcp_unfollow_traffic` is called.
+#[tauri::command]
+pub async fn mcp_get_traffic<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    server: Option<String>,
+    filter: Option<TrafficFilter>,
+    limit: Option<usize>,
+    follow: Option<bool>,
+) -> Result<Vec<TrafficEvent>, String> {
+    let traffic = manager.traffic(server.as_deref(), &filter.unwrap_or_default(), limit);
+    
+    let mut follower = TRAFFIC_FOLLOWER.lock().map_err(|e| e.to_string())?;
+    if follow.unwrap_or(false) && follower.is_none() {
+        let mut traffic_rx = manager.subscribe_traffic();
+        *follower = Some(tauri::async_runtime::spawn(async move {
+            loop {
+                match traffic_rx.recv().await {
+                    Ok(entry) => events::emit_traffic(&app, entry.into()),
+                    // Dropping messages is fine for a live view
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }));
+    }
+    
+    Ok(traffic.into_iter().map(TrafficEvent::from).collect())
+}
+
+/// Command to stop emitting `mcp:traffic` events
+#[tauri::command]
+pub async fn mcp_unfollow_traffic() -> Result<(), String> {
+    if let Some(follower) = TRAFFIC_FOLLOWER.lock().map_err(|e| e.to_string())?.take() {
+        follower.abort();
+    }
+    Ok(())
+}
+
+/// Command to record every JSON-RPC message exchanged with a server to `path`,
+/// for attaching to a bug report. A running server is restarted so the
+/// recording starts with its handshake.
+#[tauri::command]
+pub async fn mcp_start_session_recording(
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+    path: String,
+) -> Result<(), String> {
+    manager.start_session_recording(&name, &PathBuf::from(path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Command to stop recording a server's messages. Returns the file they were
+/// recorded to, if they were.
+#[tauri::command]
+pub async fn mcp_stop_session_recording(
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+) -> Result<Option<String>, String> {
+    Ok(manager.stop_session_recording(&name).map(|path| path.display().to_string()))
+}
+
+/// Command to search the audit log of tool calls and resource reads, most
+/// recent first
+#[tauri::command]
+pub async fn mcp_query_audit_log(
+    context: State<'_, McpContext>,
+    query: Option<AuditQuery>,
+) -> Result<Vec<AuditEntry>, String> {
+    context.stores().await.audit_log.query(&query.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// Command to get how many days of audit entries are kept; 0 keeps everything
+#[tauri::command]
+pub async fn mcp_get_audit_retention(context: State<'_, McpContext>) -> Result<u32, String> {
+    Ok(context.stores().await.audit_log.retention_days())
+}
+
+/// Command to set how many days of audit entries are kept. Older days are
+/// deleted right away; 0 keeps everything.
+#[tauri::command]
+pub async fn mcp_set_audit_retention(context: State<'_, McpContext>, days: u32) -> Result<(), String> {
+    context.stores().await.audit_log.set_retention_days(days).map_err(|e| e.to_string())
+}
+
+/// Read and parse the config of another MCP client, from `path` or the
+/// client's default location
+async fn read_import_source(
+    source: ImportSource,
+    path: Option<String>,
+) -> Result<(PathBuf, Vec<McpServerConfig>, Vec<SkippedServer>), String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => source.default_path()
+            .ok_or_else(|| "Cannot locate the config directory".to_string())?,
+    };
+    
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let (configs, skipped) = source.parse(&content).map_err(|e| e.to_string())?;
+    
+    Ok((path, configs, skipped))
+}
+
+/// Command to import the servers configured in Claude Desktop and save the
+/// result. `path` overrides the platform's default config location.
+#[tauri::command]
+pub async fn mcp_import_claude_config(
+    manager: State<'_, Arc<McpServerManager>>,
+    path: Option<String>,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<ImportReport, String> {
+    let (_, configs, skipped) = read_import_source(ImportSource::ClaudeDesktop, path).await?;
+    
+    let mut report = mcp_import_servers(manager, configs, on_conflict).await?;
+    report.skipped.extend(skipped);
+    Ok(report)
+}
+
+/// Command to show which servers a Claude Desktop, Cursor, VS Code or Windsurf
+/// config contains, without importing them
+#[tauri::command]
+pub async fn mcp_preview_import(
+    manager: State<'_, Arc<McpServerManager>>,
+    source: ImportSource,
+    path: Option<String>,
+) -> Result<ImportPreview, String> {
+    let (path, servers, skipped) = read_import_source(source, path).await?;
+    
+    let existing: HashSet<String> = manager
+        .get_servers()
+        .await
+        .into_iter()
+        .map(|server| server.name)
+        .collect();
+    let conflicts = servers
+        .iter()
+        .filter(|server| existing.contains(&server.name))
+        .map(|server| server.name.clone())
+        .collect();
+    
+    Ok(ImportPreview { path, servers, skipped, conflicts })
+}
+
+/// Command to import the servers accepted from a preview and save the result
+#[tauri::command]
+pub async fn mcp_import_servers(
+    manager: State<'_, Arc<McpServerManager>>,
+    servers: Vec<McpServerConfig>,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<ImportReport, String> {
+    let report = manager
+        .import_servers(servers, on_conflict.unwrap_or_default())
+        .await;
+    
+    manager.save_default_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    Ok(report)
+}
+
+/// Command to write all servers to a bundle that can be shared with other
+/// workstations. Secret environment values are replaced with references.
+#[tauri::command]
+pub async fn mcp_export_config(
+    manager: State<'_, Arc<McpServerManager>>,
+    path: String,
+) -> Result<ExportReport, String> {
+    let (bundle, report) = ConfigBundle::export(manager.get_servers().await);
+    
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    tokio::fs::write(&path, json)
+        .await
+        .map_err(|e| format!("Cannot write {}: {}", path, e))?;
+    
+    Ok(report)
+}
+
+/// Command to import the servers of a bundle written by `mcp_export_config`
+/// and save the result
+#[tauri::command]
+pub async fn mcp_import_config(
+    manager: State<'_, Arc<McpServerManager>>,
+    path: String,
+    merge_strategy: Option<ConflictPolicy>,
+) -> Result<ImportReport, String> {
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let bundle = ConfigBundle::parse(&content).map_err(|e| e.to_string())?;
+    
+    mcp_import_servers(manager, bundle.servers, merge_strategy).await
+}
+
 /// Command to save MCP server configurations
 #[tauri::command]
-pub async fn mcp_save_config() -> Result<(), String> {
-    SERVER_MANAGER.0.save_default_config()
+pub async fn mcp_save_config(manager: State<'_, Arc<McpServerManager>>) -> Result<(), String> {
+    manager.save_default_config()
         .await
         .map_err(|e| e.to_string())
 }
 
 /// Command to load MCP server configurations
 #[tauri::command]
-pub async fn mcp_load_config() -> Result<(), String> {
-    SERVER_MANAGER.0.load_default_config()
+pub async fn mcp_load_config(manager: State<'_, Arc<McpServerManager>>) -> Result<(), String> {
+    manager.load_default_config()
         .await
         .map_err(|e| e.to_string())
 }
@@ -0,0 +1,117 @@
+//! The state tool calls and resource reads go through.
+//!
+//! A tool call needs the server manager, the user's approvals, the audit log
+//! and the directory large content is moved to. They are registered together
+//! as one `McpContext`, so the commands making calls take a single `State`
+//! for them instead of one each.
+//!
+//! Opening the approvals, the audit log and the spill directory reads and
+//! writes files, so setup only says where they live. They are opened on a
+//! blocking thread by the startup work in the background, or by the first
+//! command that needs them if it comes earlier.
+
+use crate::mcp::approval::{ToolApprovals, APPROVALS_FILE};
+use crate::mcp::audit::{AuditLog, AUDIT_DIR};
+use crate::mcp::server::McpServerManager;
+use crate::mcp::spill::{SpillDir, SPILL_DIR};
+use crate::paths::ConfigPaths;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// The files tool calls and resource reads keep
+#[derive(Default)]
+pub struct McpStores {
+    pub approvals: ToolApprovals,
+    pub audit_log: AuditLog,
+    pub spill_dir: SpillDir,
+}
+
+impl McpStores {
+    /// Open the stores under `paths` and `cache_dir`, and prune old audit
+    /// entries and spilled content. Stores without a location, or that cannot
+    /// be opened, are kept in memory. Blocks on the file system.
+    pub fn open(paths: Option<&ConfigPaths>, cache_dir: Option<&Path>) -> Self {
+        // Tools the user has always allowed live next to the server config
+        let approvals = match paths {
+            Some(paths) => ToolApprovals::load(paths.config_dir.join(APPROVALS_FILE)),
+            None => ToolApprovals::default(),
+        };
+        
+        // Tool calls and resource reads are recorded for later review
+        let audit_log = match paths {
+            Some(paths) => AuditLog::open(paths.data_dir.join(AUDIT_DIR)).unwrap_or_else(|e| {
+                log::error!("Failed to open the audit log: {}", e);
+                AuditLog::default()
+            }),
+            None => AuditLog::default(),
+        };
+        if let Err(e) = audit_log.prune() {
+            log::warn!("Failed to prune the audit log: {}", e);
+        }
+        
+        // Large images and documents are passed to the frontend as files
+        let spill_dir = match cache_dir {
+            Some(cache_dir) => SpillDir::open(cache_dir.join(SPILL_DIR)).unwrap_or_else(|e| {
+                log::error!("Failed to create the directory for large content: {}", e);
+                SpillDir::default()
+            }),
+            None => SpillDir::default(),
+        };
+        spill_dir.prune();
+        
+        Self { approvals, audit_log, spill_dir }
+    }
+}
+
+pub struct McpContext {
+    pub manager: Arc<McpServerManager>,
+    paths: Option<ConfigPaths>,
+    cache_dir: Option<PathBuf>,
+    stores: OnceCell<McpStores>,
+}
+
+impl McpContext {
+    /// A context for `manager` with no decisions remembered, nothing audited
+    /// and large content passed as it is
+    pub fn new(manager: Arc<McpServerManager>) -> Self {
+        Self::with_stores(manager, McpStores::default())
+    }
+    
+    /// A context for `manager` using `stores` as they are
+    pub fn with_stores(manager: Arc<McpServerManager>, stores: McpStores) -> Self {
+        Self {
+            manager,
+            paths: None,
+            cache_dir: None,
+            stores: OnceCell::new_with(Some(stores)),
+        }
+    }
+    
+    /// A context for `manager` whose stores are opened under `paths` and
+    /// `cache_dir` when they are first needed
+    pub fn open_later(manager: Arc<McpServerManager>, paths: Option<ConfigPaths>, cache_dir: Option<PathBuf>) -> Self {
+        Self {
+            manager,
+            paths,
+            cache_dir,
+            stores: OnceCell::new(),
+        }
+    }
+    
+    /// The stores, opened on a blocking thread unless that already happened
+    pub async fn stores(&self) -> &McpStores {
+        self.stores
+            .get_or_init(|| async {
+                let paths = self.paths.clone();
+                let cache_dir = self.cache_dir.clone();
+                tauri::async_runtime::spawn_blocking(move || McpStores::open(paths.as_ref(), cache_dir.as_deref()))
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::error!("Failed to open the MCP stores: {}", e);
+                        McpStores::default()
+                    })
+            })
+            .await
+    }
+}
@@ -0,0 +1,149 @@
+//! One-click server installation from `mlface://add-server` links.
+//!
+//! A link such as
+//! `mlface://add-server?name=files&command=npx&args=-y&args=@modelcontextprotocol/server-filesystem`
+//! describes a server, with one `args` parameter per argument. Opening it
+//! queues an install request and emits `mcp:install-request`; the frontend
+//! asks for confirmation and settles the request with `mcp_resolve_install`.
+//! Requests from a link that launched the app wait until the frontend calls
+//! `mcp_pending_installs`.
+
+use crate::background;
+use crate::events::{self, InstallRequestEvent};
+use crate::mcp::import::{ConflictPolicy, ImportReport};
+use crate::mcp::server::{McpServerConfig, McpServerManager};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime, State, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+pub const SCHEME: &str = "mlface";
+const ADD_SERVER: &str = "add-server";
+
+/// Install requests waiting for the user's answer
+#[derive(Default)]
+pub struct PendingInstalls {
+    next_id: AtomicU32,
+    /// The parsed server of each request, by request ID
+    requests: Mutex<Vec<(u32, McpServerConfig)>>,
+}
+
+/// Read the server described by an `mlface://add-server` link
+pub fn parse_add_server(url: &Url) -> Result<McpServerConfig, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Not an {} link: {}", SCHEME, url));
+    }
+    // `mlface://add-server` has the action as its host, `mlface:add-server` as its path
+    let action = url.host_str().unwrap_or_else(|| url.path()).trim_matches('/');
+    if action != ADD_SERVER {
+        return Err(format!("Unknown {} link action: {}", SCHEME, action));
+    }
+    
+    let mut name = None;
+    let mut command = None;
+    let mut args = Vec::new();
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "name" => name = Some(value.trim().to_string()),
+            "command" => command = Some(value.trim().to_string()),
+            "args" => args.push(value.into_owned()),
+            _ => {}
+        }
+    }
+    
+    let name = name
+        .filter(|name| !name.is_empty() && !name.chars().any(char::is_control))
+        .ok_or_else(|| "The link does not name the server".to_string())?;
+    let command = command
+        .filter(|command| !command.is_empty())
+        .ok_or_else(|| "The link does not say how to start the server".to_string())?;
+    
+    Ok(McpServerConfig {
+        name,
+        command,
+        args,
+        ..Default::default()
+    })
+}
+
+/// The request to confirm installing `config`
+fn install_request(id: u32, config: &McpServerConfig) -> InstallRequestEvent {
+    InstallRequestEvent { id, name: config.name.clone(), command: config.command.clone(), args: config.args.clone() }
+}
+
+/// Queue the servers of opened links and bring the window forward to confirm them
+fn handle_urls<R: Runtime>(app: &AppHandle<R>, urls: Vec<Url>) {
+    let pending = app.state::<PendingInstalls>();
+    
+    for url in urls {
+        let config = match parse_add_server(&url) {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("Ignoring link {}: {}", url, e);
+                continue;
+            }
+        };
+        
+        let id = pending.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = install_request(id, &config);
+        if let Ok(mut requests) = pending.requests.lock() {
+            requests.push((id, config));
+        }
+        events::emit_install_request(app, request);
+        background::show_main_window(app);
+    }
+}
+
+/// Register the URL scheme and handle links opened now and at launch
+pub fn install<R: Runtime>(app: &AppHandle<R>) {
+    app.manage(PendingInstalls::default());
+    
+    // Installers register the scheme on macOS; elsewhere it is registered at runtime
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("Failed to register the {} URL scheme: {}", SCHEME, e);
+    }
+    
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| handle_urls(&handle, event.urls()));
+    
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => handle_urls(app, urls),
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to read the link the app was opened with: {}", e),
+    }
+}
+
+/// Command to get the install requests still waiting for an answer
+#[tauri::command]
+pub async fn mcp_pending_installs(pending: State<'_, PendingInstalls>) -> Result<Vec<InstallRequestEvent>, String> {
+    let requests = pending.requests.lock().map_err(|e| e.to_string())?;
+    Ok(requests.iter().map(|(id, config)| install_request(*id, config)).collect())
+}
+
+/// Command to accept or decline an install request. An accepted server is
+/// registered, renamed if its name is taken, and saved; it is not started.
+#[tauri::command]
+pub async fn mcp_resolve_install(
+    manager: State<'_, Arc<McpServerManager>>,
+    pending: State<'_, PendingInstalls>,
+    id: u32,
+    accept: bool,
+) -> Result<Option<ImportReport>, String> {
+    let (_, config) = {
+        let mut requests = pending.requests.lock().map_err(|e| e.to_string())?;
+        let index = requests
+            .iter()
+            .position(|(request, _)| *request == id)
+            .ok_or_else(|| format!("Install request {} not found", id))?;
+        requests.remove(index)
+    };
+    if !accept {
+        return Ok(None);
+    }
+    
+    let report = manager.import_servers(vec![config], ConflictPolicy::Rename).await;
+    manager.save_default_config().await.map_err(|e| e.to_string())?;
+    
+    Ok(Some(report))
+}
@@ -0,0 +1,77 @@
+//! Secrets kept in the platform keychain: Keychain on macOS, Credential
+//! Manager on Windows and the Secret Service on Linux.
+//!
+//! Server configs refer to them as `{{secret:NAME}}`; the manager resolves
+//! the references from this store when a server is launched.
+
+use crate::mcp::secrets::{validate_secret_name, SecretStore};
+use crate::mcp::server::McpServerManager;
+use anyhow::Result;
+use std::sync::Arc;
+use tauri::State;
+
+/// Service name the secrets are filed under in the keychain
+const SERVICE: &str = "mlface";
+
+pub struct KeyringSecretStore;
+
+impl SecretStore for KeyringSecretStore {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        match keyring::Entry::new(SERVICE, name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        keyring::Entry::new(SERVICE, name)?.set_password(value)?;
+        Ok(())
+    }
+    
+    fn delete(&self, name: &str) -> Result<()> {
+        match keyring::Entry::new(SERVICE, name)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Resolve secret references from the keychain
+pub fn install(manager: &McpServerManager) {
+    manager.set_secret_store(Some(Arc::new(KeyringSecretStore)));
+}
+
+/// Run a keychain operation off the async runtime; it may wait on a system
+/// service or an unlock prompt
+async fn with_store<T: Send + 'static>(
+    manager: &McpServerManager,
+    f: impl FnOnce(Arc<dyn SecretStore>) -> Result<T> + Send + 'static,
+) -> Result<T, String> {
+    let store = manager
+        .secret_store()
+        .ok_or_else(|| "No secret store is available".to_string())?;
+    tauri::async_runtime::spawn_blocking(move || f(store))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Command to store a secret that server configs can refer to as
+/// `{{secret:NAME}}`. The value is never returned to the frontend.
+#[tauri::command]
+pub async fn mcp_set_secret(
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+    value: String,
+) -> Result<(), String> {
+    validate_secret_name(&name).map_err(|e| e.to_string())?;
+    with_store(&manager, move |store| store.set(&name, &value)).await
+}
+
+/// Command to delete a stored secret
+#[tauri::command]
+pub async fn mcp_delete_secret(manager: State<'_, Arc<McpServerManager>>, name: String) -> Result<(), String> {
+    validate_secret_name(&name).map_err(|e| e.to_string())?;
+    with_store(&manager, move |store| store.delete(&name)).await
+}
@@ -0,0 +1,70 @@
+//! Forwards the manager's connection state changes to the frontend.
+//!
+//! Every transition is emitted twice: as the matching `mcp:server-*` event and
+//! as an `mcp:server-status` event carrying the new state, so the UI can either
+//! listen for what it cares about or keep one status map up to date. Slow
+//! requests are forwarded as `mcp:slow-call` events.
+
+use crate::events::{self, ServerCrash, ServerLifecycleEvent, ServerState, ServerStatusEvent, SlowCallEvent};
+use crate::mcp::server::{ConnectionState, ServerEvent};
+use crate::mcp;
+use tauri::{AppHandle, Runtime};
+use tokio::sync::broadcast::error::RecvError;
+
+fn server_state(event: &ServerEvent) -> ServerState {
+    match event.state {
+        ConnectionState::Connecting | ConnectionState::Reconnecting => ServerState::Connecting,
+        ConnectionState::Connected => ServerState::Running,
+        ConnectionState::Crashed => ServerState::Crashed,
+        // A reason means the server failed to start
+        ConnectionState::Disconnected if event.reason.is_some() => ServerState::Crashed,
+        ConnectionState::Disconnected => ServerState::Stopped,
+    }
+}
+
+/// Forward server events for the lifecycle of the app
+pub fn spawn<R: Runtime>(app: AppHandle<R>) {
+    let mut server_events = mcp::manager(&app).subscribe_events();
+    let mut slow_calls = mcp::manager(&app).subscribe_slow_calls();
+    
+    let slow_call_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match slow_calls.recv().await {
+                Ok(call) => events::emit_slow_call(&slow_call_app, SlowCallEvent::from(call)),
+                Err(RecvError::Lagged(missed)) => log::warn!("Dropped {} slow call events", missed),
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+    
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match server_events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    log::warn!("Dropped {} MCP server events", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            
+            let state = server_state(&event);
+            let timestamp = events::now_millis();
+            let crash = event.crash.map(ServerCrash::from);
+            events::emit_server_lifecycle(&app, state, ServerLifecycleEvent {
+                server: event.server.clone(),
+                reason: event.reason.clone(),
+                crash: crash.clone(),
+                timestamp,
+            });
+            events::emit_server_status(&app, ServerStatusEvent {
+                server: event.server,
+                state,
+                reason: event.reason,
+                crash,
+                timestamp,
+            });
+        }
+    });
+}
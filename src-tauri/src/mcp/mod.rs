@@ -1,5 +1,28 @@
-pub mod client;
-pub mod types;
-pub mod transport;
-pub mod server;
+pub use mlface_mcp::{audit, bundle, config, dashboard, http, import, integrity, logs, mdns, metrics, package, rate_limit, resource, secrets, server, session, spill, telemetry, traffic, types};
+#[cfg(feature = "otlp")]
+pub use mlface_mcp::otlp;
+
+pub mod approval;
+pub mod autostart;
+pub mod catalog;
 pub mod commands;
+pub mod context;
+pub mod deep_link;
+pub mod keychain;
+pub mod lifecycle;
+pub mod notifications;
+pub mod profiles;
+pub mod project;
+pub mod registry;
+pub mod updates;
+pub mod watcher;
+
+use mlface_mcp::server::McpServerManager;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// The server manager registered with `app.manage()`, for background tasks
+/// that run outside a command
+pub fn manager<R: Runtime>(app: &AppHandle<R>) -> Arc<McpServerManager> {
+    app.state::<Arc<McpServerManager>>().inner().clone()
+}
@@ -0,0 +1,93 @@
+//! Forwards server notifications to the frontend.
+//!
+//! Every notification is emitted as `mcp:notification` with the server it came
+//! from, so the UI has a single stream of server activity. The kinds the UI
+//! renders specially are also translated into typed events: progress, log
+//! messages, list changes and resource updates.
+
+use crate::events::{
+    self, ListChangedEvent, LogMessageEvent, NotificationEvent, ProgressEvent, ResourceUpdatedEvent,
+};
+use crate::mcp::server::ServerNotification;
+use crate::mcp;
+use serde_json::Value;
+use tauri::{AppHandle, Runtime};
+use tokio::sync::broadcast::error::RecvError;
+
+/// A parameter by its spec name, or in snake case as older servers send it
+fn param<'a>(params: &'a Value, camel: &str, snake: &str) -> Option<&'a Value> {
+    params.get(camel).or_else(|| params.get(snake))
+}
+
+fn translate<R: Runtime>(app: &AppHandle<R>, server: &str, method: &str, params: &Value) {
+    let server = server.to_string();
+    
+    match method {
+        "notifications/progress" => {
+            let Some(progress) = params.get("progress").and_then(Value::as_f64) else {
+                return;
+            };
+            events::emit_progress(app, ProgressEvent {
+                server,
+                progress_token: param(params, "progressToken", "progress_token").cloned().unwrap_or(Value::Null),
+                progress,
+                total: params.get("total").and_then(Value::as_f64),
+                message: params.get("message").and_then(Value::as_str).map(str::to_string),
+            });
+        }
+        "notifications/message" => {
+            events::emit_log_message(app, LogMessageEvent {
+                server,
+                level: params.get("level").and_then(Value::as_str).unwrap_or("info").to_string(),
+                logger: params.get("logger").and_then(Value::as_str).map(str::to_string),
+                data: params.get("data").cloned().unwrap_or(Value::Null),
+            });
+        }
+        "notifications/resources/updated" => {
+            if let Some(uri) = params.get("uri").and_then(Value::as_str) {
+                events::emit_resource_updated(app, ResourceUpdatedEvent {
+                    server,
+                    uri: uri.to_string(),
+                });
+            }
+        }
+        _ => {
+            let list = method
+                .strip_prefix("notifications/")
+                .and_then(|rest| rest.strip_suffix("/list_changed"));
+            if let Some(list) = list {
+                events::emit_list_changed(app, ListChangedEvent {
+                    server,
+                    list: list.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Forward the notifications of every server for the lifecycle of the app
+pub fn spawn<R: Runtime>(app: AppHandle<R>) {
+    let mut notifications = mcp::manager(&app).subscribe_notifications();
+    
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let ServerNotification { server, notification } = match notifications.recv().await {
+                Ok(received) => received,
+                Err(RecvError::Lagged(missed)) => {
+                    log::warn!("Dropped {} MCP server notifications", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            
+            let params = notification.params.clone().unwrap_or(Value::Null);
+            translate(&app, &server, &notification.method, &params);
+            
+            events::emit_notification(&app, NotificationEvent {
+                server,
+                method: notification.method,
+                params: notification.params,
+            });
+        }
+    });
+}
@@ -0,0 +1,207 @@
+//! Named sets of servers, such as "work", "personal" or "demo".
+//!
+//! The active profile's servers are the ones in `mcp_servers.json`, so the
+//! rest of the app only ever sees that file. Every profile also has a copy in
+//! `<config>/profiles/<name>.json`, which is refreshed from `mcp_servers.json`
+//! when switching away from it.
+
+use crate::paths::config_dir;
+use crate::mcp::config::ConfigFile;
+use crate::mcp::server::McpServerManager;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Runtime, State};
+use tokio::sync::Mutex;
+
+const PROFILES_DIR: &str = "profiles";
+const SETTINGS_FILE: &str = "profiles.json";
+const DEFAULT_PROFILE: &str = "default";
+
+lazy_static::lazy_static! {
+    // Profile changes read and write several files; one at a time
+    static ref PROFILE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct ProfileSettings {
+    active: String,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+    pub servers: usize,
+}
+
+fn load_settings<R: Runtime>(app: &AppHandle<R>) -> ProfileSettings {
+    config_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings<R: Runtime>(app: &AppHandle<R>, settings: &ProfileSettings) -> Result<(), String> {
+    let dir = config_dir(app)?;
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    fs::write(dir.join(SETTINGS_FILE), json).map_err(|e| e.to_string())
+}
+
+/// Profile names become file names, so keep them to a safe set of characters
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid profile name {:?}: use letters, digits, - and _", name))
+    }
+}
+
+fn profile_path<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, String> {
+    validate_name(name)?;
+    Ok(config_dir(app)?.join(PROFILES_DIR).join(format!("{}.json", name)))
+}
+
+fn read_profile(path: &Path) -> Result<ConfigFile, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    ConfigFile::parse(&content).map_err(|e| e.to_string())
+}
+
+/// Write the registered servers to the profile's file
+async fn save_current<R: Runtime>(app: &AppHandle<R>, manager: &McpServerManager, name: &str) -> Result<(), String> {
+    let path = profile_path(app, name)?;
+    let servers = manager
+        .get_servers()
+        .await
+        .into_iter()
+        .map(|server| (server.name.clone(), server))
+        .collect();
+    
+    let json = serde_json::to_string_pretty(&ConfigFile::new(servers)).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, json).map_err(|e| e.to_string())
+}
+
+/// Command to list the profiles, sorted by name
+#[tauri::command]
+pub async fn mcp_list_profiles<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+) -> Result<Vec<ProfileInfo>, String> {
+    let _guard = PROFILE_LOCK.lock().await;
+    let active = load_settings(&app).active;
+    
+    let mut profiles = vec![ProfileInfo {
+        name: active.clone(),
+        active: true,
+        servers: manager.get_servers().await.len(),
+    }];
+    
+    if let Ok(entries) = fs::read_dir(config_dir(&app)?.join(PROFILES_DIR)) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy().to_string()) else {
+                continue;
+            };
+            if name == active || validate_name(&name).is_err() {
+                continue;
+            }
+            
+            let servers = read_profile(&path).map(|file| file.servers.len()).unwrap_or(0);
+            profiles.push(ProfileInfo { name, active: false, servers });
+        }
+    }
+    
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(profiles)
+}
+
+/// Command to create a profile, empty or as a copy of the active one
+#[tauri::command]
+pub async fn mcp_create_profile<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+    copy_active: Option<bool>,
+) -> Result<(), String> {
+    let _guard = PROFILE_LOCK.lock().await;
+    let path = profile_path(&app, &name)?;
+    if path.exists() || load_settings(&app).active == name {
+        return Err(format!("Profile {} already exists", name));
+    }
+    
+    if copy_active.unwrap_or(false) {
+        return save_current(&app, &manager, &name).await;
+    }
+    
+    let json = serde_json::to_string_pretty(&ConfigFile::new(Default::default())).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, json).map_err(|e| e.to_string())
+}
+
+/// Command to switch profiles: the active profile's servers are stopped and
+/// saved, then the new profile's servers are loaded and its autostart servers,
+/// or all of them with prewarming on, started
+#[tauri::command]
+pub async fn mcp_switch_profile<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    name: String,
+) -> Result<(), String> {
+    let _guard = PROFILE_LOCK.lock().await;
+    let mut settings = load_settings(&app);
+    if settings.active == name {
+        return Ok(());
+    }
+    
+    // Read the new profile first so a bad file leaves everything as it was
+    let target = read_profile(&profile_path(&app, &name)?)?;
+    save_current(&app, &manager, &settings.active).await?;
+    
+    for server in manager.get_servers().await {
+        if let Err(e) = manager.stop_server(&server.name).await {
+            log::warn!("Failed to stop MCP server {}: {}", server.name, e);
+        }
+    }
+    
+    manager.apply_config(target.servers).await.map_err(|e| e.to_string())?;
+    manager.save_default_config().await.map_err(|e| e.to_string())?;
+    
+    settings.active = name;
+    save_settings(&app, &settings)?;
+    
+    let prewarm = crate::startup::load_settings(&app).prewarm;
+    tauri::async_runtime::spawn(crate::mcp::autostart::start_autostart_servers(manager.inner().clone(), prewarm));
+    Ok(())
+}
+
+/// Command to delete a profile other than the active one
+#[tauri::command]
+pub async fn mcp_delete_profile<R: Runtime>(app: AppHandle<R>, name: String) -> Result<(), String> {
+    let _guard = PROFILE_LOCK.lock().await;
+    if load_settings(&app).active == name {
+        return Err("The active profile cannot be deleted; switch to another one first".to_string());
+    }
+    
+    let path = profile_path(&app, &name)?;
+    fs::remove_file(&path).map_err(|e| format!("Cannot delete profile {}: {}", name, e))
+}
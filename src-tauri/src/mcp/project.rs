@@ -0,0 +1,146 @@
+//! Servers of the workspace folder the user opened.
+//!
+//! Opening a folder sets `${workspaceFolder}` and reads its project config.
+//! Servers approved before, unchanged since, are started right away; the
+//! rest are returned for the user to approve with
+//! `mcp_approve_project_servers`. Approvals are kept per folder in the app's
+//! config directory. Closing the folder, or opening another, stops and
+//! removes its servers.
+
+use crate::mcp::import::SkippedServer;
+use crate::mcp::server::{McpServerConfig, McpServerManager, StartResult, DEFAULT_START_PARALLELISM};
+use crate::paths::config_dir;
+use mlface_mcp::project::{self, ProjectApprovals, APPROVALS_FILE};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Runtime, State};
+use tokio::sync::Mutex;
+
+lazy_static::lazy_static! {
+    // The servers of the open workspace waiting for approval, exactly as they
+    // were shown. Opening, approving and closing hold it, one at a time.
+    static ref PENDING: Mutex<Vec<McpServerConfig>> = Mutex::new(Vec::new());
+}
+
+/// What opening a workspace folder found
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceServers {
+    pub workspace: String,
+    /// The project config read, if the folder has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+    /// The approved servers, as they started
+    pub started: Vec<StartResult>,
+    /// Servers waiting for the user's approval
+    pub pending: Vec<McpServerConfig>,
+    pub skipped: Vec<SkippedServer>,
+}
+
+fn approvals_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    Ok(config_dir(app)?.join(APPROVALS_FILE))
+}
+
+async fn load_project(dir: &Path) -> Result<Option<project::ProjectConfig>, String> {
+    let dir = dir.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || project::load(&dir))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Stop and remove the servers of the workspace that is open
+async fn close(manager: &McpServerManager) {
+    for name in manager.project_servers() {
+        if let Err(e) = manager.unregister_server(&name).await {
+            log::warn!("Failed to remove project server {}: {}", name, e);
+        }
+    }
+    manager.set_workspace_folder(None);
+}
+
+/// Register `configs` as project servers and start the enabled ones
+async fn start(manager: &McpServerManager, configs: Vec<McpServerConfig>) -> Vec<StartResult> {
+    let mut names = Vec::new();
+    let mut failed = Vec::new();
+    for config in configs {
+        let name = config.name.clone();
+        let enabled = config.enabled;
+        match manager.register_project_server(config).await {
+            Ok(()) if enabled => names.push(name),
+            Ok(()) => {}
+            Err(e) => failed.push(StartResult { server: name, error: Some(e.to_string()) }),
+        }
+    }
+    let mut results = manager.start_servers(names, DEFAULT_START_PARALLELISM).await;
+    results.extend(failed);
+    results
+}
+
+/// Command to open the workspace folder `path` in place of the one open.
+/// Its approved servers are started; the others are returned as `pending`.
+#[tauri::command]
+pub async fn mcp_open_workspace<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    path: String,
+) -> Result<WorkspaceServers, String> {
+    let dir = PathBuf::from(&path);
+    if !dir.is_dir() {
+        return Err(format!("{} is not a folder", path));
+    }
+    let mut waiting = PENDING.lock().await;
+    waiting.clear();
+    
+    close(&manager).await;
+    manager.set_workspace_folder(Some(dir.clone()));
+    let Some(config) = load_project(&dir).await? else {
+        return Ok(WorkspaceServers { workspace: path, config: None, started: Vec::new(), pending: Vec::new(), skipped: Vec::new() });
+    };
+    
+    let approvals = ProjectApprovals::load(&approvals_path(&app)?);
+    let (approved, pending): (Vec<_>, Vec<_>) = config.servers.into_iter().partition(|server| approvals.is_approved(&dir, server));
+    let started = start(&manager, approved).await;
+    waiting.clone_from(&pending);
+    Ok(WorkspaceServers {
+        workspace: path,
+        config: Some(config.path.to_string_lossy().to_string()),
+        started,
+        pending,
+        skipped: config.skipped,
+    })
+}
+
+/// Command to approve the open workspace's servers `names` and start them.
+/// The approval holds until their config changes.
+#[tauri::command]
+pub async fn mcp_approve_project_servers<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    names: Vec<String>,
+) -> Result<Vec<StartResult>, String> {
+    let mut waiting = PENDING.lock().await;
+    let dir = manager.workspace_folder().ok_or_else(|| "No workspace folder is open".to_string())?;
+    if let Some(missing) = names.iter().find(|name| !waiting.iter().any(|server| server.name == **name)) {
+        return Err(format!("{} is not waiting for approval in {}", missing, dir.display()));
+    }
+    let servers: Vec<McpServerConfig> = waiting.iter().filter(|server| names.contains(&server.name)).cloned().collect();
+    
+    let path = approvals_path(&app)?;
+    let mut approvals = ProjectApprovals::load(&path);
+    for server in &servers {
+        approvals.approve(&dir, server);
+    }
+    approvals.save(&path).map_err(|e| e.to_string())?;
+    waiting.retain(|server| !names.contains(&server.name));
+    
+    Ok(start(&manager, servers).await)
+}
+
+/// Command to close the open workspace folder, stopping its servers
+#[tauri::command]
+pub async fn mcp_close_workspace(manager: State<'_, Arc<McpServerManager>>) -> Result<(), String> {
+    PENDING.lock().await.clear();
+    close(&manager).await;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! Browsing the public MCP server registry and installing its servers.
+//!
+//! Servers are looked up live and cached in the app's cache directory, which
+//! answers when the registry can't be reached. Installing a server works like
+//! installing a catalog entry: secrets go to the keychain, and the server is
+//! registered and saved but not started.
+
+use crate::mcp::http::TlsSettings;
+use crate::mcp::import::{ConflictPolicy, ImportReport};
+use crate::mcp::server::McpServerManager;
+use mlface_mcp::registry::{Registry, RegistrySearch, RegistryServer, DEFAULT_SEARCH_LIMIT, REGISTRY_CACHE_FILE, REGISTRY_URL};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+fn registry<R: Runtime>(app: &AppHandle<R>) -> Registry {
+    Registry::new(REGISTRY_URL, app.path().app_cache_dir().ok().map(|dir| dir.join(REGISTRY_CACHE_FILE)))
+}
+
+/// Command to search the registry for `query`, returning up to `limit`
+/// servers. `offline` is set on the result when it comes from the cache.
+#[tauri::command]
+pub async fn mcp_search_registry<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    query: Option<String>,
+    limit: Option<u32>,
+) -> Result<RegistrySearch, String> {
+    let client = manager.http_clients().client(&TlsSettings::default()).map_err(|e| e.to_string())?;
+    registry(&app)
+        .search(&client, query.as_deref().unwrap_or_default(), limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Command to get the latest version of the registry server `server`
+#[tauri::command]
+pub async fn mcp_get_registry_server<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    server: String,
+) -> Result<RegistryServer, String> {
+    let client = manager.http_clients().client(&TlsSettings::default()).map_err(|e| e.to_string())?;
+    registry(&app).server(&client, &server).await.map_err(|e| e.to_string())
+}
+
+/// Command to install the registry server `server` as a server named `name`,
+/// or after the registry server, renamed if the name is taken. `values`
+/// holds its env vars, or the header its endpoint takes, by name.
+#[tauri::command]
+pub async fn mcp_install_registry_server<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, Arc<McpServerManager>>,
+    server: String,
+    name: Option<String>,
+    args: Option<Vec<String>>,
+    values: Option<HashMap<String, String>>,
+) -> Result<ImportReport, String> {
+    let client = manager.http_clients().client(&TlsSettings::default()).map_err(|e| e.to_string())?;
+    let server = registry(&app).server(&client, &server).await.map_err(|e| e.to_string())?;
+    
+    // Keychain writes may wait on an unlock prompt
+    let secrets = manager.secret_store();
+    let config = tauri::async_runtime::spawn_blocking(move || {
+        server.install(name.as_deref(), &args.unwrap_or_default(), &values.unwrap_or_default(), secrets.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+    
+    let report = manager.import_servers(vec![config], ConflictPolicy::Rename).await;
+    manager.save_default_config().await.map_err(|e| e.to_string())?;
+    Ok(report)
+}
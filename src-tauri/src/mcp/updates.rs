@@ -0,0 +1,53 @@
+//! Periodic update checks for the packages servers run.
+//!
+//! While the app runs in full mode it checks the configured servers'
+//! packages after launch and every `UPDATE_CHECK_INTERVAL` from then on,
+//! emitting `mcp:updates-available` when any are behind. The results are kept
+//! in the managed `PackageUpdates` for `mcp_get_server_status`.
+
+use crate::events::{self, UpdatesAvailableEvent};
+use crate::mcp;
+use crate::mcp::http::TlsSettings;
+use mlface_mcp::updates::{PackageUpdates, UPDATE_CHECK_INTERVAL};
+use std::collections::HashSet;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Check the packages of all configured servers, returning the servers with
+/// an update available
+pub async fn check_for_updates<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<String>, String> {
+    let manager = mcp::manager(app);
+    let servers = manager.get_servers().await;
+    let mut seen = HashSet::new();
+    let packages: Vec<_> = servers
+        .iter()
+        .filter_map(|server| server.package.clone())
+        .filter(|package| seen.insert(package.clone()))
+        .collect();
+    if packages.is_empty() {
+        return Ok(Vec::new());
+    }
+    
+    let client = manager.http_clients().client(&TlsSettings::default()).map_err(|e| e.to_string())?;
+    let available = app.state::<PackageUpdates>().check(&client, packages).await;
+    let names: Vec<String> = servers
+        .into_iter()
+        .filter(|server| server.package.as_ref().is_some_and(|package| available.iter().any(|(spec, _)| spec == package)))
+        .map(|server| server.name)
+        .collect();
+    
+    if !names.is_empty() {
+        events::emit_updates_available(app, UpdatesAvailableEvent { servers: names.clone() });
+    }
+    Ok(names)
+}
+
+/// Check for updates now and then every `UPDATE_CHECK_INTERVAL`, for as long
+/// as the app runs
+pub async fn run_update_checks<R: Runtime>(app: &AppHandle<R>) {
+    loop {
+        if let Err(e) = check_for_updates(app).await {
+            log::warn!("Failed to check MCP servers for updates: {}", e);
+        }
+        tokio::time::sleep(UPDATE_CHECK_INTERVAL).await;
+    }
+}
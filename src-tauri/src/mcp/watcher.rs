@@ -0,0 +1,84 @@
+//! Reloads the server config when `mcp_servers.json` is edited by hand.
+//!
+//! The parent directory is watched rather than the file itself because most
+//! editors save by writing a new file and renaming it over the old one, which
+//! would end a watch on the file. Events are debounced and the file is diffed
+//! against the manager's registry, so the app's own saves change nothing.
+
+use crate::events::{self, ConfigReloadedEvent};
+use crate::mcp::{self, config::ConfigFile};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use tokio::sync::mpsc;
+
+/// Edits arriving within this window are applied together
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Start watching the config file at `path`. Watching stops when the returned
+/// watcher is dropped.
+pub fn watch_config<R: Runtime>(app: AppHandle<R>, path: PathBuf) -> notify::Result<RecommendedWatcher> {
+    let dir = path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| notify::Error::path_not_found().add_path(path.clone()))?;
+    let file_name = path.file_name().map(|name| name.to_os_string());
+    
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+        Ok(event) => {
+            let touches_config = !event.kind.is_access()
+                && event.paths.iter().any(|changed| changed.file_name() == file_name.as_deref());
+            if touches_config {
+                let _ = tx.send(());
+            }
+        }
+        Err(e) => log::warn!("Error watching the MCP server config: {}", e),
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    
+    tauri::async_runtime::spawn(async move {
+        while rx.recv().await.is_some() {
+            // Let the rest of a save land before reading the file
+            tokio::time::sleep(DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+            
+            reload(&app, &path).await;
+        }
+    });
+    
+    Ok(watcher)
+}
+
+async fn reload<R: Runtime>(app: &AppHandle<R>, path: &Path) {
+    // A half-written or deleted file is picked up again by the next event
+    let file = match tokio::fs::read_to_string(path).await {
+        Ok(content) => match ConfigFile::parse(&content) {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("Ignoring invalid MCP server config {}: {}", path.display(), e);
+                return;
+            }
+        },
+        Err(_) => return,
+    };
+    
+    match mcp::manager(app).apply_config(file.servers).await {
+        Ok(diff) if !diff.is_empty() => {
+            log::info!(
+                "Reloaded MCP server config: {} added, {} removed, {} changed",
+                diff.added.len(),
+                diff.removed.len(),
+                diff.changed.len()
+            );
+            events::emit_config_reloaded(app, ConfigReloadedEvent {
+                added: diff.added,
+                removed: diff.removed,
+                changed: diff.changed,
+            });
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to apply the edited MCP server config: {}", e),
+    }
+}
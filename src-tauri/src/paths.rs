@@ -0,0 +1,87 @@
+//! Where the app keeps its data and configuration.
+//!
+//! `ConfigPaths` is resolved once during setup and registered as managed
+//! state; the server manager is handed the config file from it. Normally it
+//! points into the platform's app data directory. A file named `portable`
+//! next to the executable keeps everything in a `data` folder beside it
+//! instead, for installs run from removable drives. Tests build one over a
+//! temporary directory with `ConfigPaths::in_dir`.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, Runtime};
+
+pub const SERVER_CONFIG_FILE: &str = "mcp_servers.json";
+const PORTABLE_MARKER: &str = "portable";
+
+#[derive(Debug, Clone)]
+pub struct ConfigPaths {
+    pub data_dir: PathBuf,
+    /// `<data>/config`
+    pub config_dir: PathBuf,
+    /// `<config>/mcp_servers.json`
+    pub server_config: PathBuf,
+    /// `<data>/models`, where GGUF models for embedded inference go
+    pub models_dir: PathBuf,
+}
+
+impl ConfigPaths {
+    /// Paths under `data_dir`
+    pub fn in_dir(data_dir: impl Into<PathBuf>) -> Self {
+        let data_dir = data_dir.into();
+        let config_dir = data_dir.join("config");
+        Self {
+            server_config: config_dir.join(SERVER_CONFIG_FILE),
+            models_dir: data_dir.join("models"),
+            config_dir,
+            data_dir,
+        }
+    }
+    
+    /// The portable data folder if there is one, the app data directory otherwise
+    pub fn resolve<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        if let Some(dir) = portable_data_dir() {
+            return Ok(Self::in_dir(dir));
+        }
+        
+        app.path()
+            .app_data_dir()
+            .map(Self::in_dir)
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Create the config directory and an empty server config if they are missing
+    pub fn ensure(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.config_dir)?;
+        
+        if !self.server_config.exists() {
+            let empty_config = serde_json::json!({
+                "version": crate::mcp::config::CONFIG_VERSION,
+                "servers": {},
+            });
+            fs::write(&self.server_config, empty_config.to_string())?;
+        }
+        
+        Ok(())
+    }
+}
+
+fn portable_data_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let exe_dir = exe.parent()?;
+    exe_dir.join(PORTABLE_MARKER).exists().then(|| exe_dir.join("data"))
+}
+
+/// The registered paths, or freshly resolved ones before setup has run
+pub(crate) fn paths<R: Runtime>(app: &AppHandle<R>) -> Result<ConfigPaths, String> {
+    match app.try_state::<ConfigPaths>() {
+        Some(paths) => Ok(paths.inner().clone()),
+        None => ConfigPaths::resolve(app),
+    }
+}
+
+/// The app's config directory, `<data>/config`
+pub(crate) fn config_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    paths(app).map(|paths| paths.config_dir)
+}
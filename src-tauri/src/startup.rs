@@ -0,0 +1,178 @@
+//! What the app brings up at launch.
+//!
+//! In `full` mode the autostart servers are started and every background task
+//! runs from the start. `minimal` mode is meant for low-power laptops: no
+//! servers are started, the runtime watchdog stays off, and the MCP reaper
+//! waits until the first server is started by hand. Changes apply at the
+//! next launch.
+//!
+//! `prewarm` trades some startup work for fast first tool calls, such as in a
+//! demo: in `full` mode every enabled server is started, not only the
+//! autostart ones, at launch and when switching profiles.
+//!
+//! Either way the startup settings are read and the MCP bootstrapping, which
+//! reads and writes files, runs in the background once setup returned, so the
+//! window never waits for it. The frontend learns the saved servers are listed
+//! from `mcp:config-loaded`.
+
+use crate::events::{self, ConfigLoadedEvent};
+use crate::mcp::context::McpContext;
+use crate::paths::{config_dir, ConfigPaths};
+use crate::{mcp, watchdog};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::sync::broadcast::error::RecvError;
+
+const SETTINGS_FILE: &str = "startup.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartupMode {
+    Minimal,
+    #[default]
+    Full,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StartupSettings {
+    pub mode: StartupMode,
+    /// Start every enabled server in the background, not only autostart ones
+    pub prewarm: bool,
+}
+
+pub fn load_settings<R: Runtime>(app: &AppHandle<R>) -> StartupSettings {
+    config_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(SETTINGS_FILE)).ok())
+        .and_then(|content| serde_json::from_str::<StartupSettings>(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn load_mode<R: Runtime>(app: &AppHandle<R>) -> StartupMode {
+    load_settings(app).mode
+}
+
+fn save_settings<R: Runtime>(app: &AppHandle<R>, settings: &StartupSettings) -> Result<(), String> {
+    let dir = config_dir(app)?;
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    fs::write(dir.join(SETTINGS_FILE), json).map_err(|e| e.to_string())
+}
+
+/// Start the background work the configured startup mode calls for
+pub fn start_background<R: Runtime>(app: &AppHandle<R>) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let loading = handle.clone();
+        let StartupSettings { mode, prewarm } = tauri::async_runtime::spawn_blocking(move || load_settings(&loading))
+            .await
+            .unwrap_or_default();
+        start_tasks(&handle, mode);
+        
+        let prepared = handle.clone();
+        if let Err(e) = tauri::async_runtime::spawn_blocking(move || prepare(&prepared)).await {
+            log::error!("Failed to prepare the MCP config: {}", e);
+        }
+        // Open the approvals, audit log and spilled content before the first call
+        if let Some(context) = handle.try_state::<McpContext>() {
+            context.stores().await;
+        }
+        
+        // In minimal mode the saved servers are still listed, just not started
+        if load_config(&handle).await && mode == StartupMode::Full {
+            mcp::autostart::start_configured_servers(mcp::manager(&handle), prewarm).await;
+            mcp::updates::run_update_checks(&handle).await;
+        }
+    });
+}
+
+/// Start the watchdog and the MCP reaper as `mode` says
+fn start_tasks<R: Runtime>(app: &AppHandle<R>, mode: StartupMode) {
+    let manager = mcp::manager(app);
+    match mode {
+        StartupMode::Full => {
+            watchdog::spawn();
+            
+            // Release idle servers and old logs once budgets are configured
+            tauri::async_runtime::spawn(async move {
+                manager.run_reaper().await;
+            });
+        }
+        StartupMode::Minimal => {
+            // Nothing to reap until a server has been started
+            let mut events = manager.subscribe_events();
+            tauri::async_runtime::spawn(async move {
+                // Missed events still mean a server was touched
+                if !matches!(events.recv().await, Err(RecvError::Closed)) {
+                    drop(events);
+                    manager.run_reaper().await;
+                }
+            });
+        }
+    }
+}
+
+/// Create the config file if it is missing and watch it. Blocks on the file
+/// system.
+fn prepare<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(paths) = app.try_state::<ConfigPaths>() {
+        if let Err(e) = paths.ensure() {
+            log::error!("Failed to create the config directory: {}", e);
+        }
+        
+        // Pick up hand edits to the config while the app is running
+        match mcp::watcher::watch_config(app.clone(), paths.server_config.clone()) {
+            Ok(watcher) => {
+                app.manage(watcher);
+            }
+            Err(e) => log::warn!("Failed to watch the MCP server config: {}", e),
+        }
+    }
+}
+
+/// Load the saved server config and announce the servers it lists. Returns
+/// whether it could be loaded.
+async fn load_config<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let manager = mcp::manager(app);
+    let error = match manager.load_default_config().await {
+        Ok(()) => None,
+        Err(e) => {
+            log::error!("Failed to load MCP server config: {}", e);
+            Some(e.to_string())
+        }
+    };
+    let loaded = error.is_none();
+    
+    let servers = manager.get_servers().await.into_iter().map(|server| server.name).collect();
+    events::emit_config_loaded(app, ConfigLoadedEvent { servers, error });
+    loaded
+}
+
+/// Command to get the startup mode
+#[tauri::command]
+pub async fn app_get_startup_mode<R: Runtime>(app: AppHandle<R>) -> Result<StartupMode, String> {
+    Ok(load_mode(&app))
+}
+
+/// Command to change the startup mode; it applies at the next launch
+#[tauri::command]
+pub async fn app_set_startup_mode<R: Runtime>(app: AppHandle<R>, mode: StartupMode) -> Result<(), String> {
+    let settings = StartupSettings { mode, ..load_settings(&app) };
+    save_settings(&app, &settings)
+}
+
+/// Command to get whether all enabled servers are started in the background
+#[tauri::command]
+pub async fn app_get_prewarm<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    Ok(load_settings(&app).prewarm)
+}
+
+/// Command to choose whether all enabled servers are started in the background
+/// at launch and on profile switch; it applies from the next of either
+#[tauri::command]
+pub async fn app_set_prewarm<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let settings = StartupSettings { prewarm: enabled, ..load_settings(&app) };
+    save_settings(&app, &settings)
+}
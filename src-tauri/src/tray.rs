@@ -0,0 +1,165 @@
+//! Tray icon for controlling servers while the main window is closed.
+//!
+//! The menu lists every registered server with a running indicator and
+//! start, stop and restart actions, followed by "Stop All". It is rebuilt
+//! whenever a server changes state or the registry changes, so it never has
+//! to be refreshed by the frontend. In background mode the tray is what is
+//! left of the app once the window is closed; "Quit" exits it.
+
+use crate::background;
+use crate::mcp;
+use crate::mcp::server::{ConnectionState, McpServerConfig};
+use tauri::menu::{IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Runtime};
+use tokio::sync::broadcast::error::RecvError;
+
+const TRAY_ID: &str = "main";
+
+const STOP_ALL: &str = "stop-all";
+const SHOW: &str = "show";
+const QUIT: &str = "quit";
+
+/// Server actions; menu item ids are `<action>:<server>`
+const START: &str = "start";
+const STOP: &str = "stop";
+const RESTART: &str = "restart";
+
+/// Add the tray icon and keep its menu in sync with the server manager
+pub fn create<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("mlface")
+        .menu(&Menu::new(app)?)
+        .show_menu_on_left_click(true)
+        .on_menu_event(handle_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    
+    let manager = mcp::manager(app);
+    let mut server_events = manager.subscribe_events();
+    let mut registry = manager.subscribe_registry();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh(&app).await;
+            
+            tokio::select! {
+                received = server_events.recv() => {
+                    if let Err(RecvError::Closed) = received {
+                        return;
+                    }
+                }
+                changed = registry.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    });
+    
+    Ok(())
+}
+
+/// Rebuild the tray menu from the manager's current servers
+async fn refresh<R: Runtime>(app: &AppHandle<R>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    
+    let manager = mcp::manager(app);
+    let mut servers = manager.get_servers().await;
+    servers.sort_by(|a, b| a.name.cmp(&b.name));
+    
+    let mut states = Vec::with_capacity(servers.len());
+    for server in servers {
+        let state = manager.connection_state(&server.name).await;
+        states.push((server, state));
+    }
+    
+    let menu = build_menu(app, &states).and_then(|menu| tray.set_menu(Some(menu)));
+    if let Err(e) = menu {
+        log::warn!("Failed to update the tray menu: {}", e);
+    }
+}
+
+fn build_menu<R: Runtime>(
+    app: &AppHandle<R>,
+    servers: &[(McpServerConfig, ConnectionState)],
+) -> tauri::Result<Menu<R>> {
+    let menu = Menu::new(app)?;
+    
+    if servers.is_empty() {
+        menu.append(&MenuItem::new(app, "No servers registered", false, None::<&str>)?)?;
+    }
+    
+    let mut any_running = false;
+    for (server, state) in servers {
+        let running = *state == ConnectionState::Connected;
+        any_running |= running;
+        
+        let label = match (server.enabled, running) {
+            (false, _) => format!("○ {} (disabled)", server.name),
+            (true, true) => format!("● {}", server.name),
+            (true, false) => format!("○ {}", server.name),
+        };
+        let can_start = server.enabled && !running;
+        let start = MenuItem::with_id(app, item_id(START, &server.name), "Start", can_start, None::<&str>)?;
+        let stop = MenuItem::with_id(app, item_id(STOP, &server.name), "Stop", running, None::<&str>)?;
+        let restart = MenuItem::with_id(app, item_id(RESTART, &server.name), "Restart", server.enabled, None::<&str>)?;
+        let items: [&dyn IsMenuItem<R>; 3] = [&start, &stop, &restart];
+        menu.append(&Submenu::with_items(app, label, true, &items)?)?;
+    }
+    
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, STOP_ALL, "Stop All", any_running, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, SHOW, "Show mlface", true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(app, QUIT, "Quit", true, None::<&str>)?)?;
+    
+    Ok(menu)
+}
+
+fn item_id(action: &str, server: &str) -> String {
+    format!("{}:{}", action, server)
+}
+
+fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    match event.id().as_ref() {
+        SHOW => background::show_main_window(app),
+        QUIT => app.exit(0),
+        STOP_ALL => {
+            let manager = mcp::manager(app);
+            tauri::async_runtime::spawn(async move {
+                for server in manager.get_servers().await {
+                    if let Err(e) = manager.stop_server(&server.name).await {
+                        log::warn!("Failed to stop MCP server {}: {}", server.name, e);
+                    }
+                }
+            });
+        }
+        id => {
+            let Some((action, name)) = id.split_once(':') else {
+                return;
+            };
+            let action = action.to_string();
+            let name = name.to_string();
+            let manager = mcp::manager(app);
+            
+            // State changes rebuild the menu, so there is nothing to do on success
+            tauri::async_runtime::spawn(async move {
+                let result = match action.as_str() {
+                    START => manager.start_server(&name).await,
+                    STOP => manager.stop_server(&name).await,
+                    RESTART => manager.restart_server(&name).await,
+                    _ => return,
+                };
+                if let Err(e) = result {
+                    log::warn!("Failed to {} MCP server {}: {}", action, name, e);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Watchdog for the async runtime.
+//!
+//! A plain thread schedules a probe task on the Tauri runtime every second and
+//! waits for it to run. If a blocking call (sync I/O, a contended std `Mutex`)
+//! occupies every worker, the probe runs late; the stall is logged together
+//! with the runtime's metrics so "the app just freezes" reports have something
+//! to go on. Task dumps would need a `tokio_unstable` build, so they are not
+//! included.
+
+use serde::Serialize;
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const PROBE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A probe running later than this counts as a stall
+const STALL_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// A stall is reported again at this interval while it lasts
+const STALL_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// What the watchdog has observed so far
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RuntimeHealth {
+    /// Whether the latest probe is still waiting to run
+    pub stalled: bool,
+    /// Scheduling delay of the latest probe that ran, in milliseconds
+    pub probe_latency_ms: u64,
+    pub max_probe_latency_ms: u64,
+    pub stalls: u64,
+    /// Milliseconds since the Unix epoch
+    pub last_stall_at: Option<u64>,
+    pub last_stall_ms: Option<u64>,
+    pub workers: usize,
+    pub alive_tasks: usize,
+    /// Tasks waiting in the shared queue for a free worker
+    pub global_queue_depth: usize,
+}
+
+lazy_static::lazy_static! {
+    static ref HEALTH: Mutex<RuntimeHealth> = Mutex::new(RuntimeHealth::default());
+}
+
+fn update(f: impl FnOnce(&mut RuntimeHealth)) {
+    if let Ok(mut health) = HEALTH.lock() {
+        f(&mut health);
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Runtime metrics in the form they are logged and reported
+fn metrics() -> (usize, usize, usize) {
+    let metrics = tauri::async_runtime::handle().inner().metrics();
+    (metrics.num_workers(), metrics.num_alive_tasks(), metrics.global_queue_depth())
+}
+
+/// Start the watchdog thread. It runs for the lifetime of the app.
+pub fn spawn() {
+    let result = std::thread::Builder::new()
+        .name("runtime-watchdog".to_string())
+        .spawn(|| loop {
+            std::thread::sleep(PROBE_INTERVAL);
+            
+            let (probe_tx, probe_rx) = mpsc::channel();
+            let scheduled = Instant::now();
+            let scheduled_at = now_millis();
+            tauri::async_runtime::spawn(async move {
+                let _ = probe_tx.send(Instant::now());
+            });
+            
+            let ran = match probe_rx.recv_timeout(STALL_THRESHOLD) {
+                Ok(ran) => ran,
+                Err(mpsc::RecvTimeoutError::Disconnected) => continue,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    update(|health| health.stalled = true);
+                    
+                    // Keep reporting until the probe finally runs
+                    let ran = loop {
+                        let (workers, alive_tasks, queued) = metrics();
+                        log::warn!(
+                            "Async runtime stalled for {} ms: {} workers, {} live tasks, {} queued. \
+                             A blocking call is probably running on a runtime thread.",
+                            scheduled.elapsed().as_millis(),
+                            workers,
+                            alive_tasks,
+                            queued,
+                        );
+                        
+                        match probe_rx.recv_timeout(STALL_REPORT_INTERVAL) {
+                            Ok(ran) => break ran,
+                            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                        }
+                    };
+                    
+                    let stall_ms = ran.duration_since(scheduled).as_millis() as u64;
+                    log::warn!("Async runtime recovered after {} ms", stall_ms);
+                    update(|health| {
+                        health.stalls += 1;
+                        health.last_stall_at = Some(scheduled_at);
+                        health.last_stall_ms = Some(stall_ms);
+                    });
+                    ran
+                }
+            };
+            
+            let latency = ran.duration_since(scheduled).as_millis() as u64;
+            let (workers, alive_tasks, global_queue_depth) = metrics();
+            update(|health| {
+                health.stalled = false;
+                health.probe_latency_ms = latency;
+                health.max_probe_latency_ms = health.max_probe_latency_ms.max(latency);
+                health.workers = workers;
+                health.alive_tasks = alive_tasks;
+                health.global_queue_depth = global_queue_depth;
+            });
+        });
+    
+    if let Err(e) = result {
+        log::error!("Failed to start the runtime watchdog: {}", e);
+    }
+}
+
+/// Command to get what the watchdog has observed. It is synchronous so it is
+/// answered even while the async runtime is stalled.
+#[tauri::command]
+pub fn runtime_health() -> Result<RuntimeHealth, String> {
+    HEALTH.lock()
+        .map(|health| health.clone())
+        .map_err(|e| e.to_string())
+}
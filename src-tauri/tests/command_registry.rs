@@ -0,0 +1,169 @@
+//! Checks that every command the frontend calls is registered with the app.
+//!
+//! Commands are invoked with empty arguments, so most fail on their missing
+//! parameters; what matters is that none is rejected as unknown. The ones
+//! that take no arguments run against a temporary data directory.
+
+use mlface_lib::mcp::context::McpContext;
+use mlface_lib::mcp::server::McpServerManager;
+use mlface_lib::paths::ConfigPaths;
+use mlface_mcp::updates::PackageUpdates;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime, INVOKE_KEY};
+use tauri::webview::InvokeRequest;
+use tauri::{WebviewWindow, WebviewWindowBuilder};
+
+const COMMANDS: &[&str] = &[
+    "greet",
+    "mcp_register_server",
+    "mcp_unregister_server",
+    "mcp_start_server",
+    "mcp_start_all",
+    "mcp_stop_server",
+    "mcp_restart_server",
+    "mcp_set_server_enabled",
+    "mcp_set_server_settings",
+    "mcp_pin_server_binary",
+    "mcp_check_server_binary",
+    "mcp_check_node",
+    "mcp_check_python",
+    "mcp_check_package",
+    "mcp_install_package",
+    "mcp_cancel_package_install",
+    "mcp_check_server_updates",
+    "mcp_update_server_package",
+    "mcp_get_servers",
+    "mcp_test_connection",
+    "mcp_discover_servers",
+    "mcp_start_discovery",
+    "mcp_list_tools",
+    "mcp_call_tool",
+    "mcp_call_tool_streamed",
+    "mcp_call_tools_batch",
+    "mcp_cancel_tool_call",
+    "mcp_list_resources",
+    "mcp_read_resource",
+    "mcp_read_resource_streamed",
+    "mcp_read_resource_to_file",
+    "mcp_list_prompts",
+    "mcp_get_prompt",
+    "mcp_get_server_status",
+    "mcp_get_server_info",
+    "mcp_get_server_logs",
+    "mcp_get_traffic",
+    "mcp_unfollow_traffic",
+    "mcp_start_session_recording",
+    "mcp_stop_session_recording",
+    "mcp_query_audit_log",
+    "mcp_get_audit_retention",
+    "mcp_set_audit_retention",
+    "mcp_set_idle_timeout",
+    "mcp_set_resource_budgets",
+    "mcp_set_rate_limit",
+    "mcp_get_rate_limit_usage",
+    "mcp_get_metrics",
+    "mcp_get_dashboard",
+    "mcp_import_claude_config",
+    "mcp_preview_import",
+    "mcp_import_servers",
+    "mcp_export_config",
+    "mcp_import_config",
+    "mcp_save_config",
+    "mcp_load_config",
+    "mcp_set_secret",
+    "mcp_delete_secret",
+    "mcp_pending_installs",
+    "mcp_resolve_install",
+    "mcp_get_catalog",
+    "mcp_refresh_catalog",
+    "mcp_install_catalog_entry",
+    "mcp_search_registry",
+    "mcp_get_registry_server",
+    "mcp_install_registry_server",
+    "mcp_open_workspace",
+    "mcp_approve_project_servers",
+    "mcp_close_workspace",
+    "llm_list_models",
+    "llm_server_health",
+    "llm_pull_model",
+    "llm_chat",
+    "llm_chat_stream",
+    "llm_count_tokens",
+    "llm_run_agent",
+    "llm_cancel",
+    "mcp_resolve_tool_approval",
+    "mcp_list_tool_approvals",
+    "mcp_set_tool_approval",
+    "mcp_list_permissions",
+    "mcp_revoke_permission",
+    "mcp_list_profiles",
+    "mcp_create_profile",
+    "mcp_switch_profile",
+    "mcp_delete_profile",
+    "backup_get_settings",
+    "backup_set_settings",
+    "backup_now",
+    "backup_list",
+    "backup_restore",
+    "app_get_startup_mode",
+    "app_set_startup_mode",
+    "app_get_prewarm",
+    "app_set_prewarm",
+    "app_get_background_mode",
+    "app_set_background_mode",
+    "app_get_logs",
+    "app_pack_logs",
+    "mcp_set_debug_mode",
+    "mcp_get_debug_mode",
+    "runtime_health",
+];
+
+fn invoke(window: &WebviewWindow<MockRuntime>, cmd: &str) -> Result<(), Value> {
+    get_ipc_response(
+        window,
+        InvokeRequest {
+            cmd: cmd.into(),
+            callback: CallbackFn(0),
+            error: CallbackFn(1),
+            url: "http://tauri.localhost".parse().unwrap(),
+            body: InvokeBody::Json(json!({})),
+            headers: Default::default(),
+            invoke_key: INVOKE_KEY.to_string(),
+        },
+    )
+    .map(|_| ())
+}
+
+fn not_found(cmd: &str) -> Value {
+    json!(format!("Command {} not found", cmd))
+}
+
+#[test]
+fn every_command_is_registered() {
+    let data_dir = std::env::temp_dir().join(format!("mlface-commands-{}", std::process::id()));
+    let manager = Arc::new(McpServerManager::new());
+    let app = mock_builder()
+        .manage(manager.clone())
+        .manage(ConfigPaths::in_dir(&data_dir))
+        .manage(McpContext::new(manager))
+        .manage(PackageUpdates::default())
+        .invoke_handler(mlface_lib::invoke_handler())
+        .build(mock_context(noop_assets()))
+        .expect("failed to build mock app");
+    let window = WebviewWindowBuilder::new(&app, "main", Default::default())
+        .build()
+        .expect("failed to create mock window");
+
+    // The check below relies on how unknown commands are rejected
+    assert_eq!(invoke(&window, "mcp_no_such_command"), Err(not_found("mcp_no_such_command")));
+
+    for cmd in COMMANDS {
+        if let Err(e) = invoke(&window, cmd) {
+            assert_ne!(e, not_found(cmd), "{} is not registered", cmd);
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(&data_dir);
+}
@@ -0,0 +1,33 @@
+//! Parsing of `mlface://add-server` links.
+
+use mlface_lib::mcp::deep_link::parse_add_server;
+use tauri::Url;
+
+fn parse(link: &str) -> Result<mlface_lib::mcp::server::McpServerConfig, String> {
+    parse_add_server(&Url::parse(link).unwrap())
+}
+
+#[test]
+fn add_server_link_describes_the_server() {
+    let config = parse(
+        "mlface://add-server?name=files&command=npx&args=-y&args=%40modelcontextprotocol%2Fserver-filesystem&args=%2Fmy%20docs",
+    )
+    .unwrap();
+
+    assert_eq!(config.name, "files");
+    assert_eq!(config.command, "npx");
+    assert_eq!(config.args, vec!["-y", "@modelcontextprotocol/server-filesystem", "/my docs"]);
+    assert!(!config.autostart);
+
+    // Without the `//` the action is the path
+    assert_eq!(parse("mlface:add-server?name=files&command=npx").unwrap().name, "files");
+}
+
+#[test]
+fn incomplete_or_foreign_links_are_rejected() {
+    assert!(parse("mlface://add-server?command=npx").is_err());
+    assert!(parse("mlface://add-server?name=files").is_err());
+    assert!(parse("mlface://add-server?name=%20&command=npx").is_err());
+    assert!(parse("mlface://remove-server?name=files&command=npx").is_err());
+    assert!(parse("https://add-server?name=files&command=npx").is_err());
+}
@@ -0,0 +1,118 @@
+#!/usr/bin/env node
+/**
+ * Scripted MCP server used by the integration tests.
+ *
+ * Speaks newline-delimited JSON-RPC over stdio and answers every method the
+ * mcp_* commands use with canned data. A tool call is preceded by a burst of
+ * notifications so the tests also cover messages that arrive while the client
+ * is not waiting for a response.
+ */
+
+import readline from 'readline';
+
+const tools = [
+  {
+    name: 'echo',
+    description: 'Echo the given text back',
+    input_schema: {
+      type: 'object',
+      properties: { text: { type: 'string' } },
+      required: ['text'],
+    },
+  },
+];
+
+const resources = [
+  { uri: 'fixture://greeting', name: 'greeting', mime_type: 'text/plain' },
+];
+
+const prompts = [
+  { id: 'summarize', name: 'Summarize', description: 'Summarize a document' },
+];
+
+function send(message) {
+  process.stdout.write(JSON.stringify({ jsonrpc: '2.0', ...message }) + '\n');
+}
+
+function notify(method, params) {
+  send({ method, params });
+}
+
+class RpcError extends Error {
+  constructor(code, message, data) {
+    super(message);
+    this.code = code;
+    this.data = data;
+  }
+}
+
+const handlers = {
+  initialize: () => ({
+    protocol_version: '0.1.0',
+    name: 'scripted-server',
+    version: '1.0.0',
+    capabilities: { tools: { listChanged: true }, resources: {}, prompts: {} },
+    instructions: 'Call echo to hear yourself.',
+  }),
+  'tools/list': () => ({ tools }),
+  'tools/call': (params) => {
+    for (let i = 0; i < 10; i++) {
+      notify('notifications/message', { level: 'info', data: `step ${i}` });
+    }
+    if (params.name !== 'echo') {
+      return { is_error: true, content: [{ type: 'text', text: `unknown tool ${params.name}` }] };
+    }
+    return { content: [{ type: 'text', text: params.arguments?.text ?? '' }] };
+  },
+  'resources/list': () => ({ resources }),
+  'resources/read': (params) => {
+    if (!resources.some((resource) => resource.uri === params.uri)) {
+      throw new RpcError(-32002, 'Resource not found', { uri: params.uri });
+    }
+    return { content: [{ type: 'text', text: `contents of ${params.uri}` }] };
+  },
+  'prompts/list': () => ({ prompts }),
+  'prompts/get': (params) => ({
+    content: [{ type: 'text', text: `prompt ${params.id}` }],
+  }),
+  ping: () => ({}),
+  shutdown: () => ({}),
+};
+
+const rl = readline.createInterface({ input: process.stdin });
+
+// Diagnostics go to stderr, where the client captures them as server logs
+process.stderr.write('scripted-server ready\n');
+
+rl.on('line', (line) => {
+  if (!line.trim()) {
+    return;
+  }
+
+  const message = JSON.parse(line);
+
+  // Notifications (initialized, exit) need no answer
+  if (message.id === undefined) {
+    if (message.method === 'exit') {
+      process.exit(0);
+    }
+    return;
+  }
+
+  const handler = handlers[message.method];
+  if (!handler) {
+    send({ id: message.id, error: { code: -32601, message: `Method not found: ${message.method}` } });
+    return;
+  }
+
+  try {
+    send({ id: message.id, result: handler(message.params ?? {}) });
+  } catch (error) {
+    if (!(error instanceof RpcError)) {
+      throw error;
+    }
+    send({ id: message.id, error: { code: error.code, message: error.message, data: error.data } });
+  }
+});
+
+rl.on('close', () => process.exit(0));
@@ -0,0 +1,375 @@
+//! End-to-end tests of the `mcp_*` commands against a scripted stdio server.
+//!
+//! The app is booted on Tauri's mock runtime and every command is invoked
+//! through the IPC layer, exactly as the frontend would. The server fixture
+//! lives in `tests/fixtures/scripted-server.mjs` and requires `node` on PATH.
+
+use mlface_lib::mcp::approval::{ApprovedTool, PermissionScope, ToolApprovals, ToolPermission};
+use mlface_lib::mcp::commands::McpServerStatus;
+use mlface_lib::mcp::context::{McpContext, McpStores};
+use mlface_lib::mcp::server::{McpServerConfig, McpServerManager};
+use mlface_lib::mcp::types::*;
+use mlface_mcp::updates::PackageUpdates;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime, INVOKE_KEY};
+use tauri::webview::InvokeRequest;
+use tauri::{App, Manager, WebviewWindow, WebviewWindowBuilder};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/scripted-server.mjs");
+
+fn create_window() -> (App<MockRuntime>, WebviewWindow<MockRuntime>) {
+    create_window_with(ToolApprovals::default())
+}
+
+fn create_window_with(approvals: ToolApprovals) -> (App<MockRuntime>, WebviewWindow<MockRuntime>) {
+    // Each app gets its own manager, so tests can run in parallel
+    let manager = Arc::new(McpServerManager::new());
+    let app = mock_builder()
+        .manage(manager.clone())
+        .manage(McpContext::with_stores(manager, McpStores { approvals, ..Default::default() }))
+        .manage(PackageUpdates::default())
+        .invoke_handler(mlface_lib::invoke_handler())
+        .build(mock_context(noop_assets()))
+        .expect("failed to build mock app");
+
+    let window = WebviewWindowBuilder::new(&app, "main", Default::default())
+        .build()
+        .expect("failed to create mock window");
+
+    (app, window)
+}
+
+fn invoke<T: DeserializeOwned>(window: &WebviewWindow<MockRuntime>, cmd: &str, args: Value) -> Result<T, Value> {
+    get_ipc_response(
+        window,
+        InvokeRequest {
+            cmd: cmd.into(),
+            callback: CallbackFn(0),
+            error: CallbackFn(1),
+            url: "http://tauri.localhost".parse().unwrap(),
+            body: InvokeBody::Json(args),
+            headers: Default::default(),
+            invoke_key: INVOKE_KEY.to_string(),
+        },
+    )
+    .map(|body| body.deserialize::<T>().expect("unexpected response shape"))
+}
+
+fn register_fixture(window: &WebviewWindow<MockRuntime>, name: &str) {
+    invoke::<()>(
+        window,
+        "mcp_register_server",
+        json!({ "config": { "name": name, "command": "node", "args": [FIXTURE], "env": {} } }),
+    )
+    .expect("register failed");
+}
+
+#[test]
+fn server_lifecycle() {
+    let (_app, window) = create_window();
+    register_fixture(&window, "lifecycle");
+
+    let servers: Vec<McpServerConfig> = invoke(&window, "mcp_get_servers", json!({})).unwrap();
+    assert!(servers.iter().any(|s| s.name == "lifecycle"));
+
+    invoke::<()>(&window, "mcp_start_server", json!({ "name": "lifecycle" })).unwrap();
+    let status: Vec<McpServerStatus> = invoke(&window, "mcp_get_server_status", json!({})).unwrap();
+    let server = status.iter().find(|s| s.name == "lifecycle").unwrap();
+    assert!(server.is_running);
+
+    // Starting twice must not spawn a second process
+    invoke::<()>(&window, "mcp_start_server", json!({ "name": "lifecycle" })).unwrap();
+
+    invoke::<()>(&window, "mcp_restart_server", json!({ "name": "lifecycle" })).unwrap();
+    let status: Vec<McpServerStatus> = invoke(&window, "mcp_get_server_status", json!({})).unwrap();
+    let server = status.iter().find(|s| s.name == "lifecycle").unwrap();
+    assert!(server.is_running);
+
+    invoke::<()>(&window, "mcp_stop_server", json!({ "name": "lifecycle" })).unwrap();
+    let status: Vec<McpServerStatus> = invoke(&window, "mcp_get_server_status", json!({})).unwrap();
+    let server = status.iter().find(|s| s.name == "lifecycle").unwrap();
+    assert!(!server.is_running);
+
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "lifecycle" })).unwrap();
+    let servers: Vec<McpServerConfig> = invoke(&window, "mcp_get_servers", json!({})).unwrap();
+    assert!(!servers.iter().any(|s| s.name == "lifecycle"));
+}
+
+#[test]
+fn disabled_server_is_not_started() {
+    let (_app, window) = create_window();
+    register_fixture(&window, "toggled");
+
+    invoke::<()>(&window, "mcp_start_server", json!({ "name": "toggled" })).unwrap();
+    invoke::<()>(&window, "mcp_set_server_enabled", json!({ "name": "toggled", "enabled": false })).unwrap();
+
+    // Disabling stops the server and keeps its configuration
+    let status: Vec<McpServerStatus> = invoke(&window, "mcp_get_server_status", json!({})).unwrap();
+    let server = status.iter().find(|s| s.name == "toggled").unwrap();
+    assert!(!server.enabled);
+    assert!(!server.is_running);
+
+    let err = invoke::<ListToolsResult>(&window, "mcp_list_tools", json!({ "serverName": "toggled" })).unwrap_err();
+    assert!(err["message"].as_str().unwrap().contains("disabled"));
+
+    invoke::<()>(&window, "mcp_set_server_enabled", json!({ "name": "toggled", "enabled": true })).unwrap();
+    let tools: ListToolsResult = invoke(&window, "mcp_list_tools", json!({ "serverName": "toggled" })).unwrap();
+    assert_eq!(tools.tools[0].name, "echo");
+
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "toggled" })).unwrap();
+}
+
+#[test]
+fn permissions_can_be_listed_and_revoked() {
+    let (_app, window) = create_window();
+
+    invoke::<()>(&window, "mcp_set_tool_approval", json!({ "server": "files", "tool": "read", "always": true })).unwrap();
+    let permissions: Vec<ToolPermission> = invoke(&window, "mcp_list_permissions", json!({})).unwrap();
+    assert_eq!(permissions, vec![ToolPermission { server: "files".into(), tool: Some("read".into()), scope: PermissionScope::Always }]);
+
+    assert!(invoke::<bool>(&window, "mcp_revoke_permission", json!({ "server": "files", "tool": "read" })).unwrap());
+    assert!(!invoke::<bool>(&window, "mcp_revoke_permission", json!({ "server": "files", "tool": "read" })).unwrap());
+    let permissions: Vec<ToolPermission> = invoke(&window, "mcp_list_permissions", json!({})).unwrap();
+    assert!(permissions.is_empty());
+}
+
+#[test]
+fn servers_can_be_allowed_as_a_whole() {
+    let path = std::env::temp_dir().join(format!("mlface-approvals-{}.json", std::process::id()));
+    std::fs::write(&path, r#"{ "servers": ["files"], "never": { "files": ["delete"] } }"#).unwrap();
+    let (_app, window) = create_window_with(ToolApprovals::load(path.clone()));
+
+    // The server's grant is listed before the decisions about its tools
+    let permissions: Vec<ToolPermission> = invoke(&window, "mcp_list_permissions", json!({})).unwrap();
+    assert_eq!(permissions, vec![
+        ToolPermission { server: "files".into(), tool: None, scope: PermissionScope::Server },
+        ToolPermission { server: "files".into(), tool: Some("delete".into()), scope: PermissionScope::Never },
+    ]);
+
+    assert!(invoke::<bool>(&window, "mcp_revoke_permission", json!({ "server": "files" })).unwrap());
+    assert!(!invoke::<bool>(&window, "mcp_revoke_permission", json!({ "server": "files", "tool": null })).unwrap());
+    let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["servers"], json!([]));
+    assert_eq!(saved["never"]["files"], json!(["delete"]));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn tools_resources_and_prompts() {
+    let (_app, window) = create_window();
+    register_fixture(&window, "features");
+
+    let tools: ListToolsResult = invoke(&window, "mcp_list_tools", json!({ "serverName": "features" })).unwrap();
+    assert_eq!(tools.tools.len(), 1);
+    assert_eq!(tools.tools[0].name, "echo");
+
+    // Nobody is there to answer the approval prompt
+    invoke::<()>(
+        &window,
+        "mcp_set_tool_approval",
+        json!({ "server": "features", "tool": "echo", "always": true }),
+    )
+    .unwrap();
+    let approved: Vec<ApprovedTool> = invoke(&window, "mcp_list_tool_approvals", json!({})).unwrap();
+    assert_eq!(approved, vec![ApprovedTool { server: "features".into(), tool: "echo".into() }]);
+
+    // The fixture sends a burst of notifications before each tool result
+    for i in 0..3 {
+        let text = format!("hello {}", i);
+        let result: CallToolResult = invoke(
+            &window,
+            "mcp_call_tool",
+            json!({ "serverName": "features", "toolName": "echo", "args": { "text": text } }),
+        )
+        .unwrap();
+        assert!(matches!(&result.content[0], Content::Text { text: t } if *t == text));
+    }
+
+    let resources: ListResourcesResult = invoke(&window, "mcp_list_resources", json!({ "serverName": "features" })).unwrap();
+    assert_eq!(resources.resources[0].uri, "fixture://greeting");
+
+    let read: ReadResourceResult = invoke(
+        &window,
+        "mcp_read_resource",
+        json!({ "serverName": "features", "uri": "fixture://greeting" }),
+    )
+    .unwrap();
+    assert!(matches!(&read.content[0], Content::Text { text } if text == "contents of fixture://greeting"));
+
+    // Server errors keep their kind and data payload
+    let err = invoke::<ReadResourceResult>(
+        &window,
+        "mcp_read_resource",
+        json!({ "serverName": "features", "uri": "fixture://missing" }),
+    )
+    .unwrap_err();
+    assert_eq!(err["kind"], "resource_not_found");
+    assert_eq!(err["code"], -32002);
+    assert_eq!(err["data"]["uri"], "fixture://missing");
+
+    let prompts: ListPromptsResult = invoke(&window, "mcp_list_prompts", json!({ "serverName": "features" })).unwrap();
+    assert_eq!(prompts.prompts[0].id, "summarize");
+
+    let prompt: GetPromptResult = invoke(
+        &window,
+        "mcp_get_prompt",
+        json!({ "serverName": "features", "promptId": "summarize", "params": null }),
+    )
+    .unwrap();
+    assert!(matches!(&prompt.content[0], Content::Text { text } if text == "prompt summarize"));
+
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "features" })).unwrap();
+}
+
+#[test]
+fn server_output_is_captured() {
+    let (_app, window) = create_window();
+    register_fixture(&window, "logging");
+
+    invoke::<()>(&window, "mcp_start_server", json!({ "name": "logging" })).unwrap();
+
+    let logs: Vec<Value> = invoke(&window, "mcp_get_server_logs", json!({ "name": "logging", "tail": 10 })).unwrap();
+    assert!(logs.iter().any(|l| l["stream"] == "stderr" && l["line"] == "scripted-server ready"));
+
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "logging" })).unwrap();
+}
+
+#[test]
+fn unknown_server_is_an_error() {
+    let (_app, window) = create_window();
+
+    let err = invoke::<ListToolsResult>(&window, "mcp_list_tools", json!({ "serverName": "missing" })).unwrap_err();
+    assert_eq!(err["kind"], "internal_error");
+    assert!(err["message"].as_str().unwrap().contains("not found"));
+}
+
+#[test]
+fn connection_test_and_discovery() {
+    let (_app, window) = create_window();
+
+    assert!(invoke::<bool>(&window, "mcp_test_connection", json!({ "url": "node" })).unwrap());
+
+    let empty_dir = std::env::temp_dir().join(format!("mlface-discovery-{}", std::process::id()));
+    std::fs::create_dir_all(&empty_dir).unwrap();
+    let found: Vec<McpServerConfig> = invoke(
+        &window,
+        "mcp_discover_servers",
+        json!({ "path": empty_dir.to_string_lossy() }),
+    )
+    .unwrap();
+    assert!(found.is_empty());
+    std::fs::remove_dir_all(&empty_dir).unwrap();
+}
+
+#[test]
+fn save_and_load_config() {
+    let (app, window) = create_window();
+
+    let config_path = std::env::temp_dir().join(format!("mlface-config-{}.json", std::process::id()));
+    app.state::<Arc<McpServerManager>>().set_config_path(Some(config_path.clone()));
+
+    register_fixture(&window, "persisted");
+    invoke::<()>(&window, "mcp_save_config", json!({})).unwrap();
+
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "persisted" })).unwrap();
+    invoke::<()>(&window, "mcp_load_config", json!({})).unwrap();
+
+    let servers: Vec<McpServerConfig> = invoke(&window, "mcp_get_servers", json!({})).unwrap();
+    let restored = servers.iter().find(|s| s.name == "persisted").expect("server not restored");
+    assert_eq!(restored.command, "node");
+    assert_eq!(restored.args, vec![FIXTURE.to_string()]);
+
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "persisted" })).unwrap();
+    std::fs::remove_file(&config_path).unwrap();
+}
+
+#[test]
+fn import_claude_desktop_config() {
+    let (_app, window) = create_window();
+
+    let config_path = std::env::temp_dir().join(format!("mlface-claude-{}.json", std::process::id()));
+    std::fs::write(
+        &config_path,
+        json!({
+            "mcpServers": {
+                "imported": { "command": "node", "args": [FIXTURE], "env": { "TOKEN": "x" } },
+                "broken": { "args": [] }
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let path = config_path.to_string_lossy();
+
+    let report: Value = invoke(&window, "mcp_import_claude_config", json!({ "path": path })).unwrap();
+    assert_eq!(report["imported"], json!(["imported"]));
+    assert_eq!(report["skipped"][0]["name"], "broken");
+
+    // Importing again clashes with the existing server
+    let report: Value = invoke(&window, "mcp_import_claude_config", json!({ "path": path })).unwrap();
+    assert!(report["imported"].as_array().unwrap().is_empty());
+
+    let report: Value = invoke(
+        &window,
+        "mcp_import_claude_config",
+        json!({ "path": path, "onConflict": "rename" }),
+    )
+    .unwrap();
+    assert_eq!(report["imported"], json!(["imported-2"]));
+
+    let servers: Vec<McpServerConfig> = invoke(&window, "mcp_get_servers", json!({})).unwrap();
+    let imported = servers.iter().find(|s| s.name == "imported").unwrap();
+    assert_eq!(imported.env["TOKEN"], "x");
+
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "imported" })).unwrap();
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "imported-2" })).unwrap();
+    std::fs::remove_file(&config_path).unwrap();
+}
+
+#[test]
+fn preview_and_import_vscode_settings() {
+    let (_app, window) = create_window();
+    register_fixture(&window, "existing");
+
+    let settings_path = std::env::temp_dir().join(format!("mlface-vscode-{}.json", std::process::id()));
+    std::fs::write(
+        &settings_path,
+        r#"{
+            // VS Code allows comments and trailing commas
+            "mcp": {
+                "servers": {
+                    "existing": { "command": "node", "args": [] },
+                    "remote": { "type": "sse", "url": "http://localhost:9/sse" },
+                },
+            },
+        }"#,
+    )
+    .unwrap();
+
+    let preview: Value = invoke(
+        &window,
+        "mcp_preview_import",
+        json!({ "source": "vs_code", "path": settings_path.to_string_lossy() }),
+    )
+    .unwrap();
+    assert_eq!(preview["servers"].as_array().unwrap().len(), 2);
+    assert_eq!(preview["conflicts"], json!(["existing"]));
+
+    // Accept only the server that doesn't clash
+    let accepted: Vec<Value> = preview["servers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|s| s["name"] == "remote")
+        .cloned()
+        .collect();
+    let report: Value = invoke(&window, "mcp_import_servers", json!({ "servers": accepted })).unwrap();
+    assert_eq!(report["imported"], json!(["remote"]));
+
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "remote" })).unwrap();
+    invoke::<()>(&window, "mcp_unregister_server", json!({ "name": "existing" })).unwrap();
+    std::fs::remove_file(&settings_path).unwrap();
+}
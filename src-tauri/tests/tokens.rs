@@ -0,0 +1,79 @@
+//! Token counts and which messages go when a conversation is too long.
+
+use mlface_lib::llm::tokens::{known_context_window, overflowing, texts, Encoding, TokenCount, MESSAGE_OVERHEAD, REPLY_OVERHEAD};
+use mlface_lib::llm::{ChatMessage, ChatRequest, ToolCall};
+use serde_json::json;
+
+fn message(role: &str, content: &str) -> ChatMessage {
+    ChatMessage { role: role.to_string(), content: content.to_string(), tool_calls: Vec::new(), tool_call_id: None }
+}
+
+fn request(messages: Vec<ChatMessage>) -> ChatRequest {
+    ChatRequest { provider: None, model: "gpt-4o".to_string(), messages, options: None, context: None }
+}
+
+/// A conversation with a tool call in its first turn
+fn conversation() -> Vec<ChatMessage> {
+    let mut call = message("assistant", "");
+    call.tool_calls.push(ToolCall { id: "call_0".to_string(), name: "files__read".to_string(), arguments: json!({ "path": "/a" }) });
+    let mut result = message("tool", "contents of a");
+    result.tool_call_id = Some("call_0".to_string());
+    vec![
+        message("system", "You are helpful."),
+        message("user", "What is in /a?"),
+        call,
+        result,
+        message("assistant", "It holds the contents of a."),
+        message("user", "And /b?"),
+    ]
+}
+
+#[test]
+fn openai_models_are_counted_with_their_encoding() {
+    assert_eq!(Encoding::for_model("gpt-4o-mini"), Some(Encoding::O200k));
+    assert_eq!(Encoding::for_model("openai/gpt-4.1"), Some(Encoding::O200k));
+    assert_eq!(Encoding::for_model("gpt-4-0613"), Some(Encoding::Cl100k));
+    assert_eq!(Encoding::for_model("claude-sonnet-4"), None);
+    assert_eq!(Encoding::for_model("llama3.2"), None);
+
+    assert_eq!(Encoding::O200k.count(&["Hello, world!".to_string(), String::new()]), vec![4, 0]);
+}
+
+#[test]
+fn counts_cover_messages_tool_calls_and_tools() {
+    let mut request = request(conversation());
+    let without_tools = TokenCount::new(&request, Encoding::O200k.count(&texts(&request)), true);
+    assert_eq!(without_tools.messages.len(), 6);
+    assert_eq!(without_tools.tools, 0);
+    // The call's name and arguments count, though it has no content
+    assert!(without_tools.messages[2] > MESSAGE_OVERHEAD);
+    assert_eq!(without_tools.total(), without_tools.messages.iter().sum::<u64>() + REPLY_OVERHEAD);
+
+    request.options = Some(json!({ "temperature": 0, "tools": [{ "name": "files__read" }] }));
+    let with_tools = TokenCount::new(&request, Encoding::O200k.count(&texts(&request)), true);
+    assert!(with_tools.tools > 0);
+    assert_eq!(with_tools.total(), without_tools.total() + with_tools.tools);
+}
+
+#[test]
+fn the_oldest_messages_go_first_with_their_tool_results() {
+    let messages = conversation();
+    let count = TokenCount { messages: vec![10; 6], tools: 0, exact: true };
+    // 63 tokens in all
+    assert_eq!(overflowing(&messages, &count, 100).unwrap(), 1..1);
+    // Leaving out the question leaves the call, its result and the answer,
+    // which go too so the rest starts with a user message
+    assert_eq!(overflowing(&messages, &count, 55).unwrap(), 1..5);
+    // The system prompt and the last turn stay
+    assert_eq!(overflowing(&messages, &count, 23).unwrap(), 1..5);
+    assert!(overflowing(&messages, &count, 22).is_err());
+}
+
+#[test]
+fn context_windows_of_hosted_models_are_known() {
+    assert_eq!(known_context_window("gpt-4o-2024-08-06"), Some(128_000));
+    assert_eq!(known_context_window("gpt-4.1-mini"), Some(1_047_576));
+    assert_eq!(known_context_window("gpt-4-0613"), Some(8_192));
+    assert_eq!(known_context_window("anthropic/claude-3-5-haiku"), Some(200_000));
+    assert_eq!(known_context_window("mistral-large"), None);
+}
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * What happened in a step of an agent run
+ */
+export type AgentStep = { "kind": "reply", content: string, tool_calls: number, } | { "kind": "tool_call", id: string, server: string, tool: string, arguments: JsonValue, } | { "kind": "tool_result", id: string, server: string, tool: string, content: string, is_error: boolean, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AgentStep } from "./AgentStep";
+
+/**
+ * A step of `llm_run_agent`
+ */
+export type AgentStepEvent = { channel: string, 
+/**
+ * Counts the model's replies, from 1
+ */
+step: number, detail: AgentStep, };
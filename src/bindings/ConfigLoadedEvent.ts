@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The servers listed once the saved config was loaded at launch
+ */
+export type ConfigLoadedEvent = { servers: Array<string>, error?: string, };
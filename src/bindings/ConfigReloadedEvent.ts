@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Servers affected by a config reload
+ */
+export type ConfigReloadedEvent = { added: Array<string>, removed: Array<string>, changed: Array<string>, };
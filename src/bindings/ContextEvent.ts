@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The tokens a chat of `llm_chat_stream` or a step of `llm_run_agent` is
+ * about to send, after fitting it into the context window
+ */
+export type ContextEvent = { channel: string, prompt_tokens: number, 
+/**
+ * Whether the model's own tokenizer counted them, rather than an estimate
+ */
+exact: boolean, context_window?: number, 
+/**
+ * Tokens kept for the reply
+ */
+reply_tokens: number, 
+/**
+ * Messages left out or summarized to fit
+ */
+removed: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Servers found by a discovery run in the background
+ */
+export type DiscoveryFinishedEvent = { 
+/**
+ * The directory searched
+ */
+path: string, 
+/**
+ * Configs for the servers found, as `mcp_discover_servers` returns them
+ */
+servers: Array<JsonValue>, error?: string, };
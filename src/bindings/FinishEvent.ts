@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The end of a chat of `llm_chat_stream`, the last event on its channel
+ */
+export type FinishEvent = { channel: string, 
+/**
+ * The provider's reason, such as `stop`, `length` or `tool_use`, or
+ * `cancelled` or `error`
+ */
+reason: string, error?: string, };
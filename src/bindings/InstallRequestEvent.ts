@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A server offered by an `mlface://add-server` link. Nothing is registered
+ * until the user accepts it with `mcp_resolve_install`.
+ */
+export type InstallRequestEvent = { id: number, name: string, command: string, args: Array<string>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type LogMessageEvent = { server: string, 
+/**
+ * Syslog severity as sent by the server, e.g. `info` or `error`
+ */
+level: string, logger?: string, data: JsonValue, };
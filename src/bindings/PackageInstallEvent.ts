@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A line the installer printed during `mcp_install_package`
+ */
+export type PackageInstallEvent = { 
+/**
+ * The `install_id` the install was started with
+ */
+id: string, stream: "stdout" | "stderr", line: string, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A progress update of `llm_pull_model`
+ */
+export type PullProgressEvent = { 
+/**
+ * The `pull_id` the pull was started with
+ */
+id: string, status: string, 
+/**
+ * The layer being downloaded, which `total` and `completed` count bytes of
+ */
+digest?: string, total?: number, completed?: number, };
@@ -0,0 +1,29 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * A piece of one content item of a resource read with
+ * `mcp_read_resource_streamed`. Joining the pieces of an item in order gives
+ * back its text or base64 data.
+ */
+export type ResourceChunkEvent = { 
+/**
+ * Identifies the read the piece belongs to
+ */
+channel: string, 
+/**
+ * Which content item of the resource the piece belongs to
+ */
+index: number, 
+/**
+ * Where the piece starts in the item's text or data, in bytes
+ */
+offset: number, 
+/**
+ * The item with its text or data cut down to this piece
+ */
+content: JsonValue, 
+/**
+ * Whether this is the last piece of the item
+ */
+last: boolean, };
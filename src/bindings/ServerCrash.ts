@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How a local server's process ended when it crashed, and the last lines it
+ * wrote to stderr
+ */
+export type ServerCrash = { server: string, 
+/**
+ * Milliseconds since the Unix epoch
+ */
+timestamp: number, exit: { code?: number, signal?: number }, stderr: Array<string>, 
+/**
+ * Exit status and the stderr line most likely to explain it
+ */
+summary: string, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ServerCrash } from "./ServerCrash";
+
+/**
+ * Payload of the `mcp:server-*` lifecycle events
+ */
+export type ServerLifecycleEvent = { server: string, reason?: string, 
+/**
+ * How the process ended, when a local server crashed
+ */
+crash?: ServerCrash, 
+/**
+ * Milliseconds since the Unix epoch
+ */
+timestamp: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ServerLogEvent = { server: string, stream: "stdout" | "stderr", line: string, 
+/**
+ * Milliseconds since the Unix epoch
+ */
+timestamp: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Lifecycle state of an MCP server
+ */
+export type ServerState = "connecting" | "running" | "stopped" | "crashed";
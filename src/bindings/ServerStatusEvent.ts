@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ServerCrash } from "./ServerCrash";
+import type { ServerState } from "./ServerState";
+
+export type ServerStatusEvent = { server: string, state: ServerState, reason?: string, crash?: ServerCrash, 
+/**
+ * Milliseconds since the Unix epoch
+ */
+timestamp: number, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A request still waiting for its response past the server's slow-call
+ * threshold. Sent once when the threshold is crossed and again, with
+ * `finished` set, when the response arrives or the request fails.
+ */
+export type SlowCallEvent = { server: string, method: string, 
+/**
+ * Milliseconds since the request was sent
+ */
+elapsed_ms: number, finished: boolean, };
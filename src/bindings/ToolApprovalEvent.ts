@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * A tool call held until the user answers it with `mcp_resolve_tool_approval`
+ */
+export type ToolApprovalEvent = { id: number, server: string, tool: string, arguments?: JsonValue, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * One piece of a streamed tool call. A stream ends with `result` or `error`.
+ */
+export type ToolStreamChunk = { "kind": "progress", progress: number, total?: number, message?: string, } | { "kind": "content", index: number, content: JsonValue, } | { "kind": "result", is_error: boolean, } | { "kind": "error", code: number, message: string, };
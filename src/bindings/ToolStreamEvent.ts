@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ToolStreamChunk } from "./ToolStreamChunk";
+
+export type ToolStreamEvent = { 
+/**
+ * Identifies the call the chunk belongs to
+ */
+channel: string, chunk: ToolStreamChunk, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * A recorded JSON-RPC message, as returned by `mcp_get_traffic`
+ */
+export type TrafficEvent = { seq: number, server: string, direction: "sent" | "received", 
+/**
+ * Milliseconds since the Unix epoch
+ */
+timestamp: number, kind: "request" | "response" | "notification", 
+/**
+ * For responses, the method of the request they answer
+ */
+method?: string, id?: JsonValue, 
+/**
+ * For responses, milliseconds since their request
+ */
+latency_ms?: number, error: boolean, message: JsonValue, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The servers an update check found behind their package's latest release
+ */
+export type UpdatesAvailableEvent = { servers: Array<string>, };
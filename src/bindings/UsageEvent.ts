@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The tokens a chat of `llm_chat_stream` used
+ */
+export type UsageEvent = { channel: string, prompt_tokens?: number, completion_tokens?: number, };
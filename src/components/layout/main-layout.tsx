@@ -2,6 +2,8 @@ import React, { useState } from "react";
 import { Sidebar } from "../sidebar/sidebar";
 import { ChatWindow } from "../chat/chat-window";
 import { SettingsDialog } from "../settings/settings-dialog";
+import { McpInstallDialog } from "../mcp/install-dialog";
+import { McpToolApprovalDialog } from "../mcp/tool-approval-dialog";
 
 export function MainLayout() {
   const [isSettingsOpen, setIsSettingsOpen] = useState(false);
@@ -64,6 +66,12 @@ export function MainLayout() {
         isOpen={isSettingsOpen} 
         onClose={() => setIsSettingsOpen(false)} 
       />
+      
+      {/* Servers offered by "Add to mlFace" links */}
+      <McpInstallDialog />
+      
+      {/* Consent before a tool runs */}
+      <McpToolApprovalDialog />
     </div>
   );
 }
\ No newline at end of file
@@ -1,13 +1,49 @@
 import { invoke } from "@tauri-apps/api/tauri";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+import { ConfigLoadedEvent } from "../../bindings/ConfigLoadedEvent";
+import { ConfigReloadedEvent } from "../../bindings/ConfigReloadedEvent";
+import { DiscoveryFinishedEvent } from "../../bindings/DiscoveryFinishedEvent";
+import { InstallRequestEvent } from "../../bindings/InstallRequestEvent";
+import { ServerStatusEvent } from "../../bindings/ServerStatusEvent";
+import { SlowCallEvent } from "../../bindings/SlowCallEvent";
+import { NotificationEvent } from "../../bindings/NotificationEvent";
+import { PackageInstallEvent } from "../../bindings/PackageInstallEvent";
+import { ResourceChunkEvent } from "../../bindings/ResourceChunkEvent";
+import { ToolStreamChunk } from "../../bindings/ToolStreamChunk";
+import { ToolApprovalEvent } from "../../bindings/ToolApprovalEvent";
+import { ToolStreamEvent } from "../../bindings/ToolStreamEvent";
+import { TrafficEvent } from "../../bindings/TrafficEvent";
+import { UpdatesAvailableEvent } from "../../bindings/UpdatesAvailableEvent";
 import {
   McpServerConfig,
   McpServerStatus,
+  ServerSettings,
+  ServerAuth,
+  BinaryPin,
+  BinaryCheck,
+  NodeRuntime,
+  PythonRuntime,
+  PackageSpec,
   ListToolsResult,
   CallToolResult,
+  BatchToolCall,
+  BatchToolResult,
   ListResourcesResult,
   ReadResourceResult,
   ListPromptsResult,
   GetPromptResult,
+  AuditEntry,
+  AuditQuery,
+  RateLimit,
+  RateLimitReport,
+  MetricsReport,
+  Dashboard,
+  LogLevel,
+  LogModule,
+  LogLevelsReport,
+  InitializeResult,
+  TrafficFilter,
 } from "./types";
 
 // Helper function to check if the Tauri backend has MCP commands
@@ -35,18 +71,154 @@ export async function registerServer(
   name: string,
   command: string,
   args: string[] = [],
-  env: Record<string, string> = {}
+  env: Record<string, string> = {},
+  cwd?: string,
+  autostart = false,
+  settings?: ServerSettings,
+  auth?: ServerAuth,
+  roots?: string[],
+  binary?: BinaryPin,
+  pkg?: string | PackageSpec,
+  description?: string
 ): Promise<void> {
+  const config = { name, command, args, env, cwd, autostart, settings, auth, roots, binary, package: pkg, description };
   if (await checkMcpEnabled()) {
-    return invoke("mcp_register_server", { name, command, args, env });
+    return invoke("mcp_register_server", { config });
   }
   
   // Fallback: store in localStorage
   const servers = JSON.parse(localStorage.getItem("mcp_servers") || "{}");
-  servers[name] = { name, command, args, env };
+  servers[name] = config;
   localStorage.setItem("mcp_servers", JSON.stringify(servers));
 }
 
+// Pin a local server to its executable as it is now; with enforce, a changed
+// binary is refused at launch instead of started with a warning
+export async function pinServerBinary(name: string, enforce = false): Promise<BinaryPin | null> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_pin_server_binary", { name, enforce });
+  }
+  return null;
+}
+
+export async function checkServerBinary(name: string): Promise<BinaryCheck> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_check_server_binary", { name });
+  }
+  return { status: "unpinned" };
+}
+
+// Rejects with what to install when Node.js is missing
+export async function checkNode(): Promise<NodeRuntime> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_check_node");
+  }
+  throw new Error("Running npm packages is not available in this build");
+}
+
+// Rejects with what to install when neither uvx nor pipx is there
+export async function checkPython(): Promise<PythonRuntime> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_check_python");
+  }
+  throw new Error("Running Python packages is not available in this build");
+}
+
+// Whether a package is installed already; offer installPackage when it isn't,
+// so the server's first start doesn't wait for the download
+export async function checkPackage(pkg: PackageSpec): Promise<boolean> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_check_package", { package: pkg });
+  }
+  return false;
+}
+
+// Install a package with npm, uv or pipx, passing on each line the installer
+// prints. Stop it with cancelPackageInstall(installId), which rejects this.
+export async function installPackage(
+  pkg: PackageSpec,
+  onOutput: (stream: "stdout" | "stderr", line: string) => void,
+  installId: string = crypto.randomUUID()
+): Promise<void> {
+  if (!(await checkMcpEnabled())) {
+    throw new Error("Installing packages is not available in this build");
+  }
+  
+  // Output is sent to the calling window only
+  const unlisten = await getCurrentWebviewWindow().listen<PackageInstallEvent>("mcp:package-install", (event) => {
+    if (event.payload.id === installId) {
+      onOutput(event.payload.stream, event.payload.line);
+    }
+  });
+  try {
+    await invoke("mcp_install_package", { package: pkg, installId });
+  } finally {
+    unlisten();
+  }
+}
+
+// Resolves to false if the install already finished
+export async function cancelPackageInstall(installId: string): Promise<boolean> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_cancel_package_install", { installId });
+  }
+  return false;
+}
+
+// Look for newer releases of the packages servers run, resolving to the
+// servers with an update available. The app also checks every few hours.
+export async function checkServerUpdates(): Promise<string[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_check_server_updates");
+  }
+  return [];
+}
+
+// Called when an update check found servers behind their package's latest release
+export async function onUpdatesAvailable(handler: (servers: string[]) => void): Promise<UnlistenFn> {
+  if (await checkMcpEnabled()) {
+    return listen<UpdatesAvailableEvent>("mcp:updates-available", (event) => handler(event.payload.servers));
+  }
+  return () => {};
+}
+
+// Update the package a server runs to its latest release, passing on the
+// installer's output like installPackage. A running server is restarted.
+// Resolves to the version installed.
+export async function updateServerPackage(
+  name: string,
+  onOutput: (stream: "stdout" | "stderr", line: string) => void,
+  installId: string = crypto.randomUUID()
+): Promise<string> {
+  if (!(await checkMcpEnabled())) {
+    throw new Error("Updating server packages is not available in this build");
+  }
+  
+  const unlisten = await getCurrentWebviewWindow().listen<PackageInstallEvent>("mcp:package-install", (event) => {
+    if (event.payload.id === installId) {
+      onOutput(event.payload.stream, event.payload.line);
+    }
+  });
+  try {
+    return await invoke("mcp_update_server_package", { name, installId });
+  } finally {
+    unlisten();
+  }
+}
+
+// Takes effect from the server's next start
+export async function setServerSettings(name: string, settings: ServerSettings): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_set_server_settings", { name, settings });
+  }
+  
+  const servers = JSON.parse(localStorage.getItem("mcp_servers") || "{}");
+  if (servers[name]) {
+    servers[name].settings = settings;
+    localStorage.setItem("mcp_servers", JSON.stringify(servers));
+  }
+}
+
 export async function unregisterServer(name: string): Promise<void> {
   if (await checkMcpEnabled()) {
     return invoke("mcp_unregister_server", { name });
@@ -67,6 +239,22 @@ export async function startServer(name: string): Promise<void> {
   console.warn("Unable to start server in this build");
 }
 
+export interface StartResult {
+  server: string;
+  error?: string;
+}
+
+// Start every enabled server concurrently; failures are reported per server
+export async function startAllServers(maxParallel?: number): Promise<StartResult[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_start_all", { maxParallel });
+  }
+  
+  // Fallback: no-op, can't start servers without Tauri
+  console.warn("Unable to start servers in this build");
+  return [];
+}
+
 export async function stopServer(name: string): Promise<void> {
   if (await checkMcpEnabled()) {
     return invoke("mcp_stop_server", { name });
@@ -76,6 +264,29 @@ export async function stopServer(name: string): Promise<void> {
   console.warn("Unable to stop server in this build");
 }
 
+export async function restartServer(name: string): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_restart_server", { name });
+  }
+  
+  // Fallback: no-op, can't restart servers without Tauri
+  console.warn("Unable to restart server in this build");
+}
+
+// Disabled servers keep their configuration but are never started
+export async function setServerEnabled(name: string, enabled: boolean): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_set_server_enabled", { name, enabled });
+  }
+  
+  // Fallback: store the flag in localStorage
+  const servers = JSON.parse(localStorage.getItem("mcp_servers") || "{}");
+  if (servers[name]) {
+    servers[name].enabled = enabled;
+    localStorage.setItem("mcp_servers", JSON.stringify(servers));
+  }
+}
+
 export async function getServers(): Promise<McpServerConfig[]> {
   if (await checkMcpEnabled()) {
     return invoke("mcp_get_servers");
@@ -105,9 +316,33 @@ export async function testConnection(url: string): Promise<boolean> {
   }
 }
 
-export async function discoverServers(path?: string): Promise<McpServerConfig[]> {
+// Discover servers without waiting for the probes; the results arrive through
+// onDiscoveryFinished. Resolves to the directory searched.
+export async function startDiscovery(path?: string, network = true, probe = false): Promise<string> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_start_discovery", { path, network, probe });
+  }
+  throw new Error("Discovering servers in the background is not available in this build");
+}
+
+// Called when a discovery started with startDiscovery finished
+export async function onDiscoveryFinished(
+  handler: (path: string, servers: McpServerConfig[], error?: string) => void
+): Promise<UnlistenFn> {
   if (await checkMcpEnabled()) {
-    return invoke("mcp_discover_servers", { path });
+    return listen<DiscoveryFinishedEvent>("mcp:discovery-finished", (event) =>
+      handler(event.payload.path, event.payload.servers as unknown as McpServerConfig[], event.payload.error)
+    );
+  }
+  return () => {};
+}
+
+// Servers in path, on PATH and in known install locations and, with network,
+// those advertised on the local network as _mcp._tcp services. Candidates are
+// only run to check them when probe is set, which the user has to agree to.
+export async function discoverServers(path?: string, network = true, probe = false): Promise<McpServerConfig[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_discover_servers", { path, network, probe });
   }
   
   // Fallback: try some common local endpoints
@@ -172,7 +407,9 @@ export async function getServerStatus(): Promise<McpServerStatus[]> {
       command: serverConfig.command,
       args: serverConfig.args,
       env: serverConfig.env,
+      enabled: serverConfig.enabled,
       is_running: isRunning,
+      connection_state: isRunning ? "connected" : "disconnected",
       url: serverConfig.command.startsWith('http') ? serverConfig.command : undefined,
     });
   }
@@ -180,6 +417,14 @@ export async function getServerStatus(): Promise<McpServerStatus[]> {
   return statuses;
 }
 
+// What a server supports, as reported when it last connected; null before it has
+export async function getServerInfo(name: string): Promise<InitializeResult | null> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_get_server_info", { name });
+  }
+  return null;
+}
+
 // MCP tools
 export async function listTools(serverName: string): Promise<ListToolsResult> {
   if (await checkMcpEnabled()) {
@@ -190,13 +435,16 @@ export async function listTools(serverName: string): Promise<ListToolsResult> {
   return { tools: [] };
 }
 
+// conversationId is recorded in the audit log along with the call
 export async function callTool(
   serverName: string,
   toolName: string,
-  args?: any
+  args?: any,
+  callId?: string,
+  conversationId?: string
 ): Promise<CallToolResult> {
   if (await checkMcpEnabled()) {
-    return invoke("mcp_call_tool", { serverName, toolName, args });
+    return invoke("mcp_call_tool", { serverName, toolName, args, callId, conversationId });
   }
   
   // Fallback: error message
@@ -211,6 +459,76 @@ export async function callTool(
   };
 }
 
+// Call a tool and receive its progress and content as they arrive. Cancel it
+// with cancelToolCall(channelId).
+export async function callToolStreamed(
+  serverName: string,
+  toolName: string,
+  args: any,
+  onChunk: (chunk: ToolStreamChunk) => void,
+  channelId: string = crypto.randomUUID(),
+  conversationId?: string
+): Promise<CallToolResult> {
+  if (!(await checkMcpEnabled())) {
+    return callTool(serverName, toolName, args, undefined, conversationId);
+  }
+  
+  // Events may arrive after the command has returned; wait for the last one.
+  // They are sent to the calling window only, so listen on it rather than globally.
+  let finished = () => {};
+  const lastChunk = new Promise<void>((resolve) => (finished = resolve));
+  const unlisten = await getCurrentWebviewWindow().listen<ToolStreamEvent>("mcp:tool-stream", (event) => {
+    if (event.payload.channel !== channelId) {
+      return;
+    }
+    onChunk(event.payload.chunk);
+    if (event.payload.chunk.kind === "result" || event.payload.chunk.kind === "error") {
+      finished();
+    }
+  });
+  
+  try {
+    const result = await invoke<CallToolResult>("mcp_call_tool_streamed", {
+      server: serverName,
+      tool: toolName,
+      args,
+      channelId,
+      conversationId,
+    });
+    await lastChunk;
+    return result;
+  } finally {
+    unlisten();
+  }
+}
+
+// Run several tool calls at once, at most maxParallel at a time. Each one is
+// approved like a single call; results are in the order of calls.
+export async function callToolsBatch(
+  calls: BatchToolCall[],
+  maxParallel?: number,
+  conversationId?: string
+): Promise<BatchToolResult[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_call_tools_batch", { calls, maxParallel, conversationId });
+  }
+  
+  // Fallback: no tool execution available
+  return calls.map(({ server, tool }) => ({
+    server,
+    tool,
+    error: { kind: "internal_error", code: -32603, message: "Tool execution is not available in this build" },
+  }));
+}
+
+// Stop a tool call started with a callId; resolves to false if it already finished
+export async function cancelToolCall(callId: string): Promise<boolean> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_cancel_tool_call", { callId });
+  }
+  return false;
+}
+
 // MCP resources
 export async function listResources(serverName: string): Promise<ListResourcesResult> {
   if (await checkMcpEnabled()) {
@@ -223,10 +541,11 @@ export async function listResources(serverName: string): Promise<ListResourcesRe
 
 export async function readResource(
   serverName: string,
-  uri: string
+  uri: string,
+  conversationId?: string
 ): Promise<ReadResourceResult> {
   if (await checkMcpEnabled()) {
-    return invoke("mcp_read_resource", { serverName, uri });
+    return invoke("mcp_read_resource", { serverName, uri, conversationId });
   }
   
   // Fallback: error message
@@ -240,6 +559,70 @@ export async function readResource(
   };
 }
 
+// Read a resource and receive it in pieces of at most chunkBytes bytes of text
+// or base64 data, for resources too large to pass back in one message. The
+// backend still reads the whole resource first. Resolves to the number of
+// pieces once all of them were received.
+export async function readResourceStreamed(
+  serverName: string,
+  uri: string,
+  onChunk: (chunk: ResourceChunkEvent) => void,
+  chunkBytes?: number,
+  channelId: string = crypto.randomUUID(),
+  conversationId?: string
+): Promise<number> {
+  if (!(await checkMcpEnabled())) {
+    return 0;
+  }
+  
+  // Events may arrive after the command has returned; wait until all were received
+  let received = 0;
+  let expected: number | undefined;
+  let finished = () => {};
+  const allChunks = new Promise<void>((resolve) => (finished = resolve));
+  const unlisten = await getCurrentWebviewWindow().listen<ResourceChunkEvent>("mcp:resource-chunk", (event) => {
+    if (event.payload.channel !== channelId) {
+      return;
+    }
+    onChunk(event.payload);
+    received += 1;
+    if (received === expected) {
+      finished();
+    }
+  });
+  
+  try {
+    expected = await invoke<number>("mcp_read_resource_streamed", {
+      serverName,
+      uri,
+      channelId,
+      chunkBytes,
+      conversationId,
+    });
+    if (received >= expected) {
+      finished();
+    }
+    await allChunks;
+    return expected;
+  } finally {
+    unlisten();
+  }
+}
+
+// Read a resource and write it to path, decoding binary contents. Resolves to
+// the number of bytes written.
+export async function readResourceToFile(
+  serverName: string,
+  uri: string,
+  path: string,
+  conversationId?: string
+): Promise<number> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_read_resource_to_file", { serverName, uri, path, conversationId });
+  }
+  return 0;
+}
+
 // MCP prompts
 export async function listPrompts(serverName: string): Promise<ListPromptsResult> {
   if (await checkMcpEnabled()) {
@@ -270,6 +653,83 @@ export async function getPrompt(
   };
 }
 
+export type ConflictPolicy = "skip" | "overwrite" | "rename";
+
+export interface ImportReport {
+  imported: string[];
+  skipped: { name: string; reason: string }[];
+}
+
+// Import the servers configured in Claude Desktop and save them
+export async function importClaudeConfig(
+  onConflict: ConflictPolicy = "skip",
+  path?: string
+): Promise<ImportReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_import_claude_config", { path, onConflict });
+  }
+  
+  // Fallback: the Claude Desktop config can't be read without Tauri
+  throw new Error("Importing the Claude Desktop config is not available in this build");
+}
+
+export type ImportSource = "claude_desktop" | "cursor" | "vs_code" | "windsurf";
+
+export interface ImportPreview {
+  path: string;
+  servers: McpServerConfig[];
+  skipped: { name: string; reason: string }[];
+  conflicts: string[];
+}
+
+// Show which servers another client's config contains without importing them
+export async function previewImport(source: ImportSource, path?: string): Promise<ImportPreview> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_preview_import", { source, path });
+  }
+  
+  throw new Error("Importing configs is not available in this build");
+}
+
+// Import the servers accepted from a preview and save them
+export async function importServers(
+  servers: McpServerConfig[],
+  onConflict: ConflictPolicy = "skip"
+): Promise<ImportReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_import_servers", { servers, onConflict });
+  }
+  
+  throw new Error("Importing configs is not available in this build");
+}
+
+export interface ExportReport {
+  servers: string[];
+  // Secrets replaced with ${env:NAME} references that each machine provides
+  placeheld: { server: string; variable: string }[];
+}
+
+// Write all servers to a bundle that can be shared with other workstations
+export async function exportConfig(path: string): Promise<ExportReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_export_config", { path });
+  }
+  
+  throw new Error("Exporting configs is not available in this build");
+}
+
+// Import the servers of a bundle written by exportConfig
+export async function importConfig(
+  path: string,
+  mergeStrategy: ConflictPolicy = "skip"
+): Promise<ImportReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_import_config", { path, mergeStrategy });
+  }
+  
+  throw new Error("Importing configs is not available in this build");
+}
+
 // Configuration saving/loading
 export async function saveConfig(): Promise<void> {
   if (await checkMcpEnabled()) {
@@ -286,3 +746,482 @@ export async function loadConfig(): Promise<void> {
   
   // Already loaded from localStorage in other methods
 }
+
+// Stop local servers after this many seconds without use; null keeps them running
+export async function setIdleTimeout(seconds: number | null): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_set_idle_timeout", { seconds });
+  }
+}
+
+export interface ResourceBudgets {
+  idleSeconds?: number;
+  remoteIdleSeconds?: number;
+  maxLogBytes?: number;
+}
+
+// Limits for releasing idle servers and old log files; omitted limits are disabled
+export async function setResourceBudgets(budgets: ResourceBudgets): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_set_resource_budgets", { ...budgets });
+  }
+}
+
+// Limit on tool calls to all servers together; null removes it
+export async function setRateLimit(limit: RateLimit | null): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_set_rate_limit", { limit });
+  }
+}
+
+export async function getRateLimitUsage(): Promise<RateLimitReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_get_rate_limit_usage");
+  }
+  return { global: null, servers: {} };
+}
+
+// Request counts, error rates and latency percentiles per server and method
+export async function getMetrics(server?: string): Promise<MetricsReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_get_metrics", { server });
+  }
+  return { servers: {} };
+}
+
+// Everything the overview page shows about every server, in one call
+export async function getDashboard(): Promise<Dashboard> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_get_dashboard");
+  }
+  return { servers: [] };
+}
+
+// Change how verbose the app log is, right away. With a module, only for that
+// part of the MCP stack; no level then makes it follow the global level again.
+// Without a module, no level goes back to "info".
+export async function setDebugMode(level?: LogLevel, module?: LogModule): Promise<LogLevelsReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_set_debug_mode", { level, module });
+  }
+  return { global: "info", modules: {} };
+}
+
+export async function getDebugMode(): Promise<LogLevelsReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_get_debug_mode");
+  }
+  return { global: "info", modules: {} };
+}
+
+// Called whenever a server starts, stops, crashes or begins connecting
+export async function onServerStatus(
+  handler: (event: ServerStatusEvent) => void
+): Promise<UnlistenFn> {
+  if (await checkMcpEnabled()) {
+    return listen<ServerStatusEvent>("mcp:server-status", (event) => handler(event.payload));
+  }
+  return () => {};
+}
+
+// Called for every notification sent by any server; progress, log messages,
+// list changes and resource updates also have typed events of their own
+export async function onServerNotification(
+  handler: (event: NotificationEvent) => void
+): Promise<UnlistenFn> {
+  if (await checkMcpEnabled()) {
+    return listen<NotificationEvent>("mcp:notification", (event) => handler(event.payload));
+  }
+  return () => {};
+}
+
+// Called once the saved servers were loaded in the background after launch
+export async function onConfigLoaded(
+  handler: (event: ConfigLoadedEvent) => void
+): Promise<UnlistenFn> {
+  if (await checkMcpEnabled()) {
+    return listen<ConfigLoadedEvent>("mcp:config-loaded", (event) => handler(event.payload));
+  }
+  return () => {};
+}
+
+// Called when mcp_servers.json was edited outside the app and reloaded
+export async function onConfigReloaded(
+  handler: (event: ConfigReloadedEvent) => void
+): Promise<UnlistenFn> {
+  if (await checkMcpEnabled()) {
+    return listen<ConfigReloadedEvent>("mcp:config-reloaded", (event) => handler(event.payload));
+  }
+  return () => {};
+}
+
+export interface ProfileInfo {
+  name: string;
+  active: boolean;
+  servers: number;
+}
+
+// Named sets of servers; the active one is what the rest of the app sees
+export async function listProfiles(): Promise<ProfileInfo[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_list_profiles");
+  }
+  return [];
+}
+
+export async function createProfile(name: string, copyActive = false): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_create_profile", { name, copyActive });
+  }
+  throw new Error("Profiles are not available in this build");
+}
+
+// Stops the active profile's servers and starts the new profile's autostart set
+export async function switchProfile(name: string): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_switch_profile", { name });
+  }
+  throw new Error("Profiles are not available in this build");
+}
+
+export async function deleteProfile(name: string): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_delete_profile", { name });
+  }
+  throw new Error("Profiles are not available in this build");
+}
+
+// Store a secret in the OS keychain; server configs refer to it as {{secret:NAME}}
+export async function setSecret(name: string, value: string): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_set_secret", { name, value });
+  }
+  throw new Error("Secrets are not available in this build");
+}
+
+export async function deleteSecret(name: string): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_delete_secret", { name });
+  }
+  throw new Error("Secrets are not available in this build");
+}
+
+// Servers offered by opened mlface://add-server links, waiting for an answer
+export async function pendingInstalls(): Promise<InstallRequestEvent[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_pending_installs");
+  }
+  return [];
+}
+
+// Accepting registers and saves the server, renamed if the name is taken
+export async function resolveInstall(id: number, accept: boolean): Promise<ImportReport | null> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_resolve_install", { id, accept });
+  }
+  throw new Error("Installing servers from links is not available in this build");
+}
+
+// Called when an mlface://add-server link is opened
+export async function onInstallRequest(
+  handler: (event: InstallRequestEvent) => void
+): Promise<UnlistenFn> {
+  if (await checkMcpEnabled()) {
+    return listen<InstallRequestEvent>("mcp:install-request", (event) => handler(event.payload));
+  }
+  return () => {};
+}
+
+// An environment variable asked for when a catalog entry is installed
+export interface CatalogEnvVar {
+  name: string;
+  description: string;
+  // Kept in the keychain; the config refers to it as {{secret:NAME}}
+  secret?: boolean;
+  // Defaults to true
+  required?: boolean;
+}
+
+// A server in the curated catalog
+export interface CatalogEntry {
+  id: string;
+  name: string;
+  description: string;
+  package: string | PackageSpec;
+  args?: string[];
+  // What args the user is expected to add
+  args_hint?: string;
+  env?: CatalogEnvVar[];
+  homepage?: string;
+  tags?: string[];
+}
+
+// Entries whose id, name, description or tags contain every word of query
+export async function getCatalog(query?: string): Promise<CatalogEntry[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_get_catalog", { query });
+  }
+  return [];
+}
+
+// Download the latest catalog; resolves to how many servers it offers
+export async function refreshCatalog(): Promise<number> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_refresh_catalog");
+  }
+  throw new Error("The server catalog is not available in this build");
+}
+
+// values holds the entry's env vars by name; rejects naming missing required ones
+export async function installCatalogEntry(
+  id: string,
+  name?: string,
+  args?: string[],
+  values?: Record<string, string>
+): Promise<ImportReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_install_catalog_entry", { id, name, args, values });
+  }
+  throw new Error("The server catalog is not available in this build");
+}
+
+// An environment variable, or a header, asked for when a registry server is installed
+export interface RegistryInput {
+  name: string;
+  description: string;
+  isRequired: boolean;
+  isSecret: boolean;
+}
+
+// A server in the public MCP registry, as the registry describes it
+export interface RegistryServer {
+  // Namespaced, like io.github.user/weather
+  name: string;
+  description: string;
+  version?: string;
+  websiteUrl?: string;
+  repository?: { url: string };
+  packages?: {
+    // npm, pypi, oci and so on
+    registryType: string;
+    identifier: string;
+    version?: string;
+    transport?: { type: string };
+    packageArguments?: { type: "positional" | "named"; name?: string; value?: string; valueHint?: string }[];
+    environmentVariables?: RegistryInput[];
+  }[];
+  remotes?: { type: string; url: string; headers?: RegistryInput[] }[];
+}
+
+// offline is set when the registry couldn't be reached and the servers come from the cache
+export interface RegistrySearch {
+  servers: RegistryServer[];
+  offline: boolean;
+}
+
+// Search the public MCP registry, falling back to servers seen before when offline
+export async function searchRegistry(query?: string, limit?: number): Promise<RegistrySearch> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_search_registry", { query, limit });
+  }
+  return { servers: [], offline: true };
+}
+
+export async function getRegistryServer(server: string): Promise<RegistryServer> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_get_registry_server", { server });
+  }
+  throw new Error("The MCP registry is not available in this build");
+}
+
+// values holds the server's env vars, or the header its endpoint takes, by name
+export async function installRegistryServer(
+  server: string,
+  name?: string,
+  args?: string[],
+  values?: Record<string, string>
+): Promise<ImportReport> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_install_registry_server", { server, name, args, values });
+  }
+  throw new Error("The MCP registry is not available in this build");
+}
+
+export interface WorkspaceServers {
+  workspace: string;
+  // The .mlface/mcp.json or .mcp.json read, if the folder has one
+  config?: string;
+  // Servers approved before, as they started
+  started: StartResult[];
+  // Servers to show the user for approveProjectServers
+  pending: McpServerConfig[];
+  skipped: { name: string; reason: string }[];
+}
+
+// Open a workspace folder in place of the one open, starting the project
+// servers the user approved before. The others come back as pending.
+export async function openWorkspace(path: string): Promise<WorkspaceServers> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_open_workspace", { path });
+  }
+  throw new Error("Project servers are not available in this build");
+}
+
+// Approve pending project servers by name and start them; the approval is
+// asked again if their config changes
+export async function approveProjectServers(names: string[]): Promise<StartResult[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_approve_project_servers", { names });
+  }
+  throw new Error("Project servers are not available in this build");
+}
+
+export async function closeWorkspace(): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_close_workspace");
+  }
+}
+
+export type ApprovalDecision = "allow_once" | "session" | "always" | "always_server" | "deny" | "never";
+
+export interface ApprovedTool {
+  server: string;
+  tool: string;
+}
+
+// A remembered decision: allowed until the app quits, allowed for good, denied
+// for good, or with no tool, every tool of the server allowed for good
+export interface ToolPermission {
+  server: string;
+  tool: string | null;
+  scope: "session" | "always" | "server" | "never";
+}
+
+// Called when a tool call from this window waits for the user's consent
+export async function onToolApproval(
+  handler: (event: ToolApprovalEvent) => void
+): Promise<UnlistenFn> {
+  if (await checkMcpEnabled()) {
+    return getCurrentWebviewWindow().listen<ToolApprovalEvent>("mcp:tool-approval", (event) =>
+      handler(event.payload)
+    );
+  }
+  return () => {};
+}
+
+// "session" and "always" also let the tool run without asking, until the app
+// quits or from now on; "always_server" does the same for every tool of the
+// server; "never" denies it from now on without asking
+export async function resolveToolApproval(id: number, decision: ApprovalDecision): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_resolve_tool_approval", { id, decision });
+  }
+  throw new Error("Tool approvals are not available in this build");
+}
+
+// Tools that run without asking
+export async function listToolApprovals(): Promise<ApprovedTool[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_list_tool_approvals");
+  }
+  return [];
+}
+
+export async function setToolApproval(server: string, tool: string, always: boolean): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_set_tool_approval", { server, tool, always });
+  }
+  throw new Error("Tool approvals are not available in this build");
+}
+
+// Every remembered decision, sorted by server and tool
+export async function listPermissions(): Promise<ToolPermission[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_list_permissions");
+  }
+  return [];
+}
+
+// Forget the decision about a tool, or with a null tool the grant for the
+// whole server, so it is asked about again. Resolves to false if there was none.
+export async function revokePermission(server: string, tool: string | null): Promise<boolean> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_revoke_permission", { server, tool });
+  }
+  throw new Error("Tool approvals are not available in this build");
+}
+
+// Recorded tool calls and resource reads, most recent first
+export async function queryAuditLog(query: AuditQuery = {}): Promise<AuditEntry[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_query_audit_log", { query });
+  }
+  return [];
+}
+
+// Days of audit entries kept; 0 keeps everything
+export async function getAuditRetention(): Promise<number> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_get_audit_retention");
+  }
+  return 0;
+}
+
+export async function setAuditRetention(days: number): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_set_audit_retention", { days });
+  }
+  throw new Error("The audit log is not available in this build");
+}
+
+// Recorded JSON-RPC messages of one server, or of all, oldest first. With
+// follow, new messages are also delivered to onTraffic until unfollowTraffic.
+export async function getTraffic(
+  server?: string,
+  filter: TrafficFilter = {},
+  limit?: number,
+  follow = false
+): Promise<TrafficEvent[]> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_get_traffic", { server, filter, limit, follow });
+  }
+  return [];
+}
+
+export async function unfollowTraffic(): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_unfollow_traffic");
+  }
+}
+
+// Record every message exchanged with a server to a file for a bug report.
+// A running server is restarted so the recording starts with its handshake.
+export async function startSessionRecording(name: string, path: string): Promise<void> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_start_session_recording", { name, path });
+  }
+}
+
+// Stop recording; resolves to the recording's file, if there was one
+export async function stopSessionRecording(name: string): Promise<string | null> {
+  if (await checkMcpEnabled()) {
+    return invoke("mcp_stop_session_recording", { name });
+  }
+  return null;
+}
+
+export async function onTraffic(handler: (event: TrafficEvent) => void): Promise<UnlistenFn> {
+  if (await checkMcpEnabled()) {
+    return listen<TrafficEvent>("mcp:traffic", (event) => handler(event.payload));
+  }
+  return () => {};
+}
+
+// Requests past their server's slow-call threshold, once when they cross it
+// and again with `finished` set when they complete
+export async function onSlowCall(handler: (event: SlowCallEvent) => void): Promise<UnlistenFn> {
+  if (await checkMcpEnabled()) {
+    return listen<SlowCallEvent>("mcp:slow-call", (event) => handler(event.payload));
+  }
+  return () => {};
+}
@@ -3,6 +3,8 @@ export * from './types';
 export * from './api';
 export * from './mcp-manager';
 export * from './mcp-settings';
+export * from './install-dialog';
+export * from './tool-approval-dialog';
 export * from './mcp-tools';
 export * from './mcp-resources';
 export * from './mcp-chat-panel';
@@ -0,0 +1,86 @@
+import { useEffect, useState } from "react";
+import {
+  Button,
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogFooter,
+  DialogDescription,
+  AlertCircle,
+} from "../ui";
+import { onInstallRequest, pendingInstalls, resolveInstall } from "./api";
+import { InstallRequestEvent } from "../../bindings/InstallRequestEvent";
+
+// Asks before registering servers offered by "Add to mlFace" links
+export function McpInstallDialog() {
+  const [requests, setRequests] = useState<InstallRequestEvent[]>([]);
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    const addRequest = (request: InstallRequestEvent) =>
+      setRequests((current) =>
+        current.some((r) => r.id === request.id) ? current : [...current, request]
+      );
+
+    // Links that launched the app arrived before this listener
+    pendingInstalls().then((pending) => pending.forEach(addRequest));
+    const unlisten = onInstallRequest(addRequest);
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  const request = requests[0];
+  if (!request) {
+    return null;
+  }
+
+  const handleResolve = async (accept: boolean) => {
+    setBusy(true);
+    setError(null);
+    try {
+      await resolveInstall(request.id, accept);
+      setRequests((current) => current.filter((r) => r.id !== request.id));
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Dialog open onOpenChange={(open: boolean) => !open && handleResolve(false)}>
+      <DialogContent>
+        <DialogHeader>
+          <DialogTitle>Add MCP Server</DialogTitle>
+          <DialogDescription>
+            A link asks to add the server "{request.name}". It will run this command on
+            your computer when started; only add servers you trust.
+          </DialogDescription>
+        </DialogHeader>
+        <div className="space-y-4 py-4">
+          <pre className="text-sm bg-muted rounded-md p-3 whitespace-pre-wrap break-all">
+            {[request.command, ...request.args].join(" ")}
+          </pre>
+
+          {error && (
+            <div className="flex items-center space-x-2 text-sm text-red-600">
+              <AlertCircle className="h-4 w-4" />
+              <span>{error}</span>
+            </div>
+          )}
+        </div>
+        <DialogFooter>
+          <Button variant="outline" onClick={() => handleResolve(false)} disabled={busy}>
+            Cancel
+          </Button>
+          <Button onClick={() => handleResolve(true)} disabled={busy}>
+            Add Server
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -14,14 +14,33 @@ import {
 } from "../ui"; // Import your UI components
 import {
   registerServer,
+  onConfigReloaded,
+  onServerStatus,
   unregisterServer,
   getServerStatus,
   testConnection,
   startServer,
   stopServer,
+  setServerEnabled,
+  restartServer,
+  previewImport,
+  importServers,
+  ImportPreview,
+  ImportSource,
+  listProfiles,
+  createProfile,
+  switchProfile,
+  ProfileInfo,
 } from "./api";
 import { McpServerStatus } from "./types";
 
+const IMPORT_SOURCES: { source: ImportSource; label: string }[] = [
+  { source: "claude_desktop", label: "Claude Desktop" },
+  { source: "cursor", label: "Cursor" },
+  { source: "vs_code", label: "VS Code" },
+  { source: "windsurf", label: "Windsurf" },
+];
+
 export function McpSettings() {
   const [servers, setServers] = useState<McpServerStatus[]>([]);
   const [isAddServerDialogOpen, setIsAddServerDialogOpen] = useState(false);
@@ -30,15 +49,32 @@ export function McpSettings() {
     command: "",
     args: "",
     env: "",
+    cwd: "",
+    autostart: false,
   });
   const [testUrl, setTestUrl] = useState("");
   const [testResult, setTestResult] = useState<boolean | null>(null);
   const [testLoading, setTestLoading] = useState(false);
   const [refreshing, setRefreshing] = useState(false);
+  const [isImportDialogOpen, setIsImportDialogOpen] = useState(false);
+  const [importPreview, setImportPreview] = useState<ImportPreview | null>(null);
+  const [importError, setImportError] = useState<string | null>(null);
+  const [acceptedImports, setAcceptedImports] = useState<Set<string>>(new Set());
+  const [profiles, setProfiles] = useState<ProfileInfo[]>([]);
 
   // Load servers on mount
   useEffect(() => {
     loadServers();
+    
+    // Hand edits to the config file and status changes show up without a
+    // manual refresh
+    const unlisteners = [
+      onConfigReloaded(() => loadServers()),
+      onServerStatus(() => loadServers()),
+    ];
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((stop) => stop()));
+    };
   }, []);
 
   async function loadServers() {
@@ -46,6 +82,7 @@ export function McpSettings() {
       setRefreshing(true);
       const status = await getServerStatus();
       setServers(status);
+      setProfiles(await listProfiles());
     } catch (error) {
       console.error("Failed to load servers:", error);
     } finally {
@@ -53,6 +90,29 @@ export function McpSettings() {
     }
   }
 
+  async function handleSwitchProfile(name: string) {
+    try {
+      await switchProfile(name);
+      await loadServers();
+    } catch (error) {
+      console.error(`Failed to switch to profile ${name}:`, error);
+    }
+  }
+
+  async function handleCreateProfile() {
+    const name = window.prompt("Name of the new profile (letters, digits, - and _)");
+    if (!name) {
+      return;
+    }
+    
+    try {
+      await createProfile(name);
+      await handleSwitchProfile(name);
+    } catch (error) {
+      console.error(`Failed to create profile ${name}:`, error);
+    }
+  }
+
   async function handleAddServer() {
     try {
       const argsArray = newServer.args
@@ -71,9 +131,10 @@ export function McpSettings() {
         });
       }
 
-      await registerServer(newServer.name, newServer.command, argsArray, envMap);
+      const cwd = newServer.cwd.trim() || undefined;
+      await registerServer(newServer.name, newServer.command, argsArray, envMap, cwd, newServer.autostart);
       setIsAddServerDialogOpen(false);
-      setNewServer({ name: "", command: "", args: "", env: "" });
+      setNewServer({ name: "", command: "", args: "", env: "", cwd: "", autostart: false });
       await loadServers();
     } catch (error) {
       console.error("Failed to add server:", error);
@@ -102,6 +163,68 @@ export function McpSettings() {
     }
   }
 
+  async function handleRestartServer(name: string) {
+    try {
+      await restartServer(name);
+      await loadServers();
+    } catch (error) {
+      console.error(`Failed to restart server ${name}:`, error);
+    }
+  }
+
+  async function handleToggleEnabled(server: McpServerStatus) {
+    try {
+      await setServerEnabled(server.name, server.enabled === false);
+      await loadServers();
+    } catch (error) {
+      console.error(`Failed to toggle server ${server.name}:`, error);
+    }
+  }
+
+  async function handlePreviewImport(source: ImportSource) {
+    try {
+      setImportError(null);
+      const preview = await previewImport(source);
+      setImportPreview(preview);
+      // Accept everything that doesn't clash with an existing server by default
+      setAcceptedImports(
+        new Set(
+          preview.servers
+            .map((server) => server.name)
+            .filter((name) => !preview.conflicts.includes(name))
+        )
+      );
+    } catch (error) {
+      setImportPreview(null);
+      setImportError(String(error));
+    }
+  }
+
+  function toggleAcceptedImport(name: string) {
+    const accepted = new Set(acceptedImports);
+    if (accepted.has(name)) {
+      accepted.delete(name);
+    } else {
+      accepted.add(name);
+    }
+    setAcceptedImports(accepted);
+  }
+
+  async function handleImport() {
+    if (!importPreview) {
+      return;
+    }
+    try {
+      const servers = importPreview.servers.filter((server) => acceptedImports.has(server.name));
+      await importServers(servers, "overwrite");
+      setIsImportDialogOpen(false);
+      setImportPreview(null);
+      await loadServers();
+    } catch (error) {
+      setImportError(String(error));
+    }
+  }
+
   async function handleTestConnection() {
     try {
       setTestLoading(true);
@@ -122,6 +245,22 @@ export function McpSettings() {
       <div className="flex justify-between items-center">
         <h2 className="text-2xl font-bold">MCP Server Endpoints</h2>
         <div className="flex space-x-2">
+          {profiles.length > 0 && (
+            <select
+              className="border rounded-md px-2 text-sm bg-transparent"
+              value={profiles.find((profile) => profile.active)?.name}
+              onChange={(e) => handleSwitchProfile(e.target.value)}
+            >
+              {profiles.map((profile) => (
+                <option key={profile.name} value={profile.name}>
+                  {profile.name} ({profile.servers})
+                </option>
+              ))}
+            </select>
+          )}
+          <Button variant="outline" size="sm" onClick={handleCreateProfile}>
+            New Profile
+          </Button>
           <Button
             variant="outline"
             size="sm"
@@ -130,6 +269,13 @@ export function McpSettings() {
           >
             {refreshing ? "Refreshing..." : "Refresh"}
           </Button>
+          <Button
+            variant="outline"
+            size="sm"
+            onClick={() => setIsImportDialogOpen(true)}
+          >
+            Import
+          </Button>
           <Button
             variant="default"
             size="sm"
@@ -198,8 +344,25 @@ export function McpSettings() {
                 <div className="flex items-center space-x-3">
                   <Switch
                     checked={server.is_running}
+                    disabled={server.enabled === false}
                     onCheckedChange={() => handleToggleServer(server)}
                   />
+                  {server.is_running && (
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      onClick={() => handleRestartServer(server.name)}
+                    >
+                      Restart
+                    </Button>
+                  )}
+                  <Button
+                    variant="ghost"
+                    size="sm"
+                    onClick={() => handleToggleEnabled(server)}
+                  >
+                    {server.enabled === false ? "Enable" : "Disable"}
+                  </Button>
                   <Button
                     variant="ghost"
                     size="sm"
@@ -263,6 +426,23 @@ export function McpSettings() {
                 placeholder="e.g., API_KEY=abc123,DEBUG=true"
               />
             </div>
+            <div className="space-y-2">
+              <Label htmlFor="cwd">Working Directory (optional)</Label>
+              <Input
+                id="cwd"
+                value={newServer.cwd}
+                onChange={(e) => setNewServer({ ...newServer, cwd: e.target.value })}
+                placeholder="e.g., ~/projects/my-repo"
+              />
+            </div>
+            <div className="flex items-center justify-between">
+              <Label htmlFor="autostart">Start when mlFace launches</Label>
+              <Switch
+                id="autostart"
+                checked={newServer.autostart}
+                onCheckedChange={(checked: boolean) => setNewServer({ ...newServer, autostart: checked })}
+              />
+            </div>
           </div>
           <DialogFooter>
             <Button variant="outline" onClick={() => setIsAddServerDialogOpen(false)}>
@@ -274,6 +454,75 @@ export function McpSettings() {
           </DialogFooter>
         </DialogContent>
       </Dialog>
+
+      {/* Import Dialog */}
+      <Dialog
+        open={isImportDialogOpen}
+        onOpenChange={setIsImportDialogOpen}
+      >
+        <DialogContent>
+          <DialogHeader>
+            <DialogTitle>Import MCP Servers</DialogTitle>
+            <DialogDescription>
+              Copy server definitions from another MCP client.
+            </DialogDescription>
+          </DialogHeader>
+          <div className="space-y-4 py-4">
+            <div className="flex flex-wrap gap-2">
+              {IMPORT_SOURCES.map(({ source, label }) => (
+                <Button
+                  key={source}
+                  variant="outline"
+                  size="sm"
+                  onClick={() => handlePreviewImport(source)}
+                >
+                  {label}
+                </Button>
+              ))}
+            </div>
+
+            {importError && (
+              <div className="flex items-center space-x-2 text-sm text-red-600">
+                <AlertCircle className="h-4 w-4" />
+                <span>{importError}</span>
+              </div>
+            )}
+
+            {importPreview && (
+              <div className="space-y-2">
+                <div className="text-sm text-muted-foreground">{importPreview.path}</div>
+                {importPreview.servers.map((server) => (
+                  <label key={server.name} className="flex items-center space-x-2">
+                    <input
+                      type="checkbox"
+                      checked={acceptedImports.has(server.name)}
+                      onChange={() => toggleAcceptedImport(server.name)}
+                    />
+                    <span className="font-medium">{server.name}</span>
+                    <span className="text-sm text-muted-foreground">{server.command}</span>
+                    {importPreview.conflicts.includes(server.name) && (
+                      <span className="text-sm text-amber-600">replaces existing</span>
+                    )}
+                  </label>
+                ))}
+                {importPreview.skipped.map((skipped) => (
+                  <div key={skipped.name} className="text-sm text-muted-foreground">
+                    {skipped.name}: {skipped.reason}
+                  </div>
+                ))}
+              </div>
+            )}
+          </div>
+          <DialogFooter>
+            <Button variant="outline" onClick={() => setIsImportDialogOpen(false)}>
+              Cancel
+            </Button>
+            <Button onClick={handleImport} disabled={!importPreview || acceptedImports.size === 0}>
+              Import {acceptedImports.size > 0 ? acceptedImports.size : ""} Servers
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
     </div>
   );
 }
@@ -0,0 +1,85 @@
+import { useEffect, useState } from "react";
+import {
+  Button,
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogFooter,
+  DialogDescription,
+} from "../ui";
+import { ApprovalDecision, onToolApproval, resolveToolApproval } from "./api";
+import { ToolApprovalEvent } from "../../bindings/ToolApprovalEvent";
+
+// Asks before a tool called from this window runs
+export function McpToolApprovalDialog() {
+  const [requests, setRequests] = useState<ToolApprovalEvent[]>([]);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    const unlisten = onToolApproval((request) =>
+      setRequests((current) => [...current, request])
+    );
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  const request = requests[0];
+  if (!request) {
+    return null;
+  }
+
+  const handleResolve = async (decision: ApprovalDecision) => {
+    setBusy(true);
+    try {
+      await resolveToolApproval(request.id, decision);
+    } catch (e) {
+      // The call was cancelled meanwhile; there is nothing left to answer
+      console.warn("Tool approval no longer pending:", e);
+    } finally {
+      setRequests((current) => current.filter((r) => r.id !== request.id));
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Dialog open onOpenChange={(open: boolean) => !open && handleResolve("deny")}>
+      <DialogContent>
+        <DialogHeader>
+          <DialogTitle>Run Tool?</DialogTitle>
+          <DialogDescription>
+            The server "{request.server}" is about to run the tool "{request.tool}".
+          </DialogDescription>
+        </DialogHeader>
+        <div className="space-y-4 py-4">
+          {request.arguments !== undefined && (
+            <pre className="text-sm bg-muted rounded-md p-3 whitespace-pre-wrap break-all max-h-64 overflow-auto">
+              {JSON.stringify(request.arguments, null, 2)}
+            </pre>
+          )}
+        </div>
+        <DialogFooter>
+          <Button variant="outline" onClick={() => handleResolve("never")} disabled={busy}>
+            Never Allow
+          </Button>
+          <Button variant="outline" onClick={() => handleResolve("deny")} disabled={busy}>
+            Deny
+          </Button>
+          <Button variant="outline" onClick={() => handleResolve("always_server")} disabled={busy}>
+            Always Allow Server
+          </Button>
+          <Button variant="outline" onClick={() => handleResolve("always")} disabled={busy}>
+            Always Allow
+          </Button>
+          <Button variant="outline" onClick={() => handleResolve("session")} disabled={busy}>
+            Allow This Session
+          </Button>
+          <Button onClick={() => handleResolve("allow_once")} disabled={busy}>
+            Allow Once
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -1,15 +1,200 @@
 // MCP types for TypeScript
 
+import type { ServerCrash } from "../../bindings/ServerCrash";
+
 export interface McpServerConfig {
   name: string;
+  // Executable or URL; empty for a server run from package
   command: string;
   args: string[];
   env: Record<string, string>;
+  cwd?: string;
+  autostart?: boolean;
+  enabled?: boolean;
+  settings?: ServerSettings;
+  auth?: ServerAuth;
+  // Directories the server works in; paths outside them are refused
+  roots?: string[];
+  // Executable of a local server, checked before every launch
+  binary?: BinaryPin;
+  // Package run instead of command; a plain string names an npm package
+  package?: string | PackageSpec;
+  // What the server does, from its manifest or catalog entry
+  description?: string;
+}
+
+export interface PackageSpec {
+  name: string;
+  // "node" packages run with npx, "python" ones with uvx or pipx
+  runtime?: "node" | "python";
+  // Exact version to run instead of the latest one
+  version?: string;
+}
+
+// The Node.js installation npm package servers are run with
+export interface NodeRuntime {
+  node: string;
+  npx: string;
+  // As node --version prints it, e.g. "v20.11.0"
+  version: string;
+}
+
+// The runner Python package servers are launched with
+export interface PythonRuntime {
+  // uvx, or pipx where uv is not installed
+  launcher: string;
+  version: string;
+}
+
+export interface BinaryPin {
+  sha256: string;
+  // Refuse to launch a changed binary instead of warning about it
+  enforce?: boolean;
+}
+
+export type BinaryCheck =
+  | { status: "unpinned" }
+  | { status: "unchanged"; path: string }
+  | { status: "changed"; path: string; expected: string; actual: string };
+
+// Credentials for a remote server; value_ref names a secret set with setSecret
+export type ServerAuth =
+  | { type: "bearer"; value_ref: string }
+  | { type: "header"; name: string; value_ref: string };
+
+export type LogVerbosity = "quiet" | "normal" | "trace";
+
+// "off" only advertises the roots, "warn" logs violations, "strict" refuses them
+export type RootsEnforcement = "off" | "warn" | "strict";
+
+// Unset values use the app's defaults
+export interface ServerSettings {
+  request_timeout_secs?: number;
+  // Restart attempts after a crash; 0 disables restarts
+  max_restarts?: number;
+  // Ping the server at this interval and restart it when a ping fails
+  health_check_interval_secs?: number;
+  log_level?: LogVerbosity;
+  roots_enforcement?: RootsEnforcement;
+  rate_limit?: RateLimit;
+  // Report requests waiting longer than this as slow; defaults to 5000
+  slow_call_ms?: number;
+  // Requests in flight at once; the rest wait, user actions ahead of
+  // background work
+  max_concurrent_requests?: number;
+  // TLS options of a remote server that differ from the defaults
+  tls?: TlsSettings;
+}
+
+export interface TlsSettings {
+  // PEM file with further root certificates to trust, e.g. a company CA
+  ca_file?: string;
+  // Accept any certificate; only meant for development servers
+  accept_invalid_certs?: boolean;
+}
+
+// Whether a call over the limit waits for its turn or fails
+export type LimitMode = "queue" | "reject";
+
+export interface RateLimit {
+  per_minute: number;
+  // Calls allowed at once after a quiet period; defaults to per_minute
+  burst?: number;
+  when_limited?: LimitMode;
+}
+
+export interface RateLimitUsage {
+  limit: RateLimit;
+  available: number;
+  waiting: number;
+  allowed: number;
+  rejected: number;
+}
+
+export interface RateLimitReport {
+  global: RateLimitUsage | null;
+  servers: Record<string, RateLimitUsage>;
+}
+
+export interface MethodMetrics {
+  requests: number;
+  errors: number;
+  // Failed requests by McpErrorKind
+  errors_by_kind: Record<string, number>;
+  // From 0 to 1
+  error_rate: number;
+  // Latency percentiles in milliseconds, over the most recent requests
+  p50_ms: number | null;
+  p95_ms: number | null;
+  p99_ms: number | null;
+}
+
+export interface ServerMetrics {
+  total: MethodMetrics;
+  methods: Record<string, MethodMetrics>;
+}
+
+export interface MetricsReport {
+  servers: Record<string, ServerMetrics>;
+}
+
+export type ConnectionState = "disconnected" | "connecting" | "connected" | "reconnecting" | "crashed";
+
+// One server on the overview page
+export interface ServerSummary {
+  name: string;
+  connection_type: "stdio" | "remote";
+  state: ConnectionState;
+  enabled: boolean;
+  // Milliseconds since the current connection came up; null while not connected
+  uptime_ms: number | null;
+  // Restarts after the connection died, since the server was registered
+  restarts: number;
+  // timestamp is milliseconds since the epoch
+  last_error: { message: string; timestamp: number } | null;
+  // null while not connected, or if the server did not list its tools
+  tool_count: number | null;
+  requests: number;
+  errors: number;
+}
+
+export interface Dashboard {
+  servers: ServerSummary[];
 }
 
 export interface McpServerStatus extends McpServerConfig {
   is_running: boolean;
+  connection_state: ConnectionState;
   url?: string;
+  // The last time the server's process crashed, if it ever did
+  last_crash?: ServerCrash;
+  // Set for package servers once checkServerUpdates has looked them up
+  update?: PackageUpdate;
+}
+
+// current is null when the version that runs can't be told, in which case
+// available is false
+export interface PackageUpdate {
+  current: string | null;
+  latest: string;
+  available: boolean;
+}
+
+// Present capabilities are supported; the flags say which notifications come with them
+export interface ServerCapabilities {
+  resources?: { subscribe?: boolean; list_changed?: boolean };
+  tools?: { list_changed?: boolean };
+  prompts?: { list_changed?: boolean };
+  sampling?: {};
+}
+
+// What a server reported about itself when it connected
+export interface InitializeResult {
+  protocol_version: string;
+  name: string;
+  version: string;
+  capabilities: ServerCapabilities;
+  instructions?: string;
 }
 
 export interface Tool {
@@ -28,6 +213,8 @@ export interface Content {
   mime_type?: string;
   data?: string;
   uri?: string;
+  // Large images are passed as a file instead: an embedded_resource with the
+  // properties spilled: "true", path, mime_type and size
   properties?: Record<string, string>;
 }
 
@@ -65,3 +252,102 @@ export interface ListPromptsResult {
 export interface GetPromptResult {
   content: Content[];
 }
+
+export type McpErrorKind =
+  | "parse_error"
+  | "invalid_request"
+  | "method_not_found"
+  | "invalid_params"
+  | "internal_error"
+  | "resource_not_found"
+  | "server_error"
+  | "transport_error"
+  | "protocol_error"
+  | "timeout"
+  | "connection_closed"
+  | "cancelled"
+  | "denied"
+  | "rate_limited";
+
+// Error returned by the mcp_* commands that talk to a server
+export interface McpError {
+  kind: McpErrorKind;
+  code: number;
+  message: string;
+  data?: any;
+}
+
+// One call of callToolsBatch
+export interface BatchToolCall {
+  server: string;
+  tool: string;
+  args?: any;
+}
+
+// Outcome of one call of callToolsBatch: its result or the error it failed with
+export interface BatchToolResult {
+  server: string;
+  tool: string;
+  result?: CallToolResult;
+  error?: McpError;
+}
+
+// A line of server output, as returned by mcp_get_server_logs
+export interface LogLine {
+  timestamp: number;
+  stream: "stdout" | "stderr";
+  line: string;
+}
+
+export type AuditAction = "tool_call" | "resource_read";
+
+export type AuditOutcome = "success" | "tool_error" | "failed" | "denied" | "cancelled";
+
+// A recorded tool call or resource read, as returned by mcp_query_audit_log
+export interface AuditEntry {
+  timestamp: number;
+  action: AuditAction;
+  server: string;
+  // Tool name or resource URI
+  target: string;
+  // SHA-256 of the arguments as JSON
+  args_hash?: string;
+  result_bytes?: number;
+  duration_ms: number;
+  outcome: AuditOutcome;
+  error?: string;
+  conversation?: string;
+}
+
+// Unset fields match everything; times are milliseconds since the epoch
+export interface AuditQuery {
+  server?: string;
+  action?: AuditAction;
+  outcome?: AuditOutcome;
+  conversation?: string;
+  since?: number;
+  until?: number;
+  limit?: number;
+}
+
+// Which recorded JSON-RPC messages getTraffic returns; unset fields match everything
+export interface TrafficFilter {
+  direction?: "sent" | "received";
+  kind?: "request" | "response" | "notification";
+  // Matches methods containing this text
+  method?: string;
+  errors_only?: boolean;
+  // Only messages recorded after this seq
+  after?: number;
+}
+
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+// Parts of the MCP stack whose log level can be set apart from the global one
+export type LogModule = "transport" | "client" | "manager";
+
+export interface LogLevelsReport {
+  global: LogLevel;
+  // Modules logged at another level than the global one
+  modules: Partial<Record<LogModule, LogLevel>>;
+}
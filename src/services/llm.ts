@@ -0,0 +1,277 @@
+// Model backends
+// Requests go through the Rust side, which streams replies back as events
+import { invoke } from '@tauri-apps/api/tauri';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import { AgentStepEvent } from '../bindings/AgentStepEvent';
+import { ContextEvent } from '../bindings/ContextEvent';
+import { FinishEvent } from '../bindings/FinishEvent';
+import { PullProgressEvent } from '../bindings/PullProgressEvent';
+import { TokenEvent } from '../bindings/TokenEvent';
+import { UsageEvent } from '../bindings/UsageEvent';
+
+// Where models run. Ollama defaults to the local instance,
+// http://localhost:11434, llama.cpp to http://localhost:8080 and LM Studio to
+// http://localhost:1234; base_url of an OpenAI-compatible API is like
+// https://api.openai.com/v1, and api_key may be a {{secret:NAME}} reference.
+// Anthropic's base_url is only needed behind a proxy. Embedded models are
+// .gguf files in the app's models folder unless models_dir is given, and need
+// a build with the local-inference feature.
+export type LlmProvider =
+  | { type: 'ollama'; url?: string }
+  | { type: 'llama_cpp'; url?: string }
+  | { type: 'lm_studio'; url?: string }
+  | {
+      type: 'embedded';
+      models_dir?: string;
+      device?: 'auto' | 'cpu' | 'metal' | 'cuda';
+      // Tokens, 4096 by default
+      context_size?: number;
+    }
+  | { type: 'openai'; base_url: string; api_key?: string }
+  | { type: 'anthropic'; base_url?: string; api_key?: string };
+
+export interface ToolCall {
+  id: string;
+  name: string;
+  arguments: Record<string, unknown>;
+}
+
+export interface ChatMessage {
+  role: 'system' | 'user' | 'assistant' | 'tool';
+  content: string;
+  // Tools the assistant asked to call
+  tool_calls?: ToolCall[];
+  // For tool messages, the call content is the result of
+  tool_call_id?: string;
+}
+
+export interface ModelInfo {
+  id: string;
+  // Bytes on disk, for local models
+  size?: number;
+  details?: Record<string, unknown>;
+}
+
+export interface ChatReply {
+  message: ChatMessage;
+  done_reason?: string;
+  prompt_tokens?: number;
+  completion_tokens?: number;
+}
+
+export interface ServerHealth {
+  // Whether the server answered at all
+  running: boolean;
+  // Whether it has a model loaded and can chat
+  ready: boolean;
+  // Why it is not ready, or how to start it
+  message?: string;
+}
+
+// provider defaults to the local Ollama instance
+export async function listModels(provider?: LlmProvider): Promise<ModelInfo[]> {
+  return invoke('llm_list_models', { provider });
+}
+
+// Only for local servers
+export async function checkServerHealth(provider?: LlmProvider): Promise<ServerHealth> {
+  return invoke('llm_server_health', { provider });
+}
+
+// Download a model into Ollama, passing on each progress update. Stop it
+// with cancelLlmRequest(pullId), which rejects this.
+export async function pullOllamaModel(
+  model: string,
+  onProgress: (progress: PullProgressEvent) => void,
+  url?: string,
+  pullId: string = crypto.randomUUID()
+): Promise<void> {
+  const unlisten = await getCurrentWebviewWindow().listen<PullProgressEvent>('llm:pull-progress', (event) => {
+    if (event.payload.id === pullId) {
+      onProgress(event.payload);
+    }
+  });
+  try {
+    await invoke('llm_pull_model', { model, pullId, url });
+  } finally {
+    unlisten();
+  }
+}
+
+// Chat with a model, passing on each piece of the reply as it is generated.
+// Stop it with cancelLlmRequest(channel).
+export async function sendLlmMessage(
+  model: string,
+  messages: ChatMessage[],
+  onDelta: (delta: string) => void,
+  options?: Record<string, unknown>,
+  provider?: LlmProvider,
+  channel: string = crypto.randomUUID()
+): Promise<ChatReply> {
+  const unlisten = await getCurrentWebviewWindow().listen<TokenEvent>('llm:token', (event) => {
+    if (event.payload.channel === channel) {
+      onDelta(event.payload.delta);
+    }
+  });
+  try {
+    return await invoke('llm_chat', {
+      model,
+      messages: messages.map(msg => ({
+        role: msg.role,
+        content: msg.content,
+        tool_calls: msg.tool_calls,
+        tool_call_id: msg.tool_call_id,
+      })),
+      channel,
+      options,
+      provider,
+    });
+  } finally {
+    unlisten();
+  }
+}
+
+// How a chat is fitted into the model's context window. window replaces
+// what the provider reports, reply_tokens defaults to options.max_tokens or
+// 1024, and overflow to 'keep', which sends the conversation as it is.
+export interface ContextOptions {
+  window?: number;
+  reply_tokens?: number;
+  overflow?: 'keep' | 'truncate' | 'summarize';
+}
+
+export interface ChatRequest {
+  // Defaults to the local Ollama instance
+  provider?: LlmProvider;
+  model: string;
+  messages: ChatMessage[];
+  options?: Record<string, unknown>;
+  // Without it, the messages are sent as they are
+  context?: ContextOptions;
+}
+
+export interface TokenEstimate {
+  prompt_tokens: number;
+  // False when another tokenizer estimated the model's
+  exact: boolean;
+  context_window?: number;
+  reply_tokens: number;
+  removed: number;
+}
+
+// Count the tokens request would send and compare them with the model's
+// context window, without sending it
+export async function countTokens(request: ChatRequest): Promise<TokenEstimate> {
+  return invoke('llm_count_tokens', { request });
+}
+
+export interface StreamHandlers {
+  onToken: (delta: string) => void;
+  onUsage?: (usage: UsageEvent) => void;
+  onContext?: (context: ContextEvent) => void;
+}
+
+// Stream a chat on channel. Resolves with how it finished once it does;
+// cancelLlmRequest(channel) finishes it as 'cancelled'.
+export async function streamLlmChat(
+  request: ChatRequest,
+  handlers: StreamHandlers,
+  channel: string = crypto.randomUUID()
+): Promise<FinishEvent> {
+  const window = getCurrentWebviewWindow();
+  const unlisteners = await Promise.all([
+    window.listen<TokenEvent>('llm:token', (event) => {
+      if (event.payload.channel === channel) {
+        handlers.onToken(event.payload.delta);
+      }
+    }),
+    window.listen<UsageEvent>('llm:usage', (event) => {
+      if (event.payload.channel === channel) {
+        handlers.onUsage?.(event.payload);
+      }
+    }),
+    window.listen<ContextEvent>('llm:context', (event) => {
+      if (event.payload.channel === channel) {
+        handlers.onContext?.(event.payload);
+      }
+    }),
+  ]);
+  let finished: (event: FinishEvent) => void = () => {};
+  const finish = new Promise<FinishEvent>((resolve) => {
+    finished = resolve;
+  });
+  unlisteners.push(
+    await window.listen<FinishEvent>('llm:finish', (event) => {
+      if (event.payload.channel === channel) {
+        finished(event.payload);
+      }
+    })
+  );
+  try {
+    await invoke('llm_chat_stream', { request, channel });
+    return await finish;
+  } finally {
+    unlisteners.forEach(unlisten => unlisten());
+  }
+}
+
+export interface AgentOptions {
+  // The most replies of the model to ask for, 10 by default
+  max_steps?: number;
+  // The servers whose tools are offered; by default all connected ones
+  servers?: string[];
+  conversation_id?: string;
+}
+
+export interface AgentRun {
+  // The model's replies and the tool results, to add to the conversation
+  messages: ChatMessage[];
+  steps: number;
+  // 'max_steps' when the run stopped at its limit
+  done_reason: string;
+}
+
+export interface AgentHandlers {
+  onToken: (delta: string) => void;
+  onStep?: (step: AgentStepEvent) => void;
+  onContext?: (context: ContextEvent) => void;
+}
+
+// Let the model call the tools of connected MCP servers until it is done.
+// Each call waits for approval like any other tool call. Stop it with
+// cancelLlmRequest(channel).
+export async function runAgent(
+  request: ChatRequest,
+  handlers: AgentHandlers,
+  options?: AgentOptions,
+  channel: string = crypto.randomUUID()
+): Promise<AgentRun> {
+  const window = getCurrentWebviewWindow();
+  const unlisteners = await Promise.all([
+    window.listen<TokenEvent>('llm:token', (event) => {
+      if (event.payload.channel === channel) {
+        handlers.onToken(event.payload.delta);
+      }
+    }),
+    window.listen<AgentStepEvent>('llm:agent-step', (event) => {
+      if (event.payload.channel === channel) {
+        handlers.onStep?.(event.payload);
+      }
+    }),
+    window.listen<ContextEvent>('llm:context', (event) => {
+      if (event.payload.channel === channel) {
+        handlers.onContext?.(event.payload);
+      }
+    }),
+  ]);
+  try {
+    return await invoke('llm_run_agent', { request, channel, options });
+  } finally {
+    unlisteners.forEach(unlisten => unlisten());
+  }
+}
+
+// Resolves to false if the request already finished
+export async function cancelLlmRequest(id: string): Promise<boolean> {
+  return invoke('llm_cancel', { id });
+}